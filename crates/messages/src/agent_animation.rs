@@ -0,0 +1,114 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_uuid, read_variable_1, write_variable_1};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::ReadBytesExt;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor};
+use uuid::Uuid;
+
+// ID: 5
+// Frequency: High
+
+impl Packet {
+    pub fn new_agent_animation(agent_animation: AgentAnimation) -> Self {
+        Packet::new(
+            5,
+            PacketFrequency::High,
+            PacketType::AgentAnimation(Box::new(agent_animation)),
+        )
+        .reliable(true)
+    }
+}
+
+/// Starts and stops animations on the agent's own avatar. The simulator tells everyone nearby
+/// which animations are playing with an AvatarAnimation.
+/// https://wiki.secondlife.com/wiki/AgentAnimation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentAnimation {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub animations: Vec<AnimationChange>,
+    /// extra data for physical avatar events. Viewers send this empty.
+    pub physical_avatar_events: Vec<Vec<u8>>,
+}
+
+/// an animation to start or stop
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnimationChange {
+    /// the UUID of the animation asset
+    pub anim_id: Uuid,
+    /// true to start playing the animation, false to stop it
+    pub start: bool,
+}
+
+impl AgentAnimation {
+    /// start or stop each animation, given as (animation UUID, start) pairs
+    pub fn new(
+        agent_id: Uuid,
+        session_id: Uuid,
+        animations: impl IntoIterator<Item = (Uuid, bool)>,
+    ) -> Self {
+        AgentAnimation {
+            agent_id,
+            session_id,
+            animations: animations
+                .into_iter()
+                .map(|(anim_id, start)| AnimationChange { anim_id, start })
+                .collect(),
+            physical_avatar_events: Vec::new(),
+        }
+    }
+}
+
+impl PacketData for AgentAnimation {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let agent_id = read_uuid(&mut cursor)?;
+        let session_id = read_uuid(&mut cursor)?;
+
+        let animation_count = cursor.read_u8()?;
+        let mut animations = Vec::with_capacity(animation_count as usize);
+        for _ in 0..animation_count {
+            animations.push(AnimationChange {
+                anim_id: read_uuid(&mut cursor)?,
+                start: cursor.read_u8()? != 0,
+            });
+        }
+
+        let event_count = cursor.read_u8()?;
+        let mut physical_avatar_events = Vec::with_capacity(event_count as usize);
+        for _ in 0..event_count {
+            physical_avatar_events.push(read_variable_1(&mut cursor)?);
+        }
+
+        Ok((
+            AgentAnimation {
+                agent_id,
+                session_id,
+                animations,
+                physical_avatar_events,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(34 + self.animations.len() * 17);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.push(self.animations.len() as u8);
+        for animation in &self.animations {
+            bytes.extend_from_slice(animation.anim_id.as_bytes());
+            bytes.push(animation.start as u8);
+        }
+        bytes.push(self.physical_avatar_events.len() as u8);
+        for type_data in &self.physical_avatar_events {
+            write_variable_1(&mut bytes, type_data);
+        }
+        bytes
+    }
+}
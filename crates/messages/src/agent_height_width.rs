@@ -0,0 +1,76 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 83
+// Frequency: Low
+
+impl Packet {
+    pub fn new_agent_height_width(agent_height_width: AgentHeightWidth) -> Self {
+        Packet::new(
+            83,
+            PacketFrequency::Low,
+            PacketType::AgentHeightWidth(Box::new(agent_height_width)),
+        )
+        .reliable(true)
+    }
+}
+
+/// Tells the simulator the size of the client's window, in pixels.
+/// The simulator uses it with the field of view to decide which objects the agent can see.
+/// https://wiki.secondlife.com/wiki/AgentHeightWidth
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentHeightWidth {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub circuit_code: u32,
+    /// incremented with every resize, so the simulator can ignore old ones
+    pub gen_counter: u32,
+    pub height: u16,
+    pub width: u16,
+}
+
+impl PacketData for AgentHeightWidth {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        let circuit_code = cursor.read_u32::<LittleEndian>()?;
+        let gen_counter = cursor.read_u32::<LittleEndian>()?;
+        let height = cursor.read_u16::<LittleEndian>()?;
+        let width = cursor.read_u16::<LittleEndian>()?;
+
+        Ok((
+            AgentHeightWidth {
+                agent_id,
+                session_id,
+                circuit_code,
+                gen_counter,
+                height,
+                width,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(44);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.extend_from_slice(&self.circuit_code.to_le_bytes());
+        bytes.extend_from_slice(&self.gen_counter.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes
+    }
+}
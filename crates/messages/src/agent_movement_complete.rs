@@ -0,0 +1,100 @@
+use byteorder::{LittleEndian, ReadBytesExt};
+use glam::Vec3;
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_string_2, write_string_2};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use serde::{Deserialize, Serialize};
+
+// ID: 250
+// Frequency: Low
+
+impl Packet {
+    pub fn new_agent_movement_complete(agent_movement_complete: AgentMovementComplete) -> Self {
+        Packet::new(
+            250,
+            PacketFrequency::Low,
+            PacketType::AgentMovementComplete(Box::new(agent_movement_complete)),
+        )
+    }
+}
+
+/// Sent by the simulator in reply to CompleteAgentMovement, once the agent has arrived in the
+/// region. The session is fully live after this is received.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentMovementComplete {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// where the agent arrived, in region coordinates
+    pub position: Vec3,
+    /// the direction the agent is facing
+    pub look_at: Vec3,
+    /// the global position of the region the agent arrived in
+    pub region_handle: u64,
+    pub timestamp: u32,
+    /// the version of the simulator software
+    pub channel_version: String,
+}
+
+impl PacketData for AgentMovementComplete {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+
+        let position = Vec3::new(
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+        );
+        let look_at = Vec3::new(
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+        );
+        let region_handle = cursor.read_u64::<LittleEndian>()?;
+        let timestamp = cursor.read_u32::<LittleEndian>()?;
+
+        let channel_version = read_string_2(&mut cursor)?;
+
+        Ok((
+            AgentMovementComplete {
+                agent_id,
+                session_id,
+                position,
+                look_at,
+                region_handle,
+                timestamp,
+                channel_version,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        for value in self
+            .position
+            .to_array()
+            .iter()
+            .chain(&self.look_at.to_array())
+        {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.region_handle.to_le_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        write_string_2(&mut bytes, &self.channel_version);
+        bytes
+    }
+}
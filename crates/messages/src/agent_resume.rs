@@ -0,0 +1,62 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 155
+// Frequency: Low
+
+impl Packet {
+    pub fn new_agent_resume(agent_resume: AgentResume) -> Self {
+        Packet::new(
+            155,
+            PacketFrequency::Low,
+            PacketType::AgentResume(Box::new(agent_resume)),
+        )
+        .reliable(true)
+    }
+}
+
+/// asks the simulator to start sending updates to the agent again, after an AgentPause.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentResume {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// incremented with every pause and resume, so the simulator can ignore old ones
+    pub serial_num: u32,
+}
+
+impl PacketData for AgentResume {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        let serial_num = cursor.read_u32::<LittleEndian>()?;
+
+        Ok((
+            AgentResume {
+                agent_id,
+                session_id,
+                serial_num,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(36);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.extend_from_slice(&self.serial_num.to_le_bytes());
+        bytes
+    }
+}
@@ -0,0 +1,141 @@
+use crate::packet_types::PacketType;
+use crate::utils::texture_entry::TextureEntry;
+use crate::utils::variable::{
+    read_variable_1, read_variable_2, write_variable_1, write_variable_2,
+};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 84
+// Frequency: Low
+
+/// the size of an avatar with the default shape, in meters
+pub const DEFAULT_AVATAR_SIZE: Vec3 = Vec3::new(0.45, 0.6, 1.9);
+
+impl Packet {
+    pub fn new_agent_set_appearance(agent_set_appearance: AgentSetAppearance) -> Self {
+        Packet::new(
+            84,
+            PacketFrequency::Low,
+            PacketType::AgentSetAppearance(Box::new(agent_set_appearance)),
+        )
+        .reliable(true)
+    }
+}
+
+/// Tells the simulator what the agent looks like, so other viewers can draw it.
+/// Until this is sent, other viewers draw the agent as a cloud.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSetAppearance {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// incremented every time the appearance is sent, so the simulator can ignore old ones
+    pub serial_num: u32,
+    /// the size of the avatar's bounding box, in meters
+    pub size: Vec3,
+    /// the cached baked textures, one for each bake
+    pub wearables: Vec<WearableCache>,
+    /// the baked textures of the avatar's faces
+    pub texture_entry: TextureEntry,
+    /// the shape of the avatar, one byte for each visual parameter
+    pub visual_params: Vec<u8>,
+}
+
+/// the cache id of a baked texture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WearableCache {
+    pub cache_id: Uuid,
+    /// the bake the texture is for, like the head, the upper body or the lower body
+    pub texture_index: u8,
+}
+
+impl AgentSetAppearance {
+    /// the appearance of an avatar with the default size and no cached bakes
+    pub fn new(
+        agent_id: Uuid,
+        session_id: Uuid,
+        visual_params: Vec<u8>,
+        texture_entry: TextureEntry,
+    ) -> Self {
+        AgentSetAppearance {
+            agent_id,
+            session_id,
+            serial_num: 1,
+            size: DEFAULT_AVATAR_SIZE,
+            wearables: Vec::new(),
+            texture_entry,
+            visual_params,
+        }
+    }
+}
+
+impl PacketData for AgentSetAppearance {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        let serial_num = cursor.read_u32::<LittleEndian>()?;
+        let size = Vec3::new(
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+        );
+
+        let wearable_count = cursor.read_u8()?;
+        let mut wearables = Vec::with_capacity(wearable_count as usize);
+        for _ in 0..wearable_count {
+            cursor.read_exact(&mut uuid_bytes)?;
+            wearables.push(WearableCache {
+                cache_id: Uuid::from_bytes(uuid_bytes),
+                texture_index: cursor.read_u8()?,
+            });
+        }
+
+        let texture_entry = TextureEntry::from_bytes(&read_variable_2(&mut cursor)?)?;
+        let visual_params = read_variable_1(&mut cursor)?;
+
+        Ok((
+            AgentSetAppearance {
+                agent_id,
+                session_id,
+                serial_num,
+                size,
+                wearables,
+                texture_entry,
+                visual_params,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.extend_from_slice(&self.serial_num.to_le_bytes());
+        bytes.extend_from_slice(&self.size.x.to_le_bytes());
+        bytes.extend_from_slice(&self.size.y.to_le_bytes());
+        bytes.extend_from_slice(&self.size.z.to_le_bytes());
+
+        bytes.push(self.wearables.len() as u8);
+        for wearable in &self.wearables {
+            bytes.extend_from_slice(wearable.cache_id.as_bytes());
+            bytes.push(wearable.texture_index);
+        }
+
+        write_variable_2(&mut bytes, &self.texture_entry.to_bytes());
+        write_variable_1(&mut bytes, &self.visual_params);
+        bytes
+    }
+}
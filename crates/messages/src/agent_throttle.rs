@@ -0,0 +1,128 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_variable_1, write_variable_1};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 81
+// Frequency: Low
+
+impl Packet {
+    pub fn new_agent_throttle(agent_throttle: AgentThrottle) -> Self {
+        Packet::new(
+            81,
+            PacketFrequency::Low,
+            PacketType::AgentThrottle(Box::new(agent_throttle)),
+        )
+        .reliable(true)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentThrottle {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub circuit_code: u32,
+    /// incremented every time a new throttle is sent, so the server can drop stale ones
+    pub gen_counter: u32,
+    pub throttles: Throttles,
+}
+
+/// the seven throttle categories, in bytes per second.
+/// the server uses these to pace the streams of each category to the client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Throttles {
+    pub resend: f32,
+    pub land: f32,
+    pub wind: f32,
+    pub cloud: f32,
+    pub task: f32,
+    pub texture: f32,
+    pub asset: f32,
+}
+
+impl Default for Throttles {
+    /// these are roughly the defaults the official viewer sends for a 1.5Mbps connection
+    fn default() -> Self {
+        Throttles {
+            resend: 150000.0,
+            land: 170000.0,
+            wind: 34000.0,
+            cloud: 34000.0,
+            task: 446000.0,
+            texture: 446000.0,
+            asset: 220000.0,
+        }
+    }
+}
+
+impl Throttles {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(28);
+        bytes.extend_from_slice(&self.resend.to_le_bytes());
+        bytes.extend_from_slice(&self.land.to_le_bytes());
+        bytes.extend_from_slice(&self.wind.to_le_bytes());
+        bytes.extend_from_slice(&self.cloud.to_le_bytes());
+        bytes.extend_from_slice(&self.task.to_le_bytes());
+        bytes.extend_from_slice(&self.texture.to_le_bytes());
+        bytes.extend_from_slice(&self.asset.to_le_bytes());
+        bytes
+    }
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut cursor = Cursor::new(bytes);
+        Ok(Throttles {
+            resend: cursor.read_f32::<LittleEndian>()?,
+            land: cursor.read_f32::<LittleEndian>()?,
+            wind: cursor.read_f32::<LittleEndian>()?,
+            cloud: cursor.read_f32::<LittleEndian>()?,
+            task: cursor.read_f32::<LittleEndian>()?,
+            texture: cursor.read_f32::<LittleEndian>()?,
+            asset: cursor.read_f32::<LittleEndian>()?,
+        })
+    }
+}
+
+impl PacketData for AgentThrottle {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut agent_id_bytes = [0u8; 16];
+        cursor.read_exact(&mut agent_id_bytes)?;
+        let agent_id = Uuid::from_bytes(agent_id_bytes);
+
+        let mut session_id_bytes = [0u8; 16];
+        cursor.read_exact(&mut session_id_bytes)?;
+        let session_id = Uuid::from_bytes(session_id_bytes);
+
+        let circuit_code = cursor.read_u32::<LittleEndian>()?;
+        let gen_counter = cursor.read_u32::<LittleEndian>()?;
+
+        let throttles = Throttles::from_bytes(&read_variable_1(&mut cursor)?)?;
+
+        Ok((
+            AgentThrottle {
+                agent_id,
+                session_id,
+                circuit_code,
+                gen_counter,
+                throttles,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.extend_from_slice(&self.circuit_code.to_le_bytes());
+        bytes.extend_from_slice(&self.gen_counter.to_le_bytes());
+        write_variable_1(&mut bytes, &self.throttles.to_bytes());
+        bytes
+    }
+}
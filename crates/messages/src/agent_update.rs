@@ -5,9 +5,10 @@ use uuid::Uuid;
 use crate::packet_types::PacketType;
 
 use super::{
-    header::{Header, PacketFrequency},
+    header::PacketFrequency,
     packet::{Packet, PacketData},
 };
+use serde::{Deserialize, Serialize};
 
 const AGENT_STATE_TYPING: u8 = 0x04; // 00000100 in binary
 const AGENT_STATE_EDITING: u8 = 0x10; // 00010000 in binary
@@ -50,24 +51,15 @@ const AU_FLAGS_HIDETITLE: u8 = 0x01;
 
 impl Packet {
     pub fn new_agent_update(agent_update: AgentUpdate) -> Self {
-        Packet {
-            header: Header {
-                id: 4,
-                frequency: PacketFrequency::High,
-                reliable: false,
-                sequence_number: 1,
-                appended_acks: false,
-                zerocoded: false,
-                resent: false,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::AgentUpdate(Box::new(agent_update)),
-        }
+        Packet::new(
+            4,
+            PacketFrequency::High,
+            PacketType::AgentUpdate(Box::new(agent_update)),
+        )
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct State {
     pub typing: bool,
     pub editing: bool,
@@ -91,7 +83,7 @@ impl State {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ControlFlags {
     pub at_pos: bool,
     pub at_neg: bool,
@@ -265,7 +257,7 @@ impl ControlFlags {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Flags {
     pub none: bool,
     pub hide_title: bool,
@@ -289,7 +281,7 @@ impl Flags {
         bits
     }
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentUpdate {
     pub agent_id: Uuid,
     pub session_id: Uuid,
@@ -329,9 +321,15 @@ impl ToFromBytes for Quat {
 }
 
 impl PacketData for AgentUpdate {
-    fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
+    fn from_bytes_with_len(bytes: &[u8]) -> std::io::Result<(Self, usize)> {
         // THIS DOES NOT WORK AT ALL
-        // THIS WILL CRASH AND BREAK YOUR SHIT
+        // the fields are read at fixed offsets, so make sure they are all there first
+        if bytes.len() < 122 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "AgentUpdate is too short",
+            ));
+        }
         let agent_id = Uuid::from_slice(&bytes[0..16]).unwrap();
         let session_id = Uuid::from_slice(&bytes[16..32]).unwrap();
         let body_rotation = Quat::from_bytes(&bytes[32..48]);
@@ -362,20 +360,23 @@ impl PacketData for AgentUpdate {
         let control_flags =
             ControlFlags::from_bytes(u32::from_le_bytes(bytes[117..121].try_into().unwrap()));
         let flags = Flags::from_bytes(bytes[121]);
-        Ok(Self {
-            agent_id,
-            session_id,
-            body_rotation,
-            head_rotation,
-            state,
-            camera_center,
-            camera_at_axis,
-            camera_left_axis,
-            camera_up_axis,
-            far,
-            control_flags,
-            flags,
-        })
+        Ok((
+            Self {
+                agent_id,
+                session_id,
+                body_rotation,
+                head_rotation,
+                state,
+                camera_center,
+                camera_at_axis,
+                camera_left_axis,
+                camera_up_axis,
+                far,
+                control_flags,
+                flags,
+            },
+            122,
+        ))
     }
 
     fn to_bytes(&self) -> Vec<u8> {
@@ -0,0 +1,116 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_variable_1, write_variable_1};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 20
+// Frequency: High
+
+impl Packet {
+    pub fn new_avatar_animation(avatar_animation: AvatarAnimation) -> Self {
+        Packet::new(
+            20,
+            PacketFrequency::High,
+            PacketType::AvatarAnimation(Box::new(avatar_animation)),
+        )
+    }
+}
+
+/// the list of animations an agent is currently playing.
+/// The server sends the full list every time it changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvatarAnimation {
+    /// the agent that is playing the animations
+    pub agent_id: Uuid,
+    pub animations: Vec<Animation>,
+    /// the objects that triggered the animations, if any
+    pub animation_sources: Vec<Uuid>,
+    /// physical avatar event data. Mostly unused.
+    pub physical_avatar_events: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Animation {
+    /// the UUID of the animation asset
+    pub animation_id: Uuid,
+    /// the order the animation was started in
+    pub sequence_id: i32,
+}
+
+impl PacketData for AvatarAnimation {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+
+        let animation_count = cursor.read_u8()? as usize;
+        let mut animations = Vec::with_capacity(animation_count);
+        for _ in 0..animation_count {
+            cursor.read_exact(&mut uuid_bytes)?;
+            let animation_id = Uuid::from_bytes(uuid_bytes);
+            let sequence_id = cursor.read_i32::<LittleEndian>()?;
+            animations.push(Animation {
+                animation_id,
+                sequence_id,
+            });
+        }
+
+        let source_count = cursor.read_u8()? as usize;
+        let mut animation_sources = Vec::with_capacity(source_count);
+        for _ in 0..source_count {
+            cursor.read_exact(&mut uuid_bytes)?;
+            animation_sources.push(Uuid::from_bytes(uuid_bytes));
+        }
+
+        // some servers leave off the physical avatar event block entirely
+        let mut physical_avatar_events = Vec::new();
+        if let Ok(event_count) = cursor.read_u8() {
+            for _ in 0..event_count {
+                physical_avatar_events.push(read_variable_1(&mut cursor)?);
+            }
+        }
+
+        Ok((
+            AvatarAnimation {
+                agent_id,
+                animations,
+                animation_sources,
+                physical_avatar_events,
+            },
+            cursor.position() as usize,
+        ))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+
+        bytes.push(self.animations.len() as u8);
+        for animation in &self.animations {
+            bytes.extend_from_slice(animation.animation_id.as_bytes());
+            bytes
+                .write_i32::<LittleEndian>(animation.sequence_id)
+                .unwrap();
+        }
+
+        bytes.push(self.animation_sources.len() as u8);
+        for source in &self.animation_sources {
+            bytes.extend_from_slice(source.as_bytes());
+        }
+
+        bytes.push(self.physical_avatar_events.len() as u8);
+        for type_data in &self.physical_avatar_events {
+            write_variable_1(&mut bytes, type_data);
+        }
+        bytes
+    }
+}
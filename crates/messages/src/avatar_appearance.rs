@@ -0,0 +1,178 @@
+use crate::packet_types::PacketType;
+use crate::utils::texture_entry::TextureEntry;
+use crate::utils::variable::{
+    read_uuid, read_variable_1, read_variable_2, write_variable_1, write_variable_2,
+};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor};
+use uuid::Uuid;
+
+// ID: 158
+// Frequency: Low
+
+/// the faces of an avatar's TextureEntry that hold its baked textures: the head, upper body,
+/// lower body, eyes, skirt and hair
+pub const BAKED_TEXTURE_FACES: [u8; 6] = [8, 9, 10, 11, 19, 20];
+
+/// the texture simulators put on faces that don't have a bake yet
+pub const DEFAULT_AVATAR_TEXTURE: Uuid = Uuid::from_u128(0xc228d1cf_4b5d_4ba8_84f4_899a0796aa97);
+
+impl Packet {
+    pub fn new_avatar_appearance(avatar_appearance: AvatarAppearance) -> Self {
+        Packet::new(
+            158,
+            PacketFrequency::Low,
+            PacketType::AvatarAppearance(Box::new(avatar_appearance)),
+        )
+        .reliable(true)
+    }
+}
+
+/// What an avatar in the region looks like. The simulator sends this for every avatar the agent
+/// can see, whenever the avatar's appearance changes. Until it arrives, the avatar is drawn as a
+/// cloud.
+/// https://wiki.secondlife.com/wiki/AvatarAppearance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AvatarAppearance {
+    /// the avatar the appearance belongs to
+    pub agent_id: Uuid,
+    pub is_trial: bool,
+    /// the baked textures of the avatar's faces
+    pub texture_entry: TextureEntry,
+    /// the shape of the avatar, one byte for each visual parameter
+    pub visual_params: Vec<u8>,
+    /// the versions of the appearance, on simulators that send them
+    pub appearance_data: Vec<AppearanceData>,
+    /// how far the avatar is raised off the ground, on simulators that send it
+    pub hover_height: Vec<Vec3>,
+    /// the attachments the avatar is wearing, on simulators that send them
+    pub attachments: Vec<AppearanceAttachment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AppearanceData {
+    /// 0 for textures baked by the viewer, 1 for textures baked by the server
+    pub appearance_version: u8,
+    /// the version of the avatar's current outfit folder
+    pub cof_version: i32,
+    pub flags: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AppearanceAttachment {
+    /// the item ID of the attachment
+    pub id: Uuid,
+    /// where on the avatar it is attached
+    pub attachment_point: u8,
+}
+
+impl AvatarAppearance {
+    /// the baked textures of the avatar, leaving out faces that don't have a bake yet
+    pub fn baked_textures(&self) -> Vec<Uuid> {
+        let mut textures = Vec::new();
+        for face in BAKED_TEXTURE_FACES {
+            let texture = self.texture_entry.texture(face);
+            if !texture.is_nil()
+                && texture != DEFAULT_AVATAR_TEXTURE
+                && !textures.contains(&texture)
+            {
+                textures.push(texture);
+            }
+        }
+        textures
+    }
+}
+
+impl PacketData for AvatarAppearance {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let agent_id = read_uuid(&mut cursor)?;
+        let is_trial = cursor.read_u8()? != 0;
+
+        let texture_entry = TextureEntry::from_bytes(&read_variable_2(&mut cursor)?)?;
+        let visual_params = read_variable_1(&mut cursor)?;
+
+        // older simulators end the packet after the visual params
+        let mut appearance_data = Vec::new();
+        let mut hover_height = Vec::new();
+        let mut attachments = Vec::new();
+        if has_more(&cursor) {
+            for _ in 0..cursor.read_u8()? {
+                appearance_data.push(AppearanceData {
+                    appearance_version: cursor.read_u8()?,
+                    cof_version: cursor.read_i32::<LittleEndian>()?,
+                    flags: cursor.read_u32::<LittleEndian>()?,
+                });
+            }
+        }
+        if has_more(&cursor) {
+            for _ in 0..cursor.read_u8()? {
+                hover_height.push(Vec3::new(
+                    cursor.read_f32::<LittleEndian>()?,
+                    cursor.read_f32::<LittleEndian>()?,
+                    cursor.read_f32::<LittleEndian>()?,
+                ));
+            }
+        }
+        if has_more(&cursor) {
+            for _ in 0..cursor.read_u8()? {
+                attachments.push(AppearanceAttachment {
+                    id: read_uuid(&mut cursor)?,
+                    attachment_point: cursor.read_u8()?,
+                });
+            }
+        }
+
+        Ok((
+            AvatarAppearance {
+                agent_id,
+                is_trial,
+                texture_entry,
+                visual_params,
+                appearance_data,
+                hover_height,
+                attachments,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.push(self.is_trial as u8);
+
+        write_variable_2(&mut bytes, &self.texture_entry.to_bytes());
+        write_variable_1(&mut bytes, &self.visual_params);
+
+        bytes.push(self.appearance_data.len() as u8);
+        for data in &self.appearance_data {
+            bytes.push(data.appearance_version);
+            bytes.extend_from_slice(&data.cof_version.to_le_bytes());
+            bytes.extend_from_slice(&data.flags.to_le_bytes());
+        }
+        bytes.push(self.hover_height.len() as u8);
+        for hover in &self.hover_height {
+            bytes.extend_from_slice(&hover.x.to_le_bytes());
+            bytes.extend_from_slice(&hover.y.to_le_bytes());
+            bytes.extend_from_slice(&hover.z.to_le_bytes());
+        }
+        bytes.push(self.attachments.len() as u8);
+        for attachment in &self.attachments {
+            bytes.extend_from_slice(attachment.id.as_bytes());
+            bytes.push(attachment.attachment_point);
+        }
+        bytes
+    }
+}
+
+fn has_more(cursor: &Cursor<&[u8]>) -> bool {
+    (cursor.position() as usize) < cursor.get_ref().len()
+}
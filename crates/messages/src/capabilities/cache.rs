@@ -0,0 +1,95 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use uuid::Uuid;
+
+use super::download::{download_asset, ProgressCallback};
+use crate::errors::CapabilityError;
+
+/// The default maximum size of the asset cache, in bytes
+pub const DEFAULT_MAX_CACHE_SIZE: u64 = 512 * 1024 * 1024;
+
+/// An on-disk cache of downloaded assets, keyed by asset UUID.
+/// Assets are stored one per file in the cache directory. When the cache grows past max_size the
+/// least recently used assets are deleted, using the file modification time as the access time.
+#[derive(Debug, Clone)]
+pub struct AssetCache {
+    pub dir: PathBuf,
+    pub max_size: u64,
+}
+
+impl AssetCache {
+    /// Creates the cache directory if it doesn't already exist
+    pub fn new(dir: impl Into<PathBuf>, max_size: u64) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(AssetCache { dir, max_size })
+    }
+
+    fn path(&self, asset_id: Uuid) -> PathBuf {
+        self.dir.join(asset_id.to_string())
+    }
+
+    /// Returns the cached asset, if there is one, and marks it as recently used
+    pub fn get(&self, asset_id: Uuid) -> Option<Vec<u8>> {
+        let path = self.path(asset_id);
+        let data = fs::read(&path).ok()?;
+        if let Ok(file) = File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(data)
+    }
+
+    /// Writes an asset to the cache, and evicts the least recently used assets if the cache is
+    /// over its maximum size
+    pub fn insert(&self, asset_id: Uuid, data: &[u8]) -> io::Result<()> {
+        fs::write(self.path(asset_id), data)?;
+        self.evict()
+    }
+
+    /// Deletes the least recently used assets until the cache is under its maximum size
+    pub fn evict(&self) -> io::Result<()> {
+        let mut entries = Vec::new();
+        let mut total = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+            total += metadata.len();
+            entries.push((metadata.modified()?, metadata.len(), entry.path()));
+        }
+
+        entries.sort_by_key(|(modified, _, _)| *modified);
+        for (_, size, path) in entries {
+            if total <= self.max_size {
+                break;
+            }
+            fs::remove_file(path)?;
+            total -= size;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the asset from the cache if it exists, and otherwise downloads it from the capability
+/// URL and stores it in the cache.
+/// Failing to write to the cache is not an error, since the asset was still downloaded.
+pub async fn download_cached_asset(
+    cache: &AssetCache,
+    asset_id: Uuid,
+    url: &str,
+    progress: Option<&ProgressCallback>,
+) -> Result<Vec<u8>, CapabilityError> {
+    if let Some(data) = cache.get(asset_id) {
+        return Ok(data);
+    }
+    let data = download_asset(url, progress).await?;
+    if let Err(e) = cache.insert(asset_id, &data) {
+        log::warn!("Failed to cache asset {}: {}", asset_id, e);
+    }
+    Ok(data)
+}
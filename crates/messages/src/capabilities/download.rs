@@ -0,0 +1,63 @@
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+
+use super::error_response;
+use crate::errors::CapabilityError;
+
+/// The default number of assets downloaded at the same time by download_assets
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 8;
+
+/// Called while an asset downloads, with the number of bytes downloaded so far and the total size
+/// of the asset if the server sent one.
+pub type ProgressCallback = dyn Fn(u64, Option<u64>) + Send + Sync;
+
+/// Downloads an asset from a capability URL, like the GetMesh or ViewerAsset capabilities.
+/// The body is streamed in chunks instead of being buffered all at once, and the progress callback
+/// is called after every chunk so the UI can display a progress bar for large assets.
+pub async fn download_asset(
+    url: &str,
+    progress: Option<&ProgressCallback>,
+) -> Result<Vec<u8>, CapabilityError> {
+    let mut response = Client::new()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| CapabilityError::new(format!("Failed to download asset: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(error_response("Failed to download asset", response).await);
+    }
+
+    // the Content-Length is only reported, not trusted to size the buffer
+    let total = response.content_length();
+    let mut asset = Vec::new();
+    if let Some(progress) = progress {
+        progress(0, total);
+    }
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| CapabilityError::new(format!("Failed to download asset: {}", e)))?
+    {
+        asset.extend_from_slice(&chunk);
+        if let Some(progress) = progress {
+            progress(asset.len() as u64, total);
+        }
+    }
+    Ok(asset)
+}
+
+/// Downloads many assets at once, like all of the parts of an outfit, with no more than
+/// max_concurrent downloads running at the same time.
+/// The results are returned in the same order as the urls, so one failed part doesn't prevent the
+/// rest from loading.
+pub async fn download_assets(
+    urls: &[String],
+    max_concurrent: usize,
+) -> Vec<Result<Vec<u8>, CapabilityError>> {
+    stream::iter(urls)
+        .map(|url| download_asset(url, None))
+        .buffered(max_concurrent.max(1))
+        .collect()
+        .await
+}
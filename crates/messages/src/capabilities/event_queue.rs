@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::io;
+
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::{Client, StatusCode};
+
+use super::error_response;
+use super::llsd::LLSDValue;
+use crate::errors::CapabilityError;
+use crate::packet::PacketData;
+
+/// A single event pushed by the simulator over the EventQueueGet capability, like
+/// TeleportFinish, ChatterBoxInvitation or EstablishAgentCommunication.
+/// <http://wiki.secondlife.com/wiki/EventQueueGet>
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventQueueEvent {
+    /// the name of the event
+    pub message: String,
+    /// the body of the event. Its layout depends on the message.
+    pub body: LLSDValue,
+}
+
+impl EventQueueEvent {
+    fn from_llsd(event: &LLSDValue) -> Option<Self> {
+        Some(EventQueueEvent {
+            message: event.get("message")?.as_str()?.to_string(),
+            body: event.get("body").cloned().unwrap_or(LLSDValue::Undefined),
+        })
+    }
+    fn to_llsd(&self) -> LLSDValue {
+        let mut event = HashMap::new();
+        event.insert(
+            "message".to_string(),
+            LLSDValue::String(self.message.clone()),
+        );
+        event.insert("body".to_string(), self.body.clone());
+        LLSDValue::Map(event)
+    }
+}
+
+// events are sent to the UI as the same LLSD XML the simulator sent them in
+impl PacketData for EventQueueEvent {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let xml = std::str::from_utf8(bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let event =
+            LLSDValue::from_xml(xml).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        // the whole document is the event
+        let event = EventQueueEvent::from_llsd(&event)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Event has no message"))?;
+        Ok((event, bytes.len()))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_llsd().to_xml().into_bytes()
+    }
+}
+
+/// The result of a single EventQueueGet poll
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventQueuePoll {
+    /// the simulator sent events. The id is acked in the next poll.
+    Events {
+        /// the id of this batch of events
+        id: i32,
+        /// the events, in the order they were sent
+        events: Vec<EventQueueEvent>,
+    },
+    /// the simulator had nothing to send before the request timed out. Poll again.
+    Timeout,
+    /// the event queue is gone, because the agent left the region or logged out. Stop polling.
+    Closed,
+}
+
+/// Long-polls the EventQueueGet capability once.
+/// ack is the id of the last batch of events that was received, so the simulator doesn't send
+/// it again. It is None for the first poll.
+/// The request is held open by the simulator until there are events, or it times out.
+pub async fn event_queue_get(
+    cap_url: &str,
+    ack: Option<i32>,
+) -> Result<EventQueuePoll, CapabilityError> {
+    let mut request = HashMap::new();
+    request.insert(
+        "ack".to_string(),
+        ack.map_or(LLSDValue::Undefined, LLSDValue::Integer),
+    );
+    request.insert("done".to_string(), LLSDValue::Boolean(false));
+
+    let response = Client::new()
+        .post(cap_url)
+        .header(CONTENT_TYPE, "application/llsd+xml")
+        .header(ACCEPT, "application/llsd+xml")
+        .body(LLSDValue::Map(request).to_xml())
+        .send()
+        .await
+        .map_err(|e| CapabilityError::new(format!("Failed to poll event queue: {}", e)))?;
+
+    match response.status() {
+        // the simulator answers an idle poll with a gateway error
+        StatusCode::BAD_GATEWAY | StatusCode::GATEWAY_TIMEOUT => Ok(EventQueuePoll::Timeout),
+        StatusCode::NOT_FOUND | StatusCode::GONE => Ok(EventQueuePoll::Closed),
+        status if !status.is_success() => {
+            Err(error_response("Failed to poll event queue", response).await)
+        }
+        _ => {
+            let text = response.text().await.map_err(|e| {
+                CapabilityError::new(format!("Failed to read event queue response: {}", e))
+            })?;
+            parse_event_queue_response(&text)
+        }
+    }
+}
+
+/// Parses the LLSD response of the EventQueueGet capability
+pub fn parse_event_queue_response(xml: &str) -> Result<EventQueuePoll, CapabilityError> {
+    let response = LLSDValue::from_xml(xml)?;
+    // some simulators answer an idle poll with an empty document instead of an error
+    if response == LLSDValue::Undefined {
+        return Ok(EventQueuePoll::Timeout);
+    }
+    let id = response
+        .get("id")
+        .and_then(|id| id.as_i32())
+        .ok_or_else(|| CapabilityError::new("Event queue response contains no id"))?;
+    let events = response
+        .get("events")
+        .and_then(|events| events.as_array())
+        .map(|events| {
+            events
+                .iter()
+                .filter_map(EventQueueEvent::from_llsd)
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(EventQueuePoll::Events { id, events })
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::error_response;
+use super::llsd::LLSDValue;
+use crate::errors::CapabilityError;
+
+/// The asset type of a link to another inventory item. Its asset_id is the item_id of the item it
+/// points to.
+pub const LINK_ASSET_TYPE: i32 = 24;
+
+/// The metadata of a single inventory item, as returned by the FetchInventoryDescendents2
+/// capability.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemMetadata {
+    /// the ID of the inventory item
+    pub item_id: Uuid,
+    /// the ID of the folder that contains the item
+    pub parent_id: Uuid,
+    /// the ID of the asset the item points to
+    pub asset_id: Uuid,
+    /// the owner of the item
+    pub owner_id: Uuid,
+    /// the name of the item
+    pub name: String,
+    /// the description of the item
+    pub description: String,
+    /// the asset type of the item, like texture, sound or object
+    pub asset_type: i32,
+    /// the inventory type of the item
+    pub inventory_type: i32,
+    /// item flags
+    pub flags: u32,
+    /// creation date of the item, in unix seconds
+    pub created_at: i32,
+}
+
+impl ItemMetadata {
+    /// whether this item is a link to another item, like the worn items in the Current Outfit
+    /// Folder
+    pub fn is_link(&self) -> bool {
+        self.asset_type == LINK_ASSET_TYPE
+    }
+
+    fn from_llsd(item: &LLSDValue) -> Option<Self> {
+        let owner_id = item
+            .get("permissions")
+            .and_then(|permissions| permissions.get("owner_id"))
+            .and_then(|owner_id| owner_id.as_uuid())
+            .unwrap_or_default();
+        Some(ItemMetadata {
+            item_id: item.get("item_id")?.as_uuid()?,
+            parent_id: item.get("parent_id")?.as_uuid()?,
+            asset_id: item
+                .get("asset_id")
+                .and_then(|asset_id| asset_id.as_uuid())
+                .unwrap_or_default(),
+            owner_id,
+            name: item
+                .get("name")
+                .and_then(|name| name.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            description: item
+                .get("desc")
+                .and_then(|desc| desc.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            asset_type: item
+                .get("type")
+                .and_then(|asset_type| asset_type.as_i32())
+                .unwrap_or(-1),
+            inventory_type: item
+                .get("inv_type")
+                .and_then(|inventory_type| inventory_type.as_i32())
+                .unwrap_or(-1),
+            flags: item
+                .get("flags")
+                .and_then(|flags| flags.as_i32())
+                .unwrap_or_default() as u32,
+            created_at: item
+                .get("created_at")
+                .and_then(|created_at| created_at.as_i32())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Fetches the items contained in an inventory folder using the FetchInventoryDescendents2
+/// capability.
+/// cap_url is the URL of the capability, retrieved from the seed capability.
+/// folder_id is the ID of the folder to open, like the ones in the login response's
+/// inventory_skeleton.
+/// owner_id is the ID of the agent that owns the folder.
+pub async fn fetch_inventory_folder(
+    cap_url: &str,
+    folder_id: Uuid,
+    owner_id: Uuid,
+) -> Result<Vec<ItemMetadata>, CapabilityError> {
+    let mut folder_request = HashMap::new();
+    folder_request.insert("folder_id".to_string(), LLSDValue::UUID(folder_id));
+    folder_request.insert("owner_id".to_string(), LLSDValue::UUID(owner_id));
+    folder_request.insert("fetch_folders".to_string(), LLSDValue::Boolean(true));
+    folder_request.insert("fetch_items".to_string(), LLSDValue::Boolean(true));
+    folder_request.insert("sort_order".to_string(), LLSDValue::Integer(0));
+
+    let mut body = HashMap::new();
+    body.insert(
+        "folders".to_string(),
+        LLSDValue::Array(vec![LLSDValue::Map(folder_request)]),
+    );
+
+    let response = Client::new()
+        .post(cap_url)
+        .header(CONTENT_TYPE, "application/llsd+xml")
+        .header(ACCEPT, "application/llsd+xml")
+        .body(LLSDValue::Map(body).to_xml())
+        .send()
+        .await
+        .map_err(|e| CapabilityError::new(format!("Failed to fetch folder: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(error_response("Failed to fetch folder", response).await);
+    }
+
+    let text = response
+        .text()
+        .await
+        .map_err(|e| CapabilityError::new(format!("Failed to read folder response: {}", e)))?;
+
+    parse_inventory_folder(&text)
+}
+
+/// Parses the LLSD response of the FetchInventoryDescendents2 capability into the items of all of
+/// the returned folders.
+pub fn parse_inventory_folder(xml: &str) -> Result<Vec<ItemMetadata>, CapabilityError> {
+    let response = LLSDValue::from_xml(xml)?;
+    let folders = response
+        .get("folders")
+        .and_then(|folders| folders.as_array())
+        .ok_or_else(|| CapabilityError::new("Response contains no folders"))?;
+
+    let mut items = Vec::new();
+    for folder in folders {
+        if let Some(folder_items) = folder.get("items").and_then(|items| items.as_array()) {
+            items.extend(folder_items.iter().filter_map(ItemMetadata::from_llsd));
+        }
+    }
+    Ok(items)
+}
+
+/// Replaces every link in items with the item it points to, so it can be downloaded like the
+/// real item. known is every item that has been fetched, which has to include the targets of the
+/// links.
+/// Links whose target is not in known are returned separately, so their folders can be fetched.
+pub fn resolve_links(
+    items: &[ItemMetadata],
+    known: &[ItemMetadata],
+) -> (Vec<ItemMetadata>, Vec<ItemMetadata>) {
+    let known: HashMap<Uuid, &ItemMetadata> =
+        known.iter().map(|item| (item.item_id, item)).collect();
+    let mut resolved = Vec::new();
+    let mut unresolved = Vec::new();
+    for item in items {
+        if !item.is_link() {
+            resolved.push(item.clone());
+            continue;
+        }
+        match known.get(&item.asset_id) {
+            // links can't point to other links
+            Some(target) if !target.is_link() => resolved.push((*target).clone()),
+            _ => unresolved.push(item.clone()),
+        }
+    }
+    (resolved, unresolved)
+}
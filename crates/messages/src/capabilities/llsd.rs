@@ -0,0 +1,248 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::errors::CapabilityError;
+
+/// A minimal representation of LLSD values, used for the bodies of capability requests.
+/// <http://wiki.secondlife.com/wiki/LLSD>
+#[derive(Debug, Clone, PartialEq)]
+pub enum LLSDValue {
+    Undefined,
+    Boolean(bool),
+    Integer(i32),
+    Real(f64),
+    String(String),
+    UUID(Uuid),
+    Date(String),
+    URI(String),
+    Binary(String),
+    Map(HashMap<String, LLSDValue>),
+    Array(Vec<LLSDValue>),
+}
+
+impl LLSDValue {
+    /// look up a key in a map. Returns None if this is not a map or the key doesn't exist.
+    pub fn get(&self, key: &str) -> Option<&LLSDValue> {
+        match self {
+            LLSDValue::Map(map) => map.get(key),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&Vec<LLSDValue>> {
+        match self {
+            LLSDValue::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            LLSDValue::String(value) | LLSDValue::URI(value) | LLSDValue::Date(value) => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            LLSDValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            LLSDValue::Boolean(value) => Some(*value),
+            LLSDValue::Integer(value) => Some(*value != 0),
+            _ => None,
+        }
+    }
+    /// UUIDs are sometimes sent as strings, so accept both
+    pub fn as_uuid(&self) -> Option<Uuid> {
+        match self {
+            LLSDValue::UUID(value) => Some(*value),
+            LLSDValue::String(value) => Uuid::parse_str(value).ok(),
+            _ => None,
+        }
+    }
+
+    /// serialize the value as an LLSD XML document
+    pub fn to_xml(&self) -> String {
+        format!(
+            "<?xml version=\"1.0\" ?><llsd>{}</llsd>",
+            self.to_xml_value()
+        )
+    }
+
+    fn to_xml_value(&self) -> String {
+        match self {
+            LLSDValue::Undefined => "<undef />".to_string(),
+            LLSDValue::Boolean(value) => format!("<boolean>{}</boolean>", *value as u8),
+            LLSDValue::Integer(value) => format!("<integer>{}</integer>", value),
+            LLSDValue::Real(value) => format!("<real>{}</real>", value),
+            LLSDValue::String(value) => format!("<string>{}</string>", escape(value)),
+            LLSDValue::UUID(value) => format!("<uuid>{}</uuid>", value),
+            LLSDValue::Date(value) => format!("<date>{}</date>", escape(value)),
+            LLSDValue::URI(value) => format!("<uri>{}</uri>", escape(value)),
+            LLSDValue::Binary(value) => format!("<binary>{}</binary>", escape(value)),
+            LLSDValue::Map(map) => {
+                let mut xml = "<map>".to_string();
+                for (key, value) in map {
+                    xml.push_str(&format!("<key>{}</key>", escape(key)));
+                    xml.push_str(&value.to_xml_value());
+                }
+                xml.push_str("</map>");
+                xml
+            }
+            LLSDValue::Array(array) => {
+                let mut xml = "<array>".to_string();
+                for value in array {
+                    xml.push_str(&value.to_xml_value());
+                }
+                xml.push_str("</array>");
+                xml
+            }
+        }
+    }
+
+    /// parse an LLSD XML document
+    pub fn from_xml(xml: &str) -> Result<Self, CapabilityError> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e)) if e.name().as_ref() == b"llsd" => {
+                    let value = match next_value(&mut reader)? {
+                        Some(value) => value,
+                        None => LLSDValue::Undefined,
+                    };
+                    return Ok(value);
+                }
+                Ok(Event::Empty(e)) if e.name().as_ref() == b"llsd" => {
+                    return Ok(LLSDValue::Undefined)
+                }
+                Ok(Event::Eof) => return Err(CapabilityError::new("No llsd element found")),
+                Ok(_) => {}
+                Err(e) => return Err(CapabilityError::new(format!("Invalid LLSD: {}", e))),
+            }
+        }
+    }
+}
+
+/// read the next value from the reader.
+/// Returns None when the enclosing element ends instead.
+fn next_value(reader: &mut Reader<&[u8]>) -> Result<Option<LLSDValue>, CapabilityError> {
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = e.name().as_ref().to_vec();
+                return match name.as_slice() {
+                    b"map" => {
+                        let mut map = HashMap::new();
+                        while let Some(key) = next_key(reader)? {
+                            let value = next_value(reader)?.ok_or_else(|| {
+                                CapabilityError::new(format!("Missing value for key {}", key))
+                            })?;
+                            map.insert(key, value);
+                        }
+                        Ok(Some(LLSDValue::Map(map)))
+                    }
+                    b"array" => {
+                        let mut array = Vec::new();
+                        while let Some(value) = next_value(reader)? {
+                            array.push(value);
+                        }
+                        Ok(Some(LLSDValue::Array(array)))
+                    }
+                    _ => {
+                        let text = read_text(reader)?;
+                        Ok(Some(scalar(&name, text)?))
+                    }
+                };
+            }
+            Ok(Event::Empty(e)) => {
+                let name = e.name().as_ref().to_vec();
+                return match name.as_slice() {
+                    b"map" => Ok(Some(LLSDValue::Map(HashMap::new()))),
+                    b"array" => Ok(Some(LLSDValue::Array(Vec::new()))),
+                    _ => Ok(Some(scalar(&name, String::new())?)),
+                };
+            }
+            Ok(Event::End(_)) => return Ok(None),
+            Ok(Event::Eof) => return Err(CapabilityError::new("Unexpected end of LLSD")),
+            Ok(_) => {}
+            Err(e) => return Err(CapabilityError::new(format!("Invalid LLSD: {}", e))),
+        }
+    }
+}
+
+/// read the next key of a map. Returns None when the map ends.
+fn next_key(reader: &mut Reader<&[u8]>) -> Result<Option<String>, CapabilityError> {
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.name().as_ref() == b"key" => {
+                return Ok(Some(read_text(reader)?))
+            }
+            Ok(Event::Empty(e)) if e.name().as_ref() == b"key" => return Ok(Some(String::new())),
+            Ok(Event::End(_)) => return Ok(None),
+            Ok(Event::Eof) => return Err(CapabilityError::new("Unexpected end of LLSD map")),
+            Ok(_) => {}
+            Err(e) => return Err(CapabilityError::new(format!("Invalid LLSD: {}", e))),
+        }
+    }
+}
+
+/// read the text of an element up to its end tag
+fn read_text(reader: &mut Reader<&[u8]>) -> Result<String, CapabilityError> {
+    let mut text = String::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Text(t)) => text.push_str(
+                &t.unescape()
+                    .map_err(|e| CapabilityError::new(format!("Invalid LLSD text: {}", e)))?,
+            ),
+            Ok(Event::CData(t)) => text.push_str(&String::from_utf8_lossy(&t)),
+            Ok(Event::End(_)) => return Ok(text),
+            Ok(Event::Eof) => return Err(CapabilityError::new("Unexpected end of LLSD value")),
+            Ok(_) => {}
+            Err(e) => return Err(CapabilityError::new(format!("Invalid LLSD: {}", e))),
+        }
+    }
+}
+
+fn scalar(name: &[u8], text: String) -> Result<LLSDValue, CapabilityError> {
+    let invalid = |kind: &str| CapabilityError::new(format!("Invalid LLSD {}: {}", kind, text));
+    Ok(match name {
+        b"undef" => LLSDValue::Undefined,
+        b"boolean" => LLSDValue::Boolean(text == "1" || text == "true"),
+        b"integer" => LLSDValue::Integer(if text.is_empty() {
+            0
+        } else {
+            text.parse().map_err(|_| invalid("integer"))?
+        }),
+        b"real" => LLSDValue::Real(if text.is_empty() {
+            0.0
+        } else {
+            text.parse().map_err(|_| invalid("real"))?
+        }),
+        b"uuid" => LLSDValue::UUID(if text.is_empty() {
+            Uuid::nil()
+        } else {
+            Uuid::parse_str(&text).map_err(|_| invalid("uuid"))?
+        }),
+        b"string" => LLSDValue::String(text),
+        b"date" => LLSDValue::Date(text),
+        b"uri" => LLSDValue::URI(text),
+        b"binary" => LLSDValue::Binary(text),
+        other => {
+            return Err(CapabilityError::new(format!(
+                "Unknown LLSD type {}",
+                String::from_utf8_lossy(other)
+            )))
+        }
+    })
+}
+
+fn escape(value: &str) -> String {
+    quick_xml::escape::escape(value).to_string()
+}
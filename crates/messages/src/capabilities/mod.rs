@@ -0,0 +1,47 @@
+//! # Capabilities
+//!
+//! Capabilities are HTTP endpoints handed out by the simulator for things that are too large or
+//! too slow for UDP packets, like inventory and asset fetching.
+//! The request and response bodies are LLSD XML.
+//! <http://wiki.secondlife.com/wiki/Capabilities>
+pub mod cache;
+pub mod download;
+pub mod event_queue;
+pub mod fetch_inventory;
+pub mod llsd;
+
+use crate::errors::{CapabilityError, HttpErrorResponse};
+use reqwest::Response;
+
+/// Builds the error for a capability that answered with an error status, keeping the status,
+/// the headers and the start of the body.
+pub(crate) async fn error_response(context: &str, mut response: Response) -> CapabilityError {
+    let status = response.status();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect();
+    // the body is only a hint, so a body that fails to read partway is kept as it is
+    let mut body = Vec::new();
+    while body.len() < HttpErrorResponse::BODY_PREVIEW_LENGTH {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            _ => break,
+        }
+    }
+    body.truncate(HttpErrorResponse::BODY_PREVIEW_LENGTH);
+    CapabilityError::with_response(
+        format!("{}: {}", context, status),
+        HttpErrorResponse {
+            status: status.as_u16(),
+            headers,
+            body,
+        },
+    )
+}
@@ -1,13 +1,16 @@
 use crate::packet_types::PacketType;
+use crate::utils::variable::{read_string_1, read_string_2, write_string_1, write_string_2};
 
 use super::{
-    header::Header,
+    chat_from_viewer::{ChatFromViewer, ClientChatType},
+    header::PacketFrequency,
     packet::{Packet, PacketData},
 };
 use byteorder::ReadBytesExt;
 use glam::Vec3;
-use std::io::Read;
-use std::io::{self, BufRead, Cursor};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, Cursor, Read};
 use uuid::Uuid;
 
 // ID: 139
@@ -15,24 +18,15 @@ use uuid::Uuid;
 
 impl Packet {
     pub fn new_chat_from_simulator(chat_from_simulator: ChatFromSimulator) -> Self {
-        Packet {
-            header: Header {
-                id: 139,
-                frequency: super::header::PacketFrequency::Low,
-                reliable: false,
-                sequence_number: 0,
-                appended_acks: false,
-                zerocoded: false,
-                resent: false,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::ChatFromSimulator(Box::new(chat_from_simulator)),
-        }
+        Packet::new(
+            139,
+            PacketFrequency::Low,
+            PacketType::ChatFromSimulator(Box::new(chat_from_simulator)),
+        )
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatFromSimulator {
     pub from_name: String,
     pub source_id: Uuid,
@@ -44,7 +38,7 @@ pub struct ChatFromSimulator {
     pub message: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SourceType {
     System,
     Agent,
@@ -69,8 +63,18 @@ impl SourceType {
         }
     }
 }
+impl fmt::Display for SourceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceType::System => write!(f, "system"),
+            SourceType::Agent => write!(f, "agent"),
+            SourceType::Object => write!(f, "object"),
+            SourceType::Unknown => write!(f, "unknown"),
+        }
+    }
+}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Audible {
     Not,
     Barely,
@@ -95,8 +99,18 @@ impl Audible {
         }
     }
 }
+impl fmt::Display for Audible {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Audible::Not => write!(f, "inaudible"),
+            Audible::Barely => write!(f, "barely audible"),
+            Audible::Fully => write!(f, "audible"),
+            Audible::Unknown => write!(f, "unknown"),
+        }
+    }
+}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ChatType {
     Whisper,
     Normal,
@@ -136,35 +150,69 @@ impl ChatType {
         }
     }
 }
+// the verb used to render a chat line, as in "Name says: hello"
+impl fmt::Display for ChatType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChatType::Whisper => write!(f, "whispers"),
+            ChatType::Shout => write!(f, "shouts"),
+            ChatType::Normal | ChatType::Say | ChatType::OwnerSay | ChatType::Debug => {
+                write!(f, "says")
+            }
+            ChatType::StartTyping => write!(f, "started typing"),
+            ChatType::StopTyping => write!(f, "stopped typing"),
+            ChatType::Unknown => write!(f, "says"),
+        }
+    }
+}
+
+impl From<&ClientChatType> for ChatType {
+    fn from(chat_type: &ClientChatType) -> Self {
+        match chat_type {
+            ClientChatType::Whisper => ChatType::Whisper,
+            ClientChatType::Normal => ChatType::Normal,
+            ClientChatType::Shout => ChatType::Shout,
+            ClientChatType::Say => ChatType::Say,
+            ClientChatType::StartTyping => ChatType::StartTyping,
+            ClientChatType::StopTyping => ChatType::StopTyping,
+            ClientChatType::Debug => ChatType::Debug,
+            ClientChatType::Unknown => ChatType::Unknown,
+        }
+    }
+}
+
+impl ChatFromSimulator {
+    /// builds the chat the server would echo back for a message sent by this agent.
+    /// The source and owner are the sending agent, so the UI can tell it is self-origin.
+    pub fn local_echo(from_name: String, chat: &ChatFromViewer) -> Self {
+        ChatFromSimulator {
+            from_name,
+            source_id: chat.agent_id,
+            owner_id: chat.agent_id,
+            source_type: SourceType::Agent,
+            chat_type: ChatType::from(&chat.message_type),
+            audible: Audible::Fully,
+            position: Vec3::ZERO,
+            message: chat.message.clone(),
+        }
+    }
+}
 
 impl PacketData for ChatFromSimulator {
-    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
         let mut cursor = Cursor::new(bytes);
 
         // FromName
-        // skip one byte of prefix
-        // TODO: IF EVERYTHING BREAKS WHEN CONNECTING TO A REAL SERVER
-        // THIS IS WHY. YOUR SENDCHATFROMVIEWER IS BROKEN
-        // AND SO IS THIS
-        let mut from_name_bytes = Vec::new();
-        cursor.read_until(0, &mut from_name_bytes)?; // Read until null terminator
-        let from_name =
-            String::from_utf8(from_name_bytes.into_iter().filter(|&b| b != 0).collect())
-                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let from_name = read_string_1(&mut cursor)?;
 
         // SourceID
-        let source_id = Uuid::from_slice(
-            &cursor.get_ref()[cursor.position() as usize..cursor.position() as usize + 16],
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        cursor.set_position(cursor.position() + 16);
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let source_id = Uuid::from_bytes(uuid_bytes);
 
         // OwnerID
-        let owner_id = Uuid::from_slice(
-            &cursor.get_ref()[cursor.position() as usize..cursor.position() as usize + 16],
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        cursor.set_position(cursor.position() + 16);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let owner_id = Uuid::from_bytes(uuid_bytes);
 
         // SourceType
         let source_type_byte = cursor.read_u8()?;
@@ -185,36 +233,29 @@ impl PacketData for ChatFromSimulator {
             z: cursor.read_f32::<byteorder::LittleEndian>()?,
         };
 
-        // skip two bytes of size prefix
-        cursor.set_position(cursor.position() + 1);
         // Message
-        let mut message_bytes = Vec::new();
-        cursor.read_to_end(&mut message_bytes)?;
-        if !message_bytes.is_empty() {
-            message_bytes.pop();
-        }
-        let message = String::from_utf8(message_bytes)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let message = read_string_2(&mut cursor)?;
 
-        Ok(Self {
-            from_name,
-            source_id,
-            owner_id,
-            source_type,
-            chat_type,
-            audible,
-            position,
-            message,
-        })
+        Ok((
+            Self {
+                from_name,
+                source_id,
+                owner_id,
+                source_type,
+                chat_type,
+                audible,
+                position,
+                message,
+            },
+            cursor.position() as usize,
+        ))
     }
 
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
 
-        // Convert `from_name` to bytes (null-terminated)
-        let name_bytes = self.from_name.as_bytes();
-        bytes.extend_from_slice(name_bytes);
-        bytes.push(0);
+        // Convert `from_name` to bytes (size prefixed and null-terminated)
+        write_string_1(&mut bytes, &self.from_name);
 
         // Convert `source_id` and `owner_id` to bytes
         bytes.extend_from_slice(self.source_id.as_bytes());
@@ -230,10 +271,8 @@ impl PacketData for ChatFromSimulator {
         bytes.extend_from_slice(&self.position.y.to_le_bytes());
         bytes.extend_from_slice(&self.position.z.to_le_bytes());
 
-        // Convert `message` to bytes (null-terminated)
-        let message_bytes = self.message.as_bytes();
-        bytes.extend_from_slice(message_bytes);
-        bytes.push(0);
+        // Convert `message` to bytes (size prefixed and null-terminated)
+        write_string_2(&mut bytes, &self.message);
 
         bytes
     }
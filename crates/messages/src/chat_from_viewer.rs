@@ -1,43 +1,44 @@
 use crate::packet_types::PacketType;
+use crate::utils::variable::{read_string_2, write_string_2};
 
 use super::{
-    header::{Header, PacketFrequency},
+    header::PacketFrequency,
     packet::{Packet, PacketData},
 };
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
 use std::io::Read;
 use std::io::{self, Cursor};
 use uuid::Uuid;
 
 impl Packet {
     pub fn new_chat_from_viewer(chat_from_viewer: ChatFromViewer) -> Self {
-        Packet {
-            header: Header {
-                id: 80,
-                frequency: PacketFrequency::Low,
-                reliable: true,
-                sequence_number: 0,
-                appended_acks: false,
-                zerocoded: false,
-                resent: false,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::ChatFromViewer(Box::new(chat_from_viewer)),
-        }
+        Packet::new(
+            80,
+            PacketFrequency::Low,
+            PacketType::ChatFromViewer(Box::new(chat_from_viewer)),
+        )
+        .reliable(true)
     }
 }
 
-#[derive(Debug, Clone)]
+/// the channel that nearby avatars hear
+pub const PUBLIC_CHANNEL: i32 = 0;
+/// the channel script errors and debug messages are sent on
+pub const DEBUG_CHANNEL: i32 = i32::MAX;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatFromViewer {
     pub agent_id: Uuid,
     pub session_id: Uuid,
     pub message: String,
     pub message_type: ClientChatType,
+    /// the channel to chat on. Only PUBLIC_CHANNEL is shown to other avatars, every other
+    /// channel, including negative ones, can only be heard by scripts listening on it.
     pub channel: i32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ClientChatType {
     Whisper,
     Normal,
@@ -77,7 +78,7 @@ impl ClientChatType {
 }
 
 impl PacketData for ChatFromViewer {
-    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
         let mut cursor = Cursor::new(bytes);
 
         // Deserialize AgentData
@@ -89,25 +90,23 @@ impl PacketData for ChatFromViewer {
         cursor.read_exact(&mut session_id_bytes)?;
         let session_id = Uuid::from_bytes(session_id_bytes);
 
-        let message_length = cursor.read_u16::<LittleEndian>()? as usize;
-
-        let mut message_bytes = vec![0u8; message_length];
-        cursor.read_exact(&mut message_bytes)?;
-
-        let message = String::from_utf8(message_bytes).unwrap();
+        let message = read_string_2(&mut cursor)?;
 
         let message_type_byte = cursor.read_u8()?;
         let message_type = ClientChatType::from_bytes(message_type_byte);
 
-        let channel = cursor.read_i32::<BigEndian>()?;
+        let channel = cursor.read_i32::<LittleEndian>()?;
 
-        Ok(ChatFromViewer {
-            agent_id,
-            session_id,
-            message,
-            message_type,
-            channel,
-        })
+        Ok((
+            ChatFromViewer {
+                agent_id,
+                session_id,
+                message,
+                message_type,
+                channel,
+            },
+            cursor.position() as usize,
+        ))
     }
 
     fn to_bytes(&self) -> Vec<u8> {
@@ -116,16 +115,11 @@ impl PacketData for ChatFromViewer {
         bytes.extend_from_slice(self.agent_id.as_bytes());
         bytes.extend_from_slice(self.session_id.as_bytes());
 
-        let message_bytes = self.message.as_bytes();
-
-        bytes.extend_from_slice(&(message_bytes.len() as u16).to_le_bytes());
-        bytes.extend_from_slice(message_bytes);
+        write_string_2(&mut bytes, &self.message);
 
         bytes.push(self.message_type.to_bytes());
         bytes.extend_from_slice(&self.channel.to_le_bytes());
 
-        bytes.push(0);
-
         bytes
     }
 }
@@ -1,29 +1,23 @@
-use crate::header::{Header, PacketFrequency};
+use crate::header::PacketFrequency;
 use crate::packet::{Packet, PacketData};
 use crate::packet_types::PacketType;
-use std::io;
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
 use uuid::Uuid;
 
 impl Packet {
     pub fn new_circuit_code(circuit_code_block: CircuitCodeData) -> Self {
-        Packet {
-            header: Header {
-                id: 3,
-                frequency: PacketFrequency::Low,
-                reliable: true,
-                sequence_number: 0,
-                appended_acks: false,
-                zerocoded: false,
-                resent: false,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::CircuitCode(Box::new(circuit_code_block)),
-        }
+        Packet::new(
+            3,
+            PacketFrequency::Low,
+            PacketType::CircuitCode(Box::new(circuit_code_block)),
+        )
+        .reliable(true)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CircuitCodeData {
     pub code: u32,
     pub session_id: Uuid,
@@ -31,16 +25,24 @@ pub struct CircuitCodeData {
 }
 
 impl PacketData for CircuitCodeData {
-    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
-        let code = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
-        let session_id = Uuid::from_slice(&bytes[4..20]).unwrap();
-        let id = Uuid::from_slice(&bytes[20..36]).unwrap();
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+        let code = cursor.read_u32::<LittleEndian>()?;
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let id = Uuid::from_bytes(uuid_bytes);
 
-        Ok(Self {
-            code,
-            session_id,
-            id,
-        })
+        Ok((
+            Self {
+                code,
+                session_id,
+                id,
+            },
+            cursor.position() as usize,
+        ))
     }
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(36);
@@ -1,15 +1,34 @@
-use super::packet::PacketData;
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use crate::packet_types::PacketType;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::io::{self, Cursor, Write};
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read, Write};
+use uuid::Uuid;
 
-/// ID: 6
-/// Frequency: Medium
+// ID: 6
+// Frequency: Medium
 
-#[derive(Debug, Clone)]
+impl Packet {
+    pub fn new_coarse_location_update(coarse_location_update: CoarseLocationUpdate) -> Self {
+        Packet::new(
+            6,
+            PacketFrequency::Medium,
+            PacketType::CoarseLocationUpdate(Box::new(coarse_location_update)),
+        )
+    }
+}
+
+/// the position of an avatar in the region, in meters.
+/// z is stored divided by four, so it can fit in a single byte.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MinimapEntities {
-    x: u8,
-    y: u8,
-    z: u8,
+    pub x: u8,
+    pub y: u8,
+    pub z: u8,
 }
 impl MinimapEntities {
     pub fn from_bytes(bytes: &[u8], i: &mut usize) -> io::Result<Self> {
@@ -28,15 +47,47 @@ impl MinimapEntities {
         Ok(())
     }
 }
-#[derive(Debug, Clone)]
+/// the positions of the nearby avatars, used for drawing the minimap
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoarseLocationUpdate {
-    locations: Vec<MinimapEntities>,
-    you: i16,
-    prey: i16,
+    pub locations: Vec<MinimapEntities>,
+    /// the index of the user in locations, or -1 if they aren't in it
+    pub you: i16,
+    /// the index of the tracked avatar in locations, or -1 if there isn't one
+    pub prey: i16,
+    /// the agent IDs for each location, in the same order.
+    /// Older servers don't send these.
+    pub agent_ids: Vec<Uuid>,
+}
+
+impl CoarseLocationUpdate {
+    /// returns each avatar's agent ID, if the server sent it, along with its position in meters
+    pub fn positions(&self) -> Vec<(Option<Uuid>, Vec3)> {
+        self.locations
+            .iter()
+            .enumerate()
+            .map(|(i, location)| {
+                (
+                    self.agent_ids.get(i).copied(),
+                    Vec3::new(
+                        location.x as f32,
+                        location.y as f32,
+                        location.z as f32 * 4.0,
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    /// the position of the user, if they are in the update
+    pub fn your_position(&self) -> Option<Vec3> {
+        let you = usize::try_from(self.you).ok()?;
+        self.positions().get(you).map(|(_, position)| *position)
+    }
 }
 
 impl PacketData for CoarseLocationUpdate {
-    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
         let mut cursor = Cursor::new(bytes);
         let location_count = cursor.read_u8()? as usize;
         let mut locations = Vec::with_capacity(location_count);
@@ -52,11 +103,25 @@ impl PacketData for CoarseLocationUpdate {
         let you = cursor.read_i16::<LittleEndian>()?;
         let prey = cursor.read_i16::<LittleEndian>()?;
 
-        Ok(CoarseLocationUpdate {
-            locations,
-            you,
-            prey,
-        })
+        // the agent data block is missing from packets sent by older servers
+        let mut agent_ids = Vec::new();
+        if let Ok(agent_count) = cursor.read_u8() {
+            let mut uuid_bytes = [0u8; 16];
+            for _ in 0..agent_count {
+                cursor.read_exact(&mut uuid_bytes)?;
+                agent_ids.push(Uuid::from_bytes(uuid_bytes));
+            }
+        }
+
+        Ok((
+            CoarseLocationUpdate {
+                locations,
+                you,
+                prey,
+                agent_ids,
+            },
+            cursor.position() as usize,
+        ))
     }
 
     fn to_bytes(&self) -> Vec<u8> {
@@ -74,6 +139,12 @@ impl PacketData for CoarseLocationUpdate {
         bytes.write_i16::<LittleEndian>(self.you).unwrap();
         bytes.write_i16::<LittleEndian>(self.prey).unwrap();
 
+        // Serialize AgentDataBlocks
+        bytes.push(self.agent_ids.len() as u8);
+        for agent_id in &self.agent_ids {
+            bytes.extend_from_slice(agent_id.as_bytes());
+        }
+
         bytes
     }
 }
@@ -1,34 +1,28 @@
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{Cursor, Read};
 use uuid::Uuid;
 
 use crate::packet_types::PacketType;
 
 use super::{
-    header::{Header, PacketFrequency},
+    header::PacketFrequency,
     packet::{Packet, PacketData},
 };
+use serde::{Deserialize, Serialize};
 
 impl Packet {
     pub fn new_complete_agent_movement(
         complete_agent_movement_data: CompleteAgentMovementData,
     ) -> Self {
-        Packet {
-            header: Header {
-                id: 249,
-                frequency: PacketFrequency::Low,
-                reliable: false,
-                sequence_number: 0,
-                appended_acks: false,
-                zerocoded: false,
-                resent: false,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::CompleteAgentMovementData(Box::new(complete_agent_movement_data)),
-        }
+        Packet::new(
+            249,
+            PacketFrequency::Low,
+            PacketType::CompleteAgentMovementData(Box::new(complete_agent_movement_data)),
+        )
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompleteAgentMovementData {
     pub agent_id: Uuid,
     pub session_id: Uuid,
@@ -36,16 +30,25 @@ pub struct CompleteAgentMovementData {
 }
 
 impl PacketData for CompleteAgentMovementData {
-    fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
-        let circuit_code = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
-        let session_id = Uuid::from_slice(&bytes[4..20]).unwrap();
-        let agent_id = Uuid::from_slice(&bytes[20..36]).unwrap();
-
-        Ok(CompleteAgentMovementData {
-            agent_id,
-            session_id,
-            circuit_code,
-        })
+    fn from_bytes_with_len(bytes: &[u8]) -> std::io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+
+        let circuit_code = cursor.read_u32::<LittleEndian>()?;
+
+        Ok((
+            CompleteAgentMovementData {
+                agent_id,
+                session_id,
+                circuit_code,
+            },
+            cursor.position() as usize,
+        ))
     }
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(36);
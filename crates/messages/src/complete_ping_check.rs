@@ -1,42 +1,40 @@
 use super::{
-    header::{Header, PacketFrequency},
+    header::PacketFrequency,
     packet::{Packet, PacketData},
 };
 use crate::packet_types::PacketType;
+use serde::{Deserialize, Serialize};
+
+// ID: 2
+// Frequency: High
 
 impl Packet {
     pub fn new_complete_ping_check(complete_ping_check: CompletePingCheck) -> Self {
-        Packet {
-            header: Header {
-                id: 2,
-                reliable: false,
-                resent: false,
-                zerocoded: false,
-                appended_acks: false,
-                sequence_number: 0,
-                frequency: PacketFrequency::High,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::CompletePingCheck(Box::new(complete_ping_check)),
-        }
+        Packet::new(
+            2,
+            PacketFrequency::High,
+            PacketType::CompletePingCheck(Box::new(complete_ping_check)),
+        )
     }
 }
 
-#[derive(Debug, Clone)]
+/// The answer to a StartPingCheck. The time between the two is the latency of the circuit.
+/// The body is a single byte, the ping id of the StartPingCheck.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompletePingCheck {
+    /// the ping_id of the StartPingCheck being answered
     pub ping_id: u8,
 }
 
 impl PacketData for CompletePingCheck {
-    fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
-        let ping_id = bytes[0];
+    fn from_bytes_with_len(bytes: &[u8]) -> std::io::Result<(Self, usize)> {
+        let ping_id = *bytes.first().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Missing ping ID")
+        })?;
 
-        Ok(CompletePingCheck { ping_id })
+        Ok((CompletePingCheck { ping_id }, 1))
     }
     fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(5);
-        bytes.push(self.ping_id);
-        bytes
+        vec![self.ping_id]
     }
 }
@@ -1,15 +1,16 @@
 use super::packet::PacketData;
+use serde::{Deserialize, Serialize};
 use std::io;
 
 // ID: 152
 // Frequency: Low
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DisableSimulator {}
 
 impl PacketData for DisableSimulator {
-    fn from_bytes(_: &[u8]) -> io::Result<Self> {
-        Ok(DisableSimulator {})
+    fn from_bytes_with_len(_: &[u8]) -> io::Result<(Self, usize)> {
+        Ok((DisableSimulator {}, 0))
     }
     fn to_bytes(&self) -> Vec<u8> {
         vec![]
@@ -0,0 +1,70 @@
+use crate::packet_types::PacketType;
+use crate::utils::region_handle::RegionHandle;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use std::net::{Ipv4Addr, SocketAddr};
+
+// ID: 151
+// Frequency: Low
+
+impl Packet {
+    pub fn new_enable_simulator(enable_simulator: EnableSimulator) -> Self {
+        Packet::new(
+            151,
+            PacketFrequency::Low,
+            PacketType::EnableSimulator(Box::new(enable_simulator)),
+        )
+        .reliable(true)
+    }
+}
+
+/// A neighboring region the client should connect to ahead of time, so crossing into it is
+/// seamless. The agent already has a child presence there, using the same circuit code.
+/// https://wiki.secondlife.com/wiki/EnableSimulator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnableSimulator {
+    pub region_handle: RegionHandle,
+    pub ip: Ipv4Addr,
+    pub port: u16,
+}
+
+impl EnableSimulator {
+    /// the address of the neighbor's circuit
+    pub fn address(&self) -> SocketAddr {
+        SocketAddr::from((self.ip, self.port))
+    }
+}
+
+impl PacketData for EnableSimulator {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let region_handle = RegionHandle(cursor.read_u64::<LittleEndian>()?);
+        let mut ip = [0u8; 4];
+        cursor.read_exact(&mut ip)?;
+        // ports are sent in network order, unlike everything else
+        let port = cursor.read_u16::<BigEndian>()?;
+
+        Ok((
+            EnableSimulator {
+                region_handle,
+                ip: Ipv4Addr::from(ip),
+                port,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(14);
+        bytes.extend_from_slice(&self.region_handle.0.to_le_bytes());
+        bytes.extend_from_slice(&self.ip.octets());
+        bytes.extend_from_slice(&self.port.to_be_bytes());
+        bytes
+    }
+}
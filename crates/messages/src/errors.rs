@@ -42,16 +42,30 @@ impl CompleteAgentMovementError {
 /// Acks are sent to and from the server to verify packages got to their destination.
 /// https://wiki.secondlife.com/wiki/PacketAck
 #[derive(Debug, Clone, Error, Serialize, Deserialize)]
-#[error("{message}")]
+#[error("{message}: packet {packet_id} to {address} after {attempts} attempts")]
 pub struct AckError {
     /// String message that contains error information
     pub message: String,
+    /// the sequence number of the packet that was not acked
+    pub packet_id: u32,
+    /// how many times the packet was sent
+    pub attempts: u8,
+    /// the address the packet was sent to
+    pub address: String,
 }
 impl AckError {
     /// Function for creating a new AckError
-    pub fn new(message: impl Into<String>) -> Self {
+    pub fn new(
+        message: impl Into<String>,
+        packet_id: u32,
+        attempts: u8,
+        address: impl Into<String>,
+    ) -> Self {
         Self {
             message: message.into(),
+            packet_id,
+            attempts,
+            address: address.into(),
         }
     }
 }
@@ -73,6 +87,103 @@ impl MailboxError {
     }
 }
 
+/// This represents errors that can arise from capability requests failing.
+/// Capabilities are HTTP endpoints provided by the simulator.
+/// https://wiki.secondlife.com/wiki/Capabilities
+#[derive(Debug, Clone, Error, Serialize, Deserialize)]
+#[error("{message}")]
+pub struct CapabilityError {
+    /// String message that contains error information
+    pub message: String,
+    /// the response, if the capability answered with an error status
+    pub response: Option<HttpErrorResponse>,
+}
+impl CapabilityError {
+    /// Function for creating a new CapabilityError
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            response: None,
+        }
+    }
+    /// Function for creating a CapabilityError from an error response
+    pub fn with_response(message: impl Into<String>, response: HttpErrorResponse) -> Self {
+        Self {
+            message: message.into(),
+            response: Some(response),
+        }
+    }
+    /// the HTTP status the capability answered with, if it answered with an error
+    pub fn status(&self) -> Option<u16> {
+        self.response.as_ref().map(|response| response.status)
+    }
+}
+
+/// An error response from a capability.
+/// The status and body tell a capability that expired or was never granted, which answers 404
+/// or an HTML error page, apart from an asset that is really missing or corrupt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpErrorResponse {
+    /// the HTTP status code
+    pub status: u16,
+    /// the response headers, in the order they were sent
+    pub headers: Vec<(String, String)>,
+    /// the start of the body, up to HttpErrorResponse::BODY_PREVIEW_LENGTH bytes
+    pub body: Vec<u8>,
+}
+impl HttpErrorResponse {
+    /// how much of the body is kept. Error pages are small, and the start is enough to tell
+    /// what sent them.
+    pub const BODY_PREVIEW_LENGTH: usize = 512;
+
+    /// the value of a header, ignoring the case of its name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+    /// the start of the body as text, for logging
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// This represents errors that arise from resuming a circuit that went silent, like after the
+/// client's network changed. The session is kept, but a new socket sends the circuit handshake
+/// again.
+#[derive(Debug, Clone, Error, Serialize, Deserialize)]
+#[error("{message}")]
+pub struct ResumeError {
+    /// String message that contains error information
+    pub message: String,
+}
+impl ResumeError {
+    /// Function for creating a new ResumeError
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+/// This represents errors that arise from waiting too long for something to happen, like for the
+/// mailbox to start running
+#[derive(Debug, Clone, Error, Serialize, Deserialize)]
+#[error("{message}")]
+pub struct TimeoutError {
+    /// String message that contains error information
+    pub message: String,
+}
+impl TimeoutError {
+    /// Function for creating a new TimeoutError
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
 /// Represents errors that arise from failures within the session
 #[derive(Clone, Debug, Error, Serialize, Deserialize)]
 pub enum SessionError {
@@ -91,6 +202,15 @@ pub enum SessionError {
     /// This is sent when Acknowledgement packets fail
     #[error("AckError: {0}")]
     AckError(#[from] AckError),
+    /// This is sent when a capability request fails
+    #[error("CapabilityError: {0}")]
+    Capability(#[from] CapabilityError),
+    /// This is sent when the circuit could not be resumed, or the server rejected it
+    #[error("ResumeError: {0}")]
+    Resume(#[from] ResumeError),
+    /// This is sent when something took longer than it was allowed to
+    #[error("TimeoutError: {0}")]
+    Timeout(#[from] TimeoutError),
 }
 impl SessionError {
     /// Create a new LoginError from the message's login error
@@ -0,0 +1,38 @@
+use super::packet::PacketData;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+/// A friend of the agent came online or went offline.
+/// This is not a packet in the spec. The mailbox sends one to the UI for each friend in an
+/// OnlineNotification or OfflineNotification.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FriendPresence {
+    pub agent_id: Uuid,
+    pub online: bool,
+}
+
+impl PacketData for FriendPresence {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let mut online = [0u8; 1];
+        cursor.read_exact(&mut online)?;
+
+        Ok((
+            FriendPresence {
+                agent_id: Uuid::from_bytes(uuid_bytes),
+                online: online[0] != 0,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(17);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.push(self.online as u8);
+        bytes
+    }
+}
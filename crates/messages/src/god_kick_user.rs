@@ -0,0 +1,97 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_string_2, write_string_2};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 165
+// Frequency: Low
+
+/// log the agent out
+pub const KICK_FLAGS_DEFAULT: u32 = 0;
+/// freeze the agent in place instead of logging it out
+pub const KICK_FLAGS_FREEZE: u32 = 1 << 0;
+/// let a frozen agent move again
+pub const KICK_FLAGS_UNFREEZE: u32 = 1 << 1;
+
+impl Packet {
+    pub fn new_god_kick_user(god_kick_user: GodKickUser) -> Self {
+        Packet::new(
+            165,
+            PacketFrequency::Low,
+            PacketType::GodKickUser(Box::new(god_kick_user)),
+        )
+        .reliable(true)
+    }
+}
+
+/// Kicks, freezes or unfreezes an agent. The sender needs god powers, from a
+/// RequestGodlikePowers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GodKickUser {
+    /// the agent ID of the god doing the kicking
+    pub god_id: Uuid,
+    pub god_session_id: Uuid,
+    /// the agent being kicked
+    pub agent_id: Uuid,
+    /// one of the KICK_FLAGS constants
+    pub kick_flags: u32,
+    /// the message shown to the kicked agent
+    pub reason: String,
+}
+
+impl GodKickUser {
+    /// log an agent out, showing it the reason
+    pub fn kick(god_id: Uuid, god_session_id: Uuid, agent_id: Uuid, reason: &str) -> Self {
+        GodKickUser {
+            god_id,
+            god_session_id,
+            agent_id,
+            kick_flags: KICK_FLAGS_DEFAULT,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl PacketData for GodKickUser {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let god_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let god_session_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        let kick_flags = cursor.read_u32::<LittleEndian>()?;
+
+        let reason = read_string_2(&mut cursor)?;
+
+        Ok((
+            GodKickUser {
+                god_id,
+                god_session_id,
+                agent_id,
+                kick_flags,
+                reason,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(55 + self.reason.len());
+        bytes.extend_from_slice(self.god_id.as_bytes());
+        bytes.extend_from_slice(self.god_session_id.as_bytes());
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(&self.kick_flags.to_le_bytes());
+        write_string_2(&mut bytes, &self.reason);
+        bytes
+    }
+}
@@ -0,0 +1,86 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::ReadBytesExt;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 259
+// Frequency: Low
+
+/// no god powers
+pub const GOD_LEVEL_NONE: u8 = 0;
+/// customer service
+pub const GOD_LEVEL_CUSTOMER_SERVICE: u8 = 100;
+/// liaisons
+pub const GOD_LEVEL_LIAISON: u8 = 150;
+/// full god powers
+pub const GOD_LEVEL_FULL: u8 = 200;
+/// grid maintenance
+pub const GOD_LEVEL_MAINTENANCE: u8 = 250;
+
+impl Packet {
+    pub fn new_grant_godlike_powers(grant_godlike_powers: GrantGodlikePowers) -> Self {
+        Packet::new(
+            259,
+            PacketFrequency::Low,
+            PacketType::GrantGodlikePowers(Box::new(grant_godlike_powers)),
+        )
+        .reliable(true)
+    }
+}
+
+/// The simulator's answer to a RequestGodlikePowers, with the god level the agent now has.
+/// A level of GOD_LEVEL_NONE means the powers were turned off, or refused.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrantGodlikePowers {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub god_level: u8,
+    /// the token from the RequestGodlikePowers
+    pub token: Uuid,
+}
+
+impl GrantGodlikePowers {
+    /// whether the agent has any god powers
+    pub fn is_godlike(&self) -> bool {
+        self.god_level > GOD_LEVEL_NONE
+    }
+}
+
+impl PacketData for GrantGodlikePowers {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        let god_level = cursor.read_u8()?;
+        cursor.read_exact(&mut uuid_bytes)?;
+        let token = Uuid::from_bytes(uuid_bytes);
+
+        Ok((
+            GrantGodlikePowers {
+                agent_id,
+                session_id,
+                god_level,
+                token,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(49);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.push(self.god_level);
+        bytes.extend_from_slice(self.token.as_bytes());
+        bytes
+    }
+}
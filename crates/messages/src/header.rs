@@ -17,10 +17,41 @@ pub struct Header {
     pub id: u16,
     pub frequency: PacketFrequency,
     pub ack_list: Option<Vec<u32>>,
+    /// the number of bytes the header takes up in the packet, before the body.
+    /// Set by the constructors and by try_from_bytes.
     pub size: Option<usize>,
 }
 impl Header {
+    /// creates an unreliable, uncompressed header with no acks for a packet with the given ID
+    pub fn new(id: u16, frequency: PacketFrequency) -> Self {
+        let mut header = Header {
+            reliable: false,
+            resent: false,
+            zerocoded: false,
+            appended_acks: false,
+            sequence_number: 0,
+            id,
+            frequency,
+            ack_list: None,
+            size: None,
+        };
+        header.size = Some(header.encoded_size());
+        header
+    }
+    /// the number of bytes to_bytes writes.
+    /// This is the flags, the sequence number, the extra header byte, and the ID. The ID of a
+    /// zerocoded low frequency packet is zerocoded too, so its leading zero takes up two bytes.
+    pub fn encoded_size(&self) -> usize {
+        6 + self.frequency.to_bytes(self).len()
+    }
     pub fn try_from_bytes(bytes: &[u8]) -> Result<Header, std::io::Error> {
+        // the flags, the sequence number, and the extra header byte come before the ID
+        if bytes.len() < 6 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Packet is too short to contain a header",
+            ));
+        }
         let mut pos = 0;
 
         let flags = bytes[pos];
@@ -48,23 +79,22 @@ impl Header {
         pos += frequency_size;
 
         let ack_list = if appended_acks {
-            let count = bytes[pos];
-            pos -= 1;
-
-            let mut acks = Vec::with_capacity(count as usize);
-
-            for _ in 0..count {
-                let offset = pos - 3;
-                let ack = u32::from_be_bytes([
-                    bytes[offset],
-                    bytes[offset + 1],
-                    bytes[offset + 2],
-                    bytes[offset + 3],
-                ]);
-
-                acks.push(ack);
-                pos -= 4;
-            }
+            // appended acks live at the very end of the packet, after the body.
+            // The last byte is the number of acks, and the acks are the 4 byte big-endian
+            // sequence numbers before it.
+            let count = bytes[bytes.len() - 1] as usize;
+            let ack_start = bytes
+                .len()
+                .checked_sub(1 + count * 4)
+                .filter(|start| *start >= pos)
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Invalid appended ack count")
+                })?;
+
+            let acks = bytes[ack_start..bytes.len() - 1]
+                .chunks_exact(4)
+                .map(|ack| u32::from_be_bytes([ack[0], ack[1], ack[2], ack[3]]))
+                .collect();
             Some(acks)
         } else {
             None
@@ -112,15 +142,8 @@ impl Header {
         // Add the ID and frequency
         bytes.extend_from_slice(&self.frequency.to_bytes(self));
 
-        // Append the ack list if appended_acks is true
-        if self.appended_acks {
-            if let Some(ref ack_list) = self.ack_list {
-                bytes.push(ack_list.len() as u8);
-                for ack in ack_list {
-                    bytes.extend_from_slice(&ack.to_be_bytes());
-                }
-            }
-        }
+        // the appended acks are not part of the header, they are written to the end of the
+        // packet by Packet::to_bytes
 
         bytes
     }
@@ -167,7 +190,12 @@ impl PacketFrequency {
                 bytes.push(0xFF);
                 bytes.push(0xFF);
                 let id_bytes = uint16_to_bytes_big(header.id);
-                bytes.extend_from_slice(&id_bytes);
+                // the ID is part of the zerocoded data, so a zero is written as a run of one
+                if header.zerocoded && id_bytes[0] == 0 {
+                    bytes.extend_from_slice(&[0x00, 0x01, id_bytes[1]]);
+                } else {
+                    bytes.extend_from_slice(&id_bytes);
+                }
             }
             PacketFrequency::Fixed => {
                 bytes.push(0xFF);
@@ -186,55 +214,31 @@ impl PacketFrequency {
             ));
         }
 
-        let id;
-        let frequency;
-        let size;
-
-        // this match against variable sizes is dangerous and doesn't really give that many
-        // benefits
-        // this should probably be rewritten so it accepts a fixed size to the end zeroed out, and
-        // then does operations on that.
-        match bytes.len() {
-            2 => {
-                frequency = PacketFrequency::High;
-                id = bytes[0] as u16;
-                size = 1;
-            }
-            3 => {
-                frequency = PacketFrequency::Medium;
-                id = bytes[2] as u16;
-                size = 3;
-            }
-            5 | 6 => {
-                if bytes[1] == 0xFF && bytes[2] == 0xFF && bytes[3] == 0xFF {
-                    frequency = PacketFrequency::Fixed;
-                    id = bytes[4] as u16;
-                    size = 5;
-                } else if bytes[1] == 0xFF && bytes[2] == 0xFF {
-                    frequency = PacketFrequency::Low;
-                    id = if zerocoded && bytes[3] == 0 {
-                        bytes[5] as u16
-                    } else {
-                        u16::from_be_bytes([bytes[3], bytes[4]])
-                    };
-                    size = 5;
-                } else if bytes[1] == 0xFF {
-                    frequency = PacketFrequency::Medium;
-                    id = bytes[2] as u16;
-                    size = 3; // First 2 bytes considered for Medium frequency
-                } else {
-                    frequency = PacketFrequency::High;
-                    id = bytes[1] as u16;
-                    size = 2; // First byte considered for High frequency
-                }
-            }
-            _ => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Unsupported packet length",
-                ));
-            }
-        }
+        // the first byte is the extra header byte, and the ID starts after it.
+        // The frequency is decided by how many 0xFF bytes lead the ID, not by how many bytes
+        // are left, because short packets like CompletePingCheck have fewer than six.
+        let byte = |index: usize| {
+            bytes
+                .get(index)
+                .copied()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated packet ID"))
+        };
+        let (frequency, id, size) = if byte(1)? != 0xFF {
+            (PacketFrequency::High, byte(1)? as u16, 2)
+        } else if byte(2)? != 0xFF {
+            (PacketFrequency::Medium, byte(2)? as u16, 3)
+        } else if byte(3)? == 0xFF {
+            (PacketFrequency::Fixed, byte(4)? as u16, 5)
+        } else if zerocoded && byte(3)? == 0 {
+            // the leading zero of the ID is encoded as 00 01
+            (PacketFrequency::Low, byte(5)? as u16, 6)
+        } else {
+            (
+                PacketFrequency::Low,
+                u16::from_be_bytes([byte(3)?, byte(4)?]),
+                5,
+            )
+        };
         Ok((frequency, id, size))
     }
 }
@@ -0,0 +1,73 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_variable_2, write_variable_2};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 9
+// Frequency: High
+
+impl Packet {
+    pub fn new_image_data(image_data: ImageData) -> Self {
+        Packet::new(
+            9,
+            PacketFrequency::High,
+            PacketType::ImageData(Box::new(image_data)),
+        )
+    }
+}
+
+/// the first packet of a texture sent in response to a RequestImage.
+/// The rest of the texture follows in ImagePackets, numbered from 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageData {
+    pub id: Uuid,
+    /// the image format. 2 is JPEG2000.
+    pub codec: u8,
+    /// the size of the whole texture in bytes
+    pub size: u32,
+    /// how many packets the texture is sent in, including this one
+    pub packets: u16,
+    pub data: Vec<u8>,
+}
+
+impl PacketData for ImageData {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let id = Uuid::from_bytes(uuid_bytes);
+        let codec = cursor.read_u8()?;
+        let size = cursor.read_u32::<LittleEndian>()?;
+        let packets = cursor.read_u16::<LittleEndian>()?;
+
+        let data = read_variable_2(&mut cursor)?;
+
+        Ok((
+            ImageData {
+                id,
+                codec,
+                size,
+                packets,
+                data,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(25 + self.data.len());
+        bytes.extend_from_slice(self.id.as_bytes());
+        bytes.push(self.codec);
+        bytes.extend_from_slice(&self.size.to_le_bytes());
+        bytes.extend_from_slice(&self.packets.to_le_bytes());
+        write_variable_2(&mut bytes, &self.data);
+        bytes
+    }
+}
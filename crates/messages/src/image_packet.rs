@@ -0,0 +1,55 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_variable_2, write_variable_2};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 10
+// Frequency: High
+
+impl Packet {
+    pub fn new_image_packet(image_packet: ImagePacket) -> Self {
+        Packet::new(
+            10,
+            PacketFrequency::High,
+            PacketType::ImagePacket(Box::new(image_packet)),
+        )
+    }
+}
+
+/// a piece of a texture that follows its ImageData
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImagePacket {
+    pub id: Uuid,
+    /// the position of this piece in the texture. The ImageData is packet 0.
+    pub packet: u16,
+    pub data: Vec<u8>,
+}
+
+impl PacketData for ImagePacket {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let id = Uuid::from_bytes(uuid_bytes);
+        let packet = cursor.read_u16::<LittleEndian>()?;
+
+        let data = read_variable_2(&mut cursor)?;
+
+        Ok((ImagePacket { id, packet, data }, cursor.position() as usize))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(20 + self.data.len());
+        bytes.extend_from_slice(self.id.as_bytes());
+        bytes.extend_from_slice(&self.packet.to_le_bytes());
+        write_variable_2(&mut bytes, &self.data);
+        bytes
+    }
+}
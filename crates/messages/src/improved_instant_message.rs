@@ -0,0 +1,416 @@
+use crate::packet_types::PacketType;
+use crate::teleport_lure_request::{
+    TeleportLureRequest, TELEPORT_FLAGS_VIA_GODLIKE_LURE, TELEPORT_FLAGS_VIA_LURE,
+};
+use crate::utils::variable::{
+    read_string_1, read_string_2, read_variable_2, write_string_1, write_string_2, write_variable_2,
+};
+use crate::utils::{agent_access::AgentAccess, region_handle::RegionHandle};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 254
+// Frequency: Low
+
+impl Packet {
+    pub fn new_improved_instant_message(improved_instant_message: ImprovedInstantMessage) -> Self {
+        Packet::new(
+            254,
+            PacketFrequency::Low,
+            PacketType::ImprovedInstantMessage(Box::new(improved_instant_message)),
+        )
+        .reliable(true)
+    }
+}
+
+/// A private message between agents, or from an object or group.
+/// Besides text, the dialog marks offers like group invitations, inventory offers and
+/// friendship offers, whose details are packed into the binary_bucket. Use kind() to decode them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImprovedInstantMessage {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub from_group: bool,
+    pub to_agent_id: Uuid,
+    pub parent_estate_id: u32,
+    pub region_id: Uuid,
+    pub position: Vec3,
+    /// whether the message was stored while the recipient was offline
+    pub offline: bool,
+    pub dialog: InstantMessageDialog,
+    /// the IM session, or the transaction the offer is answered with
+    pub id: Uuid,
+    pub timestamp: u32,
+    pub from_agent_name: String,
+    pub message: String,
+    /// extra data whose layout depends on the dialog
+    pub binary_bucket: Vec<u8>,
+}
+
+/// What an instant message is for
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum InstantMessageDialog {
+    MessageFromAgent,
+    MessageBox,
+    GroupInvitation,
+    InventoryOffered,
+    InventoryAccepted,
+    InventoryDeclined,
+    TaskInventoryOffered,
+    TaskInventoryAccepted,
+    TaskInventoryDeclined,
+    SessionInvite,
+    SessionSend,
+    SessionLeave,
+    MessageFromObject,
+    BusyAutoResponse,
+    /// an agent offered a teleport to their location
+    RequestTeleport,
+    AcceptTeleport,
+    DenyTeleport,
+    /// a god offered a teleport
+    GodlikeRequestTeleport,
+    GotoUrl,
+    GroupNotice,
+    GroupInvitationAccept,
+    GroupInvitationDecline,
+    FriendshipOffered,
+    FriendshipAccepted,
+    FriendshipDeclined,
+    StartTyping,
+    StopTyping,
+    Unknown(u8),
+}
+impl InstantMessageDialog {
+    pub fn from_bytes(byte: u8) -> Self {
+        match byte {
+            0 => InstantMessageDialog::MessageFromAgent,
+            1 => InstantMessageDialog::MessageBox,
+            3 => InstantMessageDialog::GroupInvitation,
+            4 => InstantMessageDialog::InventoryOffered,
+            5 => InstantMessageDialog::InventoryAccepted,
+            6 => InstantMessageDialog::InventoryDeclined,
+            9 => InstantMessageDialog::TaskInventoryOffered,
+            10 => InstantMessageDialog::TaskInventoryAccepted,
+            11 => InstantMessageDialog::TaskInventoryDeclined,
+            13 => InstantMessageDialog::SessionInvite,
+            17 => InstantMessageDialog::SessionSend,
+            18 => InstantMessageDialog::SessionLeave,
+            19 => InstantMessageDialog::MessageFromObject,
+            20 => InstantMessageDialog::BusyAutoResponse,
+            22 => InstantMessageDialog::RequestTeleport,
+            23 => InstantMessageDialog::AcceptTeleport,
+            24 => InstantMessageDialog::DenyTeleport,
+            25 => InstantMessageDialog::GodlikeRequestTeleport,
+            28 => InstantMessageDialog::GotoUrl,
+            32 => InstantMessageDialog::GroupNotice,
+            35 => InstantMessageDialog::GroupInvitationAccept,
+            36 => InstantMessageDialog::GroupInvitationDecline,
+            38 => InstantMessageDialog::FriendshipOffered,
+            39 => InstantMessageDialog::FriendshipAccepted,
+            40 => InstantMessageDialog::FriendshipDeclined,
+            41 => InstantMessageDialog::StartTyping,
+            42 => InstantMessageDialog::StopTyping,
+            byte => InstantMessageDialog::Unknown(byte),
+        }
+    }
+    pub fn to_bytes(&self) -> u8 {
+        match self {
+            InstantMessageDialog::MessageFromAgent => 0,
+            InstantMessageDialog::MessageBox => 1,
+            InstantMessageDialog::GroupInvitation => 3,
+            InstantMessageDialog::InventoryOffered => 4,
+            InstantMessageDialog::InventoryAccepted => 5,
+            InstantMessageDialog::InventoryDeclined => 6,
+            InstantMessageDialog::TaskInventoryOffered => 9,
+            InstantMessageDialog::TaskInventoryAccepted => 10,
+            InstantMessageDialog::TaskInventoryDeclined => 11,
+            InstantMessageDialog::SessionInvite => 13,
+            InstantMessageDialog::SessionSend => 17,
+            InstantMessageDialog::SessionLeave => 18,
+            InstantMessageDialog::MessageFromObject => 19,
+            InstantMessageDialog::BusyAutoResponse => 20,
+            InstantMessageDialog::RequestTeleport => 22,
+            InstantMessageDialog::AcceptTeleport => 23,
+            InstantMessageDialog::DenyTeleport => 24,
+            InstantMessageDialog::GodlikeRequestTeleport => 25,
+            InstantMessageDialog::GotoUrl => 28,
+            InstantMessageDialog::GroupNotice => 32,
+            InstantMessageDialog::GroupInvitationAccept => 35,
+            InstantMessageDialog::GroupInvitationDecline => 36,
+            InstantMessageDialog::FriendshipOffered => 38,
+            InstantMessageDialog::FriendshipAccepted => 39,
+            InstantMessageDialog::FriendshipDeclined => 40,
+            InstantMessageDialog::StartTyping => 41,
+            InstantMessageDialog::StopTyping => 42,
+            InstantMessageDialog::Unknown(byte) => *byte,
+        }
+    }
+}
+
+/// An instant message decoded by its dialog, with the binary bucket parsed
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstantMessageKind {
+    /// a plain text message
+    Message,
+    /// an invitation to join a group. The group is the sender of the message.
+    GroupInvitation {
+        group_id: Uuid,
+        /// what joining costs
+        fee: i32,
+        /// the role the agent is invited to
+        role_id: Uuid,
+    },
+    /// an agent offered an inventory item or folder
+    InventoryOffered { asset_type: i8, item_id: Uuid },
+    /// an object offered an inventory item. The item id is only known once it is accepted.
+    TaskInventoryOffered { asset_type: i8 },
+    /// an agent offered friendship
+    FriendshipOffered,
+    /// an agent offered to teleport the agent to their location
+    TeleportLure(TeleportLure),
+    /// a notice sent to a group, optionally with an attached inventory item
+    GroupNotice {
+        group_id: Uuid,
+        /// the asset type and name of the attached item
+        attachment: Option<(i8, String)>,
+    },
+    /// any other dialog, whose bucket is not decoded
+    Other(InstantMessageDialog),
+}
+
+/// A teleport offered by another agent, to wherever they are.
+/// Answer it with accept or decline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeleportLure {
+    /// the agent who offered the teleport
+    pub from_agent_id: Uuid,
+    pub from_agent_name: String,
+    /// the invitation sent with the offer
+    pub message: String,
+    /// the ID the offer is answered with
+    pub lure_id: Uuid,
+    /// whether a god offered the teleport
+    pub godlike: bool,
+    /// where the teleport goes, if the simulator said
+    pub destination: Option<LureDestination>,
+}
+
+/// Where a teleport lure lands the agent
+#[derive(Debug, Clone, PartialEq)]
+pub struct LureDestination {
+    pub region_handle: RegionHandle,
+    /// the landing position, relative to the region's corner
+    pub position: Vec3,
+    /// the direction the agent faces after landing
+    pub look_at: Vec3,
+    /// the maturity rating of the region, which the agent's access has to allow
+    pub maturity: Option<AgentAccess>,
+}
+
+impl LureDestination {
+    /// the bucket of a lure is text, like "256000|256256|128|64|22|1|0|0|M".
+    /// That is the global position of the region's corner, the landing position, the look at
+    /// direction, and optionally the region's maturity.
+    fn from_bucket(bucket: &[u8]) -> Option<Self> {
+        let text = String::from_utf8_lossy(bucket);
+        let mut fields = text.trim_end_matches('\0').split('|');
+        let mut numbers = [0.0f32; 8];
+        for number in numbers.iter_mut() {
+            *number = fields.next()?.trim().parse().ok()?;
+        }
+        let maturity = match fields.next().map(str::trim) {
+            Some("PG") => Some(AgentAccess::PG),
+            Some("M") => Some(AgentAccess::Mature),
+            Some("A") => Some(AgentAccess::Adult),
+            _ => None,
+        };
+        Some(LureDestination {
+            region_handle: RegionHandle::from_global(numbers[0] as u32, numbers[1] as u32),
+            position: Vec3::new(numbers[2], numbers[3], numbers[4]),
+            look_at: Vec3::new(numbers[5], numbers[6], numbers[7]),
+            maturity,
+        })
+    }
+}
+
+impl TeleportLure {
+    /// build the request that accepts the offer and starts the teleport
+    pub fn accept(&self, agent_id: Uuid, session_id: Uuid) -> TeleportLureRequest {
+        TeleportLureRequest {
+            agent_id,
+            session_id,
+            lure_id: self.lure_id,
+            teleport_flags: if self.godlike {
+                TELEPORT_FLAGS_VIA_GODLIKE_LURE
+            } else {
+                TELEPORT_FLAGS_VIA_LURE
+            },
+        }
+    }
+
+    /// build the instant message that tells the agent who offered the teleport it was declined
+    pub fn decline(&self, agent_id: Uuid, session_id: Uuid) -> ImprovedInstantMessage {
+        ImprovedInstantMessage {
+            agent_id,
+            session_id,
+            from_group: false,
+            to_agent_id: self.from_agent_id,
+            parent_estate_id: 0,
+            region_id: Uuid::nil(),
+            position: Vec3::ZERO,
+            offline: false,
+            dialog: InstantMessageDialog::DenyTeleport,
+            id: self.lure_id,
+            timestamp: 0,
+            from_agent_name: String::new(),
+            message: String::new(),
+            binary_bucket: Vec::new(),
+        }
+    }
+}
+
+impl ImprovedInstantMessage {
+    /// decode the dialog and binary bucket.
+    /// Returns an error if the bucket is too short for its dialog.
+    pub fn kind(&self) -> io::Result<InstantMessageKind> {
+        let mut bucket = Cursor::new(&self.binary_bucket);
+        let mut uuid_bytes = [0u8; 16];
+        Ok(match self.dialog {
+            InstantMessageDialog::MessageFromAgent => InstantMessageKind::Message,
+            InstantMessageDialog::GroupInvitation => {
+                // the fee is in network byte order
+                let fee = bucket.read_i32::<BigEndian>()?;
+                bucket.read_exact(&mut uuid_bytes)?;
+                InstantMessageKind::GroupInvitation {
+                    group_id: self.agent_id,
+                    fee,
+                    role_id: Uuid::from_bytes(uuid_bytes),
+                }
+            }
+            InstantMessageDialog::InventoryOffered => {
+                let asset_type = bucket.read_i8()?;
+                bucket.read_exact(&mut uuid_bytes)?;
+                InstantMessageKind::InventoryOffered {
+                    asset_type,
+                    item_id: Uuid::from_bytes(uuid_bytes),
+                }
+            }
+            InstantMessageDialog::TaskInventoryOffered => {
+                InstantMessageKind::TaskInventoryOffered {
+                    asset_type: bucket.read_i8()?,
+                }
+            }
+            InstantMessageDialog::FriendshipOffered => InstantMessageKind::FriendshipOffered,
+            InstantMessageDialog::RequestTeleport
+            | InstantMessageDialog::GodlikeRequestTeleport => {
+                InstantMessageKind::TeleportLure(TeleportLure {
+                    from_agent_id: self.agent_id,
+                    from_agent_name: self.from_agent_name.clone(),
+                    message: self.message.clone(),
+                    lure_id: self.id,
+                    godlike: self.dialog == InstantMessageDialog::GodlikeRequestTeleport,
+                    destination: LureDestination::from_bucket(&self.binary_bucket),
+                })
+            }
+            InstantMessageDialog::GroupNotice => {
+                let has_inventory = bucket.read_u8()? != 0;
+                let asset_type = bucket.read_i8()?;
+                bucket.read_exact(&mut uuid_bytes)?;
+                let mut name_bytes = Vec::new();
+                bucket.read_to_end(&mut name_bytes)?;
+                InstantMessageKind::GroupNotice {
+                    group_id: Uuid::from_bytes(uuid_bytes),
+                    attachment: has_inventory.then(|| {
+                        let name = String::from_utf8_lossy(&name_bytes);
+                        (asset_type, name.trim_end_matches('\0').to_string())
+                    }),
+                }
+            }
+            dialog => InstantMessageKind::Other(dialog),
+        })
+    }
+}
+
+impl PacketData for ImprovedInstantMessage {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+        let mut uuid_bytes = [0u8; 16];
+
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+
+        let from_group = cursor.read_u8()? != 0;
+        cursor.read_exact(&mut uuid_bytes)?;
+        let to_agent_id = Uuid::from_bytes(uuid_bytes);
+        let parent_estate_id = cursor.read_u32::<LittleEndian>()?;
+        cursor.read_exact(&mut uuid_bytes)?;
+        let region_id = Uuid::from_bytes(uuid_bytes);
+        let position = Vec3::new(
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+        );
+        let offline = cursor.read_u8()? != 0;
+        let dialog = InstantMessageDialog::from_bytes(cursor.read_u8()?);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let id = Uuid::from_bytes(uuid_bytes);
+        let timestamp = cursor.read_u32::<LittleEndian>()?;
+
+        let from_agent_name = read_string_1(&mut cursor)?;
+        let message = read_string_2(&mut cursor)?;
+        let binary_bucket = read_variable_2(&mut cursor)?;
+
+        Ok((
+            ImprovedInstantMessage {
+                agent_id,
+                session_id,
+                from_group,
+                to_agent_id,
+                parent_estate_id,
+                region_id,
+                position,
+                offline,
+                dialog,
+                id,
+                timestamp,
+                from_agent_name,
+                message,
+                binary_bucket,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+
+        bytes.push(self.from_group as u8);
+        bytes.extend_from_slice(self.to_agent_id.as_bytes());
+        bytes.extend_from_slice(&self.parent_estate_id.to_le_bytes());
+        bytes.extend_from_slice(self.region_id.as_bytes());
+        bytes.extend_from_slice(&self.position.x.to_le_bytes());
+        bytes.extend_from_slice(&self.position.y.to_le_bytes());
+        bytes.extend_from_slice(&self.position.z.to_le_bytes());
+        bytes.push(self.offline as u8);
+        bytes.push(self.dialog.to_bytes());
+        bytes.extend_from_slice(self.id.as_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+
+        write_string_1(&mut bytes, &self.from_agent_name);
+        write_string_2(&mut bytes, &self.message);
+        write_variable_2(&mut bytes, &self.binary_bucket);
+        bytes
+    }
+}
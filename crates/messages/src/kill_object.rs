@@ -0,0 +1,54 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor};
+
+// ID: 16
+// Frequency: High
+
+impl Packet {
+    pub fn new_kill_object(kill_object: KillObject) -> Self {
+        Packet::new(
+            16,
+            PacketFrequency::High,
+            PacketType::KillObject(Box::new(kill_object)),
+        )
+        .reliable(true)
+    }
+}
+
+/// sent by the server when objects or avatars leave the region.
+/// The client should remove everything with these local IDs from the scene.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillObject {
+    /// the region-local IDs of the objects to remove
+    pub object_ids: Vec<u32>,
+}
+
+impl PacketData for KillObject {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let object_count = cursor.read_u8()? as usize;
+        let mut object_ids = Vec::with_capacity(object_count);
+        for _ in 0..object_count {
+            object_ids.push(cursor.read_u32::<LittleEndian>()?);
+        }
+
+        Ok((KillObject { object_ids }, cursor.position() as usize))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + self.object_ids.len() * 4);
+        bytes.push(self.object_ids.len() as u8);
+        for id in &self.object_ids {
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        bytes
+    }
+}
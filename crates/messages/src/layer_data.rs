@@ -1,45 +1,41 @@
 use std::io::{self, Cursor, Read};
 
 use byteorder::{LittleEndian, ReadBytesExt};
+use log::warn;
 
+use crate::utils::variable::read_variable_2;
 use crate::{
-    header::{Header, PacketFrequency},
+    header::PacketFrequency,
     packet::{Packet, PacketData},
     packet_types::PacketType,
 };
+use serde::{Deserialize, Serialize};
 
 impl Packet {
     pub fn new_layer_data(layer_data: LayerData) -> Self {
-        Packet {
-            header: Header {
-                id: 11,
-                reliable: false,
-                resent: false,
-                zerocoded: false,
-                appended_acks: false,
-                sequence_number: 0,
-                frequency: PacketFrequency::Low,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::LayerData(Box::new(layer_data)),
-        }
+        Packet::new(
+            11,
+            PacketFrequency::High,
+            PacketType::LayerData(Box::new(layer_data)),
+        )
     }
 }
 
-/// add your struct fields here
-#[derive(Debug, Clone)]
-pub struct LayerData{
-    layer_id: LayerType,
-    stride: u16, 
-    patch_size: u8, 
-    layer_type: LayerType,
-    layer_content: Vec<u8>
+/// A layer of terrain, water, wind or cloud patches.
+/// On the wire this is a single layer type byte, followed by a block with a u16 length that
+/// holds the patch group header (stride, patch size and the layer type again) and the
+/// compressed patches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerData {
+    pub layer_type: LayerType,
+    pub stride: u16,
+    pub patch_size: u8,
+    /// the compressed patches
+    pub layer_content: Vec<u8>,
 }
 
-
-#[derive(Debug, Clone)]
-pub enum LayerType{
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LayerType {
     Land,
     LandExtended,
     Water,
@@ -48,76 +44,87 @@ pub enum LayerType{
     WindExtended,
     Cloud,
     CloudExtended,
-    Unknown
+    Unknown,
 }
 
-impl LayerType{
+impl LayerType {
     pub fn to_bytes(&self) -> u8 {
-        match self{
+        match self {
             LayerType::Land => 76,
             LayerType::LandExtended => 77,
             LayerType::Water => 87,
             LayerType::WaterExtended => 88,
-            LayerType::Wind => 55, 
-            LayerType::WindExtended => 57, 
-            LayerType::Cloud => 56, 
+            LayerType::Wind => 55,
+            LayerType::WindExtended => 57,
+            LayerType::Cloud => 56,
             LayerType::CloudExtended => 58,
             LayerType::Unknown => 0,
         }
     }
-    pub fn from_bytes(bytes: u8) -> Self{
-        match bytes{
+    pub fn from_bytes(bytes: u8) -> Self {
+        match bytes {
             76 => LayerType::Land,
-            77 => LayerType::LandExtended, 
-            87 => LayerType::Water, 
-            88 => LayerType::WaterExtended, 
-            55 => LayerType::Wind, 
-            57 => LayerType:: WindExtended,
-            56 => LayerType::Cloud, 
+            77 => LayerType::LandExtended,
+            87 => LayerType::Water,
+            88 => LayerType::WaterExtended,
+            55 => LayerType::Wind,
+            57 => LayerType::WindExtended,
+            56 => LayerType::Cloud,
             58 => LayerType::CloudExtended,
-            _ => LayerType::Unknown
+            _ => LayerType::Unknown,
         }
     }
 }
 
 impl PacketData for LayerData {
-    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
         let mut cursor = Cursor::new(bytes);
-        let layer_bytes = cursor.read_u8()?;
-        let layer_id = LayerType::from_bytes(layer_bytes);
-
-        // skip these two unused bytes
-        let _unused_bytes_1 = cursor.read_u16::<LittleEndian>()?;
-
-        let stride = cursor.read_u16::<LittleEndian>()?;
-
-        let patch_size = cursor.read_u8()?;
-        let layer_type_bytes = cursor.read_u8()?;
-        print!("layer_type_bytes: {:?}", layer_type_bytes);
-
-        // this second layer type seems redundant
-        let layer_type = LayerType::from_bytes(layer_type_bytes);
+        let layer_type_byte = cursor.read_u8()?;
+        let layer_type = LayerType::from_bytes(layer_type_byte);
+        if layer_type == LayerType::Unknown {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown LayerData layer type {}", layer_type_byte),
+            ));
+        }
 
+        let mut data = Cursor::new(read_variable_2(&mut cursor)?);
+        let stride = data.read_u16::<LittleEndian>()?;
+        let patch_size = data.read_u8()?;
+        // the patch group header repeats the layer type
+        let header_type_byte = data.read_u8()?;
+        if header_type_byte != layer_type_byte {
+            warn!(
+                "LayerData layer type {} doesn't match its patch header layer type {}",
+                layer_type_byte, header_type_byte
+            );
+        }
 
         let mut layer_content = Vec::new();
-        cursor.read_to_end(&mut layer_content)?;
-
-        println!("Received LayerData");
-
-        // handle from bytes
-        let data = LayerData{
-            layer_id,
-            stride, 
-            patch_size, 
-            layer_type,
-            layer_content
-        };
-        println!("data :{:?}", data);
-        Ok(data)
+        data.read_to_end(&mut layer_content)?;
+
+        Ok((
+            LayerData {
+                layer_type,
+                stride,
+                patch_size,
+                layer_content,
+            },
+            cursor.position() as usize,
+        ))
     }
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
-        // push your data into the new vector
+        bytes.push(self.layer_type.to_bytes());
+
+        // the size of the data block, which is everything after this
+        let data_length = 4 + self.layer_content.len();
+        bytes.extend_from_slice(&(data_length as u16).to_le_bytes());
+
+        bytes.extend_from_slice(&self.stride.to_le_bytes());
+        bytes.push(self.patch_size);
+        bytes.push(self.layer_type.to_bytes());
+        bytes.extend_from_slice(&self.layer_content);
         bytes
     }
 }
@@ -1,4 +1,14 @@
+pub mod agent_animation;
+pub mod agent_height_width;
+pub mod agent_movement_complete;
+pub mod agent_pause;
+pub mod agent_resume;
+pub mod agent_set_appearance;
+pub mod agent_throttle;
 pub mod agent_update;
+pub mod avatar_animation;
+pub mod avatar_appearance;
+pub mod capabilities;
 pub mod chat_from_simulator;
 pub mod chat_from_viewer;
 pub mod circuit_code;
@@ -6,16 +16,54 @@ pub mod coarse_location_update;
 pub mod complete_agent_movement;
 pub mod complete_ping_check;
 pub mod disable_simulator;
+pub mod enable_simulator;
 pub mod errors;
+pub mod friend_presence;
+#[cfg(feature = "admin")]
+pub mod god_kick_user;
+#[cfg(feature = "admin")]
+pub mod grant_godlike_powers;
 pub mod header;
+pub mod image_data;
+pub mod image_packet;
+pub mod improved_instant_message;
+pub mod kill_object;
 pub mod layer_data;
 pub mod login_system;
+pub mod logout_request;
+pub mod money_balance_reply;
+pub mod money_balance_request;
+pub mod object_add;
+pub mod object_delete;
+pub mod object_deselect;
+pub mod object_properties;
+pub mod object_select;
+pub mod object_update_cached;
+pub mod object_update_compressed;
+pub mod offline_notification;
+pub mod online_notification;
 pub mod packet;
 pub mod packet_ack;
 pub mod packet_types;
 pub mod region_handshake;
 pub mod region_handshake_reply;
+pub mod region_info;
+#[cfg(feature = "admin")]
+pub mod request_godlike_powers;
+pub mod request_image;
+pub mod request_multiple_objects;
+pub mod request_region_info;
+pub mod script_answer_yes;
+pub mod script_dialog;
+pub mod script_dialog_reply;
+pub mod script_question;
+pub mod sim_stats;
 pub mod start_ping_check;
+pub mod teleport_lure_request;
+pub mod texture;
 pub mod ui_events;
+pub mod uuid_name_reply;
+pub mod uuid_name_request;
+pub mod viewer_effect;
 
 pub mod utils;
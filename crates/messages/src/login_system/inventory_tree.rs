@@ -0,0 +1,154 @@
+use crate::capabilities::fetch_inventory::ItemMetadata;
+use crate::login_system::login_response::{
+    InventoryRootValues, InventorySkeletonValues, InventoryType, LoginResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// A folder of the agent's inventory, with the folders and items inside of it.
+/// The login response only lists the folders, as a flat skeleton where each folder names its
+/// parent. This assembles them into a tree that can be drawn as a collapsible inventory panel.
+/// The items are empty until they are fetched with fetch_inventory_folder and added with
+/// add_items.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InventoryFolder {
+    /// the ID of the folder
+    pub id: Uuid,
+    /// the name of the folder
+    pub name: String,
+    /// the kind of item the folder is meant to hold, like textures or landmarks
+    pub type_default: InventoryType,
+    /// the version of the folder, which goes up whenever its contents change
+    pub version: i32,
+    /// the folders inside of this one, sorted by name
+    pub children: Vec<InventoryFolder>,
+    /// the items inside of this folder
+    pub items: Vec<ItemMetadata>,
+}
+
+impl InventoryFolder {
+    /// assemble the skeleton into a tree, starting from the root folder.
+    /// Folders whose parent is not in the skeleton can't be reached from the root, and are left
+    /// out. Returns None if the root folder is not in the skeleton.
+    pub fn from_skeleton(skeleton: &[InventorySkeletonValues], root_id: Uuid) -> Option<Self> {
+        let mut folders = HashMap::new();
+        let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for folder in skeleton {
+            // folders with unparseable IDs can't be placed in the tree
+            let (Ok(id), Ok(parent_id)) = (
+                Uuid::parse_str(&folder.folder_id),
+                Uuid::parse_str(&folder.parent_id),
+            ) else {
+                continue;
+            };
+            if folders.insert(id, folder).is_none() && id != parent_id {
+                children.entry(parent_id).or_default().push(id);
+            }
+        }
+        let mut visited = HashSet::new();
+        build_folder(root_id, &folders, &children, &mut visited)
+    }
+
+    /// find a folder in the tree by its ID, including this one
+    pub fn find(&self, id: Uuid) -> Option<&InventoryFolder> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(id))
+    }
+
+    /// find a folder in the tree by its ID, to change its contents
+    pub fn find_mut(&mut self, id: Uuid) -> Option<&mut InventoryFolder> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_mut(id))
+    }
+
+    /// put each item in the folder named by its parent_id. An item that is already in its folder
+    /// is replaced. Returns the items whose folder is not in the tree.
+    pub fn add_items(
+        &mut self,
+        items: impl IntoIterator<Item = ItemMetadata>,
+    ) -> Vec<ItemMetadata> {
+        let mut unplaced = Vec::new();
+        for item in items {
+            match self.find_mut(item.parent_id) {
+                Some(folder) => {
+                    match folder
+                        .items
+                        .iter_mut()
+                        .find(|existing| existing.item_id == item.item_id)
+                    {
+                        Some(existing) => *existing = item,
+                        None => folder.items.push(item),
+                    }
+                }
+                None => unplaced.push(item),
+            }
+        }
+        unplaced
+    }
+
+    /// the number of folders in the tree, including this one
+    pub fn folder_count(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(|child| child.folder_count())
+            .sum::<usize>()
+    }
+}
+
+fn build_folder(
+    id: Uuid,
+    folders: &HashMap<Uuid, &InventorySkeletonValues>,
+    children: &HashMap<Uuid, Vec<Uuid>>,
+    visited: &mut HashSet<Uuid>,
+) -> Option<InventoryFolder> {
+    // a folder that is its own ancestor would never finish building
+    if !visited.insert(id) {
+        return None;
+    }
+    let folder = folders.get(&id)?;
+    let mut child_folders: Vec<InventoryFolder> = children
+        .get(&id)
+        .into_iter()
+        .flatten()
+        .filter_map(|child| build_folder(*child, folders, children, visited))
+        .collect();
+    child_folders.sort_by_key(|child| child.name.to_lowercase());
+    Some(InventoryFolder {
+        id,
+        name: folder.name.clone(),
+        type_default: folder.type_default.clone(),
+        version: folder.version,
+        children: child_folders,
+        items: Vec::new(),
+    })
+}
+
+fn root_id(root: &Option<Vec<InventoryRootValues>>) -> Option<Uuid> {
+    Uuid::parse_str(&root.as_ref()?.first()?.folder_id).ok()
+}
+
+impl LoginResponse {
+    /// the agent's inventory folders, assembled from inventory_root and inventory_skeleton
+    pub fn inventory_tree(&self) -> Option<InventoryFolder> {
+        InventoryFolder::from_skeleton(
+            self.inventory_skeleton.as_deref()?,
+            root_id(&self.inventory_root)?,
+        )
+    }
+
+    /// the library's folders, assembled from inventory_lib_root and inventory_skeleton_lib
+    pub fn library_tree(&self) -> Option<InventoryFolder> {
+        InventoryFolder::from_skeleton(
+            self.inventory_skeleton_lib.as_deref()?,
+            root_id(&self.inventory_lib_root)?,
+        )
+    }
+}
@@ -6,6 +6,7 @@ use crate::login_system::simulator_login_protocol::{
 use crate::packet_types::PacketType;
 use std::env;
 use std::error::Error;
+use std::fmt;
 
 use mac_address::get_mac_address;
 use md5::{Digest, Md5};
@@ -14,9 +15,9 @@ use std::fs::File;
 use std::io::Cursor;
 
 extern crate sys_info;
-use crate::header::{Header, PacketFrequency};
+use crate::header::PacketFrequency;
 use crate::packet::{Packet, PacketData};
-use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, USER_AGENT};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, USER_AGENT};
 use std::io::{self, BufRead, Read};
 
 use xmlrpc_benthic::{self as xmlrpc};
@@ -34,6 +35,18 @@ pub struct Login {
     pub read_critical: bool,
     pub url: String,
 }
+
+/// HTTP options for the login request, for grids whose load balancers or firewalls turn away
+/// requests they don't recognize
+#[derive(Debug, Clone, Default)]
+pub struct LoginHttpOptions {
+    /// the User-Agent header. None sends the channel and version of the login data, like
+    /// "benthic/0.1.0".
+    pub user_agent: Option<String>,
+    /// extra headers sent with the request, as (name, value) pairs. These replace the default
+    /// headers with the same name.
+    pub headers: Vec<(String, String)>,
+}
 ///Logs in using a SimulatorLoginProtocol object and the url string.
 /// returns a LoginResult, or an error.
 /// If the login response xml can successfully be converted into a LoginResponse struct, do that and
@@ -71,6 +84,79 @@ pub struct Login {
 pub async fn login(
     login_data: SimulatorLoginProtocol,
     url: String,
+) -> Result<LoginResponse, LoginError> {
+    login_to_any(login_data, vec![url]).await
+}
+
+/// Logs in to the first of several login URLs that answers.
+/// Some grids publish more than one login server. The URLs are tried one at a time, in order, and
+/// the next one is only tried if the last one couldn't be reached. They are not raced, because
+/// dropping a request doesn't cancel the login on the server, and a second mirror of the same grid
+/// logging in could kick the session that was just returned.
+/// If a server answers and rejects the login, its error is returned without trying the rest,
+/// because it tells the user what to fix.
+pub async fn login_to_any(
+    login_data: SimulatorLoginProtocol,
+    urls: Vec<String>,
+) -> Result<LoginResponse, LoginError> {
+    login_with_options(login_data, urls, &LoginHttpOptions::default()).await
+}
+
+/// Logs in like login_to_any, sending the User-Agent and extra headers of the options with every
+/// request. A header that can't be sent over HTTP fails the login before anything is sent.
+pub async fn login_with_options(
+    login_data: SimulatorLoginProtocol,
+    urls: Vec<String>,
+    options: &LoginHttpOptions,
+) -> Result<LoginResponse, LoginError> {
+    let headers = login_headers(&login_data, options)?;
+    let mut error = LoginError::new(Reason::Connection, "No login URLs to try");
+    for url in urls {
+        match login_to_url(login_data.clone(), url, headers.clone()).await {
+            Ok(response) => return Ok(response),
+            // the server couldn't be reached, so fall back to the next one
+            Err(e) if e.reason == Reason::Connection => error = e,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(error)
+}
+
+/// the headers of the login request: the defaults, then the options' User-Agent and extra headers
+fn login_headers(
+    login_data: &SimulatorLoginProtocol,
+    options: &LoginHttpOptions,
+) -> Result<HeaderMap, LoginError> {
+    let invalid = |e: &dyn fmt::Display| {
+        LoginError::new(Reason::Connection, &format!("invalid login header: {}", e))
+    };
+    let user_agent = options
+        .user_agent
+        .clone()
+        .unwrap_or_else(|| format!("{}/{}", login_data.channel, login_data.version));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/xml; charset=utf-8"),
+    );
+    headers.insert(
+        USER_AGENT,
+        HeaderValue::from_str(&user_agent).map_err(|e| invalid(&e))?,
+    );
+    for (name, value) in &options.headers {
+        headers.insert(
+            HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(&e))?,
+            HeaderValue::from_str(value).map_err(|e| invalid(&e))?,
+        );
+    }
+    Ok(headers)
+}
+
+async fn login_to_url(
+    login_data: SimulatorLoginProtocol,
+    url: String,
+    headers: HeaderMap,
 ) -> Result<LoginResponse, LoginError> {
     let req = xmlrpc::Request::new("login_to_simulator").arg(login_data);
     let client = Client::new();
@@ -79,15 +165,7 @@ pub async fn login(
     let mut login_response = Vec::new();
     req.write_as_xml(&mut body).unwrap();
 
-    let mut response = match client
-        .post(url)
-        .header(USER_AGENT, "benthic")
-        .header(CONTENT_TYPE, "text/xml; charset=utf-8")
-        .header(CONTENT_LENGTH, body.len())
-        .body(body)
-        .send()
-        .await
-    {
+    let mut response = match client.post(url).headers(headers).body(body).send().await {
         Ok(response) => response,
         Err(e) => return Err(LoginError::new(Reason::Connection, &format!("1{:?}", e))),
     };
@@ -194,7 +272,7 @@ fn hash_viewer_digest() -> Result<String, Box<dyn Error>> {
 }
 
 impl PacketData for Login {
-    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
         let mut cursor = std::io::Cursor::new(bytes);
 
         let read_string = |cursor: &mut std::io::Cursor<&[u8]>| -> io::Result<String> {
@@ -217,16 +295,19 @@ impl PacketData for Login {
 
         let url = read_string(&mut cursor)?;
 
-        Ok(Login {
-            first,
-            last,
-            passwd,
-            start,
-            channel,
-            agree_to_tos,
-            read_critical,
-            url,
-        })
+        Ok((
+            Login {
+                first,
+                last,
+                passwd,
+                start,
+                channel,
+                agree_to_tos,
+                read_critical,
+                url,
+            },
+            cursor.position() as usize,
+        ))
     }
 
     fn to_bytes(&self) -> Vec<u8> {
@@ -251,19 +332,11 @@ impl PacketData for Login {
 
 impl Packet {
     pub fn new_login_packet(login_packet: Login) -> Self {
-        Packet {
-            header: Header {
-                id: 66,
-                frequency: PacketFrequency::Fixed,
-                reliable: true,
-                sequence_number: 0,
-                appended_acks: false,
-                zerocoded: false,
-                resent: false,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::Login(Box::new(login_packet)),
-        }
+        Packet::new(
+            66,
+            PacketFrequency::Fixed,
+            PacketType::Login(Box::new(login_packet)),
+        )
+        .reliable(true)
     }
 }
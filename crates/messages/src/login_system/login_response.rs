@@ -7,6 +7,7 @@
 use crate::{
     login_system::errors::ConversionError,
     utils::agent_access::{parse_agent_access, AgentAccess},
+    utils::region_handle::RegionHandle,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -78,7 +79,7 @@ pub struct LoginResponse {
     /// URL from which to request map tiles
     pub map_server_url: Option<String>,
     /// The user's friend list. Contains an entry for each friend
-    pub buddy_list: Option<Vec<BuddyListValues>>,
+    pub buddy_list: Option<Vec<Buddy>>,
     /// The gestures the user currently has active
     pub gestures: Option<Vec<GesturesValues>>,
     /// Use unknown, probably obsolete
@@ -578,51 +579,74 @@ impl From<GesturesValues> for Value {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct BuddyListValues {
+/// the friend can see when the user is online
+pub const FRIEND_RIGHTS_CAN_SEE_ONLINE: i32 = 1;
+/// the friend can see where the user is on the map
+pub const FRIEND_RIGHTS_CAN_SEE_ON_MAP: i32 = 2;
+/// the friend can edit, move and return the user's objects
+pub const FRIEND_RIGHTS_CAN_MODIFY_OBJECTS: i32 = 4;
+
+/// A friend of the user, from the login response's buddy list
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Buddy {
     /// the UUID of the friend
-    pub buddy_id: String,
-    /// the rights given to this user
-    pub buddy_rights_given: FriendsRights,
-    pub buddy_rights_has: FriendsRights,
+    pub id: Uuid,
+    /// the rights the user has given to the friend
+    pub rights_given: FriendsRights,
+    /// the rights the friend has given to the user
+    pub rights_has: FriendsRights,
 }
-impl From<BuddyListValues> for Value {
-    fn from(val: BuddyListValues) -> Self {
+impl From<Buddy> for Value {
+    fn from(val: Buddy) -> Self {
         let mut map = BTreeMap::new();
-        map.insert("buddy_id".to_string(), Value::String(val.buddy_id));
-        map.insert(
-            "buddy_rights_given".to_string(),
-            val.buddy_rights_given.into(),
-        );
-        map.insert("buddy_rights_has".to_string(), val.buddy_rights_has.into());
+        map.insert("buddy_id".to_string(), Value::String(val.id.to_string()));
+        map.insert("buddy_rights_given".to_string(), val.rights_given.into());
+        map.insert("buddy_rights_has".to_string(), val.rights_has.into());
         Value::Struct(map)
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// The rights one friend has given another
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct FriendsRights {
-    /// true if friends can see if you are online
+    /// true if the friend can see if you are online
     pub can_see_online: bool,
-    /// true if friends can see where you are on the mpap
+    /// true if the friend can see where you are on the map
     pub can_see_on_map: bool,
+    /// true if the friend can edit, move and return your objects
     pub can_modify_objects: bool,
 }
+impl FriendsRights {
+    pub fn from_bits(rights: i32) -> Self {
+        Self {
+            can_see_online: rights & FRIEND_RIGHTS_CAN_SEE_ONLINE != 0,
+            can_see_on_map: rights & FRIEND_RIGHTS_CAN_SEE_ON_MAP != 0,
+            can_modify_objects: rights & FRIEND_RIGHTS_CAN_MODIFY_OBJECTS != 0,
+        }
+    }
+
+    pub fn to_bits(&self) -> i32 {
+        let mut rights = 0;
+        if self.can_see_online {
+            rights |= FRIEND_RIGHTS_CAN_SEE_ONLINE;
+        }
+        if self.can_see_on_map {
+            rights |= FRIEND_RIGHTS_CAN_SEE_ON_MAP;
+        }
+        if self.can_modify_objects {
+            rights |= FRIEND_RIGHTS_CAN_MODIFY_OBJECTS;
+        }
+        rights
+    }
+
+    /// whether every one of the rights is given, like has(FRIEND_RIGHTS_CAN_SEE_ON_MAP)
+    pub fn has(&self, rights: i32) -> bool {
+        self.to_bits() & rights == rights
+    }
+}
 impl From<FriendsRights> for Value {
     fn from(val: FriendsRights) -> Self {
-        let mut map = BTreeMap::new();
-        map.insert(
-            "can_see_online".to_string(),
-            Value::Bool(val.can_see_online),
-        );
-        map.insert(
-            "can_see_on_map".to_string(),
-            Value::Bool(val.can_see_on_map),
-        );
-        map.insert(
-            "can_modify_objects".to_string(),
-            Value::Bool(val.can_modify_objects),
-        );
-        Value::Struct(map)
+        Value::Int(val.to_bits())
     }
 }
 
@@ -650,7 +674,7 @@ impl From<InventorySkeletonValues> for Value {
     }
 }
 /// Inventory item types
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum InventoryType {
     Unknown,
     Texture,
@@ -703,18 +727,20 @@ impl From<InventoryType> for Value {
 /// sent back to the client as a string instead of a struct for some reason :(
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HomeValues {
-    pub region_handle: (String, String),
+    /// the home region. None if the server sent one that couldn't be parsed.
+    pub region_handle: Option<RegionHandle>,
     pub position: (String, String, String),
     pub look_at: (String, String, String),
 }
 impl From<HomeValues> for Value {
     fn from(val: HomeValues) -> Self {
         let mut map = BTreeMap::new();
+        let region_handle = val.region_handle.unwrap_or_default();
         map.insert(
             "region_handle".to_string(),
             Value::Array(vec![
-                Value::String(val.region_handle.0),
-                Value::String(val.region_handle.1),
+                Value::String(format!("r{}", region_handle.global_x())),
+                Value::String(format!("r{}", region_handle.global_y())),
             ]),
         );
         map.insert(
@@ -771,49 +797,23 @@ fn string_3tuple(values: xmlrpc::Value) -> Option<(String, String, String)> {
     })
 }
 
-/// converts friendRights int values to a FriendsRights struct
-fn generate_friends_rights(rights: i32) -> FriendsRights {
-    match rights {
-        1 => FriendsRights {
-            can_see_online: true,
-            can_see_on_map: false,
-            can_modify_objects: false,
-        },
-        2 => FriendsRights {
-            can_see_online: true,
-            can_see_on_map: true,
-            can_modify_objects: false,
-        },
-        4 => FriendsRights {
-            can_see_online: true,
-            can_see_on_map: false,
-            can_modify_objects: true,
-        },
-        _ => FriendsRights {
-            can_see_online: false,
-            can_see_on_map: false,
-            can_modify_objects: false,
-        },
-    }
-}
-
-/// converts xlmrpc to a buddy list
-fn parse_buddy_list(values: Option<&xmlrpc::Value>) -> Option<Vec<BuddyListValues>> {
-    let mut value_vec = vec![];
-    let unwrapped_values = match values {
-        None => return None,
-        Some(x) => x.as_array().unwrap(),
-    };
-    for value in unwrapped_values {
-        value_vec.push(BuddyListValues {
-            buddy_id: value["buddy_id"].as_str().unwrap().to_string(),
-            buddy_rights_given: generate_friends_rights(
-                value["buddy_rights_given"].as_i32().unwrap(),
-            ),
-            buddy_rights_has: generate_friends_rights(value["buddy_rights_has"].as_i32().unwrap()),
-        });
-    }
-    Some(value_vec)
+/// converts xlmrpc to a buddy list. Entries without a valid ID are left out.
+fn parse_buddy_list(values: Option<&xmlrpc::Value>) -> Option<Vec<Buddy>> {
+    let buddies = values?.as_array()?;
+    Some(
+        buddies
+            .iter()
+            .filter_map(|value| {
+                Some(Buddy {
+                    id: Uuid::parse_str(value.get("buddy_id")?.as_str()?).ok()?,
+                    rights_given: FriendsRights::from_bits(
+                        value.get("buddy_rights_given")?.as_i32()?,
+                    ),
+                    rights_has: FriendsRights::from_bits(value.get("buddy_rights_has")?.as_i32()?),
+                })
+            })
+            .collect(),
+    )
 }
 
 /// converts xmlrpc to an inventory_skeelton object
@@ -992,7 +992,7 @@ fn parse_ui_config(values: Option<&xmlrpc::Value>) -> Option<Vec<UiConfig>> {
 impl From<xmlrpc::Value> for HomeValues {
     fn from(val: xmlrpc::Value) -> Self {
         let mut home_values_object = HomeValues {
-            region_handle: ("".to_string(), "".to_string()),
+            region_handle: None,
             look_at: ("".to_string(), "".to_string(), "".to_string()),
             position: ("".to_string(), "".to_string(), "".to_string()),
         };
@@ -1002,7 +1002,7 @@ impl From<xmlrpc::Value> for HomeValues {
             Some(s) => s.to_string(),
             None => {
                 return HomeValues {
-                    region_handle: ("Error".to_string(), "Invalid value".to_string()),
+                    region_handle: None,
                     look_at: (
                         "Error".to_string(),
                         "Invalid value".to_string(),
@@ -1033,15 +1033,17 @@ impl From<xmlrpc::Value> for HomeValues {
 
             match label.as_str() {
                 "region_handle" => {
-                    if values.len() == 2 {
-                        home_values_object.region_handle =
-                            (values[0].to_string(), values[1].to_string());
+                    // the coordinates are reals, written like r256000
+                    let coordinates: Vec<u32> = values
+                        .iter()
+                        .filter_map(|value| value.trim_start_matches('r').parse::<f64>().ok())
+                        .map(|coordinate| coordinate as u32)
+                        .collect();
+                    if let [x, y] = coordinates[..] {
+                        home_values_object.region_handle = Some(RegionHandle::from_global(x, y));
                     } else {
                         return HomeValues {
-                            region_handle: (
-                                "Error".to_string(),
-                                "Invalid number of values".to_string(),
-                            ),
+                            region_handle: None,
                             look_at: home_values_object.look_at,
                             position: home_values_object.position,
                         };
@@ -1093,6 +1095,16 @@ impl From<xmlrpc::Value> for HomeValues {
     }
 }
 
+impl LoginResponse {
+    /// the handle of the start region, from region_x and region_y
+    pub fn region_handle(&self) -> Option<RegionHandle> {
+        Some(RegionHandle::from_global(
+            self.region_x? as u32,
+            self.region_y? as u32,
+        ))
+    }
+}
+
 /// converts from xlmrpc to a LoginResponse
 impl TryFrom<xmlrpc::Value> for LoginResponse {
     type Error = Box<dyn Error>;
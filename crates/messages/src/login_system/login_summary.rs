@@ -0,0 +1,27 @@
+use crate::{
+    login_system::login_response::{Buddy, HomeValues},
+    utils::region_handle::RegionHandle,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Who the agent is logged in as and where, sent to the UI once the agent has arrived in its
+/// first region. This saves the UI from digging through the LoginResponse.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LoginSummary {
+    /// full name of the agent
+    pub agent_name: String,
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// the name of the region the agent arrived in
+    pub region_name: String,
+    pub region_handle: RegionHandle,
+    /// the agent's home, from the login response
+    pub home: Option<HomeValues>,
+    /// the agent's friends, from the login response
+    #[serde(default)]
+    pub buddy_list: Vec<Buddy>,
+    /// the agent's currency balance. None if the server hasn't sent it yet. Later changes are
+    /// sent as BalanceUpdateEvents.
+    pub balance: Option<i32>,
+}
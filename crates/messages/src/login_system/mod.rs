@@ -4,6 +4,8 @@
 //! servers.
 //login functions for logging into metaverse servers
 pub mod errors;
+pub mod inventory_tree;
 pub mod login;
 pub mod login_response;
+pub mod login_summary;
 pub mod simulator_login_protocol;
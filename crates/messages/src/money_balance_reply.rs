@@ -0,0 +1,131 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_string_1, write_string_1};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 314
+// Frequency: Low
+
+impl Packet {
+    pub fn new_money_balance_reply(money_balance_reply: MoneyBalanceReply) -> Self {
+        Packet::new(
+            314,
+            PacketFrequency::Low,
+            PacketType::MoneyBalanceReply(Box::new(money_balance_reply)),
+        )
+        .reliable(true)
+    }
+}
+
+/// The agent's currency balance.
+/// This is sent in reply to a MoneyBalanceRequest, and whenever the balance changes, like after
+/// a payment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoneyBalanceReply {
+    pub agent_id: Uuid,
+    /// the transaction_id of the request, or of the transaction that changed the balance
+    pub transaction_id: Uuid,
+    pub transaction_success: bool,
+    /// the balance, in the grid's currency
+    pub money_balance: i32,
+    pub square_meters_credit: i32,
+    pub square_meters_committed: i32,
+    /// a message about the transaction, to show to the user
+    pub description: String,
+    /// details of the transaction that changed the balance. Older servers leave this out.
+    pub transaction_info: Option<TransactionInfo>,
+}
+
+/// a payment that changed the balance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionInfo {
+    pub transaction_type: i32,
+    pub source_id: Uuid,
+    pub is_source_group: bool,
+    pub dest_id: Uuid,
+    pub is_dest_group: bool,
+    pub amount: i32,
+    pub item_description: String,
+}
+
+impl PacketData for MoneyBalanceReply {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let transaction_id = Uuid::from_bytes(uuid_bytes);
+        let transaction_success = cursor.read_u8()? != 0;
+        let money_balance = cursor.read_i32::<LittleEndian>()?;
+        let square_meters_credit = cursor.read_i32::<LittleEndian>()?;
+        let square_meters_committed = cursor.read_i32::<LittleEndian>()?;
+        let description = read_string_1(&mut cursor)?;
+
+        let transaction_info = if (cursor.position() as usize) < bytes.len() {
+            let transaction_type = cursor.read_i32::<LittleEndian>()?;
+            cursor.read_exact(&mut uuid_bytes)?;
+            let source_id = Uuid::from_bytes(uuid_bytes);
+            let is_source_group = cursor.read_u8()? != 0;
+            cursor.read_exact(&mut uuid_bytes)?;
+            let dest_id = Uuid::from_bytes(uuid_bytes);
+            let is_dest_group = cursor.read_u8()? != 0;
+            let amount = cursor.read_i32::<LittleEndian>()?;
+            let item_description = read_string_1(&mut cursor)?;
+            Some(TransactionInfo {
+                transaction_type,
+                source_id,
+                is_source_group,
+                dest_id,
+                is_dest_group,
+                amount,
+                item_description,
+            })
+        } else {
+            None
+        };
+
+        Ok((
+            MoneyBalanceReply {
+                agent_id,
+                transaction_id,
+                transaction_success,
+                money_balance,
+                square_meters_credit,
+                square_meters_committed,
+                description,
+                transaction_info,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.transaction_id.as_bytes());
+        bytes.push(self.transaction_success as u8);
+        bytes.extend_from_slice(&self.money_balance.to_le_bytes());
+        bytes.extend_from_slice(&self.square_meters_credit.to_le_bytes());
+        bytes.extend_from_slice(&self.square_meters_committed.to_le_bytes());
+        write_string_1(&mut bytes, &self.description);
+
+        if let Some(info) = &self.transaction_info {
+            bytes.extend_from_slice(&info.transaction_type.to_le_bytes());
+            bytes.extend_from_slice(info.source_id.as_bytes());
+            bytes.push(info.is_source_group as u8);
+            bytes.extend_from_slice(info.dest_id.as_bytes());
+            bytes.push(info.is_dest_group as u8);
+            bytes.extend_from_slice(&info.amount.to_le_bytes());
+            write_string_1(&mut bytes, &info.item_description);
+        }
+        bytes
+    }
+}
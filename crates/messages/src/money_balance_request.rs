@@ -0,0 +1,63 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 313
+// Frequency: Low
+
+impl Packet {
+    pub fn new_money_balance_request(money_balance_request: MoneyBalanceRequest) -> Self {
+        Packet::new(
+            313,
+            PacketFrequency::Low,
+            PacketType::MoneyBalanceRequest(Box::new(money_balance_request)),
+        )
+        .reliable(true)
+    }
+}
+
+/// asks the server for the agent's currency balance.
+/// The server answers with a MoneyBalanceReply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoneyBalanceRequest {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// returned in the reply, to match it to the request
+    pub transaction_id: Uuid,
+}
+
+impl PacketData for MoneyBalanceRequest {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let transaction_id = Uuid::from_bytes(uuid_bytes);
+
+        Ok((
+            MoneyBalanceRequest {
+                agent_id,
+                session_id,
+                transaction_id,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(48);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.extend_from_slice(self.transaction_id.as_bytes());
+        bytes
+    }
+}
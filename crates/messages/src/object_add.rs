@@ -0,0 +1,349 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 1
+// Frequency: Medium
+
+impl Packet {
+    pub fn new_object_add(object_add: ObjectAdd) -> Self {
+        Packet::new(
+            1,
+            PacketFrequency::Medium,
+            PacketType::ObjectAdd(Box::new(object_add)),
+        )
+        .reliable(true)
+    }
+}
+
+/// Rezzes a new object in the region.
+/// The object is placed where a ray from ray_start to ray_end hits, or at ray_end when
+/// bypass_raycast is set. The server sends an ObjectUpdate for it once it has been created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectAdd {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// the group the object is set to
+    pub group_id: Uuid,
+    pub pcode: PCode,
+    pub material: Material,
+    pub add_flags: u32,
+    pub shape: PrimShape,
+    /// place the object at ray_end, instead of where the ray hits
+    pub bypass_raycast: bool,
+    pub ray_start: Vec3,
+    pub ray_end: Vec3,
+    /// the object the ray is aimed at. Nil for the ground.
+    pub ray_target_id: Uuid,
+    pub ray_end_is_intersection: bool,
+    pub scale: Vec3,
+    pub rotation: Quat,
+    /// the attachment point, for objects rezzed as attachments
+    pub state: u8,
+}
+
+impl ObjectAdd {
+    /// rez an object with the default box shape at a position in the region
+    pub fn new(
+        agent_id: Uuid,
+        session_id: Uuid,
+        pcode: PCode,
+        position: Vec3,
+        scale: Vec3,
+        rotation: Quat,
+    ) -> Self {
+        ObjectAdd {
+            agent_id,
+            session_id,
+            group_id: Uuid::nil(),
+            pcode,
+            material: Material::Wood,
+            add_flags: 0,
+            shape: PrimShape::default(),
+            bypass_raycast: true,
+            ray_start: position,
+            ray_end: position,
+            ray_target_id: Uuid::nil(),
+            ray_end_is_intersection: false,
+            scale,
+            rotation,
+            state: 0,
+        }
+    }
+}
+
+/// the kind of object
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PCode {
+    Primitive,
+    Avatar,
+    Grass,
+    NewTree,
+    ParticleSystem,
+    Tree,
+    Unknown(u8),
+}
+
+impl PCode {
+    pub fn from_bytes(byte: u8) -> Self {
+        match byte {
+            9 => PCode::Primitive,
+            47 => PCode::Avatar,
+            95 => PCode::Grass,
+            111 => PCode::NewTree,
+            143 => PCode::ParticleSystem,
+            255 => PCode::Tree,
+            other => PCode::Unknown(other),
+        }
+    }
+    pub fn to_bytes(&self) -> u8 {
+        match self {
+            PCode::Primitive => 9,
+            PCode::Avatar => 47,
+            PCode::Grass => 95,
+            PCode::NewTree => 111,
+            PCode::ParticleSystem => 143,
+            PCode::Tree => 255,
+            PCode::Unknown(other) => *other,
+        }
+    }
+}
+
+/// what the object is made of, which sets its physics and the sound of collisions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Material {
+    Stone,
+    Metal,
+    Glass,
+    Wood,
+    Flesh,
+    Plastic,
+    Rubber,
+    Light,
+    Unknown(u8),
+}
+
+impl Material {
+    pub fn from_bytes(byte: u8) -> Self {
+        match byte {
+            0 => Material::Stone,
+            1 => Material::Metal,
+            2 => Material::Glass,
+            3 => Material::Wood,
+            4 => Material::Flesh,
+            5 => Material::Plastic,
+            6 => Material::Rubber,
+            7 => Material::Light,
+            other => Material::Unknown(other),
+        }
+    }
+    pub fn to_bytes(&self) -> u8 {
+        match self {
+            Material::Stone => 0,
+            Material::Metal => 1,
+            Material::Glass => 2,
+            Material::Wood => 3,
+            Material::Flesh => 4,
+            Material::Plastic => 5,
+            Material::Rubber => 6,
+            Material::Light => 7,
+            Material::Unknown(other) => *other,
+        }
+    }
+}
+
+/// The shape of a prim, as a profile swept along a path.
+/// The values are stored the way they are sent, quantized into integers.
+/// https://wiki.secondlife.com/wiki/ObjectAdd
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrimShape {
+    pub path_curve: u8,
+    pub profile_curve: u8,
+    pub path_begin: u16,
+    pub path_end: u16,
+    pub path_scale_x: u8,
+    pub path_scale_y: u8,
+    pub path_shear_x: u8,
+    pub path_shear_y: u8,
+    pub path_twist: i8,
+    pub path_twist_begin: i8,
+    pub path_radius_offset: i8,
+    pub path_taper_x: i8,
+    pub path_taper_y: i8,
+    pub path_revolutions: u8,
+    pub path_skew: i8,
+    pub profile_begin: u16,
+    pub profile_end: u16,
+    pub profile_hollow: u16,
+}
+
+/// a box: a square profile swept along a straight line
+impl Default for PrimShape {
+    fn default() -> Self {
+        PrimShape {
+            path_curve: 16,
+            profile_curve: 1,
+            path_begin: 0,
+            path_end: 0,
+            path_scale_x: 100,
+            path_scale_y: 100,
+            path_shear_x: 0,
+            path_shear_y: 0,
+            path_twist: 0,
+            path_twist_begin: 0,
+            path_radius_offset: 0,
+            path_taper_x: 0,
+            path_taper_y: 0,
+            path_revolutions: 0,
+            path_skew: 0,
+            profile_begin: 0,
+            profile_end: 0,
+            profile_hollow: 0,
+        }
+    }
+}
+
+impl PacketData for ObjectAdd {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let group_id = Uuid::from_bytes(uuid_bytes);
+
+        let pcode = PCode::from_bytes(cursor.read_u8()?);
+        let material = Material::from_bytes(cursor.read_u8()?);
+        let add_flags = cursor.read_u32::<LittleEndian>()?;
+        let shape = PrimShape {
+            path_curve: cursor.read_u8()?,
+            profile_curve: cursor.read_u8()?,
+            path_begin: cursor.read_u16::<LittleEndian>()?,
+            path_end: cursor.read_u16::<LittleEndian>()?,
+            path_scale_x: cursor.read_u8()?,
+            path_scale_y: cursor.read_u8()?,
+            path_shear_x: cursor.read_u8()?,
+            path_shear_y: cursor.read_u8()?,
+            path_twist: cursor.read_i8()?,
+            path_twist_begin: cursor.read_i8()?,
+            path_radius_offset: cursor.read_i8()?,
+            path_taper_x: cursor.read_i8()?,
+            path_taper_y: cursor.read_i8()?,
+            path_revolutions: cursor.read_u8()?,
+            path_skew: cursor.read_i8()?,
+            profile_begin: cursor.read_u16::<LittleEndian>()?,
+            profile_end: cursor.read_u16::<LittleEndian>()?,
+            profile_hollow: cursor.read_u16::<LittleEndian>()?,
+        };
+
+        let bypass_raycast = cursor.read_u8()? != 0;
+        let ray_start = read_vec3(&mut cursor)?;
+        let ray_end = read_vec3(&mut cursor)?;
+        cursor.read_exact(&mut uuid_bytes)?;
+        let ray_target_id = Uuid::from_bytes(uuid_bytes);
+        let ray_end_is_intersection = cursor.read_u8()? != 0;
+        let scale = read_vec3(&mut cursor)?;
+
+        // the rotation is sent normalized, without w
+        let xyz = read_vec3(&mut cursor)?;
+        let w = (1.0 - xyz.length_squared()).max(0.0).sqrt();
+        let rotation = Quat::from_xyzw(xyz.x, xyz.y, xyz.z, w);
+
+        let state = cursor.read_u8()?;
+
+        Ok((
+            ObjectAdd {
+                agent_id,
+                session_id,
+                group_id,
+                pcode,
+                material,
+                add_flags,
+                shape,
+                bypass_raycast,
+                ray_start,
+                ray_end,
+                ray_target_id,
+                ray_end_is_intersection,
+                scale,
+                rotation,
+                state,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(144);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.extend_from_slice(self.group_id.as_bytes());
+
+        bytes.push(self.pcode.to_bytes());
+        bytes.push(self.material.to_bytes());
+        bytes.extend_from_slice(&self.add_flags.to_le_bytes());
+
+        let shape = &self.shape;
+        bytes.push(shape.path_curve);
+        bytes.push(shape.profile_curve);
+        bytes.extend_from_slice(&shape.path_begin.to_le_bytes());
+        bytes.extend_from_slice(&shape.path_end.to_le_bytes());
+        bytes.push(shape.path_scale_x);
+        bytes.push(shape.path_scale_y);
+        bytes.push(shape.path_shear_x);
+        bytes.push(shape.path_shear_y);
+        bytes.push(shape.path_twist as u8);
+        bytes.push(shape.path_twist_begin as u8);
+        bytes.push(shape.path_radius_offset as u8);
+        bytes.push(shape.path_taper_x as u8);
+        bytes.push(shape.path_taper_y as u8);
+        bytes.push(shape.path_revolutions);
+        bytes.push(shape.path_skew as u8);
+        bytes.extend_from_slice(&shape.profile_begin.to_le_bytes());
+        bytes.extend_from_slice(&shape.profile_end.to_le_bytes());
+        bytes.extend_from_slice(&shape.profile_hollow.to_le_bytes());
+
+        bytes.push(self.bypass_raycast as u8);
+        write_vec3(&mut bytes, self.ray_start);
+        write_vec3(&mut bytes, self.ray_end);
+        bytes.extend_from_slice(self.ray_target_id.as_bytes());
+        bytes.push(self.ray_end_is_intersection as u8);
+        write_vec3(&mut bytes, self.scale);
+
+        // w is left out, so the rotation has to be normalized with a positive w
+        let rotation = self.rotation.normalize();
+        let rotation = if rotation.w < 0.0 {
+            -rotation
+        } else {
+            rotation
+        };
+        write_vec3(&mut bytes, Vec3::new(rotation.x, rotation.y, rotation.z));
+
+        bytes.push(self.state);
+        bytes
+    }
+}
+
+fn read_vec3(cursor: &mut Cursor<&[u8]>) -> io::Result<Vec3> {
+    Ok(Vec3::new(
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+    ))
+}
+
+fn write_vec3(bytes: &mut Vec<u8>, vector: Vec3) {
+    bytes.extend_from_slice(&vector.x.to_le_bytes());
+    bytes.extend_from_slice(&vector.y.to_le_bytes());
+    bytes.extend_from_slice(&vector.z.to_le_bytes());
+}
@@ -0,0 +1,75 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 89
+// Frequency: Low
+
+impl Packet {
+    pub fn new_object_delete(object_delete: ObjectDelete) -> Self {
+        Packet::new(
+            89,
+            PacketFrequency::Low,
+            PacketType::ObjectDelete(Box::new(object_delete)),
+        )
+        .reliable(true)
+    }
+}
+
+/// deletes objects in the region, by their local IDs.
+/// The objects are moved to the owner's trash, and the server sends a KillObject for them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectDelete {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// delete the objects even if they aren't owned by the agent. Only gods can do this.
+    pub force: bool,
+    pub local_ids: Vec<u32>,
+}
+
+impl PacketData for ObjectDelete {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        let force = cursor.read_u8()? != 0;
+
+        let count = cursor.read_u8()?;
+        let mut local_ids = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            local_ids.push(cursor.read_u32::<LittleEndian>()?);
+        }
+
+        Ok((
+            ObjectDelete {
+                agent_id,
+                session_id,
+                force,
+                local_ids,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(34 + self.local_ids.len() * 4);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.push(self.force as u8);
+        bytes.push(self.local_ids.len() as u8);
+        for local_id in &self.local_ids {
+            bytes.extend_from_slice(&local_id.to_le_bytes());
+        }
+        bytes
+    }
+}
@@ -0,0 +1,49 @@
+use crate::object_select::{read_object_selection, write_object_selection};
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use serde::{Deserialize, Serialize};
+use std::io;
+use uuid::Uuid;
+
+// ID: 111
+// Frequency: Low
+
+impl Packet {
+    pub fn new_object_deselect(object_deselect: ObjectDeselect) -> Self {
+        Packet::new(
+            111,
+            PacketFrequency::Low,
+            PacketType::ObjectDeselect(Box::new(object_deselect)),
+        )
+        .reliable(true)
+    }
+}
+
+/// deselects objects that were selected with ObjectSelect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectDeselect {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub local_ids: Vec<u32>,
+}
+
+impl PacketData for ObjectDeselect {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let (agent_id, session_id, local_ids, len) = read_object_selection(bytes)?;
+        Ok((
+            ObjectDeselect {
+                agent_id,
+                session_id,
+                local_ids,
+            },
+            len,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        write_object_selection(&self.agent_id, &self.session_id, &self.local_ids)
+    }
+}
@@ -0,0 +1,229 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_string_1, read_variable_1, write_string_1};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 9
+// Frequency: Medium
+
+/// the permission bits of the masks in ObjectPropertiesData
+pub const PERM_TRANSFER: u32 = 1 << 13;
+pub const PERM_MODIFY: u32 = 1 << 14;
+pub const PERM_COPY: u32 = 1 << 15;
+pub const PERM_MOVE: u32 = 1 << 19;
+
+impl Packet {
+    pub fn new_object_properties(object_properties: ObjectProperties) -> Self {
+        Packet::new(
+            9,
+            PacketFrequency::Medium,
+            PacketType::ObjectProperties(Box::new(object_properties)),
+        )
+    }
+}
+
+/// the properties of objects, sent by the server when they are selected with ObjectSelect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectProperties {
+    pub objects: Vec<ObjectPropertiesData>,
+}
+
+/// the properties of a single object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectPropertiesData {
+    pub object_id: Uuid,
+    pub creator_id: Uuid,
+    pub owner_id: Uuid,
+    pub group_id: Uuid,
+    /// when the object was created, in microseconds since the unix epoch
+    pub creation_date: u64,
+    pub base_mask: u32,
+    pub owner_mask: u32,
+    pub group_mask: u32,
+    pub everyone_mask: u32,
+    pub next_owner_mask: u32,
+    pub ownership_cost: i32,
+    pub sale_type: u8,
+    pub sale_price: i32,
+    pub aggregate_perms: u8,
+    pub aggregate_perm_textures: u8,
+    pub aggregate_perm_textures_owner: u8,
+    pub category: u32,
+    pub inventory_serial: i16,
+    pub item_id: Uuid,
+    pub folder_id: Uuid,
+    pub from_task_id: Uuid,
+    pub last_owner_id: Uuid,
+    pub name: String,
+    pub description: String,
+    /// the text of the touch menu item, if the object has a custom one
+    pub touch_name: String,
+    /// the text of the sit menu item, if the object has a custom one
+    pub sit_name: String,
+    pub texture_ids: Vec<Uuid>,
+}
+
+/// what can be done with an object, decoded from one of its permission masks
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Permissions {
+    pub transfer: bool,
+    pub modify: bool,
+    pub copy: bool,
+    pub movable: bool,
+}
+impl Permissions {
+    pub fn from_bits(bits: u32) -> Self {
+        Permissions {
+            transfer: bits & PERM_TRANSFER != 0,
+            modify: bits & PERM_MODIFY != 0,
+            copy: bits & PERM_COPY != 0,
+            movable: bits & PERM_MOVE != 0,
+        }
+    }
+}
+
+impl ObjectPropertiesData {
+    /// what the owner can do with the object
+    pub fn owner_permissions(&self) -> Permissions {
+        Permissions::from_bits(self.owner_mask)
+    }
+    /// what everyone else can do with the object
+    pub fn everyone_permissions(&self) -> Permissions {
+        Permissions::from_bits(self.everyone_mask)
+    }
+    /// what the next owner can do with the object, once it is sold or given away
+    pub fn next_owner_permissions(&self) -> Permissions {
+        Permissions::from_bits(self.next_owner_mask)
+    }
+
+    fn from_bytes(cursor: &mut Cursor<&[u8]>) -> io::Result<Self> {
+        let read_uuid = |cursor: &mut Cursor<&[u8]>| -> io::Result<Uuid> {
+            let mut uuid_bytes = [0u8; 16];
+            cursor.read_exact(&mut uuid_bytes)?;
+            Ok(Uuid::from_bytes(uuid_bytes))
+        };
+
+        let object_id = read_uuid(cursor)?;
+        let creator_id = read_uuid(cursor)?;
+        let owner_id = read_uuid(cursor)?;
+        let group_id = read_uuid(cursor)?;
+        let creation_date = cursor.read_u64::<LittleEndian>()?;
+        let base_mask = cursor.read_u32::<LittleEndian>()?;
+        let owner_mask = cursor.read_u32::<LittleEndian>()?;
+        let group_mask = cursor.read_u32::<LittleEndian>()?;
+        let everyone_mask = cursor.read_u32::<LittleEndian>()?;
+        let next_owner_mask = cursor.read_u32::<LittleEndian>()?;
+        let ownership_cost = cursor.read_i32::<LittleEndian>()?;
+        let sale_type = cursor.read_u8()?;
+        let sale_price = cursor.read_i32::<LittleEndian>()?;
+        let aggregate_perms = cursor.read_u8()?;
+        let aggregate_perm_textures = cursor.read_u8()?;
+        let aggregate_perm_textures_owner = cursor.read_u8()?;
+        let category = cursor.read_u32::<LittleEndian>()?;
+        let inventory_serial = cursor.read_i16::<LittleEndian>()?;
+        let item_id = read_uuid(cursor)?;
+        let folder_id = read_uuid(cursor)?;
+        let from_task_id = read_uuid(cursor)?;
+        let last_owner_id = read_uuid(cursor)?;
+        let name = read_string_1(cursor)?;
+        let description = read_string_1(cursor)?;
+        let touch_name = read_string_1(cursor)?;
+        let sit_name = read_string_1(cursor)?;
+        let texture_ids = read_variable_1(cursor)?
+            .chunks_exact(16)
+            .map(|id| Uuid::from_slice(id).unwrap())
+            .collect();
+
+        Ok(ObjectPropertiesData {
+            object_id,
+            creator_id,
+            owner_id,
+            group_id,
+            creation_date,
+            base_mask,
+            owner_mask,
+            group_mask,
+            everyone_mask,
+            next_owner_mask,
+            ownership_cost,
+            sale_type,
+            sale_price,
+            aggregate_perms,
+            aggregate_perm_textures,
+            aggregate_perm_textures_owner,
+            category,
+            inventory_serial,
+            item_id,
+            folder_id,
+            from_task_id,
+            last_owner_id,
+            name,
+            description,
+            touch_name,
+            sit_name,
+            texture_ids,
+        })
+    }
+    fn to_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self.object_id.as_bytes());
+        bytes.extend_from_slice(self.creator_id.as_bytes());
+        bytes.extend_from_slice(self.owner_id.as_bytes());
+        bytes.extend_from_slice(self.group_id.as_bytes());
+        bytes.extend_from_slice(&self.creation_date.to_le_bytes());
+        bytes.extend_from_slice(&self.base_mask.to_le_bytes());
+        bytes.extend_from_slice(&self.owner_mask.to_le_bytes());
+        bytes.extend_from_slice(&self.group_mask.to_le_bytes());
+        bytes.extend_from_slice(&self.everyone_mask.to_le_bytes());
+        bytes.extend_from_slice(&self.next_owner_mask.to_le_bytes());
+        bytes.extend_from_slice(&self.ownership_cost.to_le_bytes());
+        bytes.push(self.sale_type);
+        bytes.extend_from_slice(&self.sale_price.to_le_bytes());
+        bytes.push(self.aggregate_perms);
+        bytes.push(self.aggregate_perm_textures);
+        bytes.push(self.aggregate_perm_textures_owner);
+        bytes.extend_from_slice(&self.category.to_le_bytes());
+        bytes.extend_from_slice(&self.inventory_serial.to_le_bytes());
+        bytes.extend_from_slice(self.item_id.as_bytes());
+        bytes.extend_from_slice(self.folder_id.as_bytes());
+        bytes.extend_from_slice(self.from_task_id.as_bytes());
+        bytes.extend_from_slice(self.last_owner_id.as_bytes());
+        for text in [
+            &self.name,
+            &self.description,
+            &self.touch_name,
+            &self.sit_name,
+        ] {
+            write_string_1(bytes, text);
+        }
+        bytes.push((self.texture_ids.len() * 16) as u8);
+        for texture_id in &self.texture_ids {
+            bytes.extend_from_slice(texture_id.as_bytes());
+        }
+    }
+}
+
+impl PacketData for ObjectProperties {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+        let count = cursor.read_u8()?;
+        let mut objects = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            objects.push(ObjectPropertiesData::from_bytes(&mut cursor)?);
+        }
+        Ok((ObjectProperties { objects }, cursor.position() as usize))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.objects.len() as u8];
+        for object in &self.objects {
+            object.to_bytes(&mut bytes);
+        }
+        bytes
+    }
+}
@@ -0,0 +1,86 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 110
+// Frequency: Low
+
+impl Packet {
+    pub fn new_object_select(object_select: ObjectSelect) -> Self {
+        Packet::new(
+            110,
+            PacketFrequency::Low,
+            PacketType::ObjectSelect(Box::new(object_select)),
+        )
+        .reliable(true)
+    }
+}
+
+/// selects objects in the region, by their local IDs.
+/// The server answers with an ObjectProperties for each of them, and keeps them selected until
+/// they are deselected with ObjectDeselect.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectSelect {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub local_ids: Vec<u32>,
+}
+
+impl PacketData for ObjectSelect {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let (agent_id, session_id, local_ids, len) = read_object_selection(bytes)?;
+        Ok((
+            ObjectSelect {
+                agent_id,
+                session_id,
+                local_ids,
+            },
+            len,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        write_object_selection(&self.agent_id, &self.session_id, &self.local_ids)
+    }
+}
+
+/// reads the agent, the session, and the local IDs that ObjectSelect and ObjectDeselect share,
+/// and the number of bytes they took up
+pub(crate) fn read_object_selection(bytes: &[u8]) -> io::Result<(Uuid, Uuid, Vec<u32>, usize)> {
+    let mut cursor = Cursor::new(bytes);
+
+    let mut uuid_bytes = [0u8; 16];
+    cursor.read_exact(&mut uuid_bytes)?;
+    let agent_id = Uuid::from_bytes(uuid_bytes);
+    cursor.read_exact(&mut uuid_bytes)?;
+    let session_id = Uuid::from_bytes(uuid_bytes);
+
+    let count = cursor.read_u8()?;
+    let mut local_ids = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        local_ids.push(cursor.read_u32::<LittleEndian>()?);
+    }
+    Ok((agent_id, session_id, local_ids, cursor.position() as usize))
+}
+
+/// writes the agent, the session, and the local IDs that ObjectSelect and ObjectDeselect share
+pub(crate) fn write_object_selection(
+    agent_id: &Uuid,
+    session_id: &Uuid,
+    local_ids: &[u32],
+) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(33 + local_ids.len() * 4);
+    bytes.extend_from_slice(agent_id.as_bytes());
+    bytes.extend_from_slice(session_id.as_bytes());
+    bytes.push(local_ids.len() as u8);
+    for local_id in local_ids {
+        bytes.extend_from_slice(&local_id.to_le_bytes());
+    }
+    bytes
+}
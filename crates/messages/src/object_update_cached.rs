@@ -0,0 +1,84 @@
+use crate::packet_types::PacketType;
+use crate::utils::region_handle::RegionHandle;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor};
+
+// ID: 14
+// Frequency: High
+
+impl Packet {
+    pub fn new_object_update_cached(object_update_cached: ObjectUpdateCached) -> Self {
+        Packet::new(
+            14,
+            PacketFrequency::High,
+            PacketType::ObjectUpdateCached(Box::new(object_update_cached)),
+        )
+    }
+}
+
+/// Objects the server expects the client to already have cached, by local ID and CRC.
+/// Objects that aren't cached, or whose CRC doesn't match the cached one, have to be requested
+/// with a RequestMultipleObjects.
+/// https://wiki.secondlife.com/wiki/ObjectUpdateCached
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectUpdateCached {
+    pub region_handle: RegionHandle,
+    /// how much slower than real time the simulator is running, scaled to 0..65535
+    pub time_dilation: u16,
+    pub objects: Vec<CachedObject>,
+}
+
+/// a single object in an ObjectUpdateCached
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CachedObject {
+    pub local_id: u32,
+    /// the CRC of the object's current state
+    pub crc: u32,
+    pub update_flags: u32,
+}
+
+impl PacketData for ObjectUpdateCached {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let region_handle = RegionHandle(cursor.read_u64::<LittleEndian>()?);
+        let time_dilation = cursor.read_u16::<LittleEndian>()?;
+
+        let count = cursor.read_u8()?;
+        let mut objects = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            objects.push(CachedObject {
+                local_id: cursor.read_u32::<LittleEndian>()?,
+                crc: cursor.read_u32::<LittleEndian>()?,
+                update_flags: cursor.read_u32::<LittleEndian>()?,
+            });
+        }
+
+        Ok((
+            ObjectUpdateCached {
+                region_handle,
+                time_dilation,
+                objects,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(11 + self.objects.len() * 12);
+        bytes.extend_from_slice(&self.region_handle.0.to_le_bytes());
+        bytes.extend_from_slice(&self.time_dilation.to_le_bytes());
+        bytes.push(self.objects.len() as u8);
+        for object in &self.objects {
+            bytes.extend_from_slice(&object.local_id.to_le_bytes());
+            bytes.extend_from_slice(&object.crc.to_le_bytes());
+            bytes.extend_from_slice(&object.update_flags.to_le_bytes());
+        }
+        bytes
+    }
+}
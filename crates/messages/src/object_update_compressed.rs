@@ -0,0 +1,502 @@
+use crate::object_add::{Material, PCode, PrimShape};
+use crate::packet_types::PacketType;
+use crate::utils::variable::{
+    read_uuid, read_variable_1, read_variable_2, write_variable_1, write_variable_2,
+};
+use crate::utils::{region_handle::RegionHandle, texture_entry::TextureEntry};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 13
+// Frequency: High
+
+impl Packet {
+    pub fn new_object_update_compressed(object_update_compressed: ObjectUpdateCompressed) -> Self {
+        Packet::new(
+            13,
+            PacketFrequency::High,
+            PacketType::ObjectUpdateCompressed(Box::new(object_update_compressed)),
+        )
+    }
+}
+
+// which of the optional fields follow the fixed part of a compressed object
+const HAS_SCRATCH_PAD: u32 = 0x01;
+const HAS_TREE: u32 = 0x02;
+const HAS_TEXT: u32 = 0x04;
+const HAS_PARTICLES: u32 = 0x08;
+const HAS_SOUND: u32 = 0x10;
+const HAS_PARENT: u32 = 0x20;
+const HAS_TEXTURE_ANIMATION: u32 = 0x40;
+const HAS_ANGULAR_VELOCITY: u32 = 0x80;
+const HAS_NAME_VALUES: u32 = 0x100;
+const HAS_MEDIA_URL: u32 = 0x200;
+const HAS_PARTICLES_NEW: u32 = 0x400;
+
+/// the legacy particle system is always this long
+const PARTICLE_SYSTEM_LENGTH: usize = 86;
+
+/// Objects in the region, with only the fields that are set.
+/// Regions send most objects this way instead of as full ObjectUpdates, to save bandwidth.
+/// https://wiki.secondlife.com/wiki/ObjectUpdateCompressed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectUpdateCompressed {
+    pub region_handle: RegionHandle,
+    /// how much slower than real time the simulator is running, scaled to 0..65535
+    pub time_dilation: u16,
+    pub objects: Vec<CompressedObject>,
+}
+
+/// a single object in an ObjectUpdateCompressed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressedObject {
+    pub update_flags: u32,
+    pub full_id: Uuid,
+    /// the ID of the object within the region, used by every other object packet
+    pub local_id: u32,
+    pub pcode: PCode,
+    pub state: u8,
+    /// changes whenever the object changes. ObjectUpdateCached entries are compared against it.
+    pub crc: u32,
+    pub material: Material,
+    pub click_action: u8,
+    pub scale: Vec3,
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub owner_id: Uuid,
+    pub angular_velocity: Option<Vec3>,
+    /// the local ID of the object this one is linked or attached to
+    pub parent_id: Option<u32>,
+    pub tree_species: Option<u8>,
+    pub scratch_pad: Option<Vec<u8>>,
+    /// the floating text above the object
+    pub text: Option<FloatingText>,
+    pub media_url: Option<String>,
+    /// the legacy particle system, left encoded
+    pub particle_system: Option<Vec<u8>>,
+    pub extra_params: Vec<ExtraParam>,
+    pub sound: Option<AttachedSound>,
+    pub name_values: Option<String>,
+    pub shape: PrimShape,
+    /// None for objects without textures, like trees
+    pub texture_entry: Option<TextureEntry>,
+    /// the texture animation, left encoded
+    pub texture_animation: Option<Vec<u8>>,
+    /// the particle system, left encoded
+    pub particles_new: Option<Vec<u8>>,
+}
+
+/// hovertext, set by scripts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FloatingText {
+    pub text: String,
+    /// the color of the text, as RGBA
+    pub color: [u8; 4],
+}
+
+/// the parameters of flexible prims, lights, sculpts and meshes, left encoded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtraParam {
+    pub param_type: u16,
+    pub data: Vec<u8>,
+}
+
+/// a sound looping on the object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttachedSound {
+    pub sound_id: Uuid,
+    pub gain: f32,
+    pub flags: u8,
+    pub radius: f32,
+}
+
+impl CompressedObject {
+    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut cursor = Cursor::new(bytes);
+
+        let full_id = read_uuid(&mut cursor)?;
+        let local_id = cursor.read_u32::<LittleEndian>()?;
+        let pcode = PCode::from_bytes(cursor.read_u8()?);
+        let state = cursor.read_u8()?;
+        let crc = cursor.read_u32::<LittleEndian>()?;
+        let material = Material::from_bytes(cursor.read_u8()?);
+        let click_action = cursor.read_u8()?;
+        let scale = read_vec3(&mut cursor)?;
+        let position = read_vec3(&mut cursor)?;
+
+        // the rotation is sent normalized, without w
+        let xyz = read_vec3(&mut cursor)?;
+        let w = (1.0 - xyz.length_squared()).max(0.0).sqrt();
+        let rotation = Quat::from_xyzw(xyz.x, xyz.y, xyz.z, w);
+
+        let flags = cursor.read_u32::<LittleEndian>()?;
+        let owner_id = read_uuid(&mut cursor)?;
+
+        let angular_velocity = if flags & HAS_ANGULAR_VELOCITY != 0 {
+            Some(read_vec3(&mut cursor)?)
+        } else {
+            None
+        };
+        let parent_id = if flags & HAS_PARENT != 0 {
+            Some(cursor.read_u32::<LittleEndian>()?)
+        } else {
+            None
+        };
+        // trees use the scratch pad's place for their species
+        let mut tree_species = None;
+        let mut scratch_pad = None;
+        if flags & HAS_TREE != 0 {
+            tree_species = Some(cursor.read_u8()?);
+        } else if flags & HAS_SCRATCH_PAD != 0 {
+            scratch_pad = Some(read_variable_1(&mut cursor)?);
+        }
+        let text = if flags & HAS_TEXT != 0 {
+            let text = read_text(&mut cursor)?;
+            let mut color = [0u8; 4];
+            cursor.read_exact(&mut color)?;
+            Some(FloatingText { text, color })
+        } else {
+            None
+        };
+        let media_url = if flags & HAS_MEDIA_URL != 0 {
+            Some(read_text(&mut cursor)?)
+        } else {
+            None
+        };
+        let particle_system = if flags & HAS_PARTICLES != 0 {
+            Some(read_block(&mut cursor, PARTICLE_SYSTEM_LENGTH)?)
+        } else {
+            None
+        };
+
+        let extra_param_count = cursor.read_u8()?;
+        let mut extra_params = Vec::with_capacity(extra_param_count as usize);
+        for _ in 0..extra_param_count {
+            let param_type = cursor.read_u16::<LittleEndian>()?;
+            let length = cursor.read_u32::<LittleEndian>()? as usize;
+            extra_params.push(ExtraParam {
+                param_type,
+                data: read_block(&mut cursor, length)?,
+            });
+        }
+
+        let sound = if flags & HAS_SOUND != 0 {
+            Some(AttachedSound {
+                sound_id: read_uuid(&mut cursor)?,
+                gain: cursor.read_f32::<LittleEndian>()?,
+                flags: cursor.read_u8()?,
+                radius: cursor.read_f32::<LittleEndian>()?,
+            })
+        } else {
+            None
+        };
+        let name_values = if flags & HAS_NAME_VALUES != 0 {
+            Some(read_text(&mut cursor)?)
+        } else {
+            None
+        };
+
+        // the profile curve comes after the path here, unlike in ObjectAdd
+        let path_curve = cursor.read_u8()?;
+        let path_begin = cursor.read_u16::<LittleEndian>()?;
+        let path_end = cursor.read_u16::<LittleEndian>()?;
+        let path_scale_x = cursor.read_u8()?;
+        let path_scale_y = cursor.read_u8()?;
+        let path_shear_x = cursor.read_u8()?;
+        let path_shear_y = cursor.read_u8()?;
+        let path_twist = cursor.read_i8()?;
+        let path_twist_begin = cursor.read_i8()?;
+        let path_radius_offset = cursor.read_i8()?;
+        let path_taper_x = cursor.read_i8()?;
+        let path_taper_y = cursor.read_i8()?;
+        let path_revolutions = cursor.read_u8()?;
+        let path_skew = cursor.read_i8()?;
+        let shape = PrimShape {
+            path_curve,
+            profile_curve: cursor.read_u8()?,
+            path_begin,
+            path_end,
+            path_scale_x,
+            path_scale_y,
+            path_shear_x,
+            path_shear_y,
+            path_twist,
+            path_twist_begin,
+            path_radius_offset,
+            path_taper_x,
+            path_taper_y,
+            path_revolutions,
+            path_skew,
+            profile_begin: cursor.read_u16::<LittleEndian>()?,
+            profile_end: cursor.read_u16::<LittleEndian>()?,
+            profile_hollow: cursor.read_u16::<LittleEndian>()?,
+        };
+
+        let length = cursor.read_u32::<LittleEndian>()? as usize;
+        let texture_entry = if length > 0 {
+            Some(TextureEntry::from_bytes(&read_block(&mut cursor, length)?)?)
+        } else {
+            None
+        };
+        let texture_animation = if flags & HAS_TEXTURE_ANIMATION != 0 {
+            let length = cursor.read_u32::<LittleEndian>()? as usize;
+            Some(read_block(&mut cursor, length)?)
+        } else {
+            None
+        };
+        // the new particle system is the last field, and takes up the rest of the data
+        let particles_new = if flags & HAS_PARTICLES_NEW != 0 {
+            let mut particles = Vec::new();
+            cursor.read_to_end(&mut particles)?;
+            Some(particles)
+        } else {
+            None
+        };
+
+        Ok(CompressedObject {
+            update_flags: 0,
+            full_id,
+            local_id,
+            pcode,
+            state,
+            crc,
+            material,
+            click_action,
+            scale,
+            position,
+            rotation,
+            owner_id,
+            angular_velocity,
+            parent_id,
+            tree_species,
+            scratch_pad,
+            text,
+            media_url,
+            particle_system,
+            extra_params,
+            sound,
+            name_values,
+            shape,
+            texture_entry,
+            texture_animation,
+            particles_new,
+        })
+    }
+
+    /// the flags for the optional fields that are set
+    fn compressed_flags(&self) -> u32 {
+        let mut flags = 0;
+        let fields = [
+            (self.scratch_pad.is_some(), HAS_SCRATCH_PAD),
+            (self.tree_species.is_some(), HAS_TREE),
+            (self.text.is_some(), HAS_TEXT),
+            (self.particle_system.is_some(), HAS_PARTICLES),
+            (self.sound.is_some(), HAS_SOUND),
+            (self.parent_id.is_some(), HAS_PARENT),
+            (self.texture_animation.is_some(), HAS_TEXTURE_ANIMATION),
+            (self.angular_velocity.is_some(), HAS_ANGULAR_VELOCITY),
+            (self.name_values.is_some(), HAS_NAME_VALUES),
+            (self.media_url.is_some(), HAS_MEDIA_URL),
+            (self.particles_new.is_some(), HAS_PARTICLES_NEW),
+        ];
+        for (is_set, flag) in fields {
+            if is_set {
+                flags |= flag;
+            }
+        }
+        flags
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.full_id.as_bytes());
+        bytes.extend_from_slice(&self.local_id.to_le_bytes());
+        bytes.push(self.pcode.to_bytes());
+        bytes.push(self.state);
+        bytes.extend_from_slice(&self.crc.to_le_bytes());
+        bytes.push(self.material.to_bytes());
+        bytes.push(self.click_action);
+        write_vec3(&mut bytes, self.scale);
+        write_vec3(&mut bytes, self.position);
+
+        // w is left out, so the rotation has to be normalized with a positive w
+        let rotation = self.rotation.normalize();
+        let rotation = if rotation.w < 0.0 {
+            -rotation
+        } else {
+            rotation
+        };
+        write_vec3(&mut bytes, Vec3::new(rotation.x, rotation.y, rotation.z));
+
+        bytes.extend_from_slice(&self.compressed_flags().to_le_bytes());
+        bytes.extend_from_slice(self.owner_id.as_bytes());
+
+        if let Some(angular_velocity) = self.angular_velocity {
+            write_vec3(&mut bytes, angular_velocity);
+        }
+        if let Some(parent_id) = self.parent_id {
+            bytes.extend_from_slice(&parent_id.to_le_bytes());
+        }
+        if let Some(tree_species) = self.tree_species {
+            bytes.push(tree_species);
+        } else if let Some(scratch_pad) = &self.scratch_pad {
+            write_variable_1(&mut bytes, scratch_pad);
+        }
+        if let Some(text) = &self.text {
+            write_text(&mut bytes, &text.text);
+            bytes.extend_from_slice(&text.color);
+        }
+        if let Some(media_url) = &self.media_url {
+            write_text(&mut bytes, media_url);
+        }
+        if let Some(particle_system) = &self.particle_system {
+            let mut particle_system = particle_system.clone();
+            particle_system.resize(PARTICLE_SYSTEM_LENGTH, 0);
+            bytes.extend_from_slice(&particle_system);
+        }
+
+        bytes.push(self.extra_params.len() as u8);
+        for param in &self.extra_params {
+            bytes.extend_from_slice(&param.param_type.to_le_bytes());
+            bytes.extend_from_slice(&(param.data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&param.data);
+        }
+
+        if let Some(sound) = &self.sound {
+            bytes.extend_from_slice(sound.sound_id.as_bytes());
+            bytes.extend_from_slice(&sound.gain.to_le_bytes());
+            bytes.push(sound.flags);
+            bytes.extend_from_slice(&sound.radius.to_le_bytes());
+        }
+        if let Some(name_values) = &self.name_values {
+            write_text(&mut bytes, name_values);
+        }
+
+        let shape = &self.shape;
+        bytes.push(shape.path_curve);
+        bytes.extend_from_slice(&shape.path_begin.to_le_bytes());
+        bytes.extend_from_slice(&shape.path_end.to_le_bytes());
+        bytes.push(shape.path_scale_x);
+        bytes.push(shape.path_scale_y);
+        bytes.push(shape.path_shear_x);
+        bytes.push(shape.path_shear_y);
+        bytes.push(shape.path_twist as u8);
+        bytes.push(shape.path_twist_begin as u8);
+        bytes.push(shape.path_radius_offset as u8);
+        bytes.push(shape.path_taper_x as u8);
+        bytes.push(shape.path_taper_y as u8);
+        bytes.push(shape.path_revolutions);
+        bytes.push(shape.path_skew as u8);
+        bytes.push(shape.profile_curve);
+        bytes.extend_from_slice(&shape.profile_begin.to_le_bytes());
+        bytes.extend_from_slice(&shape.profile_end.to_le_bytes());
+        bytes.extend_from_slice(&shape.profile_hollow.to_le_bytes());
+
+        let texture_entry = self
+            .texture_entry
+            .as_ref()
+            .map(|texture_entry| texture_entry.to_bytes())
+            .unwrap_or_default();
+        bytes.extend_from_slice(&(texture_entry.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&texture_entry);
+        if let Some(texture_animation) = &self.texture_animation {
+            bytes.extend_from_slice(&(texture_animation.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(texture_animation);
+        }
+        if let Some(particles_new) = &self.particles_new {
+            bytes.extend_from_slice(particles_new);
+        }
+        bytes
+    }
+}
+
+impl PacketData for ObjectUpdateCompressed {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let region_handle = RegionHandle(cursor.read_u64::<LittleEndian>()?);
+        let time_dilation = cursor.read_u16::<LittleEndian>()?;
+
+        let count = cursor.read_u8()?;
+        let mut objects = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let update_flags = cursor.read_u32::<LittleEndian>()?;
+            let mut object = CompressedObject::from_bytes(&read_variable_2(&mut cursor)?)?;
+            object.update_flags = update_flags;
+            objects.push(object);
+        }
+
+        Ok((
+            ObjectUpdateCompressed {
+                region_handle,
+                time_dilation,
+                objects,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.region_handle.0.to_le_bytes());
+        bytes.extend_from_slice(&self.time_dilation.to_le_bytes());
+        bytes.push(self.objects.len() as u8);
+        for object in &self.objects {
+            bytes.extend_from_slice(&object.update_flags.to_le_bytes());
+            write_variable_2(&mut bytes, &object.to_bytes());
+        }
+        bytes
+    }
+}
+
+fn read_vec3(cursor: &mut Cursor<&[u8]>) -> io::Result<Vec3> {
+    Ok(Vec3::new(
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+    ))
+}
+
+fn write_vec3(bytes: &mut Vec<u8>, vector: Vec3) {
+    bytes.extend_from_slice(&vector.x.to_le_bytes());
+    bytes.extend_from_slice(&vector.y.to_le_bytes());
+    bytes.extend_from_slice(&vector.z.to_le_bytes());
+}
+
+/// reads a block of a known length, without trusting the length to allocate
+fn read_block(cursor: &mut Cursor<&[u8]>, length: usize) -> io::Result<Vec<u8>> {
+    let mut block = Vec::new();
+    cursor.take(length as u64).read_to_end(&mut block)?;
+    if block.len() < length {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Truncated ObjectUpdateCompressed",
+        ));
+    }
+    Ok(block)
+}
+
+/// text in compressed objects is null terminated, without a length
+fn read_text(cursor: &mut Cursor<&[u8]>) -> io::Result<String> {
+    let mut text = Vec::new();
+    cursor.read_until(0, &mut text)?;
+    if text.pop() != Some(0) {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Unterminated text in ObjectUpdateCompressed",
+        ));
+    }
+    Ok(String::from_utf8_lossy(&text).into_owned())
+}
+
+fn write_text(bytes: &mut Vec<u8>, text: &str) {
+    bytes.extend_from_slice(text.as_bytes());
+    bytes.push(0);
+}
@@ -0,0 +1,62 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::ReadBytesExt;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 323
+// Frequency: Low
+
+impl Packet {
+    pub fn new_offline_notification(offline_notification: OfflineNotification) -> Self {
+        Packet::new(
+            323,
+            PacketFrequency::Low,
+            PacketType::OfflineNotification(Box::new(offline_notification)),
+        )
+        .reliable(true)
+    }
+}
+
+/// Sent by the server when friends of the agent go offline.
+/// The mailbox sends a FriendPresenceEvent to the UI for each of them.
+/// https://wiki.secondlife.com/wiki/OfflineNotification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OfflineNotification {
+    /// the agent IDs of the friends
+    pub agent_ids: Vec<Uuid>,
+}
+
+impl PacketData for OfflineNotification {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let agent_count = cursor.read_u8()? as usize;
+        let mut agent_ids = Vec::with_capacity(agent_count);
+        let mut uuid_bytes = [0u8; 16];
+        for _ in 0..agent_count {
+            cursor.read_exact(&mut uuid_bytes)?;
+            agent_ids.push(Uuid::from_bytes(uuid_bytes));
+        }
+
+        Ok((
+            OfflineNotification { agent_ids },
+            cursor.position() as usize,
+        ))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let agent_ids = &self.agent_ids[..self.agent_ids.len().min(u8::MAX as usize)];
+        let mut bytes = Vec::with_capacity(1 + agent_ids.len() * 16);
+        bytes.push(agent_ids.len() as u8);
+        for agent_id in agent_ids {
+            bytes.extend_from_slice(agent_id.as_bytes());
+        }
+        bytes
+    }
+}
@@ -1,10 +1,10 @@
 use super::packet_types::PacketType;
-use crate::header::Header;
+use crate::header::{Header, PacketFrequency};
+use crate::ui_events::UiEventTypes;
 use actix::prelude::*;
 use log::warn;
 use std::any::Any;
 use std::io;
-use std::io::{Cursor, Read};
 
 #[derive(Debug, Message, Clone)]
 #[rtype(result = "()")]
@@ -17,45 +17,153 @@ pub struct Packet {
 #[rtype(result = "()")]
 pub struct Initialize {}
 
+/// How the mailbox treats a packet. Every packet type is listed in PacketType::message_type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MessageType {
+    /// acks for reliable packets
     Acknowledgment,
+    /// packets the mailbox answers itself, like pings and the region handshake
     Request,
+    /// packets from the server that are forwarded to the UI
     Event,
     Command,
     Error,
+    /// packets from the server that the mailbox consumes, like the pieces of a texture
     Data,
+    /// packets only sent from the client to the server
     Outgoing,
     Login, // special type for login
 }
 
+/// what happens to a packet, as returned by Packet::classify.
+/// Only UiEvent packets reach the UI as they are.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PacketCategory {
+    /// acks, pings and the region handshake, which keep the circuit alive
+    Circuit,
+    /// packets from the server that are forwarded to the UI as this event
+    UiEvent(UiEventTypes),
+    /// packets from the server that the mailbox consumes. The UI may receive what is made out of
+    /// them, like the texture assembled from its pieces.
+    Data,
+    /// packets only sent from the client to the server
+    Outgoing,
+    /// the login, which happens over HTTP instead of UDP
+    Login,
+    /// errors reported to the UI
+    Error,
+}
+
+/// options for parsing packets. The default is lenient, which is what the mailbox uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// reject packets with bytes left over after the body's parser is done, instead of ignoring
+    /// them. Useful for fuzzing and for catching parsers that stop early, like a misread length
+    /// prefix.
+    pub strict: bool,
+}
+
+impl ParseOptions {
+    /// reject packets with bytes the body's parser didn't read
+    pub fn strict() -> Self {
+        ParseOptions { strict: true }
+    }
+}
+
 // this is the trait that allows for serializing and deserializing the packet's data
 pub trait PacketData: std::fmt::Debug + Send + Sync + 'static + Any {
-    fn from_bytes(bytes: &[u8]) -> io::Result<Self>
+    /// parses the packet, and returns how many bytes the parser read
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)>
     where
         Self: Sized;
+    fn from_bytes(bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        Self::from_bytes_with_len(bytes).map(|(data, _)| data)
+    }
     fn to_bytes(&self) -> Vec<u8>;
 }
 
 impl Packet {
+    /// creates a packet with the default header flags.
+    /// The flags can be set with the builder methods, like
+    /// `Packet::new(id, frequency, body).reliable(true).zerocoded(true)`
+    pub fn new(id: u16, frequency: PacketFrequency, body: PacketType) -> Self {
+        Packet {
+            header: Header::new(id, frequency),
+            body,
+        }
+    }
+    /// reliable packets are resent until the server acks them
+    pub fn reliable(mut self, reliable: bool) -> Self {
+        self.header.reliable = reliable;
+        self
+    }
+    /// zerocoded packets have runs of zeros in the body compressed
+    pub fn zerocoded(mut self, zerocoded: bool) -> Self {
+        self.header.zerocoded = zerocoded;
+        // zerocoding can change the length of the ID
+        self.header.size = Some(self.header.encoded_size());
+        self
+    }
+    /// whether the packet is resent until the server acks it
+    pub fn is_reliable(&self) -> bool {
+        self.header.reliable
+    }
+    /// what happens to the packet, from its MessageType and UI event
+    pub fn classify(&self) -> PacketCategory {
+        match self.body.message_type() {
+            MessageType::Acknowledgment | MessageType::Request => PacketCategory::Circuit,
+            MessageType::Event => PacketCategory::UiEvent(self.body.ui_event()),
+            MessageType::Data => PacketCategory::Data,
+            MessageType::Command | MessageType::Outgoing => PacketCategory::Outgoing,
+            MessageType::Login => PacketCategory::Login,
+            MessageType::Error => PacketCategory::Error,
+        }
+    }
     pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        Self::from_bytes_with_len(bytes).map(|(packet, _)| packet)
+    }
+
+    /// parses a packet, and returns it with the number of bytes that were used.
+    /// This is the header, the body as far as its parser read it, and the appended acks. It is
+    /// less than the length of bytes if there is data between the body and the appended acks that
+    /// the body's parser ignored.
+    pub fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        Self::from_bytes_with_options(bytes, ParseOptions::default())
+    }
+
+    /// parses a packet like from_bytes_with_len. In strict mode, a body with bytes left over
+    /// after its parser is done is an error.
+    pub fn from_bytes_with_options(
+        bytes: &[u8],
+        options: ParseOptions,
+    ) -> io::Result<(Self, usize)> {
         let header = Header::try_from_bytes(bytes)?;
+        // appended acks are stored at the end of the packet, and are not part of the body
+        let ack_len = match &header.ack_list {
+            Some(ack_list) => ack_list.len() * 4 + 1,
+            None => 0,
+        };
+        let body_end = bytes.len() - ack_len;
         // if the packet has a body, add the body to the packet
-        let body = if header.size.unwrap_or(0) < bytes.len() {
-            &bytes[header.size.unwrap_or(0)..]
+        let raw_body = if header.size.unwrap_or(0) < body_end {
+            &bytes[header.size.unwrap_or(0)..body_end]
         } else {
             &[]
         };
         let body_bytes = if header.zerocoded {
-            zero_decode(body)
+            zero_decode(raw_body)?
         } else {
-            body.to_vec() // Convert slice to Vec<u8>
+            raw_body.to_vec() // Convert slice to Vec<u8>
         };
 
-        println!(
-            "header id: {:?}, header frequency: {:?}",
-            header.id, header.frequency
-        );
-        let body = match PacketType::from_id(header.id, header.frequency, body_bytes.as_slice()) {
+        let (body, body_len) = match PacketType::from_id_with_len(
+            header.id,
+            header.frequency,
+            body_bytes.as_slice(),
+        ) {
             Ok(parsed_body) => parsed_body, // If parsing succeeds, use the parsed body
             Err(e) => {
                 warn!(
@@ -65,49 +173,97 @@ impl Packet {
                 return Err(e);
             }
         };
-        Ok(Self { header, body })
+        // the parser's cursor stops where the body ends, anything after it is trailing data
+        let body_len = body_len.min(body_bytes.len());
+        let trailing = body_bytes.len() - body_len;
+        if options.strict && trailing > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} trailing bytes after packet id: {}, frequency: {}",
+                    trailing, header.id, header.frequency
+                ),
+            ));
+        }
+        let body_len = if header.zerocoded {
+            zero_encoded_len(raw_body, body_len)
+        } else {
+            body_len
+        };
+        let header_len = header.size.unwrap_or(0).min(body_end);
+        Ok((Self { header, body }, header_len + body_len + ack_len))
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
         bytes.extend(self.header.to_bytes());
-        bytes.extend(self.body.to_bytes());
+        // the runs of zeros in the body of a zerocoded packet are compressed. The appended acks
+        // come after the zerocoded data, so they are written as they are.
+        if self.header.zerocoded {
+            bytes.extend(zero_encode(&self.body.to_bytes()));
+        } else {
+            bytes.extend(self.body.to_bytes());
+        }
+        // acks are appended to the end of the packet, followed by a single byte count
+        if self.header.appended_acks {
+            if let Some(ref ack_list) = self.header.ack_list {
+                for ack in ack_list {
+                    bytes.extend_from_slice(&ack.to_be_bytes());
+                }
+                bytes.push(ack_list.len() as u8);
+            }
+        }
         bytes
     }
 }
 
-fn zero_decode(bytes: &[u8]) -> Vec<u8> {
-    let mut cursor = Cursor::new(bytes);
-    let mut dest = Vec::new();
-
-    while cursor.position() < bytes.len() as u64 {
-        let mut byte = [0u8; 1];
-        cursor.read_exact(&mut byte).unwrap();
-        let byte = byte[0];
+fn zero_decode(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let mut dest = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter();
 
+    while let Some(&byte) = bytes.next() {
         if byte == 0x00 {
-            let mut repeat_count = [0u8; 1];
-            cursor.read_exact(&mut repeat_count).unwrap();
-            let repeat_count = repeat_count[0] as usize;
-
-            dest.extend(vec![0x00; repeat_count]);
+            let repeat_count = *bytes.next().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "zerocoded run is missing its length",
+                )
+            })?;
+            dest.resize(dest.len() + repeat_count as usize, 0x00);
         } else {
             dest.push(byte);
         }
     }
-    dest
+    Ok(dest)
+}
+
+/// the number of zerocoded bytes that decode to the first decoded_len bytes
+fn zero_encoded_len(encoded: &[u8], decoded_len: usize) -> usize {
+    let mut decoded = 0;
+    let mut position = 0;
+    while decoded < decoded_len && position < encoded.len() {
+        if encoded[position] == 0x00 {
+            decoded += encoded.get(position + 1).copied().unwrap_or(0) as usize;
+            position += 2;
+        } else {
+            decoded += 1;
+            position += 1;
+        }
+    }
+    position.min(encoded.len())
 }
 
-// not implemented yet
-fn _zero_encode(src: &[u8]) -> Vec<u8> {
-    let mut dest = Vec::new();
+/// compress every run of zeros into a zero followed by the length of the run. Runs longer than
+/// 255 are split, because the length is a single byte.
+fn zero_encode(src: &[u8]) -> Vec<u8> {
+    let mut dest = Vec::with_capacity(src.len());
     let mut i = 0;
 
     while i < src.len() {
         if src[i] == 0x00 {
             // Count consecutive zeros
             let mut count = 1;
-            while i + count < src.len() && src[i + count] == 0x00 {
+            while i + count < src.len() && src[i + count] == 0x00 && count < u8::MAX as usize {
                 count += 1;
             }
 
@@ -1,38 +1,45 @@
 use crate::packet_types::PacketType;
 
 use super::{
-    header::{Header, PacketFrequency},
+    header::PacketFrequency,
     packet::{Packet, PacketData},
 };
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::{Deserialize, Serialize};
 use std::io::{self, Cursor};
 
+/// the ack count is stored in a single byte, so no more than this fit in one PacketAck
+pub const MAX_PACKET_ACKS: usize = 255;
+
 impl Packet {
     pub fn new_packet_ack(packet_ack: PacketAck) -> Self {
-        Packet {
-            header: Header {
-                id: 251,
-                reliable: false,
-                resent: false,
-                zerocoded: false,
-                appended_acks: false,
-                sequence_number: 0,
-                frequency: PacketFrequency::Fixed,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::PacketAck(Box::new(packet_ack)),
-        }
+        Packet::new(
+            251,
+            PacketFrequency::Fixed,
+            PacketType::PacketAck(Box::new(packet_ack)),
+        )
+    }
+
+    /// acks for any number of packets, batched into as few PacketAcks as they fit in
+    pub fn new_packet_acks(packet_ids: &[u32]) -> Vec<Self> {
+        packet_ids
+            .chunks(MAX_PACKET_ACKS)
+            .map(|packet_ids| {
+                Packet::new_packet_ack(PacketAck {
+                    packet_ids: packet_ids.to_vec(),
+                })
+            })
+            .collect()
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PacketAck {
     pub packet_ids: Vec<u32>,
 }
 
 impl PacketData for PacketAck {
-    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
         let mut cursor = Cursor::new(bytes);
 
         let count = cursor.read_u8()? as usize;
@@ -43,7 +50,7 @@ impl PacketData for PacketAck {
             packet_ids.push(id);
         }
 
-        Ok(PacketAck { packet_ids })
+        Ok((PacketAck { packet_ids }, cursor.position() as usize))
     }
 
     fn to_bytes(&self) -> Vec<u8> {
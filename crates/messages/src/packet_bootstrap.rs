@@ -4,24 +4,12 @@
 /// *PacketName*    is the name of the packet like "RegionHandshake"
 /// *id*            is the ID of the packet 
 /// 
-use crate::{header::{Header, PacketFrequency}, packet::{Packet, PacketData}}
+use crate::{header::PacketFrequency, packet::{Packet, PacketData}}
 
 impl Packet{
     pub fn new_*local_name*(*local_name*: *PacketName* ) -> Self{
-        Packet{
-            header: Header{
-                id: *id*
-                reliable: false 
-                resent: false, 
-                zerocoded: false, 
-                appended_acks: false, 
-                sequence_number: false, 
-                frequency: PacketFrequency::Low, 
-                ack_list: None, 
-                size: None
-            },
-            body: PacketType::*PacketName*(Box::new(*local_name*)),
-        }
+        // add .reliable(true) or .zerocoded(true) to set the header flags
+        Packet::new(*id*, PacketFrequency::Low, PacketType::*PacketName*(Box::new(*local_name*)))
     }
 }
 
@@ -30,12 +18,12 @@ impl Packet{
 pub struct *PacketName*
 
 impl PacketData for *PacketName* {
-    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
         let mut cursor = Cursor::new(bytes);
         // handle from bytes
-        Ok(*PacketName*{
+        Ok((*PacketName*{
             // Struct fields 
-        })
+        }, cursor.position() as usize))
     }
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
@@ -1,16 +1,65 @@
+use crate::capabilities::event_queue::EventQueueEvent;
 use crate::errors::SessionError;
+use crate::friend_presence::FriendPresence;
+use crate::image_data::ImageData;
+use crate::image_packet::ImagePacket;
 use crate::layer_data::LayerData;
 use crate::login_system::login::Login;
 use crate::login_system::login_response::LoginResponse;
+use crate::login_system::login_summary::LoginSummary;
 use crate::packet::MessageType;
 use crate::region_handshake::RegionHandshake;
 use crate::region_handshake_reply::RegionHandshakeReply;
+use crate::request_image::RequestImage;
+use crate::texture::Texture;
 use crate::ui_events::UiEventTypes;
 
+use super::agent_animation::AgentAnimation;
+use super::agent_height_width::AgentHeightWidth;
+use super::agent_movement_complete::AgentMovementComplete;
+use super::agent_pause::AgentPause;
+use super::agent_resume::AgentResume;
+use super::agent_set_appearance::AgentSetAppearance;
+use super::agent_throttle::AgentThrottle;
 use super::agent_update::AgentUpdate;
+use super::avatar_animation::AvatarAnimation;
+use super::avatar_appearance::AvatarAppearance;
 use super::chat_from_simulator::ChatFromSimulator;
 use super::chat_from_viewer::ChatFromViewer;
 use super::complete_agent_movement::CompleteAgentMovementData;
+use super::enable_simulator::EnableSimulator;
+#[cfg(feature = "admin")]
+use super::god_kick_user::GodKickUser;
+#[cfg(feature = "admin")]
+use super::grant_godlike_powers::GrantGodlikePowers;
+use super::improved_instant_message::ImprovedInstantMessage;
+use super::kill_object::KillObject;
+use super::logout_request::LogoutRequest;
+use super::money_balance_reply::MoneyBalanceReply;
+use super::money_balance_request::MoneyBalanceRequest;
+use super::object_add::ObjectAdd;
+use super::object_delete::ObjectDelete;
+use super::object_deselect::ObjectDeselect;
+use super::object_properties::ObjectProperties;
+use super::object_select::ObjectSelect;
+use super::object_update_cached::ObjectUpdateCached;
+use super::object_update_compressed::ObjectUpdateCompressed;
+use super::offline_notification::OfflineNotification;
+use super::online_notification::OnlineNotification;
+use super::region_info::RegionInfo;
+#[cfg(feature = "admin")]
+use super::request_godlike_powers::RequestGodlikePowers;
+use super::request_multiple_objects::RequestMultipleObjects;
+use super::request_region_info::RequestRegionInfo;
+use super::script_answer_yes::ScriptAnswerYes;
+use super::script_dialog::ScriptDialog;
+use super::script_dialog_reply::ScriptDialogReply;
+use super::script_question::ScriptQuestion;
+use super::sim_stats::SimStats;
+use super::teleport_lure_request::TeleportLureRequest;
+use super::uuid_name_reply::UUIDNameReply;
+use super::uuid_name_request::UUIDNameRequest;
+use super::viewer_effect::ViewerEffect;
 use super::{
     circuit_code::CircuitCodeData, coarse_location_update::CoarseLocationUpdate,
     complete_ping_check::CompletePingCheck, disable_simulator::DisableSimulator,
@@ -32,33 +81,131 @@ pub enum PacketType {
     CoarseLocationUpdate(Box<CoarseLocationUpdate>),
     CompleteAgentMovementData(Box<CompleteAgentMovementData>),
     AgentUpdate(Box<AgentUpdate>),
+    AgentThrottle(Box<AgentThrottle>),
+    AvatarAnimation(Box<AvatarAnimation>),
     ChatFromSimulator(Box<ChatFromSimulator>),
     ChatFromViewer(Box<ChatFromViewer>),
+    KillObject(Box<KillObject>),
     StartPingCheck(Box<StartPingCheck>),
     CompletePingCheck(Box<CompletePingCheck>),
     RegionHandshake(Box<RegionHandshake>),
     RegionHandshakeReply(Box<RegionHandshakeReply>),
     LayerData(Box<LayerData>),
+    UUIDNameRequest(Box<UUIDNameRequest>),
+    UUIDNameReply(Box<UUIDNameReply>),
+    AgentMovementComplete(Box<AgentMovementComplete>),
+    LogoutRequest(Box<LogoutRequest>),
+    ImprovedInstantMessage(Box<ImprovedInstantMessage>),
+    RequestImage(Box<RequestImage>),
+    ImageData(Box<ImageData>),
+    ImagePacket(Box<ImagePacket>),
+    ObjectSelect(Box<ObjectSelect>),
+    ObjectDeselect(Box<ObjectDeselect>),
+    ObjectAdd(Box<ObjectAdd>),
+    ObjectDelete(Box<ObjectDelete>),
+    RequestMultipleObjects(Box<RequestMultipleObjects>),
+    ObjectProperties(Box<ObjectProperties>),
+    ViewerEffect(Box<ViewerEffect>),
+    MoneyBalanceRequest(Box<MoneyBalanceRequest>),
+    MoneyBalanceReply(Box<MoneyBalanceReply>),
+    RequestRegionInfo(Box<RequestRegionInfo>),
+    RegionInfo(Box<RegionInfo>),
+    ObjectUpdateCompressed(Box<ObjectUpdateCompressed>),
+    EnableSimulator(Box<EnableSimulator>),
+    ObjectUpdateCached(Box<ObjectUpdateCached>),
+    AgentSetAppearance(Box<AgentSetAppearance>),
+    AgentAnimation(Box<AgentAnimation>),
+    AgentPause(Box<AgentPause>),
+    AgentResume(Box<AgentResume>),
+    AgentHeightWidth(Box<AgentHeightWidth>),
+    AvatarAppearance(Box<AvatarAppearance>),
+    SimStats(Box<SimStats>),
+    ScriptDialog(Box<ScriptDialog>),
+    ScriptDialogReply(Box<ScriptDialogReply>),
+    ScriptQuestion(Box<ScriptQuestion>),
+    ScriptAnswerYes(Box<ScriptAnswerYes>),
+    TeleportLureRequest(Box<TeleportLureRequest>),
+    OnlineNotification(Box<OnlineNotification>),
+    OfflineNotification(Box<OfflineNotification>),
+    // grid administration, behind the admin feature
+    #[cfg(feature = "admin")]
+    RequestGodlikePowers(Box<RequestGodlikePowers>),
+    #[cfg(feature = "admin")]
+    GrantGodlikePowers(Box<GrantGodlikePowers>),
+    #[cfg(feature = "admin")]
+    GodKickUser(Box<GodKickUser>),
     // these do not exist in the packet spec! Used as utilities for communicating with server and
     // client.
     Login(Box<Login>),
     LoginResponse(Box<LoginResponse>),
+    LoginComplete(Box<LoginSummary>),
     Error(Box<SessionError>),
+    // events from the EventQueueGet capability, which arrive over HTTP instead of UDP
+    EventQueueEvent(Box<EventQueueEvent>),
+    // textures assembled from ImageData and ImagePackets
+    Texture(Box<Texture>),
+    // friends coming online and going offline, from Online and OfflineNotifications
+    FriendPresence(Box<FriendPresence>),
 }
 // I think I should remove MessageTypes entirely. They don't exist in the spec
 // I think I was using a different project's design and didn't think it through.
 impl PacketType {
+    /// how the mailbox treats the packet. There is no default, so every new packet type has to
+    /// be given one here, and Events also need a ui_event.
     pub fn message_type(&self) -> MessageType {
         match self {
             PacketType::ChatFromSimulator(_) => MessageType::Event,
             PacketType::CoarseLocationUpdate(_) => MessageType::Event,
             PacketType::DisableSimulator(_) => MessageType::Event,
             PacketType::LayerData(_) => MessageType::Event,
+            PacketType::AvatarAnimation(_) => MessageType::Event,
+            PacketType::KillObject(_) => MessageType::Event,
+            PacketType::UUIDNameReply(_) => MessageType::Event,
+            PacketType::EventQueueEvent(_) => MessageType::Event,
+            PacketType::AgentMovementComplete(_) => MessageType::Event,
+            PacketType::ImprovedInstantMessage(_) => MessageType::Event,
+            PacketType::Texture(_) => MessageType::Event,
+            PacketType::FriendPresence(_) => MessageType::Event,
+            PacketType::ObjectProperties(_) => MessageType::Event,
+            PacketType::ViewerEffect(_) => MessageType::Event,
+            PacketType::MoneyBalanceRequest(_) => MessageType::Outgoing,
+            PacketType::MoneyBalanceReply(_) => MessageType::Event,
+            PacketType::RequestRegionInfo(_) => MessageType::Outgoing,
+            PacketType::RegionInfo(_) => MessageType::Event,
+            PacketType::ObjectUpdateCompressed(_) => MessageType::Event,
+            PacketType::EnableSimulator(_) => MessageType::Event,
+            PacketType::AvatarAppearance(_) => MessageType::Event,
+            PacketType::SimStats(_) => MessageType::Event,
+            PacketType::ScriptDialog(_) => MessageType::Event,
+            PacketType::ScriptQuestion(_) => MessageType::Event,
 
             PacketType::AgentUpdate(_) => MessageType::Outgoing,
+            PacketType::AgentThrottle(_) => MessageType::Outgoing,
             PacketType::CompleteAgentMovementData(_) => MessageType::Outgoing,
             PacketType::ChatFromViewer(_) => MessageType::Outgoing,
             PacketType::CircuitCode(_) => MessageType::Outgoing,
+            PacketType::UUIDNameRequest(_) => MessageType::Outgoing,
+            PacketType::LogoutRequest(_) => MessageType::Outgoing,
+            PacketType::RequestImage(_) => MessageType::Outgoing,
+            PacketType::ObjectSelect(_) => MessageType::Outgoing,
+            PacketType::ObjectDeselect(_) => MessageType::Outgoing,
+            PacketType::ObjectAdd(_) => MessageType::Outgoing,
+            PacketType::ObjectDelete(_) => MessageType::Outgoing,
+            PacketType::RequestMultipleObjects(_) => MessageType::Outgoing,
+            PacketType::AgentSetAppearance(_) => MessageType::Outgoing,
+            PacketType::AgentAnimation(_) => MessageType::Outgoing,
+            PacketType::AgentPause(_) => MessageType::Outgoing,
+            PacketType::AgentResume(_) => MessageType::Outgoing,
+            PacketType::AgentHeightWidth(_) => MessageType::Outgoing,
+            PacketType::ScriptDialogReply(_) => MessageType::Outgoing,
+            PacketType::ScriptAnswerYes(_) => MessageType::Outgoing,
+            PacketType::TeleportLureRequest(_) => MessageType::Outgoing,
+
+            PacketType::ImageData(_) => MessageType::Data,
+            PacketType::ImagePacket(_) => MessageType::Data,
+            PacketType::ObjectUpdateCached(_) => MessageType::Data,
+            PacketType::OnlineNotification(_) => MessageType::Data,
+            PacketType::OfflineNotification(_) => MessageType::Data,
 
             PacketType::StartPingCheck(_) => MessageType::Request,
             PacketType::CompletePingCheck(_) => MessageType::Request,
@@ -67,8 +214,16 @@ impl PacketType {
 
             PacketType::PacketAck(_) => MessageType::Acknowledgment,
 
+            #[cfg(feature = "admin")]
+            PacketType::RequestGodlikePowers(_) => MessageType::Outgoing,
+            #[cfg(feature = "admin")]
+            PacketType::GrantGodlikePowers(_) => MessageType::Event,
+            #[cfg(feature = "admin")]
+            PacketType::GodKickUser(_) => MessageType::Outgoing,
+
             PacketType::Login(_) => MessageType::Login,
             PacketType::LoginResponse(_) => MessageType::Login,
+            PacketType::LoginComplete(_) => MessageType::Login,
             PacketType::Error(_) => MessageType::Error,
         }
     }
@@ -77,6 +232,27 @@ impl PacketType {
             PacketType::ChatFromSimulator(_) => UiEventTypes::ChatFromSimulatorEvent,
             PacketType::CoarseLocationUpdate(_) => UiEventTypes::CoarseLocationUpdateEvent,
             PacketType::DisableSimulator(_) => UiEventTypes::DisableSimulatorEvent,
+            PacketType::AvatarAnimation(_) => UiEventTypes::AvatarAnimationEvent,
+            PacketType::KillObject(_) => UiEventTypes::KillObjectEvent,
+            PacketType::UUIDNameReply(_) => UiEventTypes::UUIDNameReplyEvent,
+            PacketType::EventQueueEvent(_) => UiEventTypes::EventQueueEvent,
+            PacketType::AgentMovementComplete(_) => UiEventTypes::MovementCompleteEvent,
+            PacketType::ImprovedInstantMessage(_) => UiEventTypes::ImprovedInstantMessageEvent,
+            PacketType::Texture(_) => UiEventTypes::TextureEvent,
+            PacketType::FriendPresence(_) => UiEventTypes::FriendPresenceEvent,
+            PacketType::ObjectProperties(_) => UiEventTypes::ObjectPropertiesEvent,
+            PacketType::ViewerEffect(_) => UiEventTypes::ViewerEffectEvent,
+            PacketType::MoneyBalanceReply(_) => UiEventTypes::BalanceUpdateEvent,
+            PacketType::RegionInfo(_) => UiEventTypes::RegionInfoEvent,
+            PacketType::ObjectUpdateCompressed(_) => UiEventTypes::ObjectUpdateCompressedEvent,
+            PacketType::EnableSimulator(_) => UiEventTypes::EnableSimulatorEvent,
+            PacketType::LayerData(_) => UiEventTypes::LayerDataEvent,
+            PacketType::AvatarAppearance(_) => UiEventTypes::AvatarAppearanceEvent,
+            PacketType::SimStats(_) => UiEventTypes::SimStatsEvent,
+            PacketType::ScriptDialog(_) => UiEventTypes::ScriptDialogEvent,
+            PacketType::ScriptQuestion(_) => UiEventTypes::ScriptQuestionEvent,
+            #[cfg(feature = "admin")]
+            PacketType::GrantGodlikePowers(_) => UiEventTypes::GodlikePowersEvent,
             _ => UiEventTypes::None,
         }
     }
@@ -88,8 +264,11 @@ impl PacketType {
             PacketType::CoarseLocationUpdate(data) => data.to_bytes(),
             PacketType::CompleteAgentMovementData(data) => data.to_bytes(),
             PacketType::AgentUpdate(data) => data.to_bytes(),
+            PacketType::AgentThrottle(data) => data.to_bytes(),
+            PacketType::AvatarAnimation(data) => data.to_bytes(),
             PacketType::ChatFromSimulator(data) => data.to_bytes(),
             PacketType::ChatFromViewer(data) => data.to_bytes(),
+            PacketType::KillObject(data) => data.to_bytes(),
             PacketType::Login(data) => data.to_bytes(),
             PacketType::Error(data) => data.to_bytes(),
             PacketType::StartPingCheck(data) => data.to_bytes(),
@@ -97,14 +276,69 @@ impl PacketType {
             PacketType::RegionHandshake(data) => data.to_bytes(),
             PacketType::RegionHandshakeReply(data) => data.to_bytes(),
             PacketType::LayerData(data) => data.to_bytes(),
+            PacketType::UUIDNameRequest(data) => data.to_bytes(),
+            PacketType::UUIDNameReply(data) => data.to_bytes(),
+            PacketType::EventQueueEvent(data) => data.to_bytes(),
+            PacketType::AgentMovementComplete(data) => data.to_bytes(),
+            PacketType::LogoutRequest(data) => data.to_bytes(),
+            PacketType::ImprovedInstantMessage(data) => data.to_bytes(),
+            PacketType::RequestImage(data) => data.to_bytes(),
+            PacketType::ImageData(data) => data.to_bytes(),
+            PacketType::ImagePacket(data) => data.to_bytes(),
+            PacketType::Texture(data) => data.to_bytes(),
+            PacketType::FriendPresence(data) => data.to_bytes(),
+            PacketType::ObjectSelect(data) => data.to_bytes(),
+            PacketType::ObjectDeselect(data) => data.to_bytes(),
+            PacketType::ObjectAdd(data) => data.to_bytes(),
+            PacketType::ObjectDelete(data) => data.to_bytes(),
+            PacketType::RequestMultipleObjects(data) => data.to_bytes(),
+            PacketType::ObjectProperties(data) => data.to_bytes(),
+            PacketType::ViewerEffect(data) => data.to_bytes(),
+            PacketType::MoneyBalanceRequest(data) => data.to_bytes(),
+            PacketType::MoneyBalanceReply(data) => data.to_bytes(),
+            PacketType::RequestRegionInfo(data) => data.to_bytes(),
+            PacketType::RegionInfo(data) => data.to_bytes(),
+            PacketType::ObjectUpdateCompressed(data) => data.to_bytes(),
+            PacketType::EnableSimulator(data) => data.to_bytes(),
+            PacketType::ObjectUpdateCached(data) => data.to_bytes(),
+            PacketType::AgentSetAppearance(data) => data.to_bytes(),
+            PacketType::AgentAnimation(data) => data.to_bytes(),
+            PacketType::AvatarAppearance(data) => data.to_bytes(),
+            PacketType::SimStats(data) => data.to_bytes(),
+            PacketType::ScriptDialog(data) => data.to_bytes(),
+            PacketType::ScriptDialogReply(data) => data.to_bytes(),
+            PacketType::ScriptQuestion(data) => data.to_bytes(),
+            PacketType::ScriptAnswerYes(data) => data.to_bytes(),
+            PacketType::TeleportLureRequest(data) => data.to_bytes(),
+            PacketType::OnlineNotification(data) => data.to_bytes(),
+            PacketType::OfflineNotification(data) => data.to_bytes(),
+            #[cfg(feature = "admin")]
+            PacketType::RequestGodlikePowers(data) => data.to_bytes(),
+            #[cfg(feature = "admin")]
+            PacketType::GrantGodlikePowers(data) => data.to_bytes(),
+            #[cfg(feature = "admin")]
+            PacketType::GodKickUser(data) => data.to_bytes(),
+            PacketType::AgentPause(data) => data.to_bytes(),
+            PacketType::AgentResume(data) => data.to_bytes(),
+            PacketType::AgentHeightWidth(data) => data.to_bytes(),
 
             PacketType::LoginResponse(_) => Vec::new(),
+            PacketType::LoginComplete(data) => serde_json::to_vec(data).unwrap_or_default(),
         }
     }
 }
 
 impl PacketType {
     pub fn from_id(id: u16, frequency: PacketFrequency, bytes: &[u8]) -> io::Result<Self> {
+        Self::from_id_with_len(id, frequency, bytes).map(|(packet_type, _)| packet_type)
+    }
+
+    /// parses the body like from_id, and also returns how many bytes of it the parser read
+    pub fn from_id_with_len(
+        id: u16,
+        frequency: PacketFrequency,
+        bytes: &[u8],
+    ) -> io::Result<(Self, usize)> {
         // the packets are organized by frquency.
         // I really don't like it, but there's nothing I can do about it
         // I will eventually organize these by type
@@ -115,64 +349,83 @@ impl PacketType {
         // Data.
         match frequency {
             PacketFrequency::High => match id {
-                1 => Ok(PacketType::StartPingCheck(Box::new(
-                    StartPingCheck::from_bytes(bytes)?,
-                ))),
-                2 => Ok(PacketType::CompletePingCheck(Box::new(
-                    CompletePingCheck::from_bytes(bytes)?,
-                ))),
-                4 => Ok(PacketType::AgentUpdate(Box::new(AgentUpdate::from_bytes(
-                    bytes,
-                )?))),
-                11 => Ok(PacketType::LayerData(Box::new(LayerData::from_bytes(
-                    bytes,
-                )?))),
+                1 => parse(bytes, PacketType::StartPingCheck),
+                2 => parse(bytes, PacketType::CompletePingCheck),
+                4 => parse(bytes, PacketType::AgentUpdate),
+                5 => parse(bytes, PacketType::AgentAnimation),
+                8 => parse(bytes, PacketType::RequestImage),
+                9 => parse(bytes, PacketType::ImageData),
+                10 => parse(bytes, PacketType::ImagePacket),
+                20 => parse(bytes, PacketType::AvatarAnimation),
+                13 => parse(bytes, PacketType::ObjectUpdateCompressed),
+                14 => parse(bytes, PacketType::ObjectUpdateCached),
+                16 => parse(bytes, PacketType::KillObject),
+                11 => parse(bytes, PacketType::LayerData),
                 id => Err(io::Error::new(
                     io::ErrorKind::InvalidData,
                     format!("Unknown packet ID: {}, frequency: {}", id, frequency),
                 )),
             },
             PacketFrequency::Medium => match id {
-                6 => Ok(PacketType::CoarseLocationUpdate(Box::new(
-                    CoarseLocationUpdate::from_bytes(bytes)?,
-                ))),
+                1 => parse(bytes, PacketType::ObjectAdd),
+                3 => parse(bytes, PacketType::RequestMultipleObjects),
+                6 => parse(bytes, PacketType::CoarseLocationUpdate),
+                9 => parse(bytes, PacketType::ObjectProperties),
+                17 => parse(bytes, PacketType::ViewerEffect),
                 id => Err(io::Error::new(
                     io::ErrorKind::InvalidData,
                     format!("Unknown packet ID: {}, frequency: {}", id, frequency),
                 )),
             },
             PacketFrequency::Low => match id {
-                3 => Ok(PacketType::CircuitCode(Box::new(
-                    CircuitCodeData::from_bytes(bytes)?,
-                ))),
-                148 => Ok(PacketType::RegionHandshake(Box::new(
-                    RegionHandshake::from_bytes(bytes)?,
-                ))),
-                149 => Ok(PacketType::RegionHandshakeReply(Box::new(
-                    RegionHandshakeReply::from_bytes(bytes)?,
-                ))),
-                152 => Ok(PacketType::DisableSimulator(Box::new(
-                    DisableSimulator::from_bytes(bytes)?,
-                ))),
-                249 => Ok(PacketType::CompleteAgentMovementData(Box::new(
-                    CompleteAgentMovementData::from_bytes(bytes)?,
-                ))),
-                139 => Ok(PacketType::ChatFromSimulator(Box::new(
-                    ChatFromSimulator::from_bytes(bytes)?,
-                ))),
-                80 => Ok(PacketType::ChatFromViewer(Box::new(
-                    ChatFromViewer::from_bytes(bytes)?,
-                ))),
+                3 => parse(bytes, PacketType::CircuitCode),
+                141 => parse(bytes, PacketType::RequestRegionInfo),
+                140 => parse(bytes, PacketType::SimStats),
+                71 => parse(bytes, PacketType::TeleportLureRequest),
+                132 => parse(bytes, PacketType::ScriptAnswerYes),
+                188 => parse(bytes, PacketType::ScriptQuestion),
+                190 => parse(bytes, PacketType::ScriptDialog),
+                191 => parse(bytes, PacketType::ScriptDialogReply),
+                142 => parse(bytes, PacketType::RegionInfo),
+                148 => parse(bytes, PacketType::RegionHandshake),
+                89 => parse(bytes, PacketType::ObjectDelete),
+                110 => parse(bytes, PacketType::ObjectSelect),
+                111 => parse(bytes, PacketType::ObjectDeselect),
+                149 => parse(bytes, PacketType::RegionHandshakeReply),
+                151 => parse(bytes, PacketType::EnableSimulator),
+                152 => parse(bytes, PacketType::DisableSimulator),
+                158 => parse(bytes, PacketType::AvatarAppearance),
+                #[cfg(feature = "admin")]
+                165 => parse(bytes, PacketType::GodKickUser),
+                #[cfg(feature = "admin")]
+                258 => parse(bytes, PacketType::RequestGodlikePowers),
+                #[cfg(feature = "admin")]
+                259 => parse(bytes, PacketType::GrantGodlikePowers),
+                249 => parse(bytes, PacketType::CompleteAgentMovementData),
+                250 => parse(bytes, PacketType::AgentMovementComplete),
+                252 => parse(bytes, PacketType::LogoutRequest),
+                313 => parse(bytes, PacketType::MoneyBalanceRequest),
+                314 => parse(bytes, PacketType::MoneyBalanceReply),
+                322 => parse(bytes, PacketType::OnlineNotification),
+                323 => parse(bytes, PacketType::OfflineNotification),
+                254 => parse(bytes, PacketType::ImprovedInstantMessage),
+                139 => parse(bytes, PacketType::ChatFromSimulator),
+                154 => parse(bytes, PacketType::AgentPause),
+                155 => parse(bytes, PacketType::AgentResume),
+                83 => parse(bytes, PacketType::AgentHeightWidth),
+                84 => parse(bytes, PacketType::AgentSetAppearance),
+                80 => parse(bytes, PacketType::ChatFromViewer),
+                81 => parse(bytes, PacketType::AgentThrottle),
+                235 => parse(bytes, PacketType::UUIDNameRequest),
+                236 => parse(bytes, PacketType::UUIDNameReply),
                 id => Err(io::Error::new(
                     io::ErrorKind::InvalidData,
                     format!("Unknown packet ID: {}, frequency: {}", id, frequency),
                 )),
             },
             PacketFrequency::Fixed => match id {
-                251 => Ok(PacketType::PacketAck(Box::new(PacketAck::from_bytes(
-                    bytes,
-                )?))),
-                66 => Ok(PacketType::Login(Box::new(Login::from_bytes(bytes)?))),
+                251 => parse(bytes, PacketType::PacketAck),
+                66 => parse(bytes, PacketType::Login),
 
                 id => Err(io::Error::new(
                     io::ErrorKind::InvalidData,
@@ -182,3 +435,12 @@ impl PacketType {
         }
     }
 }
+
+/// parses a body with its packet's parser, and wraps it in its PacketType variant
+fn parse<T: PacketData>(
+    bytes: &[u8],
+    packet_type: fn(Box<T>) -> PacketType,
+) -> io::Result<(PacketType, usize)> {
+    let (data, len) = T::from_bytes_with_len(bytes)?;
+    Ok((packet_type(Box::new(data)), len))
+}
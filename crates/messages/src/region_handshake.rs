@@ -1,33 +1,26 @@
 use std::io::{self, Cursor, Read};
 use uuid::Uuid;
 
+use crate::utils::variable::{read_string, read_string_1, write_string_1};
 use crate::{
-    header::{Header, PacketFrequency},
-    packet::Packet,
-    packet_types::PacketType,
+    header::PacketFrequency, packet::Packet, packet_types::PacketType,
     utils::agent_access::AgentAccess,
 };
+use serde::{Deserialize, Serialize};
 
 impl Packet {
     pub fn new_region_handshake(region_handshake: RegionHandshake) -> Self {
-        Packet {
-            header: Header {
-                id: 80,
-                frequency: PacketFrequency::Low,
-                reliable: true,
-                sequence_number: 0,
-                appended_acks: false,
-                zerocoded: true,
-                resent: false,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::RegionHandshake(Box::new(region_handshake)),
-        }
+        Packet::new(
+            80,
+            PacketFrequency::Low,
+            PacketType::RegionHandshake(Box::new(region_handshake)),
+        )
+        .reliable(true)
+        .zerocoded(true)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionHandshake {
     pub region_info: RegionInfo,
     pub region_info_2: RegionInfo,
@@ -67,7 +60,7 @@ impl RegionHandshake {
         })
     }
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionInfo {
     pub region_flags: u32,
     pub sim_access: AgentAccess,
@@ -96,8 +89,7 @@ impl RegionInfo {
         let mut bytes = Vec::new();
         bytes.extend(&self.region_flags.to_le_bytes());
         bytes.push(self.sim_access.to_bytes());
-        bytes.extend(self.sim_name.len().to_le_bytes());
-        bytes.extend(self.sim_name.as_bytes());
+        write_string_1(&mut bytes, &self.sim_name);
         bytes.extend(self.sim_owner.as_bytes());
         bytes.push(self.is_estate_manager as u8);
         bytes.extend(&self.water_height.to_le_bytes());
@@ -134,12 +126,7 @@ impl RegionInfo {
         cursor.read_exact(&mut sim_access)?;
         let sim_access = AgentAccess::from_bytes(&sim_access[0]);
 
-        let mut name_len = [0u8; 1];
-        cursor.read_exact(&mut name_len)?;
-        let name_len = u8::from_le_bytes(name_len) as usize;
-        let mut name_bytes = vec![0u8; name_len];
-        cursor.read_exact(&mut name_bytes)?;
-        let sim_name = String::from_utf8(name_bytes).unwrap();
+        let sim_name = read_string_1(&mut cursor)?;
 
         let mut uuid_bytes = [0u8; 16];
         cursor.read_exact(&mut uuid_bytes)?;
@@ -216,7 +203,7 @@ impl RegionInfo {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionInfo2 {
     pub region_flags_2: u32,
     pub region_owner_id: Uuid,
@@ -251,9 +238,7 @@ impl RegionInfo2 {
         let mut name_len = [0u8; 4];
         cursor.read_exact(&mut name_len)?;
         let name_len = u32::from_le_bytes(name_len) as usize;
-        let mut name_bytes = vec![0u8; name_len];
-        cursor.read_exact(&mut name_bytes)?;
-        let region_name = String::from_utf8(name_bytes).unwrap();
+        let region_name = read_string(&mut cursor, name_len)?;
 
         let mut terrain_type = [0u8; 4];
         cursor.read_exact(&mut terrain_type)?;
@@ -273,7 +258,7 @@ impl RegionInfo2 {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionInfo3 {
     pub region_id_3: Uuid,
     pub region_type_3: u8,
@@ -323,7 +308,7 @@ impl RegionInfo3 {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionInfo4 {
     pub region_flags_4: u16,
     pub owner_name: String,
@@ -351,9 +336,7 @@ impl RegionInfo4 {
         let mut name_len = [0u8; 4];
         cursor.read_exact(&mut name_len)?;
         let name_len = u32::from_le_bytes(name_len) as usize;
-        let mut name_bytes = vec![0u8; name_len];
-        cursor.read_exact(&mut name_bytes)?;
-        let owner_name = String::from_utf8(name_bytes).unwrap();
+        let owner_name = read_string(&mut cursor, name_len)?;
 
         let mut region_size = [0u8; 4];
         cursor.read_exact(&mut region_size)?;
@@ -2,37 +2,30 @@ use std::io::{self, Cursor, Read};
 use uuid::Uuid;
 
 use crate::{
-    header::{Header, PacketFrequency},
+    header::PacketFrequency,
     packet::{Packet, PacketData},
     packet_types::PacketType,
 };
+use serde::{Deserialize, Serialize};
 
 impl Packet {
     pub fn new_region_handshake_reply(region_handshake_reply: RegionHandshakeReply) -> Self {
-        Packet {
-            header: Header {
-                id: 149,
-                reliable: false,
-                resent: false,
-                zerocoded: true,
-                appended_acks: false,
-                sequence_number: 0,
-                frequency: PacketFrequency::Low,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::RegionHandshakeReply(Box::new(region_handshake_reply)),
-        }
+        Packet::new(
+            149,
+            PacketFrequency::Low,
+            PacketType::RegionHandshakeReply(Box::new(region_handshake_reply)),
+        )
+        .zerocoded(true)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionHandshakeReply {
     pub agent_data: AgentData,
     pub region_info: ReplyRegionInfo,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentData {
     pub agent_id: Uuid,
     pub session_id: Uuid,
@@ -65,7 +58,7 @@ impl AgentData {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReplyRegionInfo {
     pub flags: u32,
 }
@@ -91,7 +84,7 @@ impl ReplyRegionInfo {
 }
 
 impl PacketData for RegionHandshakeReply {
-    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
         let mut cursor = Cursor::new(bytes);
         let mut agent_data_bytes = [0u8; 32]; // 16 bytes for agent_id + 16 bytes for session_id
         cursor.read_exact(&mut agent_data_bytes)?;
@@ -101,10 +94,13 @@ impl PacketData for RegionHandshakeReply {
         cursor.read_exact(&mut region_info_bytes)?;
         let region_info = ReplyRegionInfo::from_bytes(&region_info_bytes)?;
 
-        Ok(RegionHandshakeReply {
-            agent_data,
-            region_info,
-        })
+        Ok((
+            RegionHandshakeReply {
+                agent_data,
+                region_info,
+            },
+            cursor.position() as usize,
+        ))
     }
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
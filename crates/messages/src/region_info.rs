@@ -0,0 +1,201 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_string_1, write_string_1};
+use crate::utils::{agent_access::AgentAccess, region_flags::RegionFlags};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 142
+// Frequency: Low
+
+impl Packet {
+    pub fn new_region_info(region_info: RegionInfo) -> Self {
+        Packet::new(
+            142,
+            PacketFrequency::Low,
+            PacketType::RegionInfo(Box::new(region_info)),
+        )
+        .reliable(true)
+        .zerocoded(true)
+    }
+}
+
+/// The settings of the current region, like its rating, its limits and what is allowed in it.
+/// This is sent in reply to a RequestRegionInfo, and when the estate manager changes them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionInfo {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub sim_name: String,
+    pub estate_id: u32,
+    pub parent_estate_id: u32,
+    /// the raw region flags. Use flags() to read them.
+    pub region_flags: u32,
+    /// the maturity rating of the region
+    pub sim_access: AgentAccess,
+    /// how many agents can be in the region at once
+    pub max_agents: u8,
+    pub billable_factor: f32,
+    /// how many more prims than usual the parcels in the region can hold
+    pub object_bonus_factor: f32,
+    pub water_height: f32,
+    /// how far the terrain can be raised above its baked height, in meters
+    pub terrain_raise_limit: f32,
+    /// how far the terrain can be lowered below its baked height, in meters
+    pub terrain_lower_limit: f32,
+    pub price_per_meter: i32,
+    pub redirect_grid_x: i32,
+    pub redirect_grid_y: i32,
+    pub use_estate_sun: bool,
+    /// the fixed time of day, when the sun doesn't move
+    pub sun_hour: f32,
+    /// the product and the hard limits of the region. Older servers leave this out.
+    pub product: Option<RegionProductInfo>,
+    /// region flags that don't fit in the 32 bits of region_flags
+    pub region_flags_extended: Vec<u64>,
+}
+
+/// the kind of region, and its hard limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionProductInfo {
+    pub product_sku: String,
+    pub product_name: String,
+    /// max_agents, for regions that allow more than 255
+    pub max_agents_32: u32,
+    pub hard_max_agents: u32,
+    /// the most prims the region can hold
+    pub hard_max_objects: u32,
+}
+
+impl RegionInfo {
+    /// the region flags, like whether flying, scripts or damage are enabled
+    pub fn flags(&self) -> RegionFlags {
+        RegionFlags::from_bytes(&self.region_flags.to_le_bytes())
+    }
+}
+
+impl PacketData for RegionInfo {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+
+        let sim_name = read_string_1(&mut cursor)?;
+        let estate_id = cursor.read_u32::<LittleEndian>()?;
+        let parent_estate_id = cursor.read_u32::<LittleEndian>()?;
+        let region_flags = cursor.read_u32::<LittleEndian>()?;
+        let sim_access = AgentAccess::from_bytes(&cursor.read_u8()?);
+        let max_agents = cursor.read_u8()?;
+        let billable_factor = cursor.read_f32::<LittleEndian>()?;
+        let object_bonus_factor = cursor.read_f32::<LittleEndian>()?;
+        let water_height = cursor.read_f32::<LittleEndian>()?;
+        let terrain_raise_limit = cursor.read_f32::<LittleEndian>()?;
+        let terrain_lower_limit = cursor.read_f32::<LittleEndian>()?;
+        let price_per_meter = cursor.read_i32::<LittleEndian>()?;
+        let redirect_grid_x = cursor.read_i32::<LittleEndian>()?;
+        let redirect_grid_y = cursor.read_i32::<LittleEndian>()?;
+        let use_estate_sun = cursor.read_u8()? != 0;
+        let sun_hour = cursor.read_f32::<LittleEndian>()?;
+
+        let product = if (cursor.position() as usize) < bytes.len() {
+            Some(RegionProductInfo {
+                product_sku: read_string_1(&mut cursor)?,
+                product_name: read_string_1(&mut cursor)?,
+                max_agents_32: cursor.read_u32::<LittleEndian>()?,
+                hard_max_agents: cursor.read_u32::<LittleEndian>()?,
+                hard_max_objects: cursor.read_u32::<LittleEndian>()?,
+            })
+        } else {
+            None
+        };
+
+        let mut region_flags_extended = Vec::new();
+        if (cursor.position() as usize) < bytes.len() {
+            let count = cursor.read_u8()?;
+            for _ in 0..count {
+                region_flags_extended.push(cursor.read_u64::<LittleEndian>()?);
+            }
+        }
+
+        Ok((
+            RegionInfo {
+                agent_id,
+                session_id,
+                sim_name,
+                estate_id,
+                parent_estate_id,
+                region_flags,
+                sim_access,
+                max_agents,
+                billable_factor,
+                object_bonus_factor,
+                water_height,
+                terrain_raise_limit,
+                terrain_lower_limit,
+                price_per_meter,
+                redirect_grid_x,
+                redirect_grid_y,
+                use_estate_sun,
+                sun_hour,
+                product,
+                region_flags_extended,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+
+        write_string_1(&mut bytes, &self.sim_name);
+        bytes.extend_from_slice(&self.estate_id.to_le_bytes());
+        bytes.extend_from_slice(&self.parent_estate_id.to_le_bytes());
+        bytes.extend_from_slice(&self.region_flags.to_le_bytes());
+        bytes.push(self.sim_access.to_bytes());
+        bytes.push(self.max_agents);
+        bytes.extend_from_slice(&self.billable_factor.to_le_bytes());
+        bytes.extend_from_slice(&self.object_bonus_factor.to_le_bytes());
+        bytes.extend_from_slice(&self.water_height.to_le_bytes());
+        bytes.extend_from_slice(&self.terrain_raise_limit.to_le_bytes());
+        bytes.extend_from_slice(&self.terrain_lower_limit.to_le_bytes());
+        bytes.extend_from_slice(&self.price_per_meter.to_le_bytes());
+        bytes.extend_from_slice(&self.redirect_grid_x.to_le_bytes());
+        bytes.extend_from_slice(&self.redirect_grid_y.to_le_bytes());
+        bytes.push(self.use_estate_sun as u8);
+        bytes.extend_from_slice(&self.sun_hour.to_le_bytes());
+
+        // the extended flags come after the product, so the product has to be written for them
+        if self.product.is_some() || !self.region_flags_extended.is_empty() {
+            let product = self.product.clone().unwrap_or(RegionProductInfo {
+                product_sku: String::new(),
+                product_name: String::new(),
+                max_agents_32: self.max_agents as u32,
+                hard_max_agents: 0,
+                hard_max_objects: 0,
+            });
+            write_string_1(&mut bytes, &product.product_sku);
+            write_string_1(&mut bytes, &product.product_name);
+            bytes.extend_from_slice(&product.max_agents_32.to_le_bytes());
+            bytes.extend_from_slice(&product.hard_max_agents.to_le_bytes());
+            bytes.extend_from_slice(&product.hard_max_objects.to_le_bytes());
+        }
+        if !self.region_flags_extended.is_empty() {
+            bytes.push(self.region_flags_extended.len() as u8);
+            for flags in &self.region_flags_extended {
+                bytes.extend_from_slice(&flags.to_le_bytes());
+            }
+        }
+        bytes
+    }
+}
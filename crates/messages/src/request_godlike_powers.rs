@@ -0,0 +1,69 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::ReadBytesExt;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 258
+// Frequency: Low
+
+impl Packet {
+    pub fn new_request_godlike_powers(request_godlike_powers: RequestGodlikePowers) -> Self {
+        Packet::new(
+            258,
+            PacketFrequency::Low,
+            PacketType::RequestGodlikePowers(Box::new(request_godlike_powers)),
+        )
+        .reliable(true)
+    }
+}
+
+/// Asks the simulator to turn the agent's god powers on or off. The simulator only grants them
+/// to grid administrators, and answers with a GrantGodlikePowers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestGodlikePowers {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// true to turn god powers on, false to turn them off
+    pub godlike: bool,
+    /// a token the simulator echoes in its GrantGodlikePowers
+    pub token: Uuid,
+}
+
+impl PacketData for RequestGodlikePowers {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        let godlike = cursor.read_u8()? != 0;
+        cursor.read_exact(&mut uuid_bytes)?;
+        let token = Uuid::from_bytes(uuid_bytes);
+
+        Ok((
+            RequestGodlikePowers {
+                agent_id,
+                session_id,
+                godlike,
+                token,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(49);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.push(self.godlike as u8);
+        bytes.extend_from_slice(self.token.as_bytes());
+        bytes
+    }
+}
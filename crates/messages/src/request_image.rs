@@ -0,0 +1,95 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 8
+// Frequency: High
+
+impl Packet {
+    pub fn new_request_image(request_image: RequestImage) -> Self {
+        Packet::new(
+            8,
+            PacketFrequency::High,
+            PacketType::RequestImage(Box::new(request_image)),
+        )
+    }
+}
+
+/// asks the simulator to send textures over UDP, for grids without the GetTexture capability.
+/// The simulator answers each request with an ImageData packet, followed by ImagePackets until
+/// the texture is complete.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestImage {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub requests: Vec<ImageRequest>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageRequest {
+    pub image_id: Uuid,
+    /// how many levels of detail to leave off. 0 is the full texture, and each level halves the
+    /// width and height. -1 cancels the request.
+    pub discard_level: i8,
+    /// larger values are sent first. 0 cancels the request.
+    pub download_priority: f32,
+    /// the first packet to send. Set this to resume a texture at a lower discard level.
+    pub packet: u32,
+    /// 0 for a normal texture, 1 for a baked avatar texture
+    pub image_type: u8,
+}
+
+impl PacketData for RequestImage {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+        let mut uuid_bytes = [0u8; 16];
+
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+
+        let request_count = cursor.read_u8()? as usize;
+        let mut requests = Vec::with_capacity(request_count);
+        for _ in 0..request_count {
+            cursor.read_exact(&mut uuid_bytes)?;
+            requests.push(ImageRequest {
+                image_id: Uuid::from_bytes(uuid_bytes),
+                discard_level: cursor.read_i8()?,
+                download_priority: cursor.read_f32::<LittleEndian>()?,
+                packet: cursor.read_u32::<LittleEndian>()?,
+                image_type: cursor.read_u8()?,
+            });
+        }
+
+        Ok((
+            RequestImage {
+                agent_id,
+                session_id,
+                requests,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.push(self.requests.len() as u8);
+        for request in &self.requests {
+            bytes.extend_from_slice(request.image_id.as_bytes());
+            bytes.extend_from_slice(&request.discard_level.to_le_bytes());
+            bytes.extend_from_slice(&request.download_priority.to_le_bytes());
+            bytes.extend_from_slice(&request.packet.to_le_bytes());
+            bytes.push(request.image_type);
+        }
+        bytes
+    }
+}
@@ -0,0 +1,124 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 3
+// Frequency: Medium
+
+impl Packet {
+    pub fn new_request_multiple_objects(request_multiple_objects: RequestMultipleObjects) -> Self {
+        Packet::new(
+            3,
+            PacketFrequency::Medium,
+            PacketType::RequestMultipleObjects(Box::new(request_multiple_objects)),
+        )
+        .reliable(true)
+    }
+}
+
+/// asks the server to send the full ObjectUpdate of objects, by their local IDs.
+/// This is used for objects whose cached update is missing or out of date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestMultipleObjects {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub objects: Vec<ObjectRequest>,
+}
+
+/// A single object to request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectRequest {
+    pub cache_miss_type: CacheMissType,
+    pub local_id: u32,
+}
+
+/// Why the object is being requested
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CacheMissType {
+    /// the object is not in the cache
+    Full,
+    /// the object is in the cache, but its CRC doesn't match the one the server sent
+    Crc,
+    Unknown(u8),
+}
+impl CacheMissType {
+    pub fn from_bytes(byte: u8) -> Self {
+        match byte {
+            0 => CacheMissType::Full,
+            1 => CacheMissType::Crc,
+            byte => CacheMissType::Unknown(byte),
+        }
+    }
+    pub fn to_bytes(&self) -> u8 {
+        match self {
+            CacheMissType::Full => 0,
+            CacheMissType::Crc => 1,
+            CacheMissType::Unknown(byte) => *byte,
+        }
+    }
+}
+
+impl RequestMultipleObjects {
+    /// requests the full update of each of the objects
+    pub fn new(agent_id: Uuid, session_id: Uuid, local_ids: &[u32]) -> Self {
+        RequestMultipleObjects {
+            agent_id,
+            session_id,
+            objects: local_ids
+                .iter()
+                .map(|local_id| ObjectRequest {
+                    cache_miss_type: CacheMissType::Full,
+                    local_id: *local_id,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PacketData for RequestMultipleObjects {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+
+        let count = cursor.read_u8()?;
+        let mut objects = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            objects.push(ObjectRequest {
+                cache_miss_type: CacheMissType::from_bytes(cursor.read_u8()?),
+                local_id: cursor.read_u32::<LittleEndian>()?,
+            });
+        }
+
+        Ok((
+            RequestMultipleObjects {
+                agent_id,
+                session_id,
+                objects,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(33 + self.objects.len() * 5);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.push(self.objects.len() as u8);
+        for object in &self.objects {
+            bytes.push(object.cache_miss_type.to_bytes());
+            bytes.extend_from_slice(&object.local_id.to_le_bytes());
+        }
+        bytes
+    }
+}
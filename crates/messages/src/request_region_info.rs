@@ -0,0 +1,57 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 141
+// Frequency: Low
+
+impl Packet {
+    pub fn new_request_region_info(request_region_info: RequestRegionInfo) -> Self {
+        Packet::new(
+            141,
+            PacketFrequency::Low,
+            PacketType::RequestRegionInfo(Box::new(request_region_info)),
+        )
+        .reliable(true)
+    }
+}
+
+/// asks the server for the settings of the current region.
+/// The server answers with a RegionInfo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestRegionInfo {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+}
+
+impl PacketData for RequestRegionInfo {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+
+        Ok((
+            RequestRegionInfo {
+                agent_id,
+                session_id,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(32);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes
+    }
+}
@@ -0,0 +1,77 @@
+use crate::packet_types::PacketType;
+use crate::script_question::ScriptPermissions;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 132
+// Frequency: Low
+
+impl Packet {
+    pub fn new_script_answer_yes(script_answer_yes: ScriptAnswerYes) -> Self {
+        Packet::new(
+            132,
+            PacketFrequency::Low,
+            PacketType::ScriptAnswerYes(Box::new(script_answer_yes)),
+        )
+        .reliable(true)
+    }
+}
+
+/// The permissions the agent granted to a script that sent a ScriptQuestion.
+/// Despite the name, this is also how a request is declined, by granting nothing.
+/// Build it with ScriptQuestion::answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptAnswerYes {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// the object the script is in
+    pub task_id: Uuid,
+    /// the script that asked for the permissions
+    pub item_id: Uuid,
+    /// the permissions that were granted
+    pub questions: ScriptPermissions,
+}
+
+impl PacketData for ScriptAnswerYes {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+        let mut uuid_bytes = [0u8; 16];
+
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let task_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let item_id = Uuid::from_bytes(uuid_bytes);
+        let questions = ScriptPermissions::from_bytes(cursor.read_i32::<LittleEndian>()?);
+
+        Ok((
+            ScriptAnswerYes {
+                agent_id,
+                session_id,
+                task_id,
+                item_id,
+                questions,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.extend_from_slice(self.task_id.as_bytes());
+        bytes.extend_from_slice(self.item_id.as_bytes());
+        bytes.extend_from_slice(&self.questions.to_bytes().to_le_bytes());
+        bytes
+    }
+}
@@ -0,0 +1,148 @@
+use crate::packet_types::PacketType;
+use crate::script_dialog_reply::ScriptDialogReply;
+use crate::utils::variable::{
+    read_string_1, read_string_2, read_uuid, write_string_1, write_string_2,
+};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor};
+use uuid::Uuid;
+
+// ID: 190
+// Frequency: Low
+
+/// the only button of a dialog opened with llTextBox, which asks for text instead of a button
+pub const TEXT_BOX_BUTTON: &str = "!!llTextBox!!";
+
+impl Packet {
+    pub fn new_script_dialog(script_dialog: ScriptDialog) -> Self {
+        Packet::new(
+            190,
+            PacketFrequency::Low,
+            PacketType::ScriptDialog(Box::new(script_dialog)),
+        )
+        .reliable(true)
+    }
+}
+
+/// A blue dialog opened by a scripted object with llDialog, asking the agent to pick a button.
+/// The chosen button's label is said on the chat channel, for the script to hear.
+/// Answer it with reply.
+/// https://wiki.secondlife.com/wiki/ScriptDialog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptDialog {
+    /// the object that opened the dialog
+    pub object_id: Uuid,
+    /// the name of the object's owner
+    pub first_name: String,
+    pub last_name: String,
+    /// the name of the object
+    pub object_name: String,
+    pub message: String,
+    /// the channel the chosen button is said on
+    pub chat_channel: i32,
+    /// an image to show in the dialog, if it isn't nil
+    pub image_id: Uuid,
+    /// the labels of the buttons, in the order the script listed them
+    pub buttons: Vec<String>,
+    /// the owner of the object, on simulators that send it
+    pub owner_ids: Vec<Uuid>,
+}
+
+impl ScriptDialog {
+    /// whether the dialog was opened with llTextBox, and asks for text instead of a button
+    pub fn is_text_box(&self) -> bool {
+        self.buttons.len() == 1 && self.buttons[0] == TEXT_BOX_BUTTON
+    }
+
+    /// build the reply that picks a button, or sends text to a text box.
+    /// The label is what the script hears, so it doesn't have to be one of the buttons.
+    pub fn reply(
+        &self,
+        agent_id: Uuid,
+        session_id: Uuid,
+        button_label: impl Into<String>,
+    ) -> ScriptDialogReply {
+        let button_label = button_label.into();
+        let button_index = self
+            .buttons
+            .iter()
+            .position(|button| *button == button_label)
+            .unwrap_or_default() as i32;
+        ScriptDialogReply {
+            agent_id,
+            session_id,
+            object_id: self.object_id,
+            chat_channel: self.chat_channel,
+            button_index,
+            button_label,
+        }
+    }
+}
+
+impl PacketData for ScriptDialog {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let object_id = read_uuid(&mut cursor)?;
+        let first_name = read_string_1(&mut cursor)?;
+        let last_name = read_string_1(&mut cursor)?;
+        let object_name = read_string_1(&mut cursor)?;
+        let message = read_string_2(&mut cursor)?;
+        let chat_channel = cursor.read_i32::<LittleEndian>()?;
+        let image_id = read_uuid(&mut cursor)?;
+
+        let mut buttons = Vec::new();
+        for _ in 0..cursor.read_u8()? {
+            buttons.push(read_string_1(&mut cursor)?);
+        }
+
+        // older simulators end the packet after the buttons
+        let mut owner_ids = Vec::new();
+        if (cursor.position() as usize) < bytes.len() {
+            for _ in 0..cursor.read_u8()? {
+                owner_ids.push(read_uuid(&mut cursor)?);
+            }
+        }
+
+        Ok((
+            ScriptDialog {
+                object_id,
+                first_name,
+                last_name,
+                object_name,
+                message,
+                chat_channel,
+                image_id,
+                buttons,
+                owner_ids,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.object_id.as_bytes());
+        write_string_1(&mut bytes, &self.first_name);
+        write_string_1(&mut bytes, &self.last_name);
+        write_string_1(&mut bytes, &self.object_name);
+        write_string_2(&mut bytes, &self.message);
+        bytes.extend_from_slice(&self.chat_channel.to_le_bytes());
+        bytes.extend_from_slice(self.image_id.as_bytes());
+
+        bytes.push(self.buttons.len() as u8);
+        for button in &self.buttons {
+            write_string_1(&mut bytes, button);
+        }
+        bytes.push(self.owner_ids.len() as u8);
+        for owner_id in &self.owner_ids {
+            bytes.extend_from_slice(owner_id.as_bytes());
+        }
+        bytes
+    }
+}
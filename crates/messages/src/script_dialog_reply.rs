@@ -0,0 +1,83 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_string_1, write_string_1};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 191
+// Frequency: Low
+
+impl Packet {
+    pub fn new_script_dialog_reply(script_dialog_reply: ScriptDialogReply) -> Self {
+        Packet::new(
+            191,
+            PacketFrequency::Low,
+            PacketType::ScriptDialogReply(Box::new(script_dialog_reply)),
+        )
+        .reliable(true)
+    }
+}
+
+/// The button the agent picked in a ScriptDialog. The simulator says the label on the dialog's
+/// channel, as if the agent had typed it. Build it with ScriptDialog::reply.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptDialogReply {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// the object that opened the dialog
+    pub object_id: Uuid,
+    /// the channel of the dialog
+    pub chat_channel: i32,
+    /// the position of the button in the dialog
+    pub button_index: i32,
+    /// the label of the button, or the text typed into a text box
+    pub button_label: String,
+}
+
+impl PacketData for ScriptDialogReply {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+        let mut uuid_bytes = [0u8; 16];
+
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let object_id = Uuid::from_bytes(uuid_bytes);
+
+        let chat_channel = cursor.read_i32::<LittleEndian>()?;
+        let button_index = cursor.read_i32::<LittleEndian>()?;
+
+        let button_label = read_string_1(&mut cursor)?;
+
+        Ok((
+            ScriptDialogReply {
+                agent_id,
+                session_id,
+                object_id,
+                chat_channel,
+                button_index,
+                button_label,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.extend_from_slice(self.object_id.as_bytes());
+        bytes.extend_from_slice(&self.chat_channel.to_le_bytes());
+        bytes.extend_from_slice(&self.button_index.to_le_bytes());
+        // text box replies can be longer than a one byte prefix allows, and are cut short
+        write_string_1(&mut bytes, &self.button_label);
+        bytes
+    }
+}
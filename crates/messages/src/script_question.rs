@@ -0,0 +1,208 @@
+use crate::packet_types::PacketType;
+use crate::script_answer_yes::ScriptAnswerYes;
+use crate::utils::variable::{read_string_1, write_string_1};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 188
+// Frequency: Low
+
+const PERMISSION_DEBIT: i32 = 1 << 1;
+const PERMISSION_TAKE_CONTROLS: i32 = 1 << 2;
+const PERMISSION_REMAP_CONTROLS: i32 = 1 << 3;
+const PERMISSION_TRIGGER_ANIMATION: i32 = 1 << 4;
+const PERMISSION_ATTACH: i32 = 1 << 5;
+const PERMISSION_RELEASE_OWNERSHIP: i32 = 1 << 6;
+const PERMISSION_CHANGE_LINKS: i32 = 1 << 7;
+const PERMISSION_CHANGE_JOINTS: i32 = 1 << 8;
+const PERMISSION_CHANGE_PERMISSIONS: i32 = 1 << 9;
+const PERMISSION_TRACK_CAMERA: i32 = 1 << 10;
+const PERMISSION_CONTROL_CAMERA: i32 = 1 << 11;
+const PERMISSION_TELEPORT: i32 = 1 << 12;
+const PERMISSION_EXPERIENCE: i32 = 1 << 13;
+const PERMISSION_SILENT_ESTATE_MANAGEMENT: i32 = 1 << 14;
+const PERMISSION_OVERRIDE_ANIMATIONS: i32 = 1 << 15;
+const PERMISSION_RETURN_OBJECTS: i32 = 1 << 16;
+
+impl Packet {
+    pub fn new_script_question(script_question: ScriptQuestion) -> Self {
+        Packet::new(
+            188,
+            PacketFrequency::Low,
+            PacketType::ScriptQuestion(Box::new(script_question)),
+        )
+        .reliable(true)
+    }
+}
+
+/// A scripted object asking for permissions with llRequestPermissions, like taking money from
+/// the agent or animating its avatar. Answer it with answer.
+/// https://wiki.secondlife.com/wiki/ScriptQuestion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptQuestion {
+    /// the object the script is in
+    pub task_id: Uuid,
+    /// the script asking for the permissions
+    pub item_id: Uuid,
+    /// the name of the object
+    pub object_name: String,
+    /// the name of the object's owner
+    pub object_owner: String,
+    /// the permissions the script is asking for
+    pub questions: ScriptPermissions,
+}
+
+impl ScriptQuestion {
+    /// build the answer that grants some of the permissions. Permissions the script didn't ask
+    /// for are left out, and granting none of them declines the request.
+    pub fn answer(
+        &self,
+        agent_id: Uuid,
+        session_id: Uuid,
+        granted: ScriptPermissions,
+    ) -> ScriptAnswerYes {
+        ScriptAnswerYes {
+            agent_id,
+            session_id,
+            task_id: self.task_id,
+            item_id: self.item_id,
+            questions: ScriptPermissions::from_bytes(
+                granted.to_bytes() & self.questions.to_bytes(),
+            ),
+        }
+    }
+}
+
+/// The permissions a script can ask the agent for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScriptPermissions {
+    /// take money from the agent
+    pub debit: bool,
+    pub take_controls: bool,
+    pub remap_controls: bool,
+    /// play animations on the agent's avatar
+    pub trigger_animation: bool,
+    /// attach the object to the agent's avatar
+    pub attach: bool,
+    pub release_ownership: bool,
+    /// link and unlink the object's prims
+    pub change_links: bool,
+    pub change_joints: bool,
+    pub change_permissions: bool,
+    /// read the position of the agent's camera
+    pub track_camera: bool,
+    /// move the agent's camera
+    pub control_camera: bool,
+    /// teleport the agent
+    pub teleport: bool,
+    /// run the script as part of an experience
+    pub experience: bool,
+    pub silent_estate_management: bool,
+    /// replace the default animations of the agent's avatar
+    pub override_animations: bool,
+    /// return objects from the agent's land
+    pub return_objects: bool,
+}
+
+impl ScriptPermissions {
+    pub fn from_bytes(bits: i32) -> Self {
+        Self {
+            debit: bits & PERMISSION_DEBIT != 0,
+            take_controls: bits & PERMISSION_TAKE_CONTROLS != 0,
+            remap_controls: bits & PERMISSION_REMAP_CONTROLS != 0,
+            trigger_animation: bits & PERMISSION_TRIGGER_ANIMATION != 0,
+            attach: bits & PERMISSION_ATTACH != 0,
+            release_ownership: bits & PERMISSION_RELEASE_OWNERSHIP != 0,
+            change_links: bits & PERMISSION_CHANGE_LINKS != 0,
+            change_joints: bits & PERMISSION_CHANGE_JOINTS != 0,
+            change_permissions: bits & PERMISSION_CHANGE_PERMISSIONS != 0,
+            track_camera: bits & PERMISSION_TRACK_CAMERA != 0,
+            control_camera: bits & PERMISSION_CONTROL_CAMERA != 0,
+            teleport: bits & PERMISSION_TELEPORT != 0,
+            experience: bits & PERMISSION_EXPERIENCE != 0,
+            silent_estate_management: bits & PERMISSION_SILENT_ESTATE_MANAGEMENT != 0,
+            override_animations: bits & PERMISSION_OVERRIDE_ANIMATIONS != 0,
+            return_objects: bits & PERMISSION_RETURN_OBJECTS != 0,
+        }
+    }
+    pub fn to_bytes(&self) -> i32 {
+        let mut bits = 0;
+        for (set, bit) in [
+            (self.debit, PERMISSION_DEBIT),
+            (self.take_controls, PERMISSION_TAKE_CONTROLS),
+            (self.remap_controls, PERMISSION_REMAP_CONTROLS),
+            (self.trigger_animation, PERMISSION_TRIGGER_ANIMATION),
+            (self.attach, PERMISSION_ATTACH),
+            (self.release_ownership, PERMISSION_RELEASE_OWNERSHIP),
+            (self.change_links, PERMISSION_CHANGE_LINKS),
+            (self.change_joints, PERMISSION_CHANGE_JOINTS),
+            (self.change_permissions, PERMISSION_CHANGE_PERMISSIONS),
+            (self.track_camera, PERMISSION_TRACK_CAMERA),
+            (self.control_camera, PERMISSION_CONTROL_CAMERA),
+            (self.teleport, PERMISSION_TELEPORT),
+            (self.experience, PERMISSION_EXPERIENCE),
+            (
+                self.silent_estate_management,
+                PERMISSION_SILENT_ESTATE_MANAGEMENT,
+            ),
+            (self.override_animations, PERMISSION_OVERRIDE_ANIMATIONS),
+            (self.return_objects, PERMISSION_RETURN_OBJECTS),
+        ] {
+            if set {
+                bits |= bit;
+            }
+        }
+        bits
+    }
+    /// every permission the script asked for
+    pub fn all() -> Self {
+        Self::from_bytes(i32::MAX)
+    }
+    /// none of the permissions, which declines the request
+    pub fn none() -> Self {
+        Self::default()
+    }
+}
+
+impl PacketData for ScriptQuestion {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+        let mut uuid_bytes = [0u8; 16];
+
+        cursor.read_exact(&mut uuid_bytes)?;
+        let task_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let item_id = Uuid::from_bytes(uuid_bytes);
+        let object_name = read_string_1(&mut cursor)?;
+        let object_owner = read_string_1(&mut cursor)?;
+        let questions = ScriptPermissions::from_bytes(cursor.read_i32::<LittleEndian>()?);
+
+        Ok((
+            ScriptQuestion {
+                task_id,
+                item_id,
+                object_name,
+                object_owner,
+                questions,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.task_id.as_bytes());
+        bytes.extend_from_slice(self.item_id.as_bytes());
+        for string in [&self.object_name, &self.object_owner] {
+            write_string_1(&mut bytes, string);
+        }
+        bytes.extend_from_slice(&self.questions.to_bytes().to_le_bytes());
+        bytes
+    }
+}
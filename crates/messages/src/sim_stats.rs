@@ -0,0 +1,229 @@
+use crate::packet_types::PacketType;
+use crate::utils::region_flags::RegionFlags;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, Cursor};
+
+// ID: 140
+// Frequency: Low
+
+impl Packet {
+    pub fn new_sim_stats(sim_stats: SimStats) -> Self {
+        Packet::new(
+            140,
+            PacketFrequency::Low,
+            PacketType::SimStats(Box::new(sim_stats)),
+        )
+    }
+}
+
+/// Performance metrics of the current region, like its frame rate, time dilation and agent
+/// count. The simulator sends this about once a second, to show how healthy the region is.
+/// https://wiki.secondlife.com/wiki/SimStats
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimStats {
+    /// the grid position of the region, in regions
+    pub region_x: u32,
+    pub region_y: u32,
+    pub region_flags: u32,
+    /// the most prims the region can hold
+    pub object_capacity: u32,
+    /// the value of each stat the simulator sent
+    pub stats: BTreeMap<SimStat, f32>,
+    /// the process ID of the simulator
+    pub pid: i32,
+    /// region flags that don't fit in the 32 bits of region_flags
+    pub region_flags_extended: Vec<u64>,
+}
+
+/// The stats a simulator can report, by their stat ID.
+/// Times are in milliseconds per frame, and rates are per second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SimStat {
+    /// how much slower than real time the simulator is running, from 0 to 1
+    TimeDilation,
+    SimFps,
+    PhysicsFps,
+    AgentUpdates,
+    FrameMs,
+    NetMs,
+    OtherMs,
+    PhysicsMs,
+    AgentMs,
+    ImageMs,
+    ScriptMs,
+    TotalPrims,
+    ActivePrims,
+    /// agents in the region
+    Agents,
+    /// agents in neighboring regions that can see into this one
+    ChildAgents,
+    ActiveScripts,
+    ScriptInstructionsPerSecond,
+    PacketsIn,
+    PacketsOut,
+    PendingDownloads,
+    PendingUploads,
+    VirtualSizeKb,
+    ResidentSizeKb,
+    PendingLocalUploads,
+    UnackedBytes,
+    /// a stat this crate doesn't know, by its ID
+    Unknown(u32),
+}
+
+impl SimStat {
+    pub fn to_bytes(&self) -> u32 {
+        match self {
+            SimStat::TimeDilation => 0,
+            SimStat::SimFps => 1,
+            SimStat::PhysicsFps => 2,
+            SimStat::AgentUpdates => 3,
+            SimStat::FrameMs => 4,
+            SimStat::NetMs => 5,
+            SimStat::OtherMs => 6,
+            SimStat::PhysicsMs => 7,
+            SimStat::AgentMs => 8,
+            SimStat::ImageMs => 9,
+            SimStat::ScriptMs => 10,
+            SimStat::TotalPrims => 11,
+            SimStat::ActivePrims => 12,
+            SimStat::Agents => 13,
+            SimStat::ChildAgents => 14,
+            SimStat::ActiveScripts => 15,
+            SimStat::ScriptInstructionsPerSecond => 16,
+            SimStat::PacketsIn => 17,
+            SimStat::PacketsOut => 18,
+            SimStat::PendingDownloads => 19,
+            SimStat::PendingUploads => 20,
+            SimStat::VirtualSizeKb => 21,
+            SimStat::ResidentSizeKb => 22,
+            SimStat::PendingLocalUploads => 23,
+            SimStat::UnackedBytes => 24,
+            SimStat::Unknown(id) => *id,
+        }
+    }
+    pub fn from_bytes(id: u32) -> Self {
+        match id {
+            0 => SimStat::TimeDilation,
+            1 => SimStat::SimFps,
+            2 => SimStat::PhysicsFps,
+            3 => SimStat::AgentUpdates,
+            4 => SimStat::FrameMs,
+            5 => SimStat::NetMs,
+            6 => SimStat::OtherMs,
+            7 => SimStat::PhysicsMs,
+            8 => SimStat::AgentMs,
+            9 => SimStat::ImageMs,
+            10 => SimStat::ScriptMs,
+            11 => SimStat::TotalPrims,
+            12 => SimStat::ActivePrims,
+            13 => SimStat::Agents,
+            14 => SimStat::ChildAgents,
+            15 => SimStat::ActiveScripts,
+            16 => SimStat::ScriptInstructionsPerSecond,
+            17 => SimStat::PacketsIn,
+            18 => SimStat::PacketsOut,
+            19 => SimStat::PendingDownloads,
+            20 => SimStat::PendingUploads,
+            21 => SimStat::VirtualSizeKb,
+            22 => SimStat::ResidentSizeKb,
+            23 => SimStat::PendingLocalUploads,
+            24 => SimStat::UnackedBytes,
+            id => SimStat::Unknown(id),
+        }
+    }
+}
+
+impl SimStats {
+    /// the region flags, like whether flying, scripts or damage are enabled
+    pub fn flags(&self) -> RegionFlags {
+        RegionFlags::from_bytes(&self.region_flags.to_le_bytes())
+    }
+    /// the value of a stat, if the simulator sent it
+    pub fn get(&self, stat: SimStat) -> Option<f32> {
+        self.stats.get(&stat).copied()
+    }
+    /// how much slower than real time the simulator is running. 1.0 is full speed.
+    pub fn time_dilation(&self) -> Option<f32> {
+        self.get(SimStat::TimeDilation)
+    }
+    /// the simulator's frame rate. A healthy region runs at about 45.
+    pub fn sim_fps(&self) -> Option<f32> {
+        self.get(SimStat::SimFps)
+    }
+    /// the physics engine's frame rate
+    pub fn physics_fps(&self) -> Option<f32> {
+        self.get(SimStat::PhysicsFps)
+    }
+    /// the number of agents in the region
+    pub fn agent_count(&self) -> Option<u32> {
+        self.get(SimStat::Agents).map(|agents| agents as u32)
+    }
+}
+
+impl PacketData for SimStats {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let region_x = cursor.read_u32::<LittleEndian>()?;
+        let region_y = cursor.read_u32::<LittleEndian>()?;
+        let region_flags = cursor.read_u32::<LittleEndian>()?;
+        let object_capacity = cursor.read_u32::<LittleEndian>()?;
+
+        let stat_count = cursor.read_u8()?;
+        let mut stats = BTreeMap::new();
+        for _ in 0..stat_count {
+            let stat = SimStat::from_bytes(cursor.read_u32::<LittleEndian>()?);
+            stats.insert(stat, cursor.read_f32::<LittleEndian>()?);
+        }
+
+        let pid = cursor.read_i32::<LittleEndian>()?;
+
+        // older simulators end the packet after the PID
+        let mut region_flags_extended = Vec::new();
+        if (cursor.position() as usize) < bytes.len() {
+            let count = cursor.read_u8()?;
+            for _ in 0..count {
+                region_flags_extended.push(cursor.read_u64::<LittleEndian>()?);
+            }
+        }
+
+        Ok((
+            SimStats {
+                region_x,
+                region_y,
+                region_flags,
+                object_capacity,
+                stats,
+                pid,
+                region_flags_extended,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(22 + self.stats.len() * 8);
+        bytes.extend_from_slice(&self.region_x.to_le_bytes());
+        bytes.extend_from_slice(&self.region_y.to_le_bytes());
+        bytes.extend_from_slice(&self.region_flags.to_le_bytes());
+        bytes.extend_from_slice(&self.object_capacity.to_le_bytes());
+        bytes.push(self.stats.len() as u8);
+        for (stat, value) in &self.stats {
+            bytes.extend_from_slice(&stat.to_bytes().to_le_bytes());
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.pid.to_le_bytes());
+        bytes.push(self.region_flags_extended.len() as u8);
+        for flags in &self.region_flags_extended {
+            bytes.extend_from_slice(&flags.to_le_bytes());
+        }
+        bytes
+    }
+}
@@ -1,43 +1,50 @@
 use super::{
-    header::{Header, PacketFrequency},
+    header::PacketFrequency,
     packet::{Packet, PacketData},
 };
 use crate::packet_types::PacketType;
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+// ID: 1
+// Frequency: High
 
 impl Packet {
     pub fn new_start_ping_check(start_ping_check: StartPingCheck) -> Self {
-        Packet {
-            header: Header {
-                id: 1,
-                reliable: false,
-                resent: false,
-                zerocoded: false,
-                appended_acks: false,
-                sequence_number: 0,
-                frequency: PacketFrequency::High,
-                ack_list: None,
-                size: None,
-            },
-            body: PacketType::StartPingCheck(Box::new(start_ping_check)),
-        }
+        Packet::new(
+            1,
+            PacketFrequency::High,
+            PacketType::StartPingCheck(Box::new(start_ping_check)),
+        )
     }
 }
 
-#[derive(Debug, Clone)]
+/// Sent every few seconds by both sides of the circuit to measure latency.
+/// The receiver answers with a CompletePingCheck carrying the same ping_id.
+/// The body is five bytes: the ping id, and the oldest unacked sequence number as a
+/// little-endian u32.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StartPingCheck {
+    /// id of the ping, which wraps around. The CompletePingCheck echoes it back.
     pub ping_id: u8,
+    /// the oldest reliable packet the sender is still waiting on an ack for
     pub oldest_unacked: u32,
 }
 
 impl PacketData for StartPingCheck {
-    fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
-        let ping_id = bytes[0];
-        let oldest_unacked = u32::from_le_bytes(bytes[1..5].try_into().unwrap());
+    fn from_bytes_with_len(bytes: &[u8]) -> std::io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+        let ping_id = cursor.read_u8()?;
+        let oldest_unacked = cursor.read_u32::<LittleEndian>()?;
 
-        Ok(StartPingCheck {
-            ping_id,
-            oldest_unacked,
-        })
+        Ok((
+            StartPingCheck {
+                ping_id,
+                oldest_unacked,
+            },
+            cursor.position() as usize,
+        ))
     }
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(5);
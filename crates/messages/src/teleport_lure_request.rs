@@ -0,0 +1,74 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 71
+// Frequency: Low
+
+/// the teleport was offered by another agent
+pub const TELEPORT_FLAGS_VIA_LURE: u32 = 1 << 2;
+/// the teleport was offered by a god, and can't be refused by the destination
+pub const TELEPORT_FLAGS_VIA_GODLIKE_LURE: u32 = 1 << 8;
+
+impl Packet {
+    pub fn new_teleport_lure_request(teleport_lure_request: TeleportLureRequest) -> Self {
+        Packet::new(
+            71,
+            PacketFrequency::Low,
+            PacketType::TeleportLureRequest(Box::new(teleport_lure_request)),
+        )
+        .reliable(true)
+    }
+}
+
+/// Accepts a teleport another agent offered, and starts the teleport to them.
+/// Build it with TeleportLure::accept.
+/// https://wiki.secondlife.com/wiki/TeleportLureRequest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeleportLureRequest {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    /// the ID of the instant message that offered the teleport
+    pub lure_id: Uuid,
+    pub teleport_flags: u32,
+}
+
+impl PacketData for TeleportLureRequest {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+        let mut uuid_bytes = [0u8; 16];
+
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let lure_id = Uuid::from_bytes(uuid_bytes);
+        let teleport_flags = cursor.read_u32::<LittleEndian>()?;
+
+        Ok((
+            TeleportLureRequest {
+                agent_id,
+                session_id,
+                lure_id,
+                teleport_flags,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(52);
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.extend_from_slice(self.lure_id.as_bytes());
+        bytes.extend_from_slice(&self.teleport_flags.to_le_bytes());
+        bytes
+    }
+}
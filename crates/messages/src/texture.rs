@@ -0,0 +1,44 @@
+use super::packet::PacketData;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+/// A texture assembled from an ImageData and its ImagePackets.
+/// This is not a packet in the spec. It is sent to the UI once every piece of a texture requested
+/// with RequestImage has arrived.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Texture {
+    pub id: Uuid,
+    /// the image format. 2 is JPEG2000.
+    pub codec: u8,
+    pub data: Vec<u8>,
+}
+
+impl PacketData for Texture {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let mut codec = [0u8; 1];
+        cursor.read_exact(&mut codec)?;
+        let mut data = Vec::new();
+        cursor.read_to_end(&mut data)?;
+
+        Ok((
+            Texture {
+                id: Uuid::from_bytes(uuid_bytes),
+                codec: codec[0],
+                data,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(17 + self.data.len());
+        bytes.extend_from_slice(self.id.as_bytes());
+        bytes.push(self.codec);
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+}
@@ -1,26 +1,124 @@
+#[cfg(feature = "admin")]
+use crate::grant_godlike_powers::GrantGodlikePowers;
 use crate::{
-    errors::SessionError, login_system::login_response::LoginResponse, packet::PacketData,
+    capabilities::event_queue::EventQueueEvent,
+    errors::SessionError,
+    friend_presence::FriendPresence,
+    login_system::{login_response::LoginResponse, login_summary::LoginSummary},
+    packet::PacketData,
 };
 use core::fmt;
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{
-    chat_from_simulator::ChatFromSimulator, coarse_location_update::CoarseLocationUpdate,
-    disable_simulator::DisableSimulator, packet_types::PacketType,
+    agent_movement_complete::AgentMovementComplete, avatar_animation::AvatarAnimation,
+    avatar_appearance::AvatarAppearance, chat_from_simulator::ChatFromSimulator,
+    coarse_location_update::CoarseLocationUpdate, disable_simulator::DisableSimulator,
+    enable_simulator::EnableSimulator, improved_instant_message::ImprovedInstantMessage,
+    kill_object::KillObject, layer_data::LayerData, money_balance_reply::MoneyBalanceReply,
+    object_properties::ObjectProperties, object_update_compressed::ObjectUpdateCompressed,
+    packet_types::PacketType, region_info::RegionInfo, script_dialog::ScriptDialog,
+    script_question::ScriptQuestion, sim_stats::SimStats, texture::Texture,
+    uuid_name_reply::UUIDNameReply, viewer_effect::ViewerEffect,
 };
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// The kinds of messages the mailbox sends to the UI.
+/// Each one is sent as its u16 tag, so renaming or reordering the variants doesn't change the
+/// encoding. New events need a new tag, and a tag must never be reused.
+#[derive(Debug, Clone, PartialEq)]
+#[repr(u16)]
 pub enum UiEventTypes {
-    LoginResponseEvent,
-    Error,
-    ChatFromSimulatorEvent,
-    CoarseLocationUpdateEvent,
-    DisableSimulatorEvent,
+    LoginResponseEvent = 0,
+    // the agent has logged in and arrived in its first region
+    LoginCompleteEvent = 1,
+    Error = 2,
+    ChatFromSimulatorEvent = 3,
+    CoarseLocationUpdateEvent = 4,
+    DisableSimulatorEvent = 5,
+    AvatarAnimationEvent = 6,
+    KillObjectEvent = 7,
+    UUIDNameReplyEvent = 8,
+    EventQueueEvent = 9,
+    // the agent has arrived in the region, and the session is live
+    MovementCompleteEvent = 10,
+    ImprovedInstantMessageEvent = 11,
+    TextureEvent = 12,
+    // the properties of selected objects
+    ObjectPropertiesEvent = 13,
+    // look at, point at and beam effects of nearby agents
+    ViewerEffectEvent = 14,
+    // the agent's currency balance
+    BalanceUpdateEvent = 15,
+    // the settings of the current region
+    RegionInfoEvent = 16,
+    // objects in the region, sent with only the fields that are set
+    ObjectUpdateCompressedEvent = 17,
+    // a neighboring region the client can connect to ahead of time
+    EnableSimulatorEvent = 18,
+    // a patch of the region's terrain, wind or clouds
+    LayerDataEvent = 19,
+    // what another avatar in the region looks like
+    AvatarAppearanceEvent = 20,
     // for packets that are not events
-    None,
+    None = 21,
+    // the god level the simulator granted the agent
+    #[cfg(feature = "admin")]
+    GodlikePowersEvent = 22,
+    // the performance metrics of the current region
+    SimStatsEvent = 23,
+    // a scripted object opened a dialog with buttons
+    ScriptDialogEvent = 24,
+    // a scripted object asked for permissions
+    ScriptQuestionEvent = 25,
+    // the mailbox moved to a new state. The message is a bincode encoded ServerState from
+    // metaverse_session, so it is read with ServerState::from_bytes rather than as a packet.
+    StateChangeEvent = 26,
+    // a friend of the agent came online or went offline
+    FriendPresenceEvent = 27,
 }
 impl UiEventTypes {
+    /// the number the event is sent as
+    pub fn tag(&self) -> u16 {
+        self.clone() as u16
+    }
+
+    /// the event with the tag, if there is one
+    pub fn from_tag(tag: u16) -> Option<Self> {
+        match tag {
+            0 => Some(UiEventTypes::LoginResponseEvent),
+            1 => Some(UiEventTypes::LoginCompleteEvent),
+            2 => Some(UiEventTypes::Error),
+            3 => Some(UiEventTypes::ChatFromSimulatorEvent),
+            4 => Some(UiEventTypes::CoarseLocationUpdateEvent),
+            5 => Some(UiEventTypes::DisableSimulatorEvent),
+            6 => Some(UiEventTypes::AvatarAnimationEvent),
+            7 => Some(UiEventTypes::KillObjectEvent),
+            8 => Some(UiEventTypes::UUIDNameReplyEvent),
+            9 => Some(UiEventTypes::EventQueueEvent),
+            10 => Some(UiEventTypes::MovementCompleteEvent),
+            11 => Some(UiEventTypes::ImprovedInstantMessageEvent),
+            12 => Some(UiEventTypes::TextureEvent),
+            13 => Some(UiEventTypes::ObjectPropertiesEvent),
+            14 => Some(UiEventTypes::ViewerEffectEvent),
+            15 => Some(UiEventTypes::BalanceUpdateEvent),
+            16 => Some(UiEventTypes::RegionInfoEvent),
+            17 => Some(UiEventTypes::ObjectUpdateCompressedEvent),
+            18 => Some(UiEventTypes::EnableSimulatorEvent),
+            19 => Some(UiEventTypes::LayerDataEvent),
+            20 => Some(UiEventTypes::AvatarAppearanceEvent),
+            21 => Some(UiEventTypes::None),
+            #[cfg(feature = "admin")]
+            22 => Some(UiEventTypes::GodlikePowersEvent),
+            23 => Some(UiEventTypes::SimStatsEvent),
+            24 => Some(UiEventTypes::ScriptDialogEvent),
+            25 => Some(UiEventTypes::ScriptQuestionEvent),
+            26 => Some(UiEventTypes::StateChangeEvent),
+            27 => Some(UiEventTypes::FriendPresenceEvent),
+            _ => None,
+        }
+    }
+
     pub fn packet_type_from_bytes(&self, data: &[u8]) -> Option<PacketType> {
         match self {
             UiEventTypes::LoginResponseEvent => {
@@ -28,6 +126,9 @@ impl UiEventTypes {
                     .ok()
                     .map(|packet| PacketType::LoginResponse(Box::new(packet)))
             }
+            UiEventTypes::LoginCompleteEvent => serde_json::from_slice::<LoginSummary>(data)
+                .ok()
+                .map(|packet| PacketType::LoginComplete(Box::new(packet))),
             UiEventTypes::Error => {
                 SessionError::from_bytes(data).map(|packet| PacketType::Error(Box::new(packet)))
             }
@@ -40,18 +141,117 @@ impl UiEventTypes {
             UiEventTypes::DisableSimulatorEvent => {
                 Some(PacketType::DisableSimulator(Box::new(DisableSimulator {})))
             }
+            UiEventTypes::AvatarAnimationEvent => AvatarAnimation::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::AvatarAnimation(Box::new(packet))),
+            UiEventTypes::KillObjectEvent => KillObject::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::KillObject(Box::new(packet))),
+            UiEventTypes::UUIDNameReplyEvent => UUIDNameReply::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::UUIDNameReply(Box::new(packet))),
+            UiEventTypes::EventQueueEvent => EventQueueEvent::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::EventQueueEvent(Box::new(packet))),
+            UiEventTypes::MovementCompleteEvent => AgentMovementComplete::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::AgentMovementComplete(Box::new(packet))),
+            UiEventTypes::ImprovedInstantMessageEvent => ImprovedInstantMessage::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::ImprovedInstantMessage(Box::new(packet))),
+            UiEventTypes::TextureEvent => Texture::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::Texture(Box::new(packet))),
+            UiEventTypes::ObjectPropertiesEvent => ObjectProperties::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::ObjectProperties(Box::new(packet))),
+            UiEventTypes::ViewerEffectEvent => ViewerEffect::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::ViewerEffect(Box::new(packet))),
+            UiEventTypes::BalanceUpdateEvent => MoneyBalanceReply::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::MoneyBalanceReply(Box::new(packet))),
+            UiEventTypes::RegionInfoEvent => RegionInfo::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::RegionInfo(Box::new(packet))),
+            UiEventTypes::ObjectUpdateCompressedEvent => ObjectUpdateCompressed::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::ObjectUpdateCompressed(Box::new(packet))),
+            UiEventTypes::EnableSimulatorEvent => EnableSimulator::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::EnableSimulator(Box::new(packet))),
+            UiEventTypes::LayerDataEvent => LayerData::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::LayerData(Box::new(packet))),
+            UiEventTypes::AvatarAppearanceEvent => AvatarAppearance::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::AvatarAppearance(Box::new(packet))),
+            UiEventTypes::SimStatsEvent => SimStats::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::SimStats(Box::new(packet))),
+            UiEventTypes::ScriptDialogEvent => ScriptDialog::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::ScriptDialog(Box::new(packet))),
+            UiEventTypes::ScriptQuestionEvent => ScriptQuestion::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::ScriptQuestion(Box::new(packet))),
+            UiEventTypes::FriendPresenceEvent => FriendPresence::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::FriendPresence(Box::new(packet))),
+            #[cfg(feature = "admin")]
+            UiEventTypes::GodlikePowersEvent => GrantGodlikePowers::from_bytes(data)
+                .ok()
+                .map(|packet| PacketType::GrantGodlikePowers(Box::new(packet))),
             _ => None, // Handle unimplemented cases
         }
     }
 }
 
+impl Serialize for UiEventTypes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.tag())
+    }
+}
+
+impl<'de> Deserialize<'de> for UiEventTypes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tag = u16::deserialize(deserializer)?;
+        UiEventTypes::from_tag(tag).ok_or_else(|| {
+            de::Error::invalid_value(de::Unexpected::Unsigned(tag as u64), &"a UI event tag")
+        })
+    }
+}
+
 impl fmt::Display for UiEventTypes {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             UiEventTypes::LoginResponseEvent => write!(f, "LoginResponseEvent"),
+            UiEventTypes::LoginCompleteEvent => write!(f, "LoginCompleteEvent"),
             UiEventTypes::ChatFromSimulatorEvent => write!(f, "ChatFromSimulatorEvent"),
             UiEventTypes::CoarseLocationUpdateEvent => write!(f, "CoarseLocationUpdateEvent"),
             UiEventTypes::DisableSimulatorEvent => write!(f, "DisableSimulatorEvent"),
+            UiEventTypes::AvatarAnimationEvent => write!(f, "AvatarAnimationEvent"),
+            UiEventTypes::KillObjectEvent => write!(f, "KillObjectEvent"),
+            UiEventTypes::UUIDNameReplyEvent => write!(f, "UUIDNameReplyEvent"),
+            UiEventTypes::EventQueueEvent => write!(f, "EventQueueEvent"),
+            UiEventTypes::MovementCompleteEvent => write!(f, "MovementCompleteEvent"),
+            UiEventTypes::ImprovedInstantMessageEvent => write!(f, "ImprovedInstantMessageEvent"),
+            UiEventTypes::TextureEvent => write!(f, "TextureEvent"),
+            UiEventTypes::ObjectPropertiesEvent => write!(f, "ObjectPropertiesEvent"),
+            UiEventTypes::ViewerEffectEvent => write!(f, "ViewerEffectEvent"),
+            UiEventTypes::BalanceUpdateEvent => write!(f, "BalanceUpdateEvent"),
+            UiEventTypes::RegionInfoEvent => write!(f, "RegionInfoEvent"),
+            UiEventTypes::ObjectUpdateCompressedEvent => write!(f, "ObjectUpdateCompressedEvent"),
+            UiEventTypes::EnableSimulatorEvent => write!(f, "EnableSimulatorEvent"),
+            UiEventTypes::LayerDataEvent => write!(f, "LayerDataEvent"),
+            UiEventTypes::AvatarAppearanceEvent => write!(f, "AvatarAppearanceEvent"),
+            UiEventTypes::SimStatsEvent => write!(f, "SimStatsEvent"),
+            UiEventTypes::ScriptDialogEvent => write!(f, "ScriptDialogEvent"),
+            UiEventTypes::ScriptQuestionEvent => write!(f, "ScriptQuestionEvent"),
+            UiEventTypes::StateChangeEvent => write!(f, "StateChangeEvent"),
+            UiEventTypes::FriendPresenceEvent => write!(f, "FriendPresenceEvent"),
+            #[cfg(feature = "admin")]
+            UiEventTypes::GodlikePowersEvent => write!(f, "GodlikePowersEvent"),
             UiEventTypes::None => write!(f, "None"),
             UiEventTypes::Error => write!(f, "Error"),
         }
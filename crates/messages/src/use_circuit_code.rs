@@ -3,13 +3,14 @@ use crate::models::packet::{Frequency, Header};
 
 use byte::ctx::*;
 use byte::*;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 const HEADER_LENGTH: usize = 10;
 const CIRCUIT_CODE_LENGTH: usize = 36;
 // const USE_CIRCUIT_CODE_LENGTH: usize = HEADER_LENGTH + CIRCUIT_CODE_LENGTH;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UseCircuitCode {
     pub has_variable_blocks: bool,
     pub packet_type: PacketType,
@@ -33,7 +34,7 @@ impl TryWrite<Endian> for UseCircuitCode {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CircuitCode {
     code: u32,
     id: Uuid,
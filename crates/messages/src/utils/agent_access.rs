@@ -37,6 +37,24 @@ impl AgentAccess {
             _ => AgentAccess::Unknown,
         }
     }
+    /// the maturity rating as a level that can be compared, from lowest to highest.
+    /// Returns None for values that aren't maturity ratings, like Down.
+    fn maturity_level(&self) -> Option<u8> {
+        match self {
+            AgentAccess::General | AgentAccess::PG | AgentAccess::Trial => Some(0),
+            AgentAccess::Mature => Some(1),
+            AgentAccess::Adult => Some(2),
+            _ => None,
+        }
+    }
+    /// checks if a user with this as their maximum access can enter a region with the given rating.
+    /// Use with agent_access_max from the login response before teleporting.
+    pub fn is_allowed_in(&self, region_rating: &AgentAccess) -> bool {
+        match (self.maturity_level(), region_rating.maturity_level()) {
+            (Some(max), Some(region)) => region <= max,
+            _ => false,
+        }
+    }
 }
 impl From<AgentAccess> for Value {
     fn from(val: AgentAccess) -> Self {
@@ -54,7 +72,7 @@ impl From<AgentAccess> for Value {
     }
 }
 pub fn parse_agent_access(agent_access: Option<&xmlrpc::Value>) -> Option<AgentAccess> {
-    agent_access.map(|x| match x.clone().as_str().unwrap() {
+    agent_access.and_then(|x| x.as_str()).map(|x| match x {
         "M" => AgentAccess::Mature,
         "A" => AgentAccess::Adult,
         "PG" => AgentAccess::PG,
@@ -1,2 +1,5 @@
 pub mod agent_access;
 pub mod region_flags;
+pub mod region_handle;
+pub mod texture_entry;
+pub mod variable;
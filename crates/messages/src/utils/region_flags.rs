@@ -1,4 +1,4 @@
-#[derive(Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct RegionFlags {
     /// Agents can take damage and be killed
     pub allow_damage: bool,
@@ -0,0 +1,83 @@
+use glam::{DVec3, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// the width of a region on the grid, in meters. Varregions are larger, but still start on a
+/// multiple of this.
+pub const REGION_WIDTH: u32 = 256;
+
+/// The location of a region on the grid.
+/// The handle packs the global position of the region's south west corner, in meters, into 64
+/// bits. The x coordinate is in the upper 32 bits and the y coordinate in the lower 32 bits.
+/// So the region at grid cell (1000, 1000) has its corner at (256000, 256000), and a handle of
+/// 1099511628032000.
+/// https://wiki.secondlife.com/wiki/Region_Handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct RegionHandle(pub u64);
+
+impl RegionHandle {
+    /// the handle of the region whose corner is at the global position, in meters
+    pub fn from_global(x: u32, y: u32) -> Self {
+        RegionHandle(((x as u64) << 32) | y as u64)
+    }
+
+    /// the handle of the region at the grid cell, as shown on the map
+    pub fn from_grid(grid_x: u32, grid_y: u32) -> Self {
+        RegionHandle::from_global(grid_x * REGION_WIDTH, grid_y * REGION_WIDTH)
+    }
+
+    /// the handle of the 256m region that contains the global position
+    pub fn containing(global: DVec3) -> Self {
+        let corner =
+            |coordinate: f64| (coordinate / REGION_WIDTH as f64).floor() as u32 * REGION_WIDTH;
+        RegionHandle::from_global(corner(global.x), corner(global.y))
+    }
+
+    /// the global x coordinate of the region's corner, in meters
+    pub fn global_x(&self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+
+    /// the global y coordinate of the region's corner, in meters
+    pub fn global_y(&self) -> u32 {
+        self.0 as u32
+    }
+
+    /// the grid cell of the region, as shown on the map
+    pub fn grid(&self) -> (u32, u32) {
+        (
+            self.global_x() / REGION_WIDTH,
+            self.global_y() / REGION_WIDTH,
+        )
+    }
+
+    /// convert a position in the region to a global position
+    pub fn to_global(&self, local: Vec3) -> DVec3 {
+        DVec3::new(
+            self.global_x() as f64 + local.x as f64,
+            self.global_y() as f64 + local.y as f64,
+            local.z as f64,
+        )
+    }
+
+    /// convert a global position to a position relative to the region's corner.
+    /// Positions outside of the region are still converted, and fall outside of 0..256.
+    pub fn to_local(&self, global: DVec3) -> Vec3 {
+        Vec3::new(
+            (global.x - self.global_x() as f64) as f32,
+            (global.y - self.global_y() as f64) as f32,
+            global.z as f32,
+        )
+    }
+}
+
+impl From<u64> for RegionHandle {
+    fn from(handle: u64) -> Self {
+        RegionHandle(handle)
+    }
+}
+
+impl From<RegionHandle> for u64 {
+    fn from(handle: RegionHandle) -> Self {
+        handle.0
+    }
+}
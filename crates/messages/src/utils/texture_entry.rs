@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use uuid::Uuid;
+
+/// The textures of each face of a prim or an avatar.
+/// A TextureEntry is made of sections for the texture, color, scale, offset, rotation, bump,
+/// media, glow and material of the faces. Each section has a default value, followed by the
+/// values of the faces that don't use the default.
+/// Only the texture section is decoded. The sections after it are kept as they were received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextureEntry {
+    /// the texture of every face that doesn't have its own
+    pub default_texture: Uuid,
+    /// the textures of the faces that don't use the default, by face index
+    pub face_textures: BTreeMap<u8, Uuid>,
+    /// the sections after the textures, undecoded
+    pub properties: Vec<u8>,
+}
+
+/// the sections after the textures, with every face using the default.
+/// Each section is its default value, followed by the 0 that ends the list of faces that
+/// override it.
+fn default_properties() -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(47);
+    // the color is stored inverted, so zeros are white
+    bytes.extend_from_slice(&[0, 0, 0, 0, 0]);
+    // scale u and v
+    for scale in [1.0f32, 1.0] {
+        bytes.extend_from_slice(&scale.to_le_bytes());
+        bytes.push(0);
+    }
+    // offset u and v, and rotation
+    for _ in 0..3 {
+        bytes.extend_from_slice(&[0, 0, 0]);
+    }
+    // bump, media and glow
+    for _ in 0..3 {
+        bytes.extend_from_slice(&[0, 0]);
+    }
+    // material
+    bytes.extend_from_slice(Uuid::nil().as_bytes());
+    bytes.push(0);
+    bytes
+}
+
+impl TextureEntry {
+    /// a texture entry with the given textures, and default values for everything else
+    pub fn new(default_texture: Uuid, face_textures: BTreeMap<u8, Uuid>) -> Self {
+        TextureEntry {
+            default_texture,
+            face_textures,
+            properties: default_properties(),
+        }
+    }
+
+    /// the texture of a face
+    pub fn texture(&self, face: u8) -> Uuid {
+        self.face_textures
+            .get(&face)
+            .copied()
+            .unwrap_or(self.default_texture)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let truncated = || io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated TextureEntry");
+        let read_uuid = |position: usize| -> io::Result<Uuid> {
+            bytes
+                .get(position..position + 16)
+                .map(|id| Uuid::from_slice(id).unwrap())
+                .ok_or_else(truncated)
+        };
+
+        let default_texture = read_uuid(0)?;
+        let mut position = 16;
+        let mut face_textures = BTreeMap::new();
+        loop {
+            let (faces, length) = read_face_bits(&bytes[position..]).ok_or_else(truncated)?;
+            position += length;
+            if faces == 0 {
+                break;
+            }
+            let texture = read_uuid(position)?;
+            position += 16;
+            for face in 0..64 {
+                if faces & (1 << face) != 0 {
+                    face_textures.insert(face as u8, texture);
+                }
+            }
+        }
+
+        Ok(TextureEntry {
+            default_texture,
+            face_textures,
+            properties: bytes[position..].to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.default_texture.as_bytes());
+
+        // faces that share a texture are written together
+        let mut faces_by_texture: BTreeMap<Uuid, u64> = BTreeMap::new();
+        for (face, texture) in &self.face_textures {
+            *faces_by_texture.entry(*texture).or_default() |= 1 << face;
+        }
+        for (texture, faces) in faces_by_texture {
+            write_face_bits(&mut bytes, faces);
+            bytes.extend_from_slice(texture.as_bytes());
+        }
+        bytes.push(0);
+
+        bytes.extend_from_slice(&self.properties);
+        bytes
+    }
+}
+
+/// reads a set of faces, stored as a bitfield seven bits at a time, most significant first.
+/// Every byte but the last has its high bit set.
+fn read_face_bits(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut faces = 0u64;
+    for (i, byte) in bytes.iter().enumerate() {
+        faces = (faces << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Some((faces, i + 1));
+        }
+    }
+    None
+}
+
+fn write_face_bits(bytes: &mut Vec<u8>, faces: u64) {
+    let mut groups = Vec::new();
+    let mut remaining = faces;
+    loop {
+        groups.push((remaining & 0x7f) as u8);
+        remaining >>= 7;
+        if remaining == 0 {
+            break;
+        }
+    }
+    let last = groups.len() - 1;
+    for (i, group) in groups.iter().rev().enumerate() {
+        bytes.push(if i < last { group | 0x80 } else { *group });
+    }
+}
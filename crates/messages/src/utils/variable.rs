@@ -0,0 +1,106 @@
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// Variable length fields start with a one or two byte little endian size prefix, which counts
+// the bytes that follow it. Strings in variable fields are null terminated, and the null is
+// counted in the size.
+
+/// read a variable length field with a one byte size prefix
+pub fn read_variable_1(cursor: &mut Cursor<&[u8]>) -> io::Result<Vec<u8>> {
+    let length = cursor.read_u8()? as usize;
+    read_exactly(cursor, length)
+}
+
+/// read a variable length field with a two byte size prefix
+pub fn read_variable_2(cursor: &mut Cursor<&[u8]>) -> io::Result<Vec<u8>> {
+    let length = cursor.read_u16::<LittleEndian>()? as usize;
+    read_exactly(cursor, length)
+}
+
+/// write a variable length field with a one byte size prefix.
+/// Data longer than 255 bytes is cut short to fit the prefix.
+pub fn write_variable_1(bytes: &mut Vec<u8>, data: &[u8]) {
+    let data = &data[..data.len().min(u8::MAX as usize)];
+    bytes.push(data.len() as u8);
+    bytes.extend_from_slice(data);
+}
+
+/// write a variable length field with a two byte size prefix.
+/// Data longer than 65535 bytes is cut short to fit the prefix.
+pub fn write_variable_2(bytes: &mut Vec<u8>, data: &[u8]) {
+    let data = &data[..data.len().min(u16::MAX as usize)];
+    bytes.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(data);
+}
+
+/// read a null terminated string with a one byte size prefix
+pub fn read_string_1(cursor: &mut Cursor<&[u8]>) -> io::Result<String> {
+    null_terminated_string(read_variable_1(cursor)?)
+}
+
+/// read a null terminated string with a two byte size prefix
+pub fn read_string_2(cursor: &mut Cursor<&[u8]>) -> io::Result<String> {
+    null_terminated_string(read_variable_2(cursor)?)
+}
+
+/// write a null terminated string with a one byte size prefix.
+/// Strings longer than 254 bytes are cut short without splitting a character.
+pub fn write_string_1(bytes: &mut Vec<u8>, string: &str) {
+    let string = truncate(string, u8::MAX as usize - 1);
+    bytes.push(string.len() as u8 + 1);
+    bytes.extend_from_slice(string.as_bytes());
+    bytes.push(0);
+}
+
+/// write a null terminated string with a two byte size prefix.
+/// Strings longer than 65534 bytes are cut short without splitting a character.
+pub fn write_string_2(bytes: &mut Vec<u8>, string: &str) {
+    let string = truncate(string, u16::MAX as usize - 1);
+    bytes.extend_from_slice(&(string.len() as u16 + 1).to_le_bytes());
+    bytes.extend_from_slice(string.as_bytes());
+    bytes.push(0);
+}
+
+/// read a string of a known length, without trusting the length to allocate
+pub fn read_string(cursor: &mut Cursor<&[u8]>, length: usize) -> io::Result<String> {
+    String::from_utf8(read_exactly(cursor, length)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// read a 16 byte UUID
+pub fn read_uuid(cursor: &mut Cursor<&[u8]>) -> io::Result<Uuid> {
+    let mut bytes = [0u8; 16];
+    cursor.read_exact(&mut bytes)?;
+    Ok(Uuid::from_bytes(bytes))
+}
+
+/// reads the bytes of a field, without trusting its size to allocate
+fn read_exactly(cursor: &mut Cursor<&[u8]>, length: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    cursor.take(length as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < length {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "variable length field is longer than the packet",
+        ));
+    }
+    Ok(bytes)
+}
+
+/// strips the null terminator off of a variable length string field.
+/// Some simulators leave the terminator off, so it is optional.
+fn null_terminated_string(mut bytes: Vec<u8>) -> io::Result<String> {
+    if bytes.last() == Some(&0) {
+        bytes.pop();
+    }
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn truncate(string: &str, max: usize) -> &str {
+    let mut end = string.len().min(max);
+    while !string.is_char_boundary(end) {
+        end -= 1;
+    }
+    &string[..end]
+}
@@ -0,0 +1,71 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_string_1, write_string_1};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::ReadBytesExt;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 236
+// Frequency: Low
+
+impl Packet {
+    pub fn new_uuid_name_reply(uuid_name_reply: UUIDNameReply) -> Self {
+        Packet::new(
+            236,
+            PacketFrequency::Low,
+            PacketType::UUIDNameReply(Box::new(uuid_name_reply)),
+        )
+    }
+}
+
+/// the names of agents, sent in response to a UUIDNameRequest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UUIDNameReply {
+    pub names: Vec<UUIDNameBlock>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UUIDNameBlock {
+    pub id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+impl PacketData for UUIDNameReply {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let name_count = cursor.read_u8()? as usize;
+        let mut names = Vec::with_capacity(name_count);
+        let mut uuid_bytes = [0u8; 16];
+        for _ in 0..name_count {
+            cursor.read_exact(&mut uuid_bytes)?;
+            let id = Uuid::from_bytes(uuid_bytes);
+            let first_name = read_string_1(&mut cursor)?;
+            let last_name = read_string_1(&mut cursor)?;
+            names.push(UUIDNameBlock {
+                id,
+                first_name,
+                last_name,
+            });
+        }
+
+        Ok((UUIDNameReply { names }, cursor.position() as usize))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.push(self.names.len() as u8);
+        for name in &self.names {
+            bytes.extend_from_slice(name.id.as_bytes());
+            write_string_1(&mut bytes, &name.first_name);
+            write_string_1(&mut bytes, &name.last_name);
+        }
+        bytes
+    }
+}
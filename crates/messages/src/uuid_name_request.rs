@@ -0,0 +1,55 @@
+use crate::packet_types::PacketType;
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::ReadBytesExt;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 235
+// Frequency: Low
+
+impl Packet {
+    pub fn new_uuid_name_request(uuid_name_request: UUIDNameRequest) -> Self {
+        Packet::new(
+            235,
+            PacketFrequency::Low,
+            PacketType::UUIDNameRequest(Box::new(uuid_name_request)),
+        )
+    }
+}
+
+/// asks the server for the names of agents.
+/// The server responds with a UUIDNameReply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UUIDNameRequest {
+    pub ids: Vec<Uuid>,
+}
+
+impl PacketData for UUIDNameRequest {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let id_count = cursor.read_u8()? as usize;
+        let mut ids = Vec::with_capacity(id_count);
+        let mut uuid_bytes = [0u8; 16];
+        for _ in 0..id_count {
+            cursor.read_exact(&mut uuid_bytes)?;
+            ids.push(Uuid::from_bytes(uuid_bytes));
+        }
+
+        Ok((UUIDNameRequest { ids }, cursor.position() as usize))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + self.ids.len() * 16);
+        bytes.push(self.ids.len() as u8);
+        for id in &self.ids {
+            bytes.extend_from_slice(id.as_bytes());
+        }
+        bytes
+    }
+}
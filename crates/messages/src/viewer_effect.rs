@@ -0,0 +1,436 @@
+use crate::packet_types::PacketType;
+use crate::utils::variable::{read_variable_1, write_variable_1};
+
+use super::{
+    header::PacketFrequency,
+    packet::{Packet, PacketData},
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use glam::DVec3;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use uuid::Uuid;
+
+// ID: 17
+// Frequency: Medium
+
+/// how long the look at and point at effects built by Effect last, in seconds
+pub const DEFAULT_EFFECT_DURATION: f32 = 1.0;
+
+impl Packet {
+    pub fn new_viewer_effect(viewer_effect: ViewerEffect) -> Self {
+        Packet::new(
+            17,
+            PacketFrequency::Medium,
+            PacketType::ViewerEffect(Box::new(viewer_effect)),
+        )
+    }
+}
+
+/// Visual effects that viewers draw for an agent, like where the agent is looking, what it is
+/// pointing at, and the beam drawn while it edits an object.
+/// Viewers send their own effects, and the server relays them to the other agents nearby.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewerEffect {
+    pub agent_id: Uuid,
+    pub session_id: Uuid,
+    pub effects: Vec<Effect>,
+}
+
+/// A single effect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Effect {
+    /// the id of the effect. Sending an effect with the same id again updates it.
+    pub id: Uuid,
+    /// the agent the effect belongs to
+    pub agent_id: Uuid,
+    pub effect_type: EffectType,
+    /// how long the effect lasts, in seconds
+    pub duration: f32,
+    /// RGBA
+    pub color: [u8; 4],
+    pub data: EffectData,
+}
+
+/// The kind of effect
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EffectType {
+    Text,
+    Icon,
+    Connector,
+    FlexibleObject,
+    AnimalControls,
+    LocalAnimationObject,
+    Cloth,
+    Beam,
+    Glow,
+    Point,
+    Spiral,
+    Edit,
+    LookAt,
+    PointAt,
+    VoiceVisualizer,
+    NameTag,
+    Blob,
+    Unknown(u8),
+}
+impl EffectType {
+    pub fn from_bytes(byte: u8) -> Self {
+        match byte {
+            0 => EffectType::Text,
+            1 => EffectType::Icon,
+            2 => EffectType::Connector,
+            3 => EffectType::FlexibleObject,
+            4 => EffectType::AnimalControls,
+            5 => EffectType::LocalAnimationObject,
+            6 => EffectType::Cloth,
+            7 => EffectType::Beam,
+            8 => EffectType::Glow,
+            9 => EffectType::Point,
+            10 => EffectType::Spiral,
+            11 => EffectType::Edit,
+            12 => EffectType::LookAt,
+            13 => EffectType::PointAt,
+            14 => EffectType::VoiceVisualizer,
+            15 => EffectType::NameTag,
+            16 => EffectType::Blob,
+            byte => EffectType::Unknown(byte),
+        }
+    }
+    pub fn to_bytes(&self) -> u8 {
+        match self {
+            EffectType::Text => 0,
+            EffectType::Icon => 1,
+            EffectType::Connector => 2,
+            EffectType::FlexibleObject => 3,
+            EffectType::AnimalControls => 4,
+            EffectType::LocalAnimationObject => 5,
+            EffectType::Cloth => 6,
+            EffectType::Beam => 7,
+            EffectType::Glow => 8,
+            EffectType::Point => 9,
+            EffectType::Spiral => 10,
+            EffectType::Edit => 11,
+            EffectType::LookAt => 12,
+            EffectType::PointAt => 13,
+            EffectType::VoiceVisualizer => 14,
+            EffectType::NameTag => 15,
+            EffectType::Blob => 16,
+            EffectType::Unknown(byte) => *byte,
+        }
+    }
+}
+
+/// Why the agent is looking at something
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LookAtType {
+    None,
+    Idle,
+    AutoListen,
+    FreeLook,
+    Respond,
+    Hover,
+    Conversation,
+    Select,
+    Focus,
+    Mouselook,
+    Clear,
+    Unknown(u8),
+}
+impl LookAtType {
+    pub fn from_bytes(byte: u8) -> Self {
+        match byte {
+            0 => LookAtType::None,
+            1 => LookAtType::Idle,
+            2 => LookAtType::AutoListen,
+            3 => LookAtType::FreeLook,
+            4 => LookAtType::Respond,
+            5 => LookAtType::Hover,
+            6 => LookAtType::Conversation,
+            7 => LookAtType::Select,
+            8 => LookAtType::Focus,
+            9 => LookAtType::Mouselook,
+            10 => LookAtType::Clear,
+            byte => LookAtType::Unknown(byte),
+        }
+    }
+    pub fn to_bytes(&self) -> u8 {
+        match self {
+            LookAtType::None => 0,
+            LookAtType::Idle => 1,
+            LookAtType::AutoListen => 2,
+            LookAtType::FreeLook => 3,
+            LookAtType::Respond => 4,
+            LookAtType::Hover => 5,
+            LookAtType::Conversation => 6,
+            LookAtType::Select => 7,
+            LookAtType::Focus => 8,
+            LookAtType::Mouselook => 9,
+            LookAtType::Clear => 10,
+            LookAtType::Unknown(byte) => *byte,
+        }
+    }
+}
+
+/// Why the agent is pointing at something
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PointAtType {
+    None,
+    Select,
+    Grab,
+    Clear,
+    Unknown(u8),
+}
+impl PointAtType {
+    pub fn from_bytes(byte: u8) -> Self {
+        match byte {
+            0 => PointAtType::None,
+            1 => PointAtType::Select,
+            2 => PointAtType::Grab,
+            3 => PointAtType::Clear,
+            byte => PointAtType::Unknown(byte),
+        }
+    }
+    pub fn to_bytes(&self) -> u8 {
+        match self {
+            PointAtType::None => 0,
+            PointAtType::Select => 1,
+            PointAtType::Grab => 2,
+            PointAtType::Clear => 3,
+            PointAtType::Unknown(byte) => *byte,
+        }
+    }
+}
+
+/// The data of an effect, decoded according to its type.
+/// Positions are global, in meters, so they can point into neighbouring regions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EffectData {
+    /// the agent is looking at the target object or agent, or at the position if there is none
+    LookAt {
+        source_id: Uuid,
+        target_id: Uuid,
+        position: DVec3,
+        look_at_type: LookAtType,
+    },
+    /// the agent is pointing at the target, like while selecting or grabbing an object
+    PointAt {
+        source_id: Uuid,
+        target_id: Uuid,
+        position: DVec3,
+        point_at_type: PointAtType,
+    },
+    /// a beam from the source to the target. Used by the beam, spiral and edit effects.
+    Beam {
+        source_id: Uuid,
+        target_id: Uuid,
+        position: DVec3,
+    },
+    /// an effect whose data is not decoded
+    Other(Vec<u8>),
+}
+
+impl Effect {
+    /// an effect for the agent looking at a target. Use Uuid::nil() as the target to look at a
+    /// position.
+    pub fn look_at(
+        id: Uuid,
+        agent_id: Uuid,
+        target_id: Uuid,
+        position: DVec3,
+        look_at_type: LookAtType,
+    ) -> Self {
+        Effect {
+            id,
+            agent_id,
+            effect_type: EffectType::LookAt,
+            duration: DEFAULT_EFFECT_DURATION,
+            color: [255, 255, 255, 255],
+            data: EffectData::LookAt {
+                source_id: agent_id,
+                target_id,
+                position,
+                look_at_type,
+            },
+        }
+    }
+    /// an effect for the agent pointing at a target. Use Uuid::nil() as the target to point at a
+    /// position.
+    pub fn point_at(
+        id: Uuid,
+        agent_id: Uuid,
+        target_id: Uuid,
+        position: DVec3,
+        point_at_type: PointAtType,
+    ) -> Self {
+        Effect {
+            id,
+            agent_id,
+            effect_type: EffectType::PointAt,
+            duration: DEFAULT_EFFECT_DURATION,
+            color: [255, 255, 255, 255],
+            data: EffectData::PointAt {
+                source_id: agent_id,
+                target_id,
+                position,
+                point_at_type,
+            },
+        }
+    }
+
+    fn from_bytes(cursor: &mut Cursor<&[u8]>) -> io::Result<Self> {
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        let effect_type = EffectType::from_bytes(cursor.read_u8()?);
+        let duration = cursor.read_f32::<LittleEndian>()?;
+        let mut color = [0u8; 4];
+        cursor.read_exact(&mut color)?;
+
+        let data = EffectData::from_bytes(effect_type, read_variable_1(cursor)?)?;
+
+        Ok(Effect {
+            id,
+            agent_id,
+            effect_type,
+            duration,
+            color,
+            data,
+        })
+    }
+    fn to_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self.id.as_bytes());
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.push(self.effect_type.to_bytes());
+        bytes.extend_from_slice(&self.duration.to_le_bytes());
+        bytes.extend_from_slice(&self.color);
+        write_variable_1(bytes, &self.data.to_bytes());
+    }
+}
+
+impl EffectData {
+    fn from_bytes(effect_type: EffectType, bytes: Vec<u8>) -> io::Result<Self> {
+        // the source, the target, and a global position made of three f64s
+        let read_target = |cursor: &mut Cursor<&[u8]>| -> io::Result<(Uuid, Uuid, DVec3)> {
+            let mut uuid_bytes = [0u8; 16];
+            cursor.read_exact(&mut uuid_bytes)?;
+            let source_id = Uuid::from_bytes(uuid_bytes);
+            cursor.read_exact(&mut uuid_bytes)?;
+            let target_id = Uuid::from_bytes(uuid_bytes);
+            let position = DVec3::new(
+                cursor.read_f64::<LittleEndian>()?,
+                cursor.read_f64::<LittleEndian>()?,
+                cursor.read_f64::<LittleEndian>()?,
+            );
+            Ok((source_id, target_id, position))
+        };
+        let mut cursor = Cursor::new(bytes.as_slice());
+        let data = match effect_type {
+            EffectType::LookAt => {
+                let (source_id, target_id, position) = read_target(&mut cursor)?;
+                EffectData::LookAt {
+                    source_id,
+                    target_id,
+                    position,
+                    look_at_type: LookAtType::from_bytes(cursor.read_u8()?),
+                }
+            }
+            EffectType::PointAt => {
+                let (source_id, target_id, position) = read_target(&mut cursor)?;
+                EffectData::PointAt {
+                    source_id,
+                    target_id,
+                    position,
+                    point_at_type: PointAtType::from_bytes(cursor.read_u8()?),
+                }
+            }
+            EffectType::Beam | EffectType::Spiral | EffectType::Edit => {
+                let (source_id, target_id, position) = read_target(&mut cursor)?;
+                EffectData::Beam {
+                    source_id,
+                    target_id,
+                    position,
+                }
+            }
+            _ => EffectData::Other(bytes.clone()),
+        };
+        Ok(data)
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let write_target =
+            |bytes: &mut Vec<u8>, source_id: &Uuid, target_id: &Uuid, position: &DVec3| {
+                bytes.extend_from_slice(source_id.as_bytes());
+                bytes.extend_from_slice(target_id.as_bytes());
+                bytes.extend_from_slice(&position.x.to_le_bytes());
+                bytes.extend_from_slice(&position.y.to_le_bytes());
+                bytes.extend_from_slice(&position.z.to_le_bytes());
+            };
+        let mut bytes = Vec::with_capacity(57);
+        match self {
+            EffectData::LookAt {
+                source_id,
+                target_id,
+                position,
+                look_at_type,
+            } => {
+                write_target(&mut bytes, source_id, target_id, position);
+                bytes.push(look_at_type.to_bytes());
+            }
+            EffectData::PointAt {
+                source_id,
+                target_id,
+                position,
+                point_at_type,
+            } => {
+                write_target(&mut bytes, source_id, target_id, position);
+                bytes.push(point_at_type.to_bytes());
+            }
+            EffectData::Beam {
+                source_id,
+                target_id,
+                position,
+            } => write_target(&mut bytes, source_id, target_id, position),
+            EffectData::Other(data) => bytes.extend_from_slice(data),
+        }
+        bytes
+    }
+}
+
+impl PacketData for ViewerEffect {
+    fn from_bytes_with_len(bytes: &[u8]) -> io::Result<(Self, usize)> {
+        let mut cursor = Cursor::new(bytes);
+
+        let mut uuid_bytes = [0u8; 16];
+        cursor.read_exact(&mut uuid_bytes)?;
+        let agent_id = Uuid::from_bytes(uuid_bytes);
+        cursor.read_exact(&mut uuid_bytes)?;
+        let session_id = Uuid::from_bytes(uuid_bytes);
+
+        let count = cursor.read_u8()?;
+        let mut effects = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            effects.push(Effect::from_bytes(&mut cursor)?);
+        }
+
+        Ok((
+            ViewerEffect {
+                agent_id,
+                session_id,
+                effects,
+            },
+            cursor.position() as usize,
+        ))
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        bytes.push(self.effects.len() as u8);
+        for effect in &self.effects {
+            effect.to_bytes(&mut bytes);
+        }
+        bytes
+    }
+}
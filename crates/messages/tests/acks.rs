@@ -1,5 +1,12 @@
 use hex::FromHex;
-use metaverse_messages::packet::Packet;
+use metaverse_messages::{
+    chat_from_viewer::{ChatFromViewer, ClientChatType},
+    complete_ping_check::CompletePingCheck,
+    packet::Packet,
+    packet_ack::MAX_PACKET_ACKS,
+    packet_types::PacketType,
+};
+use uuid::Uuid;
 
 #[test]
 fn test_acks_parse() {
@@ -24,3 +31,100 @@ fn test_acks_firestorm_parse() {
         Err(e) => eprintln!("Error creating packet: {}", e),
     }
 }
+
+#[test]
+fn test_appended_acks_tofrom_bytes() {
+    let mut packet = Packet::new_complete_ping_check(CompletePingCheck { ping_id: 7 });
+    packet.header.appended_acks = true;
+    packet.header.ack_list = Some(vec![1, 2, 42069]);
+
+    let bytes = packet.to_bytes();
+    let packet_from_bytes = Packet::from_bytes(&bytes).unwrap();
+    assert_eq!(packet_from_bytes.header.ack_list, Some(vec![1, 2, 42069]));
+    match packet_from_bytes.body {
+        PacketType::CompletePingCheck(data) => assert_eq!(data.ping_id, 7),
+        body => panic!("wrong packet type: {:?}", body),
+    }
+}
+
+#[test]
+fn test_from_bytes_with_len_counts_appended_acks() {
+    let mut packet = Packet::new_complete_ping_check(CompletePingCheck { ping_id: 7 });
+    packet.header.appended_acks = true;
+    packet.header.ack_list = Some(vec![1, 2, 42069]);
+
+    let bytes = packet.to_bytes();
+    let (_, len) = Packet::from_bytes_with_len(&bytes).unwrap();
+    assert_eq!(len, bytes.len());
+}
+
+#[test]
+fn test_from_bytes_with_len_excludes_trailing_data() {
+    let mut bytes = Packet::new_complete_ping_check(CompletePingCheck { ping_id: 7 }).to_bytes();
+    let packet_len = bytes.len();
+    // CompletePingCheck has a one byte body, so the parser ignores the rest
+    bytes.extend_from_slice(&[0xaa, 0xbb]);
+    let (packet, len) = Packet::from_bytes_with_len(&bytes).unwrap();
+    assert_eq!(len, packet_len);
+    match packet.body {
+        PacketType::CompletePingCheck(data) => assert_eq!(data.ping_id, 7),
+        body => panic!("wrong packet type: {:?}", body),
+    }
+}
+
+#[test]
+fn test_from_bytes_with_len_counts_zerocoded_bytes() {
+    // a zerocoded CompletePingCheck whose ping id 0 is encoded as 00 01
+    let test_packet = match Vec::from_hex("800000000000020001") {
+        Ok(bytes) => bytes,
+        Err(_) => panic!("failed"),
+    };
+    let (_, len) = Packet::from_bytes_with_len(&test_packet).unwrap();
+    assert_eq!(len, test_packet.len());
+}
+
+#[test]
+fn test_from_bytes_with_len_counts_what_the_parser_read() {
+    let packet = Packet::new_chat_from_viewer(ChatFromViewer {
+        agent_id: Uuid::nil(),
+        session_id: Uuid::nil(),
+        message: "hi".to_string(),
+        message_type: ClientChatType::Normal,
+        channel: 0,
+    });
+    let mut bytes = packet.header.to_bytes();
+    let packet_len = bytes.len() + 41;
+    bytes.extend_from_slice(&[0; 32]);
+    // the message has no null terminator, so writing it back out would be a byte longer
+    bytes.extend_from_slice(&[2, 0, b'h', b'i']);
+    bytes.push(ClientChatType::Normal.to_bytes());
+    bytes.extend_from_slice(&0i32.to_le_bytes());
+    bytes.push(0xaa);
+
+    let (packet, len) = Packet::from_bytes_with_len(&bytes).unwrap();
+    assert_eq!(len, packet_len);
+    match packet.body {
+        PacketType::ChatFromViewer(data) => assert_eq!(data.message, "hi"),
+        body => panic!("wrong packet type: {:?}", body),
+    }
+}
+
+#[test]
+fn test_packet_acks_are_batched() {
+    let ids: Vec<u32> = (0..600).collect();
+    let packets = Packet::new_packet_acks(&ids);
+    assert_eq!(packets.len(), 3);
+
+    let mut acked = Vec::new();
+    for packet in packets {
+        match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+            PacketType::PacketAck(ack) => {
+                assert!(ack.packet_ids.len() <= MAX_PACKET_ACKS);
+                acked.extend(ack.packet_ids);
+            }
+            body => panic!("wrong packet type: {:?}", body),
+        }
+    }
+    assert_eq!(acked, ids);
+    assert!(Packet::new_packet_acks(&[]).is_empty());
+}
@@ -0,0 +1,101 @@
+use metaverse_messages::packet_types::PacketType;
+
+#[cfg(not(feature = "admin"))]
+#[test]
+fn test_admin_packets_are_unknown_without_the_feature() {
+    use metaverse_messages::header::PacketFrequency;
+    for id in [165, 258, 259] {
+        assert!(PacketType::from_id(id, PacketFrequency::Low, &[0u8; 64]).is_err());
+    }
+}
+
+#[cfg(feature = "admin")]
+mod admin {
+    use super::*;
+    use metaverse_messages::{
+        god_kick_user::{GodKickUser, KICK_FLAGS_DEFAULT, KICK_FLAGS_FREEZE},
+        grant_godlike_powers::{GrantGodlikePowers, GOD_LEVEL_FULL, GOD_LEVEL_NONE},
+        packet::{Packet, PacketCategory, PacketData},
+        request_godlike_powers::RequestGodlikePowers,
+        ui_events::UiEventTypes,
+    };
+    use uuid::Uuid;
+
+    #[test]
+    fn test_request_godlike_powers_round_trip() {
+        let request = RequestGodlikePowers {
+            agent_id: Uuid::from_u128(1),
+            session_id: Uuid::from_u128(2),
+            godlike: true,
+            token: Uuid::from_u128(3),
+        };
+        let packet = Packet::new_request_godlike_powers(request.clone());
+        assert!(packet.header.reliable);
+        assert_eq!(packet.classify(), PacketCategory::Outgoing);
+        assert_eq!(request.to_bytes().len(), 49);
+        match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+            PacketType::RequestGodlikePowers(data) => assert_eq!(*data, request),
+            other => panic!("expected RequestGodlikePowers, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_grant_godlike_powers_is_sent_to_the_ui() {
+        let grant = GrantGodlikePowers {
+            agent_id: Uuid::from_u128(1),
+            session_id: Uuid::from_u128(2),
+            god_level: GOD_LEVEL_FULL,
+            token: Uuid::from_u128(3),
+        };
+        assert!(grant.is_godlike());
+        let packet = Packet::new_grant_godlike_powers(grant.clone());
+        assert_eq!(
+            packet.classify(),
+            PacketCategory::UiEvent(UiEventTypes::GodlikePowersEvent)
+        );
+        match UiEventTypes::GodlikePowersEvent.packet_type_from_bytes(&packet.body.to_bytes()) {
+            Some(PacketType::GrantGodlikePowers(data)) => assert_eq!(*data, grant),
+            other => panic!("expected GrantGodlikePowers, got {:?}", other),
+        }
+
+        let revoked = GrantGodlikePowers {
+            god_level: GOD_LEVEL_NONE,
+            ..grant
+        };
+        assert!(!revoked.is_godlike());
+    }
+
+    #[test]
+    fn test_god_kick_user_round_trip() {
+        let kick = GodKickUser::kick(
+            Uuid::from_u128(1),
+            Uuid::from_u128(2),
+            Uuid::from_u128(3),
+            "griefing",
+        );
+        assert_eq!(kick.kick_flags, KICK_FLAGS_DEFAULT);
+        let bytes = kick.to_bytes();
+        // the reason is null terminated, and its length includes the terminator
+        assert_eq!(&bytes[52..54], &9u16.to_le_bytes());
+        assert_eq!(bytes.last(), Some(&0));
+        assert_eq!(GodKickUser::from_bytes(&bytes).unwrap(), kick);
+
+        let freeze = GodKickUser {
+            kick_flags: KICK_FLAGS_FREEZE,
+            ..kick
+        };
+        match Packet::from_bytes(&Packet::new_god_kick_user(freeze.clone()).to_bytes())
+            .unwrap()
+            .body
+        {
+            PacketType::GodKickUser(data) => assert_eq!(*data, freeze),
+            other => panic!("expected GodKickUser, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncated_god_kick_user_is_an_error() {
+        let bytes = GodKickUser::kick(Uuid::nil(), Uuid::nil(), Uuid::nil(), "bye").to_bytes();
+        assert!(GodKickUser::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+    }
+}
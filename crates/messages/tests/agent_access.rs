@@ -0,0 +1,25 @@
+use metaverse_messages::utils::agent_access::{parse_agent_access, AgentAccess};
+use xmlrpc_benthic::Value;
+
+#[test]
+fn test_parse_agent_access() {
+    let access = Value::String("M".to_string());
+    let access_max = Value::String("A".to_string());
+    assert_eq!(parse_agent_access(Some(&access)), Some(AgentAccess::Mature));
+    assert_eq!(
+        parse_agent_access(Some(&access_max)),
+        Some(AgentAccess::Adult)
+    );
+    assert_eq!(parse_agent_access(Some(&Value::Int(1))), None);
+    assert_eq!(parse_agent_access(None), None);
+}
+
+#[test]
+fn test_agent_access_is_allowed_in() {
+    assert!(AgentAccess::Adult.is_allowed_in(&AgentAccess::Mature));
+    assert!(AgentAccess::Mature.is_allowed_in(&AgentAccess::Mature));
+    assert!(AgentAccess::Mature.is_allowed_in(&AgentAccess::PG));
+    assert!(!AgentAccess::Mature.is_allowed_in(&AgentAccess::Adult));
+    assert!(!AgentAccess::PG.is_allowed_in(&AgentAccess::Mature));
+    assert!(!AgentAccess::Adult.is_allowed_in(&AgentAccess::Down));
+}
@@ -0,0 +1,59 @@
+use metaverse_messages::{
+    agent_animation::{AgentAnimation, AnimationChange},
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+};
+use uuid::{uuid, Uuid};
+
+const AGENT_ID: Uuid = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+const SESSION_ID: Uuid = uuid!("89556747-24cb-43ed-920b-47caed15465f");
+const WAVE: Uuid = Uuid::from_u128(1);
+const DANCE: Uuid = Uuid::from_u128(2);
+
+#[test]
+fn test_agent_animation_from_pairs() {
+    let animation = AgentAnimation::new(AGENT_ID, SESSION_ID, [(WAVE, true), (DANCE, false)]);
+    assert_eq!(
+        animation.animations,
+        vec![
+            AnimationChange {
+                anim_id: WAVE,
+                start: true
+            },
+            AnimationChange {
+                anim_id: DANCE,
+                start: false
+            },
+        ]
+    );
+    assert!(animation.physical_avatar_events.is_empty());
+}
+
+#[test]
+fn test_agent_animation_round_trip() {
+    let mut animation = AgentAnimation::new(AGENT_ID, SESSION_ID, [(WAVE, true), (DANCE, false)]);
+    animation.physical_avatar_events = vec![vec![1, 2, 3]];
+
+    let packet = Packet::new_agent_animation(animation.clone());
+    assert!(packet.header.reliable);
+    match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+        PacketType::AgentAnimation(data) => assert_eq!(*data, animation),
+        other => panic!("expected AgentAnimation, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_agent_animation_layout() {
+    let bytes = AgentAnimation::new(AGENT_ID, SESSION_ID, [(WAVE, true)]).to_bytes();
+    // the agent and session IDs, one animation with its start flag, and no physical events
+    assert_eq!(bytes.len(), 16 + 16 + 1 + 17 + 1);
+    assert_eq!(&bytes[33..49], WAVE.as_bytes());
+    assert_eq!(bytes[49], 1);
+    assert_eq!(bytes[50], 0);
+}
+
+#[test]
+fn test_truncated_agent_animation_is_an_error() {
+    let bytes = AgentAnimation::new(AGENT_ID, SESSION_ID, [(WAVE, true)]).to_bytes();
+    assert!(AgentAnimation::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+}
@@ -0,0 +1,51 @@
+use glam::Vec3;
+use metaverse_messages::{
+    agent_movement_complete::AgentMovementComplete,
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    ui_events::UiEventTypes,
+};
+use uuid::uuid;
+
+fn agent_movement_complete() -> AgentMovementComplete {
+    AgentMovementComplete {
+        agent_id: uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+        session_id: uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+        position: Vec3::new(128.0, 128.0, 21.5),
+        look_at: Vec3::new(1.0, 0.0, 0.0),
+        region_handle: 1099511628032000,
+        timestamp: 1700000000,
+        channel_version: "OpenSimulator Server 0.9.3".to_string(),
+    }
+}
+
+#[test]
+fn test_agent_movement_complete_tofrom_bytes() {
+    let packet = Packet::new_agent_movement_complete(agent_movement_complete());
+    match Packet::from_bytes(&packet.to_bytes()) {
+        Ok(Packet {
+            body: PacketType::AgentMovementComplete(data),
+            ..
+        }) => {
+            assert_eq!(data.position, Vec3::new(128.0, 128.0, 21.5));
+            assert_eq!(data.look_at, Vec3::new(1.0, 0.0, 0.0));
+            assert_eq!(data.region_handle, 1099511628032000);
+            assert_eq!(data.channel_version, "OpenSimulator Server 0.9.3");
+        }
+        Ok(packet) => panic!("wrong packet type: {:?}", packet),
+        Err(e) => panic!("Error creating packet: {}", e),
+    }
+}
+
+#[test]
+fn test_agent_movement_complete_is_sent_to_the_ui() {
+    let packet = Packet::new_agent_movement_complete(agent_movement_complete());
+    let event = packet.body.ui_event();
+    assert!(matches!(event, UiEventTypes::MovementCompleteEvent));
+    match event.packet_type_from_bytes(&packet.body.to_bytes()) {
+        Some(PacketType::AgentMovementComplete(data)) => {
+            assert_eq!(data.to_bytes(), agent_movement_complete().to_bytes())
+        }
+        other => panic!("wrong packet type: {:?}", other),
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use glam::Vec3;
+use metaverse_messages::{
+    agent_set_appearance::{AgentSetAppearance, WearableCache, DEFAULT_AVATAR_SIZE},
+    packet::Packet,
+    packet_types::PacketType,
+    utils::texture_entry::TextureEntry,
+};
+use uuid::{uuid, Uuid};
+
+const DEFAULT_TEXTURE: Uuid = uuid!("5748decc-f629-461c-9a36-a35a221fe21f");
+const HEAD_TEXTURE: Uuid = uuid!("0cf2d5ab-5c1e-4d7a-8a0f-6b2b3f0c1c1a");
+
+fn texture_entry() -> TextureEntry {
+    let mut faces = BTreeMap::new();
+    faces.insert(0, HEAD_TEXTURE);
+    faces.insert(7, HEAD_TEXTURE);
+    faces.insert(8, Uuid::from_u128(8));
+    TextureEntry::new(DEFAULT_TEXTURE, faces)
+}
+
+#[test]
+fn test_agent_set_appearance_round_trip() {
+    let mut appearance = AgentSetAppearance::new(
+        uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+        uuid!("89556747-24cb-43ed-920b-47caed15465f"),
+        (0..218).map(|i| i as u8).collect(),
+        texture_entry(),
+    );
+    appearance.size = Vec3::new(0.5, 0.6, 1.8);
+    appearance.wearables.push(WearableCache {
+        cache_id: Uuid::from_u128(42),
+        texture_index: 1,
+    });
+
+    let bytes = Packet::new_agent_set_appearance(appearance.clone()).to_bytes();
+    let packet = Packet::from_bytes(&bytes).unwrap();
+    assert!(packet.header.reliable);
+    match packet.body {
+        PacketType::AgentSetAppearance(decoded) => {
+            assert_eq!(decoded.agent_id, appearance.agent_id);
+            assert_eq!(decoded.session_id, appearance.session_id);
+            assert_eq!(decoded.serial_num, 1);
+            assert_eq!(decoded.size, appearance.size);
+            assert_eq!(decoded.wearables.len(), 1);
+            assert_eq!(decoded.wearables[0].cache_id, Uuid::from_u128(42));
+            assert_eq!(decoded.wearables[0].texture_index, 1);
+            assert_eq!(decoded.texture_entry, appearance.texture_entry);
+            assert_eq!(decoded.visual_params, appearance.visual_params);
+        }
+        other => panic!("expected AgentSetAppearance, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_new_appearance_uses_the_default_size() {
+    let appearance = AgentSetAppearance::new(Uuid::nil(), Uuid::nil(), vec![], texture_entry());
+    assert_eq!(appearance.size, DEFAULT_AVATAR_SIZE);
+    assert!(appearance.wearables.is_empty());
+}
+
+#[test]
+fn test_texture_entry_round_trip() {
+    let entry = texture_entry();
+    let decoded = TextureEntry::from_bytes(&entry.to_bytes()).unwrap();
+    assert_eq!(decoded, entry);
+    assert_eq!(decoded.texture(0), HEAD_TEXTURE);
+    assert_eq!(decoded.texture(7), HEAD_TEXTURE);
+    assert_eq!(decoded.texture(8), Uuid::from_u128(8));
+    assert_eq!(decoded.texture(3), DEFAULT_TEXTURE);
+}
+
+#[test]
+fn test_texture_entry_face_bits() {
+    let mut faces = BTreeMap::new();
+    faces.insert(7, HEAD_TEXTURE);
+    let bytes = TextureEntry::new(DEFAULT_TEXTURE, faces).to_bytes();
+    // face 7 doesn't fit in seven bits, so it takes two bytes
+    assert_eq!(&bytes[16..18], &[0x81, 0x00]);
+    assert_eq!(&bytes[18..34], HEAD_TEXTURE.as_bytes());
+    assert_eq!(bytes[34], 0);
+}
+
+#[test]
+fn test_truncated_texture_entry_is_an_error() {
+    let bytes = texture_entry().to_bytes();
+    assert!(TextureEntry::from_bytes(&bytes[..20]).is_err());
+}
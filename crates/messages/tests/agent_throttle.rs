@@ -0,0 +1,30 @@
+use metaverse_messages::{
+    agent_throttle::{AgentThrottle, Throttles},
+    packet::Packet,
+    packet_types::PacketType,
+};
+use uuid::uuid;
+
+#[test]
+fn test_agent_throttle_tofrom_bytes() {
+    let packet = Packet::new_agent_throttle(AgentThrottle {
+        agent_id: uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+        session_id: uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+        circuit_code: 42069,
+        gen_counter: 1,
+        throttles: Throttles::default(),
+    });
+    let bytes = packet.to_bytes();
+    match Packet::from_bytes(&bytes) {
+        Ok(Packet {
+            body: PacketType::AgentThrottle(data),
+            ..
+        }) => {
+            assert_eq!(data.circuit_code, 42069);
+            assert_eq!(data.gen_counter, 1);
+            assert_eq!(data.throttles, Throttles::default());
+        }
+        Ok(packet) => panic!("wrong packet type: {:?}", packet),
+        Err(e) => panic!("Error creating packet: {}", e),
+    }
+}
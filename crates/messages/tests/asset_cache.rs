@@ -0,0 +1,30 @@
+use std::{thread, time::Duration};
+
+use metaverse_messages::capabilities::cache::AssetCache;
+use uuid::Uuid;
+
+#[test]
+fn test_asset_cache_lru_eviction() {
+    let dir = std::env::temp_dir().join(format!("asset_cache_{}", Uuid::new_v4()));
+    let cache = AssetCache::new(&dir, 20).unwrap();
+
+    let first = Uuid::new_v4();
+    let second = Uuid::new_v4();
+    let third = Uuid::new_v4();
+
+    cache.insert(first, &[1; 10]).unwrap();
+    thread::sleep(Duration::from_millis(20));
+    cache.insert(second, &[2; 10]).unwrap();
+    thread::sleep(Duration::from_millis(20));
+
+    // reading the first asset makes the second one the least recently used
+    assert_eq!(cache.get(first), Some(vec![1; 10]));
+    thread::sleep(Duration::from_millis(20));
+    cache.insert(third, &[3; 10]).unwrap();
+
+    assert_eq!(cache.get(first), Some(vec![1; 10]));
+    assert_eq!(cache.get(second), None);
+    assert_eq!(cache.get(third), Some(vec![3; 10]));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
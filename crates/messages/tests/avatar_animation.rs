@@ -0,0 +1,37 @@
+use metaverse_messages::{
+    avatar_animation::{Animation, AvatarAnimation},
+    packet::Packet,
+    packet_types::PacketType,
+};
+use uuid::uuid;
+
+#[test]
+fn test_avatar_animation_tofrom_bytes() {
+    let animations = vec![
+        Animation {
+            animation_id: uuid!("2408fe9e-df1d-1d7d-f4ff-1384fa7b350f"),
+            sequence_id: 1,
+        },
+        Animation {
+            animation_id: uuid!("6ed24bd8-91aa-4b12-ccc7-c97c857ab4e0"),
+            sequence_id: 2,
+        },
+    ];
+    let packet = Packet::new_avatar_animation(AvatarAnimation {
+        agent_id: uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+        animations: animations.clone(),
+        animation_sources: vec![uuid!("224ecaea-372d-4d31-8b64-4805966418e5")],
+        physical_avatar_events: vec![],
+    });
+    match Packet::from_bytes(&packet.to_bytes()) {
+        Ok(Packet {
+            body: PacketType::AvatarAnimation(data),
+            ..
+        }) => {
+            assert_eq!(data.animations, animations);
+            assert_eq!(data.animation_sources.len(), 1);
+        }
+        Ok(packet) => panic!("wrong packet type: {:?}", packet),
+        Err(e) => panic!("Error creating packet: {}", e),
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use glam::Vec3;
+use metaverse_messages::{
+    avatar_appearance::{
+        AppearanceAttachment, AppearanceData, AvatarAppearance, DEFAULT_AVATAR_TEXTURE,
+    },
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    ui_events::UiEventTypes,
+    utils::texture_entry::TextureEntry,
+};
+use uuid::{uuid, Uuid};
+
+const AGENT_ID: Uuid = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+const HEAD: Uuid = Uuid::from_u128(1);
+const UPPER: Uuid = Uuid::from_u128(2);
+
+/// an avatar with baked head and upper body textures, and the default texture everywhere else
+fn appearance() -> AvatarAppearance {
+    AvatarAppearance {
+        agent_id: AGENT_ID,
+        is_trial: false,
+        texture_entry: TextureEntry::new(
+            DEFAULT_AVATAR_TEXTURE,
+            BTreeMap::from([(8, HEAD), (9, UPPER)]),
+        ),
+        visual_params: vec![127; 218],
+        appearance_data: vec![AppearanceData {
+            appearance_version: 1,
+            cof_version: 42,
+            flags: 0,
+        }],
+        hover_height: vec![Vec3::new(0.0, 0.0, 0.1)],
+        attachments: vec![AppearanceAttachment {
+            id: Uuid::from_u128(3),
+            attachment_point: 2,
+        }],
+    }
+}
+
+#[test]
+fn test_avatar_appearance_round_trip() {
+    let packet = Packet::new_avatar_appearance(appearance());
+    match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+        PacketType::AvatarAppearance(data) => assert_eq!(*data, appearance()),
+        other => panic!("expected AvatarAppearance, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_avatar_appearance_from_an_older_simulator() {
+    let mut appearance = appearance();
+    appearance.appearance_data.clear();
+    appearance.hover_height.clear();
+    appearance.attachments.clear();
+    let mut bytes = appearance.to_bytes();
+    // older simulators don't send the appearance data, hover height or attachment blocks
+    bytes.truncate(bytes.len() - 3);
+    assert_eq!(AvatarAppearance::from_bytes(&bytes).unwrap(), appearance);
+}
+
+#[test]
+fn test_truncated_avatar_appearance_is_an_error() {
+    let bytes = appearance().to_bytes();
+    assert!(AvatarAppearance::from_bytes(&bytes[..100]).is_err());
+    assert!(AvatarAppearance::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+}
+
+#[test]
+fn test_baked_textures_leave_out_missing_bakes() {
+    assert_eq!(appearance().baked_textures(), vec![HEAD, UPPER]);
+
+    let mut cloud = appearance();
+    cloud.texture_entry = TextureEntry::new(DEFAULT_AVATAR_TEXTURE, BTreeMap::new());
+    assert!(cloud.baked_textures().is_empty());
+}
+
+#[test]
+fn test_avatar_appearance_event_reaches_the_ui() {
+    let packet = Packet::new_avatar_appearance(appearance());
+    assert_eq!(packet.body.ui_event(), UiEventTypes::AvatarAppearanceEvent);
+    match UiEventTypes::AvatarAppearanceEvent.packet_type_from_bytes(&packet.body.to_bytes()) {
+        Some(PacketType::AvatarAppearance(data)) => assert_eq!(data.agent_id, AGENT_ID),
+        other => panic!("expected AvatarAppearance, got {:?}", other),
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use metaverse_messages::login_system::login_response::{
+    Buddy, FriendsRights, LoginResponse, FRIEND_RIGHTS_CAN_MODIFY_OBJECTS,
+    FRIEND_RIGHTS_CAN_SEE_ONLINE, FRIEND_RIGHTS_CAN_SEE_ON_MAP,
+};
+use uuid::Uuid;
+use xmlrpc_benthic::Value;
+
+fn buddy(id: &str, given: i32, has: i32) -> Value {
+    let mut map = BTreeMap::new();
+    map.insert("buddy_id".to_string(), Value::String(id.to_string()));
+    map.insert("buddy_rights_given".to_string(), Value::Int(given));
+    map.insert("buddy_rights_has".to_string(), Value::Int(has));
+    Value::Struct(map)
+}
+
+fn login_response(buddy_list: Vec<Value>) -> LoginResponse {
+    let mut map = BTreeMap::new();
+    map.insert("first_name".to_string(), Value::String("Test".to_string()));
+    map.insert("last_name".to_string(), Value::String("User".to_string()));
+    map.insert("circuit_code".to_string(), Value::Int(1234));
+    map.insert("login".to_string(), Value::String("true".to_string()));
+    map.insert("buddy-list".to_string(), Value::Array(buddy_list));
+    LoginResponse::try_from(Value::Struct(map)).unwrap()
+}
+
+#[test]
+fn test_rights_are_read_as_flags() {
+    let friend = Uuid::from_u128(1);
+    let response = login_response(vec![buddy(&friend.to_string(), 7, 3)]);
+    let buddy_list = response.buddy_list.unwrap();
+    assert_eq!(buddy_list.len(), 1);
+    assert_eq!(buddy_list[0].id, friend);
+
+    let given = buddy_list[0].rights_given;
+    assert!(given.can_see_online && given.can_see_on_map && given.can_modify_objects);
+    let has = buddy_list[0].rights_has;
+    assert!(has.can_see_online && has.can_see_on_map);
+    assert!(!has.can_modify_objects);
+}
+
+#[test]
+fn test_has_checks_every_right_asked_for() {
+    let rights = FriendsRights::from_bits(FRIEND_RIGHTS_CAN_SEE_ON_MAP);
+    assert!(rights.has(FRIEND_RIGHTS_CAN_SEE_ON_MAP));
+    assert!(!rights.has(FRIEND_RIGHTS_CAN_SEE_ONLINE));
+    assert!(!rights.has(FRIEND_RIGHTS_CAN_SEE_ON_MAP | FRIEND_RIGHTS_CAN_MODIFY_OBJECTS));
+    assert!(FriendsRights::default().has(0));
+    assert_eq!(FriendsRights::from_bits(5).to_bits(), 5);
+}
+
+#[test]
+fn test_malformed_buddies_are_left_out() {
+    let friend = Uuid::from_u128(2);
+    let response = login_response(vec![
+        buddy("not a uuid", 1, 1),
+        Value::String("not a buddy".to_string()),
+        buddy(&friend.to_string(), 0, 0),
+    ]);
+    let buddy_list = response.buddy_list.unwrap();
+    assert_eq!(buddy_list.len(), 1);
+    assert_eq!(buddy_list[0].id, friend);
+    assert_eq!(buddy_list[0].rights_given, FriendsRights::default());
+}
+
+#[test]
+fn test_buddy_list_converts_back_to_xmlrpc() {
+    let buddy_list = vec![Buddy {
+        id: Uuid::from_u128(3),
+        rights_given: FriendsRights::from_bits(FRIEND_RIGHTS_CAN_SEE_ONLINE),
+        rights_has: FriendsRights::from_bits(FRIEND_RIGHTS_CAN_MODIFY_OBJECTS),
+    }];
+    let values: Vec<Value> = buddy_list.iter().cloned().map(Value::from).collect();
+    assert_eq!(login_response(values).buddy_list.unwrap(), buddy_list);
+}
@@ -1,7 +1,9 @@
 use hex::FromHex;
 use metaverse_messages::{
+    chat_from_simulator::{Audible, ChatFromSimulator, ChatType, SourceType},
     chat_from_viewer::{ChatFromViewer, ClientChatType},
-    packet::Packet,
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
 };
 use uuid::uuid;
 
@@ -30,3 +32,72 @@ fn create_chat_from_viewer() {
     println!("{:?}", hex::encode(packet.to_bytes()));
     println!("\"400000110600ffff0050320dff8a7a594720a0f75a8df3698d9a224ecaea372d4d318b644805966418e5020061000100000000\"")
 }
+
+#[test]
+fn test_chat_local_echo() {
+    let agent_id = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+    let chat = ChatFromViewer {
+        agent_id,
+        session_id: uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+        channel: 0,
+        message: "hello".to_string(),
+        message_type: ClientChatType::Shout,
+    };
+    let echo = ChatFromSimulator::local_echo("Test User".to_string(), &chat);
+    assert_eq!(echo.source_id, agent_id);
+    assert_eq!(echo.owner_id, agent_id);
+    assert_eq!(echo.from_name, "Test User");
+    assert_eq!(echo.message, "hello");
+    assert!(matches!(echo.source_type, SourceType::Agent));
+    assert!(matches!(echo.chat_type, ChatType::Shout));
+}
+
+#[test]
+fn test_chat_from_viewer_wire_format() {
+    let chat = ChatFromViewer {
+        agent_id: uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+        session_id: uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+        channel: 0,
+        message: "a".to_string(),
+        message_type: ClientChatType::Normal,
+    };
+    // the body of the captured packet in test_chat_from_viewer
+    let expected = Vec::from_hex(
+        "320dff8a7a594720a0f75a8df3698d9a224ecaea372d4d318b644805966418e5020061000100000000",
+    )
+    .unwrap();
+    assert_eq!(chat.to_bytes(), expected);
+}
+
+#[test]
+fn test_chat_from_viewer_channel_tofrom_bytes() {
+    let packet = Packet::new_chat_from_viewer(ChatFromViewer {
+        agent_id: uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+        session_id: uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+        channel: -1,
+        message: "hud command".to_string(),
+        message_type: ClientChatType::Normal,
+    });
+    match Packet::from_bytes(&packet.to_bytes()) {
+        Ok(Packet {
+            body: PacketType::ChatFromViewer(data),
+            ..
+        }) => {
+            assert_eq!(data.channel, -1);
+            assert_eq!(data.message, "hud command");
+        }
+        Ok(packet) => panic!("wrong packet type: {:?}", packet),
+        Err(e) => panic!("Error creating packet: {}", e),
+    }
+}
+
+#[test]
+fn test_chat_display_strings() {
+    assert_eq!(ChatType::Whisper.to_string(), "whispers");
+    assert_eq!(ChatType::Normal.to_string(), "says");
+    assert_eq!(ChatType::Shout.to_string(), "shouts");
+    assert_eq!(ChatType::OwnerSay.to_string(), "says");
+    assert_eq!(ChatType::StartTyping.to_string(), "started typing");
+    assert_eq!(SourceType::Object.to_string(), "object");
+    assert_eq!(Audible::Barely.to_string(), "barely audible");
+}
@@ -1,5 +1,11 @@
+use glam::Vec3;
 use hex::FromHex;
-use metaverse_messages::packet::Packet;
+use metaverse_messages::{
+    coarse_location_update::{CoarseLocationUpdate, MinimapEntities},
+    packet::Packet,
+    packet_types::PacketType,
+};
+use uuid::uuid;
 
 #[test]
 fn test_coarse_location_update() {
@@ -24,3 +30,51 @@ fn test_coarse_location_update_firestorm() {
         Err(e) => eprintln!("Error creating packet: {}", e),
     }
 }
+
+#[test]
+fn test_coarse_location_update_tofrom_bytes() {
+    let agent_id = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+    let packet = Packet::new_coarse_location_update(CoarseLocationUpdate {
+        locations: vec![MinimapEntities {
+            x: 128,
+            y: 64,
+            z: 10,
+        }],
+        you: 0,
+        prey: -1,
+        agent_ids: vec![agent_id],
+    });
+    match Packet::from_bytes(&packet.to_bytes()) {
+        Ok(Packet {
+            body: PacketType::CoarseLocationUpdate(data),
+            ..
+        }) => {
+            assert_eq!(data.you, 0);
+            assert_eq!(data.prey, -1);
+            assert_eq!(
+                data.positions(),
+                vec![(Some(agent_id), Vec3::new(128.0, 64.0, 40.0))]
+            );
+        }
+        Ok(packet) => panic!("wrong packet type: {:?}", packet),
+        Err(e) => panic!("Error creating packet: {}", e),
+    }
+}
+
+#[test]
+fn test_coarse_location_update_your_position() {
+    let mut update = CoarseLocationUpdate {
+        locations: vec![
+            MinimapEntities { x: 1, y: 2, z: 3 },
+            MinimapEntities { x: 4, y: 5, z: 6 },
+        ],
+        you: 1,
+        prey: -1,
+        agent_ids: Vec::new(),
+    };
+    assert_eq!(update.your_position(), Some(Vec3::new(4.0, 5.0, 24.0)));
+    update.you = -1;
+    assert_eq!(update.your_position(), None);
+    update.you = 2;
+    assert_eq!(update.your_position(), None);
+}
@@ -0,0 +1,22 @@
+/// asserts that a PacketData survives being written to bytes and read back.
+/// The parsed value is compared by writing it out again, so the packet structs don't need to
+/// implement PartialEq.
+#[macro_export]
+macro_rules! assert_packet_roundtrip {
+    ($packet_type:ty, $value:expr) => {{
+        use metaverse_messages::packet::PacketData;
+        let value: $packet_type = $value;
+        let bytes = value.to_bytes();
+        let parsed = <$packet_type>::from_bytes(&bytes)
+            .unwrap_or_else(|e| panic!("failed to parse {}: {}", stringify!($packet_type), e));
+        assert_eq!(
+            parsed.to_bytes(),
+            bytes,
+            "{} did not round trip: {:?} became {:?}",
+            stringify!($packet_type),
+            value,
+            parsed
+        );
+        parsed
+    }};
+}
@@ -0,0 +1,202 @@
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use metaverse_messages::capabilities::{
+    download::{download_asset, download_assets},
+    fetch_inventory::fetch_inventory_folder,
+};
+use metaverse_messages::errors::HttpErrorResponse;
+use uuid::Uuid;
+
+/// serve one request with the response, and return the URL to request
+fn serve_once(response: Vec<u8>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/cap", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 4096];
+        let _ = stream.read(&mut request);
+        stream.write_all(&response).unwrap();
+    });
+    url
+}
+
+fn http_response(status: &str, content_type: &str, body: &[u8]) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+#[tokio::test]
+async fn test_error_status_keeps_the_response() {
+    let page = b"<html><body>Capability not found</body></html>";
+    let url = serve_once(http_response("404 Not Found", "text/html", page));
+
+    let error = download_asset(&url, None).await.unwrap_err();
+    assert_eq!(error.status(), Some(404));
+    assert!(error.message.contains("404"));
+    let response = error.response.unwrap();
+    assert_eq!(response.header("content-type"), Some("text/html"));
+    assert_eq!(response.body, page);
+    assert!(response.body_text().contains("Capability not found"));
+}
+
+#[tokio::test]
+async fn test_long_error_pages_are_cut_short() {
+    let page = vec![b'x'; 10_000];
+    let url = serve_once(http_response(
+        "500 Internal Server Error",
+        "text/plain",
+        &page,
+    ));
+
+    let error = download_asset(&url, None).await.unwrap_err();
+    assert_eq!(error.status(), Some(500));
+    assert_eq!(
+        error.response.unwrap().body.len(),
+        HttpErrorResponse::BODY_PREVIEW_LENGTH
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_inventory_reports_the_status_instead_of_a_parse_failure() {
+    let page = b"<html><body>Bad Gateway</body></html>";
+    let url = serve_once(http_response("502 Bad Gateway", "text/html", page));
+
+    let error = fetch_inventory_folder(&url, Uuid::new_v4(), Uuid::new_v4())
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(502));
+}
+
+#[tokio::test]
+async fn test_successful_download_is_unchanged() {
+    let url = serve_once(http_response(
+        "200 OK",
+        "application/octet-stream",
+        &[1, 2, 3],
+    ));
+    assert_eq!(download_asset(&url, None).await.unwrap(), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_connection_failure_has_no_response() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/cap", listener.local_addr().unwrap());
+    drop(listener);
+
+    let error = download_asset(&url, None).await.unwrap_err();
+    assert_eq!(error.status(), None);
+}
+
+#[tokio::test]
+async fn test_progress_is_reported_after_every_chunk() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/cap", listener.local_addr().unwrap());
+    let (progressed, progressed_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 4096];
+        let _ = stream.read(&mut request);
+        stream
+            .write_all(
+                b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+            )
+            .unwrap();
+        stream.write_all(b"3\r\nabc\r\n").unwrap();
+        // hold the second chunk back until the first has been reported
+        progressed_rx.recv().unwrap();
+        stream.write_all(b"2\r\nde\r\n0\r\n\r\n").unwrap();
+    });
+
+    let calls = Mutex::new(Vec::new());
+    let progressed = Mutex::new(progressed);
+    let progress = |downloaded: u64, total: Option<u64>| {
+        calls.lock().unwrap().push((downloaded, total));
+        if downloaded == 3 {
+            progressed.lock().unwrap().send(()).unwrap();
+        }
+    };
+    let asset = download_asset(&url, Some(&progress)).await.unwrap();
+
+    assert_eq!(asset, b"abcde");
+    // a chunked body has no Content-Length, so there is no total to report
+    assert_eq!(
+        calls.into_inner().unwrap(),
+        vec![(0, None), (3, None), (5, None)]
+    );
+}
+
+#[tokio::test]
+async fn test_download_assets_keeps_the_order_and_the_cap() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let urls: Vec<String> = (0..5)
+        .map(|i| format!("http://{}/{}", address, i))
+        .collect();
+    // how many requests are being answered at once, and the most there ever were
+    let in_flight = Arc::new((Mutex::new((0, 0)), Condvar::new()));
+    let server = {
+        let in_flight = in_flight.clone();
+        thread::spawn(move || {
+            let mut connections = Vec::new();
+            for _ in 0..5 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let in_flight = in_flight.clone();
+                connections.push(thread::spawn(move || {
+                    let mut request = [0u8; 4096];
+                    let size = stream.read(&mut request).unwrap();
+                    let path = String::from_utf8_lossy(&request[..size])
+                        .split_whitespace()
+                        .nth(1)
+                        .unwrap()
+                        .to_string();
+
+                    let (count, condvar) = &*in_flight;
+                    let mut count = count.lock().unwrap();
+                    count.0 += 1;
+                    count.1 = count.1.max(count.0);
+                    condvar.notify_all();
+                    // give the other downloads a chance to overlap with this one
+                    let (mut count, _) = condvar
+                        .wait_timeout_while(count, Duration::from_millis(200), |count| count.0 < 2)
+                        .unwrap();
+                    count.0 -= 1;
+                    drop(count);
+
+                    let response = if path == "/2" {
+                        http_response("404 Not Found", "text/plain", b"missing")
+                    } else {
+                        http_response("200 OK", "application/octet-stream", path.as_bytes())
+                    };
+                    stream.write_all(&response).unwrap();
+                }));
+            }
+            for connection in connections {
+                connection.join().unwrap();
+            }
+        })
+    };
+
+    let results = download_assets(&urls, 2).await;
+    server.join().unwrap();
+
+    assert_eq!(results.len(), 5);
+    for (i, result) in results.iter().enumerate() {
+        if i == 2 {
+            assert_eq!(result.as_ref().unwrap_err().status(), Some(404));
+        } else {
+            assert_eq!(result.as_ref().unwrap(), format!("/{}", i).as_bytes());
+        }
+    }
+    assert_eq!(in_flight.0.lock().unwrap().1, 2);
+}
@@ -0,0 +1,61 @@
+use hex::FromHex;
+use metaverse_messages::{
+    enable_simulator::EnableSimulator,
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    ui_events::UiEventTypes,
+    utils::region_handle::RegionHandle,
+};
+use std::net::{Ipv4Addr, SocketAddr};
+
+fn enable_simulator() -> EnableSimulator {
+    EnableSimulator {
+        region_handle: RegionHandle::from_grid(1000, 1001),
+        ip: Ipv4Addr::new(127, 0, 0, 1),
+        port: 13001,
+    }
+}
+
+#[test]
+fn test_enable_simulator_from_bytes() {
+    // the region at (1000, 1001), at 127.0.0.1:13001
+    let bytes = Vec::from_hex("400000000300ffff009700e9030000e803007f00000132c9").unwrap();
+    let packet = Packet::from_bytes(&bytes).unwrap();
+    match packet.body {
+        PacketType::EnableSimulator(data) => {
+            assert_eq!(*data, enable_simulator());
+            assert_eq!(
+                data.address(),
+                "127.0.0.1:13001".parse::<SocketAddr>().unwrap()
+            );
+        }
+        other => panic!("expected EnableSimulator, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_enable_simulator_round_trip() {
+    let packet = Packet::new_enable_simulator(enable_simulator());
+    assert!(packet.header.reliable);
+    match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+        PacketType::EnableSimulator(data) => assert_eq!(*data, enable_simulator()),
+        other => panic!("expected EnableSimulator, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_enable_simulator_is_sent_to_the_ui() {
+    let body = PacketType::EnableSimulator(Box::new(enable_simulator()));
+    let event = body.ui_event();
+    assert!(matches!(event, UiEventTypes::EnableSimulatorEvent));
+    match event.packet_type_from_bytes(&body.to_bytes()) {
+        Some(PacketType::EnableSimulator(data)) => assert_eq!(*data, enable_simulator()),
+        other => panic!("expected EnableSimulator, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_truncated_enable_simulator_is_an_error() {
+    let bytes = enable_simulator().to_bytes();
+    assert!(EnableSimulator::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
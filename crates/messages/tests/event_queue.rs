@@ -0,0 +1,80 @@
+use metaverse_messages::{
+    capabilities::{
+        event_queue::{parse_event_queue_response, EventQueuePoll},
+        llsd::LLSDValue,
+    },
+    packet_types::PacketType,
+    ui_events::UiEventTypes,
+};
+
+#[test]
+fn test_parse_event_queue_response() {
+    let response = r#"<?xml version="1.0" encoding="UTF-8"?>
+<llsd><map>
+    <key>id</key><integer>7</integer>
+    <key>events</key><array>
+        <map>
+            <key>message</key><string>TeleportFinish</string>
+            <key>body</key><map>
+                <key>Info</key><array><map>
+                    <key>SimPort</key><integer>13001</integer>
+                </map></array>
+            </map>
+        </map>
+        <map>
+            <key>message</key><string>EstablishAgentCommunication</string>
+            <key>body</key><map />
+        </map>
+    </array>
+</map></llsd>"#;
+
+    let EventQueuePoll::Events { id, events } = parse_event_queue_response(response).unwrap()
+    else {
+        panic!("expected events");
+    };
+    assert_eq!(id, 7);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].message, "TeleportFinish");
+    let port = events[0]
+        .body
+        .get("Info")
+        .and_then(|info| info.as_array())
+        .and_then(|info| info[0].get("SimPort"))
+        .and_then(|port| port.as_i32());
+    assert_eq!(port, Some(13001));
+    assert_eq!(events[1].message, "EstablishAgentCommunication");
+}
+
+#[test]
+fn test_empty_event_queue_response_is_a_timeout() {
+    let response = r#"<?xml version="1.0" ?><llsd><undef /></llsd>"#;
+    assert_eq!(
+        parse_event_queue_response(response).unwrap(),
+        EventQueuePoll::Timeout
+    );
+}
+
+#[test]
+fn test_event_queue_event_to_ui() {
+    let response = r#"<llsd><map><key>id</key><integer>1</integer><key>events</key><array><map>
+        <key>message</key><string>ChatterBoxInvitation</string>
+        <key>body</key><map><key>message</key><string>hi</string></map>
+    </map></array></map></llsd>"#;
+    let EventQueuePoll::Events { events, .. } = parse_event_queue_response(response).unwrap()
+    else {
+        panic!("expected events");
+    };
+
+    let packet = PacketType::EventQueueEvent(Box::new(events[0].clone()));
+    let bytes = packet.to_bytes();
+    match UiEventTypes::EventQueueEvent.packet_type_from_bytes(&bytes) {
+        Some(PacketType::EventQueueEvent(event)) => {
+            assert_eq!(event.message, "ChatterBoxInvitation");
+            assert_eq!(
+                event.body.get("message"),
+                Some(&LLSDValue::String("hi".to_string()))
+            );
+        }
+        other => panic!("expected EventQueueEvent, got {:?}", other),
+    }
+}
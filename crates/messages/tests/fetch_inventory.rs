@@ -0,0 +1,73 @@
+use metaverse_messages::capabilities::fetch_inventory::{
+    parse_inventory_folder, resolve_links, ItemMetadata, LINK_ASSET_TYPE,
+};
+use uuid::{uuid, Uuid};
+
+#[test]
+fn test_parse_inventory_folder() {
+    let response = r#"<?xml version="1.0" encoding="UTF-8"?>
+<llsd><map><key>folders</key><array><map>
+    <key>folder_id</key><uuid>9846e02a-f41b-4199-860e-cde46cc25649</uuid>
+    <key>owner_id</key><uuid>320dff8a-7a59-4720-a0f7-5a8df3698d9a</uuid>
+    <key>version</key><integer>3</integer>
+    <key>descendents</key><integer>1</integer>
+    <key>categories</key><array />
+    <key>items</key><array><map>
+        <key>item_id</key><uuid>224ecaea-372d-4d31-8b64-4805966418e5</uuid>
+        <key>parent_id</key><uuid>9846e02a-f41b-4199-860e-cde46cc25649</uuid>
+        <key>asset_id</key><uuid>2408fe9e-df1d-1d7d-f4ff-1384fa7b350f</uuid>
+        <key>permissions</key><map>
+            <key>owner_id</key><uuid>320dff8a-7a59-4720-a0f7-5a8df3698d9a</uuid>
+        </map>
+        <key>name</key><string>Default Shape &amp; Skin</string>
+        <key>desc</key><string />
+        <key>type</key><integer>13</integer>
+        <key>inv_type</key><integer>18</integer>
+        <key>flags</key><integer>0</integer>
+        <key>created_at</key><integer>1700000000</integer>
+    </map></array>
+</map></array></map></llsd>"#;
+
+    let items = parse_inventory_folder(response).unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(
+        items[0].item_id,
+        uuid!("224ecaea-372d-4d31-8b64-4805966418e5")
+    );
+    assert_eq!(
+        items[0].owner_id,
+        uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a")
+    );
+    assert_eq!(items[0].name, "Default Shape & Skin");
+    assert_eq!(items[0].asset_type, 13);
+}
+
+fn item(item_id: Uuid, asset_id: Uuid, asset_type: i32) -> ItemMetadata {
+    ItemMetadata {
+        item_id,
+        parent_id: Uuid::nil(),
+        asset_id,
+        owner_id: Uuid::nil(),
+        name: String::new(),
+        description: String::new(),
+        asset_type,
+        inventory_type: 18,
+        flags: 0,
+        created_at: 0,
+    }
+}
+
+#[test]
+fn test_links_resolve_to_their_target() {
+    let shirt = item(Uuid::new_v4(), Uuid::new_v4(), 13);
+    let hair = item(Uuid::new_v4(), Uuid::new_v4(), 13);
+    let shirt_link = item(Uuid::new_v4(), shirt.item_id, LINK_ASSET_TYPE);
+    let missing_link = item(Uuid::new_v4(), Uuid::new_v4(), LINK_ASSET_TYPE);
+
+    let (resolved, unresolved) = resolve_links(
+        &[shirt_link, hair.clone(), missing_link.clone()],
+        &[shirt.clone(), hair.clone()],
+    );
+    assert_eq!(resolved, vec![shirt, hair]);
+    assert_eq!(unresolved, vec![missing_link]);
+}
@@ -0,0 +1,107 @@
+use std::panic;
+
+use metaverse_messages::{
+    header::PacketFrequency,
+    packet::{Packet, ParseOptions},
+};
+
+/// a small xorshift generator, so every run feeds the parsers the same bytes
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+/// parse the datagram, and report it if the parser panics instead of returning an error
+fn assert_no_panic(bytes: &[u8]) {
+    let result = panic::catch_unwind(|| {
+        let _ = Packet::from_bytes(bytes);
+        let _ = Packet::from_bytes_with_options(bytes, ParseOptions::strict());
+    });
+    assert!(result.is_ok(), "parsing {:02x?} panicked", bytes);
+}
+
+/// a header for the packet with the ID, with random flags and sequence number
+fn header(rng: &mut Rng, frequency: PacketFrequency, id: u16) -> Vec<u8> {
+    // zerocoded, reliable and appended acks, but never resent
+    let flags = rng.next() as u8 & 0xd0;
+    let mut bytes = vec![flags];
+    bytes.extend_from_slice(&(rng.next() as u32).to_be_bytes());
+    bytes.push(0);
+    match frequency {
+        PacketFrequency::High => bytes.push(id as u8),
+        PacketFrequency::Medium => bytes.extend_from_slice(&[0xff, id as u8]),
+        PacketFrequency::Low => {
+            bytes.extend_from_slice(&[0xff, 0xff]);
+            bytes.extend_from_slice(&id.to_be_bytes());
+        }
+        PacketFrequency::Fixed => {
+            bytes.extend_from_slice(&[0xff, 0xff, 0xff]);
+            bytes.push(id as u8);
+        }
+    }
+    bytes
+}
+
+#[test]
+fn test_short_datagrams_never_panic() {
+    let mut rng = Rng(0x9e3779b97f4a7c15);
+    for len in 0..16 {
+        for _ in 0..2000 {
+            assert_no_panic(&rng.bytes(len));
+        }
+    }
+}
+
+#[test]
+fn test_random_bodies_never_panic() {
+    let mut rng = Rng(0x2545f4914f6cdd1d);
+    for frequency in [
+        PacketFrequency::High,
+        PacketFrequency::Medium,
+        PacketFrequency::Low,
+        PacketFrequency::Fixed,
+    ] {
+        let ids = match frequency {
+            PacketFrequency::Low => 0..400,
+            _ => 0..256,
+        };
+        for id in ids {
+            for _ in 0..50 {
+                let mut bytes = header(&mut rng, frequency, id);
+                // mostly short bodies, with some long enough to reach variable length fields
+                let len = match rng.below(4) {
+                    0 => rng.below(1400),
+                    _ => rng.below(64),
+                };
+                bytes.extend(rng.bytes(len));
+                assert_no_panic(&bytes);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_malformed_datagrams_are_errors() {
+    // a zerocoded CompletePingCheck whose run of zeros is missing its length
+    assert!(Packet::from_bytes(&[0x80, 0, 0, 0, 0, 0, 0x02, 0x00]).is_err());
+    // a RegionHandshake whose region name isn't UTF-8
+    let mut region_handshake = vec![0x00, 0, 0, 0, 0, 0, 0xff, 0xff, 0x00, 0x94];
+    region_handshake.extend_from_slice(&[0, 0, 0, 0, 0, 0, 2, 0xff, 0xfe]);
+    region_handshake.extend_from_slice(&[0u8; 200]);
+    assert!(Packet::from_bytes(&region_handshake).is_err());
+    for len in 0..6 {
+        assert!(Packet::from_bytes(&[0x40; 6][..len]).is_err());
+    }
+}
@@ -1,5 +1,15 @@
 use hex::FromHex;
-use metaverse_messages::header::{Header, PacketFrequency};
+use metaverse_messages::{
+    complete_ping_check::CompletePingCheck,
+    header::{Header, PacketFrequency},
+    image_data::ImageData,
+    layer_data::{LayerData, LayerType},
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    region_handshake_reply::{AgentData, RegionHandshakeReply, ReplyRegionInfo},
+    request_image::{ImageRequest, RequestImage},
+};
+use uuid::Uuid;
 
 #[test]
 fn test_header_for_acks() {
@@ -105,3 +115,156 @@ fn test_header_tofrom_bytes() {
 
     assert!(header_bytes == header_back_to_bytes);
 }
+
+#[test]
+fn test_packet_builder_flags() {
+    let packet = Packet::new(
+        2,
+        PacketFrequency::High,
+        PacketType::CompletePingCheck(Box::new(CompletePingCheck { ping_id: 1 })),
+    )
+    .reliable(true)
+    .zerocoded(true);
+    assert!(packet.header.reliable);
+    assert!(packet.header.zerocoded);
+    assert!(!packet.header.resent);
+    assert!(!packet.header.appended_acks);
+    assert_eq!(packet.header.id, 2);
+}
+
+#[test]
+fn test_header_for_short_high_frequency_packet() {
+    // a CompletePingCheck is only eight bytes long
+    let packet = Packet::new_complete_ping_check(CompletePingCheck { ping_id: 7 });
+    let bytes = packet.to_bytes();
+    let header = Header::try_from_bytes(&bytes).unwrap();
+    assert_eq!(header.frequency, PacketFrequency::High);
+    assert_eq!(header.id, 2);
+
+    let parsed = Packet::from_bytes(&bytes).unwrap();
+    match parsed.body {
+        PacketType::CompletePingCheck(data) => assert_eq!(data.ping_id, 7),
+        other => panic!("expected CompletePingCheck, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_header_size_matches_the_written_bytes() {
+    for frequency in [
+        PacketFrequency::High,
+        PacketFrequency::Medium,
+        PacketFrequency::Low,
+        PacketFrequency::Fixed,
+    ] {
+        let header = Header::new(3, frequency);
+        let bytes = header.to_bytes();
+        assert_eq!(header.size, Some(bytes.len()));
+        assert_eq!(Header::try_from_bytes(&bytes).unwrap().size, header.size);
+    }
+}
+
+#[test]
+fn test_zerocoded_low_frequency_header_size() {
+    // RegionHandshakeReply is low frequency 149, so its ID starts with a zero
+    let packet = Packet::new(
+        149,
+        PacketFrequency::Low,
+        PacketType::CompletePingCheck(Box::new(CompletePingCheck { ping_id: 1 })),
+    )
+    .zerocoded(true);
+    let bytes = packet.header.to_bytes();
+    assert_eq!(&bytes[6..], &[0xFF, 0xFF, 0x00, 0x01, 149]);
+    assert_eq!(packet.header.size, Some(11));
+
+    let header = Header::try_from_bytes(&bytes).unwrap();
+    assert_eq!(header.id, 149);
+    assert_eq!(header.frequency, PacketFrequency::Low);
+    assert_eq!(header.size, Some(11));
+}
+
+#[test]
+fn test_texture_packets_are_high_frequency() {
+    // RequestImage is high frequency 8, so its ID is the single byte after the header
+    let packet = Packet::new_request_image(RequestImage {
+        agent_id: Uuid::nil(),
+        session_id: Uuid::nil(),
+        requests: vec![ImageRequest {
+            image_id: Uuid::nil(),
+            discard_level: 0,
+            download_priority: 1013.0,
+            packet: 0,
+            image_type: 0,
+        }],
+    });
+    let bytes = packet.to_bytes();
+    assert_eq!(bytes[6], 8);
+    assert_eq!(packet.header.size, Some(7));
+
+    // an ImageData from the simulator starts with the single byte ID 9
+    let image_data = ImageData {
+        id: Uuid::nil(),
+        codec: 2,
+        size: 4,
+        packets: 1,
+        data: vec![0xff, 0x4f, 0xff, 0x51],
+    };
+    let mut bytes = vec![0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x09];
+    bytes.extend(image_data.to_bytes());
+    let parsed = Packet::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.header.id, 9);
+    assert_eq!(parsed.header.frequency, PacketFrequency::High);
+    match parsed.body {
+        PacketType::ImageData(data) => assert_eq!(data.data, image_data.data),
+        other => panic!("expected ImageData, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_zerocoded_packet_roundtrip() {
+    let mut packet = Packet::new_region_handshake_reply(RegionHandshakeReply {
+        agent_data: AgentData {
+            agent_id: Uuid::from_u128(1),
+            session_id: Uuid::nil(),
+        },
+        region_info: ReplyRegionInfo { flags: 0 },
+    });
+    packet.header.appended_acks = true;
+    packet.header.ack_list = Some(vec![7]);
+    let bytes = packet.to_bytes();
+    // the agent ID's 15 leading zeros are a single run, and the session ID and flags are another
+    let body = &bytes[11..bytes.len() - 5];
+    assert_eq!(body, &[0x00, 0x0f, 0x01, 0x00, 0x14]);
+    // the acks are not zerocoded
+    assert_eq!(&bytes[bytes.len() - 5..], &[0, 0, 0, 7, 1]);
+
+    let parsed = Packet::from_bytes(&bytes).unwrap();
+    assert!(parsed.header.zerocoded);
+    assert_eq!(parsed.header.ack_list, Some(vec![7]));
+    match parsed.body {
+        PacketType::RegionHandshakeReply(reply) => {
+            assert_eq!(reply.agent_data.agent_id, Uuid::from_u128(1));
+            assert_eq!(reply.agent_data.session_id, Uuid::nil());
+            assert_eq!(reply.region_info.flags, 0);
+        }
+        other => panic!("expected RegionHandshakeReply, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_long_zero_runs_are_split() {
+    let packet = Packet::new_layer_data(LayerData {
+        layer_type: LayerType::Land,
+        stride: 264,
+        patch_size: 16,
+        layer_content: vec![0; 600],
+    })
+    .zerocoded(true);
+    let bytes = packet.to_bytes();
+    // a run's length is a single byte
+    assert!(bytes.windows(2).any(|run| run == [0x00, 0xff]));
+
+    match Packet::from_bytes(&bytes).unwrap().body {
+        PacketType::LayerData(layer) => assert_eq!(layer.layer_content, vec![0; 600]),
+        other => panic!("expected LayerData, got {:?}", other),
+    }
+}
@@ -0,0 +1,197 @@
+use glam::Vec3;
+use metaverse_messages::{
+    improved_instant_message::{
+        ImprovedInstantMessage, InstantMessageDialog, InstantMessageKind, LureDestination,
+        TeleportLure,
+    },
+    packet::Packet,
+    packet_types::PacketType,
+    teleport_lure_request::{TELEPORT_FLAGS_VIA_GODLIKE_LURE, TELEPORT_FLAGS_VIA_LURE},
+    utils::{agent_access::AgentAccess, region_handle::RegionHandle},
+};
+use uuid::{uuid, Uuid};
+
+const SENDER: Uuid = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+const ITEM: Uuid = uuid!("224ecaea-372d-4d31-8b64-4805966418e5");
+
+fn instant_message(dialog: InstantMessageDialog, binary_bucket: Vec<u8>) -> ImprovedInstantMessage {
+    ImprovedInstantMessage {
+        agent_id: SENDER,
+        session_id: Uuid::nil(),
+        from_group: false,
+        to_agent_id: Uuid::new_v4(),
+        parent_estate_id: 1,
+        region_id: Uuid::new_v4(),
+        position: Vec3::new(128.0, 128.0, 22.0),
+        offline: false,
+        dialog,
+        id: Uuid::new_v4(),
+        timestamp: 0,
+        from_agent_name: "Test User".to_string(),
+        message: "hello".to_string(),
+        binary_bucket,
+    }
+}
+
+#[test]
+fn test_improved_instant_message_tofrom_bytes() {
+    let packet = Packet::new_improved_instant_message(instant_message(
+        InstantMessageDialog::InventoryOffered,
+        vec![7, 1, 2, 3],
+    ));
+    match Packet::from_bytes(&packet.to_bytes()) {
+        Ok(Packet {
+            body: PacketType::ImprovedInstantMessage(data),
+            ..
+        }) => {
+            assert_eq!(data.dialog, InstantMessageDialog::InventoryOffered);
+            assert_eq!(data.from_agent_name, "Test User");
+            assert_eq!(data.message, "hello");
+            assert_eq!(data.binary_bucket, vec![7, 1, 2, 3]);
+        }
+        Ok(packet) => panic!("wrong packet type: {:?}", packet),
+        Err(e) => panic!("Error creating packet: {}", e),
+    }
+}
+
+#[test]
+fn test_unknown_dialog_is_kept() {
+    let dialog = InstantMessageDialog::from_bytes(200);
+    assert_eq!(dialog, InstantMessageDialog::Unknown(200));
+    assert_eq!(dialog.to_bytes(), 200);
+}
+
+#[test]
+fn test_group_invitation_bucket() {
+    let role = uuid!("5c1c7b5e-7a55-4b1f-9d0b-0d8f3c1f0a11");
+    let mut bucket = 250i32.to_be_bytes().to_vec();
+    bucket.extend_from_slice(role.as_bytes());
+    let im = instant_message(InstantMessageDialog::GroupInvitation, bucket);
+    assert_eq!(
+        im.kind().unwrap(),
+        InstantMessageKind::GroupInvitation {
+            group_id: SENDER,
+            fee: 250,
+            role_id: role,
+        }
+    );
+}
+
+#[test]
+fn test_inventory_offer_bucket() {
+    let mut bucket = vec![7];
+    bucket.extend_from_slice(ITEM.as_bytes());
+    let im = instant_message(InstantMessageDialog::InventoryOffered, bucket);
+    assert_eq!(
+        im.kind().unwrap(),
+        InstantMessageKind::InventoryOffered {
+            asset_type: 7,
+            item_id: ITEM,
+        }
+    );
+
+    let im = instant_message(InstantMessageDialog::TaskInventoryOffered, vec![10]);
+    assert_eq!(
+        im.kind().unwrap(),
+        InstantMessageKind::TaskInventoryOffered { asset_type: 10 }
+    );
+}
+
+#[test]
+fn test_group_notice_bucket() {
+    let mut bucket = vec![1, 0];
+    bucket.extend_from_slice(ITEM.as_bytes());
+    bucket.extend_from_slice(b"Notecard\0");
+    let im = instant_message(InstantMessageDialog::GroupNotice, bucket);
+    assert_eq!(
+        im.kind().unwrap(),
+        InstantMessageKind::GroupNotice {
+            group_id: ITEM,
+            attachment: Some((0, "Notecard".to_string())),
+        }
+    );
+}
+
+#[test]
+fn test_short_bucket_is_an_error() {
+    let im = instant_message(InstantMessageDialog::GroupInvitation, vec![0, 0]);
+    assert!(im.kind().is_err());
+    let im = instant_message(InstantMessageDialog::FriendshipOffered, Vec::new());
+    assert_eq!(im.kind().unwrap(), InstantMessageKind::FriendshipOffered);
+}
+
+fn teleport_lure(im: &ImprovedInstantMessage) -> TeleportLure {
+    match im.kind().unwrap() {
+        InstantMessageKind::TeleportLure(lure) => lure,
+        kind => panic!("not a teleport lure: {:?}", kind),
+    }
+}
+
+#[test]
+fn test_teleport_lure_bucket() {
+    let im = instant_message(
+        InstantMessageDialog::RequestTeleport,
+        b"256000|256256|128|64|22|1|0|0|M\0".to_vec(),
+    );
+    let lure = teleport_lure(&im);
+    assert_eq!(lure.from_agent_id, SENDER);
+    assert_eq!(lure.from_agent_name, "Test User");
+    assert_eq!(lure.message, "hello");
+    assert_eq!(lure.lure_id, im.id);
+    assert!(!lure.godlike);
+    assert_eq!(
+        lure.destination,
+        Some(LureDestination {
+            region_handle: RegionHandle::from_grid(1000, 1001),
+            position: Vec3::new(128.0, 64.0, 22.0),
+            look_at: Vec3::X,
+            maturity: Some(AgentAccess::Mature),
+        })
+    );
+
+    // the destination is only a hint, so a lure without one can still be answered
+    for bucket in [Vec::new(), b"256000|256256|128".to_vec()] {
+        let im = instant_message(InstantMessageDialog::RequestTeleport, bucket);
+        assert_eq!(teleport_lure(&im).destination, None);
+    }
+}
+
+#[test]
+fn test_answering_a_teleport_lure() {
+    let agent_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4();
+    let im = instant_message(InstantMessageDialog::RequestTeleport, Vec::new());
+    let lure = teleport_lure(&im);
+
+    let request = lure.accept(agent_id, session_id);
+    assert_eq!(request.agent_id, agent_id);
+    assert_eq!(request.session_id, session_id);
+    assert_eq!(request.lure_id, im.id);
+    assert_eq!(request.teleport_flags, TELEPORT_FLAGS_VIA_LURE);
+    let packet = Packet::new_teleport_lure_request(request.clone());
+    assert!(packet.header.reliable);
+    match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+        PacketType::TeleportLureRequest(parsed) => assert_eq!(*parsed, request),
+        other => panic!("wrong packet type: {:?}", other),
+    }
+
+    let decline = lure.decline(agent_id, session_id);
+    assert_eq!(decline.agent_id, agent_id);
+    assert_eq!(decline.to_agent_id, SENDER);
+    assert_eq!(decline.dialog, InstantMessageDialog::DenyTeleport);
+    assert_eq!(decline.id, im.id);
+
+    let im = instant_message(InstantMessageDialog::from_bytes(25), Vec::new());
+    let lure = teleport_lure(&im);
+    assert!(lure.godlike);
+    assert_eq!(
+        lure.accept(agent_id, session_id).teleport_flags,
+        TELEPORT_FLAGS_VIA_GODLIKE_LURE
+    );
+}
+
+#[test]
+fn test_teleport_flags_match_the_protocol() {
+    assert_eq!(TELEPORT_FLAGS_VIA_LURE, 0x4);
+    assert_eq!(TELEPORT_FLAGS_VIA_GODLIKE_LURE, 0x100);
+}
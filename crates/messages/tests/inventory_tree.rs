@@ -0,0 +1,123 @@
+use metaverse_messages::{
+    capabilities::fetch_inventory::ItemMetadata,
+    login_system::{
+        inventory_tree::InventoryFolder,
+        login_response::{
+            InventoryRootValues, InventorySkeletonValues, InventoryType, LoginResponse,
+        },
+    },
+};
+use uuid::Uuid;
+
+const ROOT: Uuid = Uuid::from_u128(1);
+const TEXTURES: Uuid = Uuid::from_u128(2);
+const LANDMARKS: Uuid = Uuid::from_u128(3);
+const FAVORITES: Uuid = Uuid::from_u128(4);
+
+fn folder(id: Uuid, parent_id: Uuid, name: &str) -> InventorySkeletonValues {
+    InventorySkeletonValues {
+        folder_id: id.to_string(),
+        parent_id: parent_id.to_string(),
+        name: name.to_string(),
+        type_default: InventoryType::Unknown,
+        version: 1,
+    }
+}
+
+/// a skeleton in the order servers send it, with children before their parents
+fn skeleton() -> Vec<InventorySkeletonValues> {
+    vec![
+        folder(FAVORITES, LANDMARKS, "Favorites"),
+        folder(TEXTURES, ROOT, "Textures"),
+        folder(ROOT, Uuid::nil(), "My Inventory"),
+        folder(LANDMARKS, ROOT, "Landmarks"),
+    ]
+}
+
+fn item(item_id: u128, parent_id: Uuid) -> ItemMetadata {
+    ItemMetadata {
+        item_id: Uuid::from_u128(item_id),
+        parent_id,
+        asset_id: Uuid::nil(),
+        owner_id: Uuid::nil(),
+        name: "item".to_string(),
+        description: String::new(),
+        asset_type: 0,
+        inventory_type: 0,
+        flags: 0,
+        created_at: 0,
+    }
+}
+
+#[test]
+fn test_skeleton_is_assembled_into_a_tree() {
+    let tree = InventoryFolder::from_skeleton(&skeleton(), ROOT).unwrap();
+    assert_eq!(tree.name, "My Inventory");
+    assert_eq!(tree.folder_count(), 4);
+
+    // children are sorted by name
+    let names: Vec<&str> = tree
+        .children
+        .iter()
+        .map(|child| child.name.as_str())
+        .collect();
+    assert_eq!(names, ["Landmarks", "Textures"]);
+    assert_eq!(tree.children[0].children[0].id, FAVORITES);
+    assert!(tree.find(FAVORITES).unwrap().children.is_empty());
+    assert!(tree.find(Uuid::from_u128(99)).is_none());
+}
+
+#[test]
+fn test_unreachable_folders_are_left_out() {
+    let mut skeleton = skeleton();
+    // an orphan, a folder that is its own parent, and two folders that are each other's parent
+    skeleton.push(folder(Uuid::from_u128(10), Uuid::from_u128(99), "Orphan"));
+    skeleton.push(folder(Uuid::from_u128(11), Uuid::from_u128(11), "Loop"));
+    skeleton.push(folder(Uuid::from_u128(12), Uuid::from_u128(13), "Cycle A"));
+    skeleton.push(folder(Uuid::from_u128(13), Uuid::from_u128(12), "Cycle B"));
+    skeleton.push(InventorySkeletonValues {
+        folder_id: "not a uuid".to_string(),
+        ..folder(ROOT, ROOT, "Broken")
+    });
+
+    let tree = InventoryFolder::from_skeleton(&skeleton, ROOT).unwrap();
+    assert_eq!(tree.folder_count(), 4);
+    assert!(InventoryFolder::from_skeleton(&skeleton, Uuid::from_u128(99)).is_none());
+}
+
+#[test]
+fn test_items_are_added_to_their_folders() {
+    let mut tree = InventoryFolder::from_skeleton(&skeleton(), ROOT).unwrap();
+    let unplaced = tree.add_items(vec![
+        item(100, FAVORITES),
+        item(101, ROOT),
+        item(102, Uuid::from_u128(99)),
+    ]);
+    assert_eq!(unplaced, vec![item(102, Uuid::from_u128(99))]);
+    assert_eq!(tree.items, vec![item(101, ROOT)]);
+    assert_eq!(tree.find(FAVORITES).unwrap().items.len(), 1);
+
+    // fetching a folder again replaces its items instead of adding them twice
+    let mut renamed = item(100, FAVORITES);
+    renamed.name = "renamed".to_string();
+    assert!(tree.add_items(vec![renamed.clone()]).is_empty());
+    assert_eq!(tree.find(FAVORITES).unwrap().items, vec![renamed]);
+}
+
+#[test]
+fn test_login_response_trees() {
+    let response = LoginResponse {
+        inventory_root: Some(vec![InventoryRootValues {
+            folder_id: ROOT.to_string(),
+        }]),
+        inventory_skeleton: Some(skeleton()),
+        inventory_lib_root: Some(vec![InventoryRootValues {
+            folder_id: TEXTURES.to_string(),
+        }]),
+        inventory_skeleton_lib: Some(vec![folder(TEXTURES, Uuid::nil(), "Library")]),
+        ..Default::default()
+    };
+    assert_eq!(response.inventory_tree().unwrap().folder_count(), 4);
+    assert_eq!(response.library_tree().unwrap().name, "Library");
+    assert!(LoginResponse::default().inventory_tree().is_none());
+}
@@ -0,0 +1,17 @@
+use metaverse_messages::{kill_object::KillObject, packet::Packet, packet_types::PacketType};
+
+#[test]
+fn test_kill_object_tofrom_bytes() {
+    let object_ids = vec![12, 4000, 123456];
+    let packet = Packet::new_kill_object(KillObject {
+        object_ids: object_ids.clone(),
+    });
+    match Packet::from_bytes(&packet.to_bytes()) {
+        Ok(Packet {
+            body: PacketType::KillObject(data),
+            ..
+        }) => assert_eq!(data.object_ids, object_ids),
+        Ok(packet) => panic!("wrong packet type: {:?}", packet),
+        Err(e) => panic!("Error creating packet: {}", e),
+    }
+}
@@ -0,0 +1,51 @@
+use metaverse_messages::{
+    layer_data::{LayerData, LayerType},
+    packet::PacketData,
+};
+
+/// the body of a Land LayerData packet, laid out the way simulators send it
+fn land_layer() -> Vec<u8> {
+    let patches = [0x0b, 0x10, 0x22, 0x07, 0xe1, 0x3c, 0x00, 0x97];
+    let mut bytes = vec![b'L'];
+    bytes.extend_from_slice(&(4 + patches.len() as u16).to_le_bytes());
+    // stride 264, patch size 16, layer type Land
+    bytes.extend_from_slice(&[0x08, 0x01, 0x10, b'L']);
+    bytes.extend_from_slice(&patches);
+    bytes
+}
+
+#[test]
+fn test_land_layer_data() {
+    let layer = LayerData::from_bytes(&land_layer()).unwrap();
+    assert_eq!(layer.layer_type, LayerType::Land);
+    assert_eq!(layer.stride, 264);
+    assert_eq!(layer.patch_size, 16);
+    assert_eq!(
+        layer.layer_content,
+        vec![0x0b, 0x10, 0x22, 0x07, 0xe1, 0x3c, 0x00, 0x97]
+    );
+    assert_eq!(layer.to_bytes(), land_layer());
+}
+
+#[test]
+fn test_unknown_layer_type_is_an_error() {
+    let mut bytes = land_layer();
+    bytes[0] = b'?';
+    let error = LayerData::from_bytes(&bytes).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains("63"));
+}
+
+#[test]
+fn test_mismatched_patch_header_layer_type_is_still_read() {
+    let mut bytes = land_layer();
+    bytes[6] = b'W';
+    let layer = LayerData::from_bytes(&bytes).unwrap();
+    assert_eq!(layer.layer_type, LayerType::Land);
+}
+
+#[test]
+fn test_layer_data_block_shorter_than_its_length_is_an_error() {
+    let bytes = land_layer();
+    assert!(LayerData::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
@@ -0,0 +1,170 @@
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+
+use metaverse_messages::login_system::{
+    errors::Reason,
+    login::{login_to_any, login_with_options, Login, LoginHttpOptions},
+    simulator_login_protocol::SimulatorLoginProtocol,
+};
+
+fn login_data() -> SimulatorLoginProtocol {
+    SimulatorLoginProtocol::new(Login {
+        first: "default".to_string(),
+        last: "user".to_string(),
+        passwd: "password".to_string(),
+        start: "home".to_string(),
+        channel: "benthic".to_string(),
+        agree_to_tos: true,
+        read_critical: true,
+        url: String::new(),
+    })
+}
+
+#[test]
+fn test_login_to_any_without_urls_fails() {
+    let result = actix::System::new().block_on(login_to_any(login_data(), vec![]));
+    assert_eq!(result.unwrap_err().reason, Reason::Connection);
+}
+
+#[test]
+fn test_login_to_any_fails_when_every_server_is_down() {
+    // nothing listens on the discard port
+    let urls = vec![
+        "http://127.0.0.1:9".to_string(),
+        "http://127.0.0.1:9/mirror".to_string(),
+    ];
+    let result = actix::System::new().block_on(login_to_any(login_data(), urls));
+    assert_eq!(result.unwrap_err().reason, Reason::Connection);
+}
+
+/// answer one request with a 403, and return the URL to request and the request it received
+fn serve_forbidden() -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/login", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = vec![0u8; 65536];
+        let size = stream.read(&mut request).unwrap();
+        let _ = sender.send(String::from_utf8_lossy(&request[..size]).to_lowercase());
+        stream
+            .write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .unwrap();
+    });
+    (url, receiver)
+}
+
+/// a login response with just the fields LoginResponse requires
+const LOGIN_SUCCESS: &str = "<?xml version=\"1.0\"?><methodResponse><params><param><value><struct>\
+    <member><name>first_name</name><value><string>default</string></value></member>\
+    <member><name>last_name</name><value><string>user</string></value></member>\
+    <member><name>circuit_code</name><value><int>1234</int></value></member>\
+    <member><name>login</name><value><string>true</string></value></member>\
+    </struct></value></param></params></methodResponse>";
+
+/// the login response of a server that rejected the password
+const LOGIN_REJECTED: &str =
+    "<?xml version=\"1.0\"?><methodResponse><params><param><value><struct>\
+    <member><name>login</name><value><string>false</string></value></member>\
+    <member><name>reason</name><value><string>key</string></value></member>\
+    <member><name>message</name><value><string>wrong password</string></value></member>\
+    </struct></value></param></params></methodResponse>";
+
+/// answer one request with the login response, and return the URL to request
+fn serve_login(body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/login", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = vec![0u8; 65536];
+        let _ = stream.read(&mut request);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/xml\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+    url
+}
+
+#[test]
+fn test_login_to_any_falls_back_when_a_server_is_down() {
+    // the second mirror of the grid is never asked to log in
+    let unused = TcpListener::bind("127.0.0.1:0").unwrap();
+    unused.set_nonblocking(true).unwrap();
+    let urls = vec![
+        "http://127.0.0.1:9".to_string(),
+        serve_login(LOGIN_SUCCESS),
+        format!("http://{}/login", unused.local_addr().unwrap()),
+    ];
+
+    let response = actix::System::new()
+        .block_on(login_to_any(login_data(), urls))
+        .unwrap();
+    assert_eq!(response.first_name, "default");
+    assert_eq!(response.circuit_code, 1234);
+    assert!(unused.accept().is_err());
+}
+
+#[test]
+fn test_login_to_any_does_not_try_the_next_server_after_a_rejection() {
+    let unused = TcpListener::bind("127.0.0.1:0").unwrap();
+    unused.set_nonblocking(true).unwrap();
+    let urls = vec![
+        serve_login(LOGIN_REJECTED),
+        format!("http://{}/login", unused.local_addr().unwrap()),
+    ];
+
+    let result = actix::System::new().block_on(login_to_any(login_data(), urls));
+    assert_eq!(result.unwrap_err().reason, Reason::Key);
+    assert!(unused.accept().is_err());
+}
+
+#[test]
+fn test_login_sends_the_channel_as_the_user_agent() {
+    let (url, request) = serve_forbidden();
+    let data = login_data();
+    let expected = format!("user-agent: benthic/{}", data.version).to_lowercase();
+
+    let result = actix::System::new().block_on(login_to_any(data, vec![url]));
+    assert!(result.is_err());
+    let request = request.recv().unwrap();
+    assert!(request.contains(&expected), "{}", request);
+    assert!(request.contains("content-type: text/xml; charset=utf-8"));
+}
+
+#[test]
+fn test_login_sends_the_configured_headers() {
+    let (url, request) = serve_forbidden();
+    let options = LoginHttpOptions {
+        user_agent: Some("SecondLife/7.1.0".to_string()),
+        headers: vec![("X-Grid-Token".to_string(), "abc123".to_string())],
+    };
+
+    let result =
+        actix::System::new().block_on(login_with_options(login_data(), vec![url], &options));
+    assert!(result.is_err());
+    let request = request.recv().unwrap();
+    assert!(request.contains("user-agent: secondlife/7.1.0"));
+    assert!(request.contains("x-grid-token: abc123"));
+    assert!(!request.contains("user-agent: benthic"));
+}
+
+#[test]
+fn test_invalid_headers_fail_before_sending() {
+    let options = LoginHttpOptions {
+        user_agent: None,
+        headers: vec![("Bad Header".to_string(), "value".to_string())],
+    };
+    let result = actix::System::new().block_on(login_with_options(
+        login_data(),
+        vec!["http://127.0.0.1:9".to_string()],
+        &options,
+    ));
+    let error = result.unwrap_err();
+    assert_eq!(error.reason, Reason::Connection);
+    assert!(error.message.contains("invalid login header"));
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+
+use metaverse_messages::{
+    agent_set_appearance::AgentSetAppearance,
+    agent_throttle::{AgentThrottle, Throttles},
+    avatar_appearance::AvatarAppearance,
+    errors::{MailboxError, SessionError},
+    header::PacketFrequency,
+    layer_data::{LayerData, LayerType},
+    packet::{Packet, PacketCategory, PacketData},
+    packet_types::PacketType,
+    ui_events::UiEventTypes,
+    utils::texture_entry::TextureEntry,
+};
+use uuid::Uuid;
+use PacketCategory::*;
+use PacketFrequency::*;
+
+/// every packet that can arrive over UDP, and what happens to it
+fn udp_packets() -> Vec<(PacketFrequency, u16, PacketCategory)> {
+    vec![
+        (High, 1, Circuit),
+        (High, 2, Circuit),
+        (High, 4, Outgoing),
+        (High, 5, Outgoing),
+        (High, 10, Data),
+        (High, 11, UiEvent(UiEventTypes::LayerDataEvent)),
+        (High, 13, UiEvent(UiEventTypes::ObjectUpdateCompressedEvent)),
+        (High, 14, Data),
+        (High, 16, UiEvent(UiEventTypes::KillObjectEvent)),
+        (High, 20, UiEvent(UiEventTypes::AvatarAnimationEvent)),
+        (Medium, 1, Outgoing),
+        (Medium, 3, Outgoing),
+        (Medium, 6, UiEvent(UiEventTypes::CoarseLocationUpdateEvent)),
+        (Medium, 9, UiEvent(UiEventTypes::ObjectPropertiesEvent)),
+        (Medium, 17, UiEvent(UiEventTypes::ViewerEffectEvent)),
+        (Low, 3, Outgoing),
+        (Low, 8, Outgoing),
+        (Low, 9, Data),
+        (Low, 71, Outgoing),
+        (Low, 80, Outgoing),
+        (Low, 81, Outgoing),
+        (Low, 83, Outgoing),
+        (Low, 84, Outgoing),
+        (Low, 89, Outgoing),
+        (Low, 110, Outgoing),
+        (Low, 111, Outgoing),
+        (Low, 132, Outgoing),
+        (Low, 139, UiEvent(UiEventTypes::ChatFromSimulatorEvent)),
+        (Low, 140, UiEvent(UiEventTypes::SimStatsEvent)),
+        (Low, 141, Outgoing),
+        (Low, 142, UiEvent(UiEventTypes::RegionInfoEvent)),
+        (Low, 148, Circuit),
+        (Low, 149, Circuit),
+        (Low, 151, UiEvent(UiEventTypes::EnableSimulatorEvent)),
+        (Low, 152, UiEvent(UiEventTypes::DisableSimulatorEvent)),
+        (Low, 154, Outgoing),
+        (Low, 155, Outgoing),
+        (Low, 158, UiEvent(UiEventTypes::AvatarAppearanceEvent)),
+        (Low, 188, UiEvent(UiEventTypes::ScriptQuestionEvent)),
+        (Low, 190, UiEvent(UiEventTypes::ScriptDialogEvent)),
+        (Low, 191, Outgoing),
+        (Low, 235, Outgoing),
+        (Low, 236, UiEvent(UiEventTypes::UUIDNameReplyEvent)),
+        (Low, 249, Outgoing),
+        (Low, 250, UiEvent(UiEventTypes::MovementCompleteEvent)),
+        (Low, 252, Outgoing),
+        (Low, 254, UiEvent(UiEventTypes::ImprovedInstantMessageEvent)),
+        (Low, 313, Outgoing),
+        (Low, 314, UiEvent(UiEventTypes::BalanceUpdateEvent)),
+        (Low, 322, Data),
+        (Low, 323, Data),
+        (Fixed, 251, Circuit),
+    ]
+}
+
+/// a packet with an empty body. Zeros are a valid body for most packets. The rest have fields
+/// that can't be empty, like LayerData's layer type or a TextureEntry.
+fn packet(frequency: PacketFrequency, id: u16) -> Packet {
+    let body = match (frequency, id) {
+        (Low, 81) => AgentThrottle {
+            agent_id: Uuid::nil(),
+            session_id: Uuid::nil(),
+            circuit_code: 0,
+            gen_counter: 0,
+            throttles: Throttles::default(),
+        }
+        .to_bytes(),
+        (Low, 84) => AgentSetAppearance::new(
+            Uuid::nil(),
+            Uuid::nil(),
+            Vec::new(),
+            TextureEntry::new(Uuid::nil(), BTreeMap::new()),
+        )
+        .to_bytes(),
+        (Low, 158) => AvatarAppearance {
+            agent_id: Uuid::nil(),
+            is_trial: false,
+            texture_entry: TextureEntry::new(Uuid::nil(), BTreeMap::new()),
+            visual_params: Vec::new(),
+            appearance_data: Vec::new(),
+            hover_height: Vec::new(),
+            attachments: Vec::new(),
+        }
+        .to_bytes(),
+        (High, 11) => LayerData {
+            layer_type: LayerType::Land,
+            stride: 264,
+            patch_size: 16,
+            layer_content: Vec::new(),
+        }
+        .to_bytes(),
+        _ => vec![0u8; 2048],
+    };
+    let body = PacketType::from_id(id, frequency, &body)
+        .unwrap_or_else(|e| panic!("{} {} did not parse: {}", frequency, id, e));
+    Packet::new(id, frequency, body)
+}
+
+#[test]
+fn test_every_udp_packet_is_classified() {
+    for (frequency, id, category) in udp_packets() {
+        let packet = packet(frequency, id);
+        assert_eq!(
+            packet.classify(),
+            category,
+            "{} {} is {:?}",
+            frequency,
+            id,
+            packet.body
+        );
+    }
+}
+
+#[test]
+fn test_every_event_reaches_the_ui() {
+    for (frequency, id, _) in udp_packets() {
+        let packet = packet(frequency, id);
+        if let UiEvent(event) = packet.classify() {
+            assert_ne!(
+                event,
+                UiEventTypes::None,
+                "{:?} has no UI event",
+                packet.body
+            );
+            // the UI has to be able to read back what the mailbox sends it
+            assert!(
+                event
+                    .packet_type_from_bytes(&packet.body.to_bytes())
+                    .is_some(),
+                "the UI can't decode {}",
+                event
+            );
+        }
+    }
+}
+
+#[test]
+fn test_only_events_have_a_ui_event() {
+    for (frequency, id, category) in udp_packets() {
+        let packet = packet(frequency, id);
+        if !matches!(category, UiEvent(_)) {
+            assert_eq!(
+                packet.body.ui_event(),
+                UiEventTypes::None,
+                "{:?}",
+                packet.body
+            );
+        }
+    }
+}
+
+#[test]
+fn test_errors_are_classified() {
+    let error = SessionError::Mailbox(MailboxError {
+        message: "failed".to_string(),
+    });
+    let packet = Packet::new(0, Fixed, PacketType::Error(Box::new(error)));
+    assert_eq!(packet.classify(), Error);
+}
+
+#[test]
+fn test_is_reliable() {
+    let packet = packet(Low, 152);
+    assert!(!packet.is_reliable());
+    assert!(packet.reliable(true).is_reliable());
+}
@@ -0,0 +1,83 @@
+use metaverse_messages::{
+    money_balance_reply::{MoneyBalanceReply, TransactionInfo},
+    money_balance_request::MoneyBalanceRequest,
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    ui_events::UiEventTypes,
+};
+use uuid::{uuid, Uuid};
+
+const AGENT_ID: Uuid = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+const TRANSACTION_ID: Uuid = uuid!("224ecaea-372d-4d31-8b64-4805966418e5");
+
+fn reply(transaction_info: Option<TransactionInfo>) -> MoneyBalanceReply {
+    MoneyBalanceReply {
+        agent_id: AGENT_ID,
+        transaction_id: TRANSACTION_ID,
+        transaction_success: true,
+        money_balance: 1250,
+        square_meters_credit: 512,
+        square_meters_committed: 0,
+        description: "".to_string(),
+        transaction_info,
+    }
+}
+
+#[test]
+fn test_money_balance_request_round_trip() {
+    let request = MoneyBalanceRequest {
+        agent_id: AGENT_ID,
+        session_id: uuid!("89556747-24cb-43ed-920b-47caed15465f"),
+        transaction_id: TRANSACTION_ID,
+    };
+    let packet =
+        Packet::from_bytes(&Packet::new_money_balance_request(request).to_bytes()).unwrap();
+    let PacketType::MoneyBalanceRequest(parsed) = packet.body else {
+        panic!("expected MoneyBalanceRequest, got {:?}", packet.body);
+    };
+    assert_eq!(parsed.agent_id, AGENT_ID);
+    assert_eq!(parsed.transaction_id, TRANSACTION_ID);
+}
+
+#[test]
+fn test_money_balance_reply_without_transaction_info() {
+    let packet =
+        Packet::from_bytes(&Packet::new_money_balance_reply(reply(None)).to_bytes()).unwrap();
+    let PacketType::MoneyBalanceReply(parsed) = packet.body else {
+        panic!("expected MoneyBalanceReply, got {:?}", packet.body);
+    };
+    assert!(parsed.transaction_success);
+    assert_eq!(parsed.money_balance, 1250);
+    assert_eq!(parsed.square_meters_credit, 512);
+    assert!(parsed.transaction_info.is_none());
+}
+
+#[test]
+fn test_money_balance_reply_with_transaction_info() {
+    let payment = TransactionInfo {
+        transaction_type: 5008,
+        source_id: Uuid::from_u128(1),
+        is_source_group: false,
+        dest_id: AGENT_ID,
+        is_dest_group: false,
+        amount: -25,
+        item_description: "a box".to_string(),
+    };
+    let parsed = MoneyBalanceReply::from_bytes(&reply(Some(payment)).to_bytes()).unwrap();
+    let info = parsed.transaction_info.unwrap();
+    assert_eq!(info.transaction_type, 5008);
+    assert_eq!(info.source_id, Uuid::from_u128(1));
+    assert_eq!(info.amount, -25);
+    assert_eq!(info.item_description, "a box");
+}
+
+#[test]
+fn test_money_balance_reply_is_a_balance_update_event() {
+    let packet = Packet::new_money_balance_reply(reply(None));
+    let event = packet.body.ui_event();
+    assert!(matches!(event, UiEventTypes::BalanceUpdateEvent));
+    match event.packet_type_from_bytes(&packet.body.to_bytes()) {
+        Some(PacketType::MoneyBalanceReply(parsed)) => assert_eq!(parsed.money_balance, 1250),
+        other => panic!("expected MoneyBalanceReply, got {:?}", other),
+    }
+}
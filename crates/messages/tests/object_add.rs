@@ -0,0 +1,93 @@
+use glam::{Quat, Vec3};
+use metaverse_messages::{
+    object_add::{Material, ObjectAdd, PCode, PrimShape},
+    object_delete::ObjectDelete,
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+};
+use uuid::{uuid, Uuid};
+
+const AGENT_ID: Uuid = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+const SESSION_ID: Uuid = uuid!("89556747-24cb-43ed-920b-47caed15465f");
+
+#[test]
+fn test_object_add_round_trip() {
+    let rotation = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    let mut object_add = ObjectAdd::new(
+        AGENT_ID,
+        SESSION_ID,
+        PCode::Primitive,
+        Vec3::new(128.0, 64.0, 22.5),
+        Vec3::new(0.5, 1.0, 2.0),
+        rotation,
+    );
+    object_add.material = Material::Glass;
+    object_add.shape.profile_hollow = 25000;
+    object_add.shape.path_twist = -90;
+
+    let bytes = object_add.to_bytes();
+    assert_eq!(bytes.len(), 144);
+
+    let packet = Packet::from_bytes(&Packet::new_object_add(object_add).to_bytes()).unwrap();
+    assert!(packet.header.reliable);
+    let PacketType::ObjectAdd(parsed) = packet.body else {
+        panic!("expected ObjectAdd, got {:?}", packet.body);
+    };
+    assert_eq!(parsed.agent_id, AGENT_ID);
+    assert_eq!(parsed.session_id, SESSION_ID);
+    assert_eq!(parsed.pcode, PCode::Primitive);
+    assert_eq!(parsed.material, Material::Glass);
+    assert_eq!(parsed.shape.profile_hollow, 25000);
+    assert_eq!(parsed.shape.path_twist, -90);
+    assert!(parsed.bypass_raycast);
+    assert_eq!(parsed.ray_end, Vec3::new(128.0, 64.0, 22.5));
+    assert_eq!(parsed.scale, Vec3::new(0.5, 1.0, 2.0));
+    assert!(parsed.rotation.abs_diff_eq(rotation, 1e-6));
+}
+
+#[test]
+fn test_object_add_defaults_to_a_box() {
+    let object_add = ObjectAdd::new(
+        AGENT_ID,
+        SESSION_ID,
+        PCode::Primitive,
+        Vec3::ZERO,
+        Vec3::ONE,
+        Quat::IDENTITY,
+    );
+    assert_eq!(object_add.shape, PrimShape::default());
+    // pcode 9, then the material
+    assert_eq!(&object_add.to_bytes()[48..50], &[9, 3]);
+}
+
+#[test]
+fn test_rotation_with_negative_w_is_sent_with_positive_w() {
+    let rotation = Quat::from_xyzw(0.0, 0.0, 0.6, -0.8);
+    let object_add = ObjectAdd::new(
+        AGENT_ID,
+        SESSION_ID,
+        PCode::Primitive,
+        Vec3::ZERO,
+        Vec3::ONE,
+        rotation,
+    );
+    let parsed = ObjectAdd::from_bytes(&object_add.to_bytes()).unwrap();
+    assert!(parsed.rotation.abs_diff_eq(-rotation, 1e-6));
+}
+
+#[test]
+fn test_object_delete_round_trip() {
+    let object_delete = ObjectDelete {
+        agent_id: AGENT_ID,
+        session_id: SESSION_ID,
+        force: false,
+        local_ids: vec![42, 43],
+    };
+    let packet = Packet::from_bytes(&Packet::new_object_delete(object_delete).to_bytes()).unwrap();
+    let PacketType::ObjectDelete(parsed) = packet.body else {
+        panic!("expected ObjectDelete, got {:?}", packet.body);
+    };
+    assert_eq!(parsed.agent_id, AGENT_ID);
+    assert!(!parsed.force);
+    assert_eq!(parsed.local_ids, vec![42, 43]);
+}
@@ -0,0 +1,89 @@
+use metaverse_messages::{
+    object_properties::{ObjectProperties, ObjectPropertiesData, PERM_COPY, PERM_MOVE},
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    ui_events::UiEventTypes,
+};
+use uuid::{uuid, Uuid};
+
+fn object_properties() -> ObjectProperties {
+    ObjectProperties {
+        objects: vec![ObjectPropertiesData {
+            object_id: uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+            creator_id: uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+            owner_id: uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+            group_id: Uuid::nil(),
+            creation_date: 1700000000000000,
+            base_mask: 0x7fffffff,
+            owner_mask: PERM_COPY | PERM_MOVE,
+            group_mask: 0,
+            everyone_mask: 0,
+            next_owner_mask: PERM_MOVE,
+            ownership_cost: 0,
+            sale_type: 0,
+            sale_price: 10,
+            aggregate_perms: 0,
+            aggregate_perm_textures: 0,
+            aggregate_perm_textures_owner: 0,
+            category: 0,
+            inventory_serial: 3,
+            item_id: Uuid::nil(),
+            folder_id: Uuid::nil(),
+            from_task_id: Uuid::nil(),
+            last_owner_id: Uuid::nil(),
+            name: "Door".to_string(),
+            description: "opens when touched".to_string(),
+            touch_name: "Open".to_string(),
+            sit_name: String::new(),
+            texture_ids: vec![uuid!("89556747-24cb-43ed-920b-47caed15465f")],
+        }],
+    }
+}
+
+#[test]
+fn test_object_properties_tofrom_bytes() {
+    let packet = Packet::new_object_properties(object_properties());
+    match Packet::from_bytes(&packet.to_bytes()) {
+        Ok(Packet {
+            body: PacketType::ObjectProperties(data),
+            ..
+        }) => {
+            assert_eq!(data.to_bytes(), object_properties().to_bytes());
+            let object = &data.objects[0];
+            assert_eq!(object.name, "Door");
+            assert_eq!(object.description, "opens when touched");
+            assert_eq!(object.touch_name, "Open");
+            assert_eq!(object.sit_name, "");
+            assert_eq!(object.texture_ids.len(), 1);
+        }
+        Ok(packet) => panic!("wrong packet type: {:?}", packet),
+        Err(e) => panic!("Error creating packet: {}", e),
+    }
+}
+
+#[test]
+fn test_object_permissions() {
+    let object = &object_properties().objects[0];
+    let owner = object.owner_permissions();
+    assert!(owner.copy);
+    assert!(owner.movable);
+    assert!(!owner.modify);
+    assert!(!owner.transfer);
+    let next_owner = object.next_owner_permissions();
+    assert!(!next_owner.copy);
+    assert!(next_owner.movable);
+    assert!(!object.everyone_permissions().copy);
+}
+
+#[test]
+fn test_object_properties_is_sent_to_the_ui() {
+    let packet = Packet::new_object_properties(object_properties());
+    let event = packet.body.ui_event();
+    assert!(matches!(event, UiEventTypes::ObjectPropertiesEvent));
+    match event.packet_type_from_bytes(&packet.body.to_bytes()) {
+        Some(PacketType::ObjectProperties(data)) => {
+            assert_eq!(data.objects[0].name, "Door")
+        }
+        other => panic!("wrong packet type: {:?}", other),
+    }
+}
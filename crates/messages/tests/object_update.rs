@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use glam::{Quat, Vec3};
+use metaverse_messages::{
+    object_add::{Material, PCode, PrimShape},
+    object_update_cached::{CachedObject, ObjectUpdateCached},
+    object_update_compressed::{
+        AttachedSound, CompressedObject, ExtraParam, FloatingText, ObjectUpdateCompressed,
+    },
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    ui_events::UiEventTypes,
+    utils::{region_handle::RegionHandle, texture_entry::TextureEntry},
+};
+use uuid::{uuid, Uuid};
+
+const OBJECT_ID: Uuid = uuid!("1c0f2ae3-0b0e-4d38-9d38-6c4f1bfc6e05");
+const OWNER_ID: Uuid = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+
+fn compressed_object() -> CompressedObject {
+    CompressedObject {
+        update_flags: 0x1000,
+        full_id: OBJECT_ID,
+        local_id: 42,
+        pcode: PCode::Primitive,
+        state: 0,
+        crc: 0xdeadbeef,
+        material: Material::Wood,
+        click_action: 0,
+        scale: Vec3::new(0.5, 0.5, 0.5),
+        position: Vec3::new(128.0, 64.0, 22.5),
+        rotation: Quat::IDENTITY,
+        owner_id: OWNER_ID,
+        angular_velocity: None,
+        parent_id: None,
+        tree_species: None,
+        scratch_pad: None,
+        text: None,
+        media_url: None,
+        particle_system: None,
+        extra_params: Vec::new(),
+        sound: None,
+        name_values: None,
+        shape: PrimShape::default(),
+        texture_entry: Some(TextureEntry::new(Uuid::from_u128(7), BTreeMap::new())),
+        texture_animation: None,
+        particles_new: None,
+    }
+}
+
+fn update(objects: Vec<CompressedObject>) -> ObjectUpdateCompressed {
+    ObjectUpdateCompressed {
+        region_handle: RegionHandle::from_grid(1000, 1000),
+        time_dilation: 65535,
+        objects,
+    }
+}
+
+#[test]
+fn test_object_update_compressed_round_trip() {
+    let packet = Packet::from_bytes(
+        &Packet::new_object_update_compressed(update(vec![compressed_object()])).to_bytes(),
+    )
+    .unwrap();
+    let PacketType::ObjectUpdateCompressed(parsed) = packet.body else {
+        panic!("expected ObjectUpdateCompressed, got {:?}", packet.body);
+    };
+    assert_eq!(parsed.region_handle, RegionHandle::from_grid(1000, 1000));
+    let object = &parsed.objects[0];
+    assert_eq!(object.update_flags, 0x1000);
+    assert_eq!(object.full_id, OBJECT_ID);
+    assert_eq!(object.local_id, 42);
+    assert_eq!(object.crc, 0xdeadbeef);
+    assert_eq!(object.position, Vec3::new(128.0, 64.0, 22.5));
+    assert_eq!(object.shape, PrimShape::default());
+    assert_eq!(
+        object.texture_entry.as_ref().unwrap().texture(0),
+        Uuid::from_u128(7)
+    );
+    assert!(object.text.is_none());
+}
+
+#[test]
+fn test_object_update_compressed_optional_fields() {
+    let mut object = compressed_object();
+    object.rotation = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    object.angular_velocity = Some(Vec3::new(0.0, 0.0, 1.0));
+    object.parent_id = Some(41);
+    object.scratch_pad = Some(vec![1, 2, 3]);
+    object.text = Some(FloatingText {
+        text: "for sale".to_string(),
+        color: [255, 255, 255, 255],
+    });
+    object.media_url = Some("https://example.com".to_string());
+    object.particle_system = Some(vec![9; 86]);
+    object.extra_params = vec![ExtraParam {
+        param_type: 0x20,
+        data: vec![0; 16],
+    }];
+    object.sound = Some(AttachedSound {
+        sound_id: Uuid::from_u128(8),
+        gain: 0.5,
+        flags: 1,
+        radius: 20.0,
+    });
+    object.name_values = Some("AttachItemID STRING RW SV 1234".to_string());
+    object.shape.profile_curve = 5;
+    object.shape.path_twist = -90;
+    object.texture_animation = Some(vec![1, 0, 4, 4]);
+    object.particles_new = Some(vec![5, 6, 7]);
+
+    let parsed = ObjectUpdateCompressed::from_bytes(&update(vec![object]).to_bytes()).unwrap();
+    let object = &parsed.objects[0];
+    assert!(object
+        .rotation
+        .abs_diff_eq(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2), 1e-6));
+    assert_eq!(object.angular_velocity, Some(Vec3::new(0.0, 0.0, 1.0)));
+    assert_eq!(object.parent_id, Some(41));
+    assert_eq!(object.scratch_pad, Some(vec![1, 2, 3]));
+    assert_eq!(object.text.as_ref().unwrap().text, "for sale");
+    assert_eq!(object.media_url.as_deref(), Some("https://example.com"));
+    assert_eq!(object.particle_system, Some(vec![9; 86]));
+    assert_eq!(object.extra_params[0].param_type, 0x20);
+    assert_eq!(object.sound.as_ref().unwrap().sound_id, Uuid::from_u128(8));
+    assert_eq!(
+        object.name_values.as_deref(),
+        Some("AttachItemID STRING RW SV 1234")
+    );
+    assert_eq!(object.shape.profile_curve, 5);
+    assert_eq!(object.shape.path_twist, -90);
+    assert_eq!(object.texture_animation, Some(vec![1, 0, 4, 4]));
+    assert_eq!(object.particles_new, Some(vec![5, 6, 7]));
+}
+
+#[test]
+fn test_tree_species_replaces_the_scratch_pad() {
+    let mut object = compressed_object();
+    object.pcode = PCode::Tree;
+    object.tree_species = Some(3);
+    object.texture_entry = None;
+
+    let parsed = ObjectUpdateCompressed::from_bytes(&update(vec![object]).to_bytes()).unwrap();
+    let object = &parsed.objects[0];
+    assert_eq!(object.pcode, PCode::Tree);
+    assert_eq!(object.tree_species, Some(3));
+    assert!(object.scratch_pad.is_none());
+    assert!(object.texture_entry.is_none());
+}
+
+#[test]
+fn test_truncated_object_update_compressed_is_an_error() {
+    let bytes = update(vec![compressed_object()]).to_bytes();
+    assert!(ObjectUpdateCompressed::from_bytes(&bytes[..bytes.len() - 10]).is_err());
+}
+
+#[test]
+fn test_object_update_compressed_is_an_event() {
+    let packet = Packet::new_object_update_compressed(update(vec![compressed_object()]));
+    let event = packet.body.ui_event();
+    assert!(matches!(event, UiEventTypes::ObjectUpdateCompressedEvent));
+    match event.packet_type_from_bytes(&packet.body.to_bytes()) {
+        Some(PacketType::ObjectUpdateCompressed(parsed)) => {
+            assert_eq!(parsed.objects[0].local_id, 42)
+        }
+        other => panic!("expected ObjectUpdateCompressed, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_object_update_cached_round_trip() {
+    let cached = ObjectUpdateCached {
+        region_handle: RegionHandle::from_grid(1000, 1000),
+        time_dilation: 65535,
+        objects: vec![
+            CachedObject {
+                local_id: 42,
+                crc: 0xdeadbeef,
+                update_flags: 0x1000,
+            },
+            CachedObject {
+                local_id: 43,
+                crc: 7,
+                update_flags: 0,
+            },
+        ],
+    };
+    let packet =
+        Packet::from_bytes(&Packet::new_object_update_cached(cached.clone()).to_bytes()).unwrap();
+    let PacketType::ObjectUpdateCached(parsed) = packet.body else {
+        panic!("expected ObjectUpdateCached, got {:?}", packet.body);
+    };
+    assert_eq!(parsed.objects, cached.objects);
+}
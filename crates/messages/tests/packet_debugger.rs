@@ -17,9 +17,14 @@ fn test_packet_decoder() {
         print!("Enter packet data as hex stream: ");
         io::stdout().flush().expect("Failed to flush stdout");
 
-        io::stdin()
+        // stop when stdin closes, so this doesn't spin forever under cargo test
+        if io::stdin()
             .read_line(&mut input)
-            .expect("Failed to read line");
+            .expect("Failed to read line")
+            == 0
+        {
+            break;
+        }
 
         // Remove any trailing newline characters
         let input = input.trim();
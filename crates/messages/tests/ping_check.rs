@@ -0,0 +1,40 @@
+use hex::FromHex;
+use metaverse_messages::{
+    complete_ping_check::CompletePingCheck, packet::Packet, packet_types::PacketType,
+    start_ping_check::StartPingCheck,
+};
+
+#[test]
+fn test_start_ping_check_wire_format() {
+    let packet = Packet::new_start_ping_check(StartPingCheck {
+        ping_id: 5,
+        oldest_unacked: 0x0102,
+    });
+    // flags, sequence number, extra header byte, id 1, ping id, oldest unacked (little-endian)
+    assert_eq!(
+        packet.to_bytes(),
+        Vec::from_hex("000000000000010502010000").unwrap()
+    );
+
+    match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+        PacketType::StartPingCheck(data) => {
+            assert_eq!(data.ping_id, 5);
+            assert_eq!(data.oldest_unacked, 0x0102);
+        }
+        other => panic!("expected StartPingCheck, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_complete_ping_check_wire_format() {
+    let packet = Packet::new_complete_ping_check(CompletePingCheck { ping_id: 5 });
+    assert_eq!(
+        packet.to_bytes(),
+        Vec::from_hex("0000000000000205").unwrap()
+    );
+
+    match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+        PacketType::CompletePingCheck(data) => assert_eq!(data.ping_id, 5),
+        other => panic!("expected CompletePingCheck, got {:?}", other),
+    }
+}
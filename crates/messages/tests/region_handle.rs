@@ -0,0 +1,54 @@
+use glam::{DVec3, Vec3};
+use metaverse_messages::{
+    login_system::login_response::HomeValues,
+    utils::region_handle::{RegionHandle, REGION_WIDTH},
+};
+
+#[test]
+fn test_region_handle_packs_the_global_corner() {
+    let handle = RegionHandle::from_grid(1000, 1001);
+    assert_eq!(handle.global_x(), 256000);
+    assert_eq!(handle.global_y(), 256256);
+    assert_eq!(handle.grid(), (1000, 1001));
+    assert_eq!(u64::from(handle), (256000 << 32) | 256256);
+    assert_eq!(
+        RegionHandle::from(1099511628032000),
+        RegionHandle::from_grid(1000, 1000)
+    );
+}
+
+#[test]
+fn test_region_handle_converts_positions() {
+    let handle = RegionHandle::from_grid(1000, 1000);
+    let global = handle.to_global(Vec3::new(128.5, 10.0, 22.0));
+    assert_eq!(global, DVec3::new(256128.5, 256010.0, 22.0));
+    assert_eq!(handle.to_local(global), Vec3::new(128.5, 10.0, 22.0));
+
+    assert_eq!(RegionHandle::containing(global), handle);
+    let neighbour = DVec3::new(256000.0 + REGION_WIDTH as f64, 255999.0, 0.0);
+    assert_eq!(
+        RegionHandle::containing(neighbour),
+        RegionHandle::from_grid(1001, 999)
+    );
+    assert_eq!(handle.to_local(neighbour), Vec3::new(256.0, -1.0, 0.0));
+}
+
+#[test]
+fn test_home_region_handle_is_parsed() {
+    let home: HomeValues = xmlrpc_benthic::Value::String(
+        "{'region_handle':[r256000,r256512], 'position':[r50,r100,r200], 'look_at':[r1,r0,r0]}"
+            .to_string(),
+    )
+    .into();
+    assert_eq!(
+        home.region_handle,
+        Some(RegionHandle::from_global(256000, 256512))
+    );
+    assert_eq!(home.region_handle.unwrap().grid(), (1000, 1002));
+
+    let home: HomeValues = xmlrpc_benthic::Value::String(
+        "{'region_handle':[r256000], 'position':[r50,r100,r200]}".to_string(),
+    )
+    .into();
+    assert_eq!(home.region_handle, None);
+}
@@ -0,0 +1,115 @@
+use metaverse_messages::{
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    region_info::{RegionInfo, RegionProductInfo},
+    request_region_info::RequestRegionInfo,
+    ui_events::UiEventTypes,
+    utils::agent_access::AgentAccess,
+};
+use uuid::{uuid, Uuid};
+
+const AGENT_ID: Uuid = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+const SESSION_ID: Uuid = uuid!("89556747-24cb-43ed-920b-47caed15465f");
+
+// allow_damage, skip_scripts and no_fly
+const REGION_FLAGS: u32 = (1 << 0) | (1 << 13) | (1 << 19);
+
+fn region_info(product: Option<RegionProductInfo>) -> RegionInfo {
+    RegionInfo {
+        agent_id: AGENT_ID,
+        session_id: SESSION_ID,
+        sim_name: "Da Boom".to_string(),
+        estate_id: 1,
+        parent_estate_id: 1,
+        region_flags: REGION_FLAGS,
+        sim_access: AgentAccess::Mature,
+        max_agents: 40,
+        billable_factor: 1.0,
+        object_bonus_factor: 1.5,
+        water_height: 20.0,
+        terrain_raise_limit: 4.0,
+        terrain_lower_limit: -4.0,
+        price_per_meter: 1,
+        redirect_grid_x: 0,
+        redirect_grid_y: 0,
+        use_estate_sun: true,
+        sun_hour: 6.0,
+        product,
+        region_flags_extended: Vec::new(),
+    }
+}
+
+#[test]
+fn test_request_region_info_round_trip() {
+    let request = RequestRegionInfo {
+        agent_id: AGENT_ID,
+        session_id: SESSION_ID,
+    };
+    let packet = Packet::from_bytes(&Packet::new_request_region_info(request).to_bytes()).unwrap();
+    let PacketType::RequestRegionInfo(parsed) = packet.body else {
+        panic!("expected RequestRegionInfo, got {:?}", packet.body);
+    };
+    assert_eq!(parsed.agent_id, AGENT_ID);
+    assert_eq!(parsed.session_id, SESSION_ID);
+}
+
+#[test]
+fn test_region_info_without_product() {
+    let packet = Packet::new_region_info(region_info(None));
+    assert!(packet.header.zerocoded);
+    let PacketType::RegionInfo(info) = packet.body else {
+        panic!("expected RegionInfo, got {:?}", packet.body);
+    };
+    let parsed = RegionInfo::from_bytes(&info.to_bytes()).unwrap();
+    assert_eq!(parsed.sim_name, "Da Boom");
+    assert_eq!(parsed.sim_access, AgentAccess::Mature);
+    assert_eq!(parsed.max_agents, 40);
+    assert_eq!(parsed.object_bonus_factor, 1.5);
+    assert_eq!(parsed.water_height, 20.0);
+    assert_eq!(parsed.terrain_lower_limit, -4.0);
+    assert!(parsed.use_estate_sun);
+    assert!(parsed.product.is_none());
+    assert!(parsed.region_flags_extended.is_empty());
+}
+
+#[test]
+fn test_region_info_with_product_and_extended_flags() {
+    let mut info = region_info(Some(RegionProductInfo {
+        product_sku: "023".to_string(),
+        product_name: "Estate / Full Region".to_string(),
+        max_agents_32: 100,
+        hard_max_agents: 100,
+        hard_max_objects: 20000,
+    }));
+    info.region_flags_extended = vec![REGION_FLAGS as u64 | (1 << 40)];
+
+    let parsed = RegionInfo::from_bytes(&info.to_bytes()).unwrap();
+    let product = parsed.product.unwrap();
+    assert_eq!(product.product_name, "Estate / Full Region");
+    assert_eq!(product.max_agents_32, 100);
+    assert_eq!(product.hard_max_objects, 20000);
+    assert_eq!(
+        parsed.region_flags_extended,
+        vec![REGION_FLAGS as u64 | (1 << 40)]
+    );
+}
+
+#[test]
+fn test_region_info_flags() {
+    let flags = region_info(None).flags();
+    assert!(flags.allow_damage);
+    assert!(flags.skip_scripts);
+    assert!(flags.no_fly);
+    assert!(!flags.block_terraform);
+}
+
+#[test]
+fn test_region_info_is_a_region_info_event() {
+    let packet = Packet::new_region_info(region_info(None));
+    let event = packet.body.ui_event();
+    assert!(matches!(event, UiEventTypes::RegionInfoEvent));
+    match event.packet_type_from_bytes(&packet.body.to_bytes()) {
+        Some(PacketType::RegionInfo(parsed)) => assert_eq!(parsed.sim_name, "Da Boom"),
+        other => panic!("expected RegionInfo, got {:?}", other),
+    }
+}
@@ -0,0 +1,269 @@
+mod common;
+
+use glam::Vec3;
+use metaverse_messages::{
+    agent_height_width::AgentHeightWidth,
+    agent_pause::AgentPause,
+    agent_resume::AgentResume,
+    agent_throttle::{AgentThrottle, Throttles},
+    chat_from_simulator::{Audible, ChatFromSimulator, ChatType, SourceType},
+    chat_from_viewer::{ChatFromViewer, ClientChatType},
+    circuit_code::CircuitCodeData,
+    complete_agent_movement::CompleteAgentMovementData,
+    complete_ping_check::CompletePingCheck,
+    friend_presence::FriendPresence,
+    image_data::ImageData,
+    image_packet::ImagePacket,
+    kill_object::KillObject,
+    layer_data::{LayerData, LayerType},
+    logout_request::LogoutRequest,
+    object_deselect::ObjectDeselect,
+    object_select::ObjectSelect,
+    offline_notification::OfflineNotification,
+    online_notification::OnlineNotification,
+    packet_ack::PacketAck,
+    request_image::{ImageRequest, RequestImage},
+    request_multiple_objects::RequestMultipleObjects,
+    start_ping_check::StartPingCheck,
+    uuid_name_reply::{UUIDNameBlock, UUIDNameReply},
+    uuid_name_request::UUIDNameRequest,
+};
+use uuid::uuid;
+
+#[test]
+fn test_chat_from_simulator_roundtrip() {
+    for (from_name, message) in [("", ""), ("Test User", "hello"), ("Ünïcode", "🙂 chat")] {
+        let parsed = assert_packet_roundtrip!(
+            ChatFromSimulator,
+            ChatFromSimulator {
+                from_name: from_name.to_string(),
+                source_id: uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+                owner_id: uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+                source_type: SourceType::Agent,
+                chat_type: ChatType::Shout,
+                audible: Audible::Fully,
+                position: Vec3::new(128.0, 64.5, 22.25),
+                message: message.to_string(),
+            }
+        );
+        assert_eq!(parsed.from_name, from_name);
+        assert_eq!(parsed.message, message);
+    }
+}
+
+#[test]
+fn test_layer_data_roundtrip() {
+    for layer_content in [vec![], vec![0, 1, 2, 3, 255]] {
+        let parsed = assert_packet_roundtrip!(
+            LayerData,
+            LayerData {
+                stride: 264,
+                patch_size: 16,
+                layer_type: LayerType::Land,
+                layer_content: layer_content.clone(),
+            }
+        );
+        assert_eq!(parsed.layer_content, layer_content);
+    }
+}
+
+#[test]
+fn test_circuit_code_roundtrip() {
+    for code in [0, 1, u32::MAX] {
+        let parsed = assert_packet_roundtrip!(
+            CircuitCodeData,
+            CircuitCodeData {
+                code,
+                session_id: uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+                id: uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+            }
+        );
+        assert_eq!(parsed.code, code);
+    }
+}
+
+#[test]
+fn test_packet_data_roundtrip() {
+    let agent_id = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+    let session_id = uuid!("224ecaea-372d-4d31-8b64-4805966418e5");
+
+    assert_packet_roundtrip!(
+        AgentThrottle,
+        AgentThrottle {
+            agent_id,
+            session_id,
+            circuit_code: 1234,
+            gen_counter: 2,
+            throttles: Throttles::default(),
+        }
+    );
+    assert_packet_roundtrip!(
+        ChatFromViewer,
+        ChatFromViewer {
+            agent_id,
+            session_id,
+            message: "hello".to_string(),
+            message_type: ClientChatType::Normal,
+            channel: -42,
+        }
+    );
+    assert_packet_roundtrip!(
+        CompleteAgentMovementData,
+        CompleteAgentMovementData {
+            agent_id,
+            session_id,
+            circuit_code: 1234,
+        }
+    );
+    assert_packet_roundtrip!(CompletePingCheck, CompletePingCheck { ping_id: 7 });
+    assert_packet_roundtrip!(
+        StartPingCheck,
+        StartPingCheck {
+            ping_id: 7,
+            oldest_unacked: 99,
+        }
+    );
+    assert_packet_roundtrip!(
+        PacketAck,
+        PacketAck {
+            packet_ids: vec![1, 2, u32::MAX],
+        }
+    );
+    assert_packet_roundtrip!(
+        KillObject,
+        KillObject {
+            object_ids: vec![1, 2, 3],
+        }
+    );
+    assert_packet_roundtrip!(
+        RequestImage,
+        RequestImage {
+            agent_id,
+            session_id,
+            requests: vec![ImageRequest {
+                image_id: agent_id,
+                discard_level: 2,
+                download_priority: 1013.0,
+                packet: 0,
+                image_type: 0,
+            }],
+        }
+    );
+    assert_packet_roundtrip!(
+        ImageData,
+        ImageData {
+            id: agent_id,
+            codec: 2,
+            size: 1200,
+            packets: 3,
+            data: vec![0xff, 0x4f, 0xff, 0x51],
+        }
+    );
+    assert_packet_roundtrip!(
+        ImagePacket,
+        ImagePacket {
+            id: agent_id,
+            packet: 2,
+            data: vec![1, 2, 3],
+        }
+    );
+    assert_packet_roundtrip!(
+        LogoutRequest,
+        LogoutRequest {
+            agent_id,
+            session_id,
+        }
+    );
+    assert_packet_roundtrip!(
+        ObjectSelect,
+        ObjectSelect {
+            agent_id,
+            session_id,
+            local_ids: vec![1, 70000],
+        }
+    );
+    assert_packet_roundtrip!(
+        ObjectDeselect,
+        ObjectDeselect {
+            agent_id,
+            session_id,
+            local_ids: vec![],
+        }
+    );
+    assert_packet_roundtrip!(
+        AgentPause,
+        AgentPause {
+            agent_id,
+            session_id,
+            serial_num: 1,
+        }
+    );
+    assert_packet_roundtrip!(
+        AgentResume,
+        AgentResume {
+            agent_id,
+            session_id,
+            serial_num: u32::MAX,
+        }
+    );
+    assert_packet_roundtrip!(
+        RequestMultipleObjects,
+        RequestMultipleObjects::new(agent_id, session_id, &[42, 43])
+    );
+    assert_packet_roundtrip!(
+        AgentHeightWidth,
+        AgentHeightWidth {
+            agent_id,
+            session_id,
+            circuit_code: 1234,
+            gen_counter: 2,
+            height: 1080,
+            width: 1920,
+        }
+    );
+}
+
+#[test]
+fn test_friend_notification_roundtrip() {
+    let friends = vec![
+        uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+        uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+    ];
+    let online = assert_packet_roundtrip!(
+        OnlineNotification,
+        OnlineNotification {
+            agent_ids: friends.clone(),
+        }
+    );
+    assert_eq!(online.agent_ids, friends);
+    assert_packet_roundtrip!(
+        OfflineNotification,
+        OfflineNotification { agent_ids: vec![] }
+    );
+    let presence = assert_packet_roundtrip!(
+        FriendPresence,
+        FriendPresence {
+            agent_id: friends[0],
+            online: true,
+        }
+    );
+    assert!(presence.online);
+}
+
+#[test]
+fn test_uuid_name_roundtrip() {
+    let id = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+    assert_packet_roundtrip!(UUIDNameRequest, UUIDNameRequest { ids: vec![id] });
+    let parsed = assert_packet_roundtrip!(
+        UUIDNameReply,
+        UUIDNameReply {
+            names: vec![UUIDNameBlock {
+                id,
+                first_name: "Test".to_string(),
+                last_name: "Resident".to_string(),
+            }],
+        }
+    );
+    assert_eq!(parsed.names[0].first_name, "Test");
+    assert_eq!(parsed.names[0].last_name, "Resident");
+}
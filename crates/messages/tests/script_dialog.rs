@@ -0,0 +1,157 @@
+use metaverse_messages::{
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    script_answer_yes::ScriptAnswerYes,
+    script_dialog::{ScriptDialog, TEXT_BOX_BUTTON},
+    script_dialog_reply::ScriptDialogReply,
+    script_question::{ScriptPermissions, ScriptQuestion},
+    ui_events::UiEventTypes,
+};
+use uuid::Uuid;
+
+const AGENT_ID: Uuid = Uuid::from_u128(1);
+const SESSION_ID: Uuid = Uuid::from_u128(2);
+const OBJECT_ID: Uuid = Uuid::from_u128(3);
+
+fn dialog() -> ScriptDialog {
+    ScriptDialog {
+        object_id: OBJECT_ID,
+        first_name: "Vendor".to_string(),
+        last_name: "Owner".to_string(),
+        object_name: "Teleporter".to_string(),
+        message: "Where to?".to_string(),
+        chat_channel: -4242,
+        image_id: Uuid::nil(),
+        buttons: vec!["Sky".to_string(), "Ground".to_string()],
+        owner_ids: vec![Uuid::from_u128(4)],
+    }
+}
+
+fn question() -> ScriptQuestion {
+    ScriptQuestion {
+        task_id: OBJECT_ID,
+        item_id: Uuid::from_u128(5),
+        object_name: "Dance Ball".to_string(),
+        object_owner: "Club Owner".to_string(),
+        questions: ScriptPermissions {
+            trigger_animation: true,
+            take_controls: true,
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn test_script_dialog_round_trip() {
+    let packet = Packet::new_script_dialog(dialog());
+    match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+        PacketType::ScriptDialog(data) => assert_eq!(*data, dialog()),
+        other => panic!("expected ScriptDialog, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_script_dialog_from_an_older_simulator() {
+    let mut dialog = dialog();
+    dialog.owner_ids.clear();
+    let mut bytes = dialog.to_bytes();
+    // older simulators don't send the owner block
+    bytes.pop();
+    assert_eq!(ScriptDialog::from_bytes(&bytes).unwrap(), dialog);
+    assert!(ScriptDialog::from_bytes(&bytes[..bytes.len() - 3]).is_err());
+}
+
+#[test]
+fn test_dialog_reply_says_the_button_on_the_channel() {
+    let reply = dialog().reply(AGENT_ID, SESSION_ID, "Ground");
+    assert_eq!(
+        reply,
+        ScriptDialogReply {
+            agent_id: AGENT_ID,
+            session_id: SESSION_ID,
+            object_id: OBJECT_ID,
+            chat_channel: -4242,
+            button_index: 1,
+            button_label: "Ground".to_string(),
+        }
+    );
+    match Packet::from_bytes(&Packet::new_script_dialog_reply(reply.clone()).to_bytes())
+        .unwrap()
+        .body
+    {
+        PacketType::ScriptDialogReply(data) => assert_eq!(*data, reply),
+        other => panic!("expected ScriptDialogReply, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_text_box_reply() {
+    let mut text_box = dialog();
+    text_box.buttons = vec![TEXT_BOX_BUTTON.to_string()];
+    assert!(text_box.is_text_box());
+    assert!(!dialog().is_text_box());
+
+    let reply = text_box.reply(AGENT_ID, SESSION_ID, "é".repeat(200));
+    assert_eq!(reply.button_index, 0);
+    // long text is cut to fit the label, without splitting a character
+    let parsed = ScriptDialogReply::from_bytes(&reply.to_bytes()).unwrap();
+    assert_eq!(parsed.button_label, "é".repeat(127));
+}
+
+#[test]
+fn test_script_question_round_trip() {
+    let packet = Packet::new_script_question(question());
+    match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+        PacketType::ScriptQuestion(data) => assert_eq!(*data, question()),
+        other => panic!("expected ScriptQuestion, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_script_permissions_bits() {
+    // PERMISSION_DEBIT and PERMISSION_TRIGGER_ANIMATION
+    let permissions = ScriptPermissions::from_bytes(0x12);
+    assert!(permissions.debit);
+    assert!(permissions.trigger_animation);
+    assert!(!permissions.attach);
+    assert_eq!(permissions.to_bytes(), 0x12);
+    assert_eq!(ScriptPermissions::none().to_bytes(), 0);
+}
+
+#[test]
+fn test_answer_only_grants_what_was_asked() {
+    let answer = question().answer(AGENT_ID, SESSION_ID, ScriptPermissions::all());
+    assert_eq!(answer.questions, question().questions);
+    assert_eq!(answer.task_id, OBJECT_ID);
+
+    let declined = question().answer(AGENT_ID, SESSION_ID, ScriptPermissions::none());
+    assert_eq!(declined.questions, ScriptPermissions::none());
+    match Packet::from_bytes(&Packet::new_script_answer_yes(declined.clone()).to_bytes())
+        .unwrap()
+        .body
+    {
+        PacketType::ScriptAnswerYes(data) => assert_eq!(*data, declined),
+        other => panic!("expected ScriptAnswerYes, got {:?}", other),
+    }
+    assert_eq!(
+        ScriptAnswerYes::from_bytes(&declined.to_bytes()).unwrap(),
+        declined
+    );
+}
+
+#[test]
+fn test_script_events_reach_the_ui() {
+    let packet = Packet::new_script_dialog(dialog());
+    assert_eq!(packet.body.ui_event(), UiEventTypes::ScriptDialogEvent);
+    match UiEventTypes::ScriptDialogEvent.packet_type_from_bytes(&packet.body.to_bytes()) {
+        Some(PacketType::ScriptDialog(data)) => assert_eq!(data.buttons.len(), 2),
+        other => panic!("expected ScriptDialog, got {:?}", other),
+    }
+
+    let packet = Packet::new_script_question(question());
+    assert_eq!(packet.body.ui_event(), UiEventTypes::ScriptQuestionEvent);
+    match UiEventTypes::ScriptQuestionEvent.packet_type_from_bytes(&packet.body.to_bytes()) {
+        Some(PacketType::ScriptQuestion(data)) => assert_eq!(data.object_name, "Dance Ball"),
+        other => panic!("expected ScriptQuestion, got {:?}", other),
+    }
+}
@@ -0,0 +1,37 @@
+use glam::Vec3;
+use metaverse_messages::{
+    chat_from_simulator::{Audible, ChatFromSimulator, ChatType, SourceType},
+    layer_data::{LayerData, LayerType},
+    packet::PacketData,
+};
+use uuid::uuid;
+
+#[test]
+fn test_chat_from_simulator_json_roundtrip() {
+    let chat = ChatFromSimulator {
+        from_name: "Test User".to_string(),
+        source_id: uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a"),
+        owner_id: uuid!("224ecaea-372d-4d31-8b64-4805966418e5"),
+        source_type: SourceType::Object,
+        chat_type: ChatType::Whisper,
+        audible: Audible::Barely,
+        position: Vec3::new(128.0, 64.5, 22.25),
+        message: "hello".to_string(),
+    };
+    let json = serde_json::to_string(&chat).unwrap();
+    let parsed: ChatFromSimulator = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.to_bytes(), chat.to_bytes());
+}
+
+#[test]
+fn test_layer_data_json_roundtrip() {
+    let layer = LayerData {
+        stride: 264,
+        patch_size: 16,
+        layer_type: LayerType::Wind,
+        layer_content: vec![1, 2, 3, 4],
+    };
+    let json = serde_json::to_string(&layer).unwrap();
+    let parsed: LayerData = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.to_bytes(), layer.to_bytes());
+}
@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+
+use metaverse_messages::{
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    sim_stats::{SimStat, SimStats},
+    ui_events::UiEventTypes,
+};
+
+fn sim_stats() -> SimStats {
+    SimStats {
+        region_x: 1000,
+        region_y: 1001,
+        region_flags: 0,
+        object_capacity: 15000,
+        stats: BTreeMap::from([
+            (SimStat::TimeDilation, 0.98),
+            (SimStat::SimFps, 44.5),
+            (SimStat::PhysicsFps, 45.0),
+            (SimStat::Agents, 12.0),
+            (SimStat::Unknown(99), 7.0),
+        ]),
+        pid: 4242,
+        region_flags_extended: vec![0],
+    }
+}
+
+#[test]
+fn test_sim_stats_round_trip() {
+    let packet = Packet::new_sim_stats(sim_stats());
+    assert!(!packet.header.reliable);
+    match Packet::from_bytes(&packet.to_bytes()).unwrap().body {
+        PacketType::SimStats(data) => assert_eq!(*data, sim_stats()),
+        other => panic!("expected SimStats, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_sim_stats_are_decoded_by_id() {
+    let mut bytes = Vec::new();
+    for value in [1000u32, 1001, 0, 15000] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes.push(2);
+    // stat 1 is the sim FPS, and 13 the agent count
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.extend_from_slice(&22.5f32.to_le_bytes());
+    bytes.extend_from_slice(&13u32.to_le_bytes());
+    bytes.extend_from_slice(&3.0f32.to_le_bytes());
+    bytes.extend_from_slice(&4242i32.to_le_bytes());
+
+    // older simulators don't send the extended region flags
+    let stats = SimStats::from_bytes(&bytes).unwrap();
+    assert_eq!(stats.sim_fps(), Some(22.5));
+    assert_eq!(stats.agent_count(), Some(3));
+    assert_eq!(stats.time_dilation(), None);
+    assert_eq!(stats.pid, 4242);
+    assert!(stats.region_flags_extended.is_empty());
+}
+
+#[test]
+fn test_unknown_stats_are_kept() {
+    assert_eq!(SimStat::from_bytes(99), SimStat::Unknown(99));
+    assert_eq!(SimStat::Unknown(99).to_bytes(), 99);
+    for id in 0..25 {
+        assert_eq!(SimStat::from_bytes(id).to_bytes(), id);
+        assert_ne!(SimStat::from_bytes(id), SimStat::Unknown(id));
+    }
+}
+
+#[test]
+fn test_truncated_sim_stats_is_an_error() {
+    let bytes = sim_stats().to_bytes();
+    assert!(SimStats::from_bytes(&bytes[..20]).is_err());
+}
+
+#[test]
+fn test_sim_stats_event_reaches_the_ui() {
+    let packet = Packet::new_sim_stats(sim_stats());
+    assert_eq!(packet.body.ui_event(), UiEventTypes::SimStatsEvent);
+    match UiEventTypes::SimStatsEvent.packet_type_from_bytes(&packet.body.to_bytes()) {
+        Some(PacketType::SimStats(data)) => assert_eq!(data.physics_fps(), Some(45.0)),
+        other => panic!("expected SimStats, got {:?}", other),
+    }
+}
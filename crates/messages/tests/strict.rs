@@ -0,0 +1,117 @@
+use glam::Vec3;
+use hex::FromHex;
+use metaverse_messages::{
+    agent_animation::AgentAnimation,
+    chat_from_simulator::{Audible, ChatFromSimulator, ChatType, SourceType},
+    chat_from_viewer::{ChatFromViewer, ClientChatType},
+    complete_ping_check::CompletePingCheck,
+    layer_data::{LayerData, LayerType},
+    packet::{Packet, ParseOptions},
+    packet_types::PacketType,
+};
+use uuid::Uuid;
+
+fn chat() -> Packet {
+    Packet::new_chat_from_simulator(ChatFromSimulator {
+        from_name: "Test User".to_string(),
+        source_id: Uuid::from_u128(1),
+        owner_id: Uuid::from_u128(1),
+        source_type: SourceType::Agent,
+        chat_type: ChatType::Normal,
+        audible: Audible::Fully,
+        position: Vec3::new(128.0, 128.0, 22.0),
+        message: "hello".to_string(),
+    })
+}
+
+fn well_formed() -> Vec<Vec<u8>> {
+    let mut with_acks = Packet::new_complete_ping_check(CompletePingCheck { ping_id: 7 });
+    with_acks.header.appended_acks = true;
+    with_acks.header.ack_list = Some(vec![1, 2, 42069]);
+    vec![
+        chat().to_bytes(),
+        with_acks.to_bytes(),
+        Packet::new_layer_data(LayerData {
+            layer_type: LayerType::Land,
+            stride: 264,
+            patch_size: 16,
+            layer_content: vec![1, 2, 3],
+        })
+        .to_bytes(),
+        Packet::new_agent_animation(AgentAnimation::new(
+            Uuid::from_u128(1),
+            Uuid::from_u128(2),
+            [(Uuid::from_u128(3), true)],
+        ))
+        .to_bytes(),
+        // a zerocoded CompletePingCheck
+        Vec::from_hex("800000000000020001").unwrap(),
+        // a DisableSimulator, which has no body
+        Vec::from_hex("400000000300ffff0098").unwrap(),
+    ]
+}
+
+#[test]
+fn test_strict_parsing_accepts_well_formed_packets() {
+    for bytes in well_formed() {
+        let (_, len) = Packet::from_bytes_with_options(&bytes, ParseOptions::strict())
+            .unwrap_or_else(|e| panic!("{:02x?} was rejected: {}", bytes, e));
+        assert_eq!(len, bytes.len());
+    }
+}
+
+#[test]
+fn test_strict_parsing_rejects_trailing_bytes() {
+    let mut bytes = chat().to_bytes();
+    bytes.extend_from_slice(&[0xaa, 0xbb, 0xcc]);
+
+    // the default is lenient, so the chat is still read
+    match Packet::from_bytes(&bytes).unwrap().body {
+        PacketType::ChatFromSimulator(chat) => assert_eq!(chat.message, "hello"),
+        body => panic!("wrong packet type: {:?}", body),
+    }
+    let error = Packet::from_bytes_with_options(&bytes, ParseOptions::strict()).unwrap_err();
+    assert!(error.to_string().contains("3 trailing bytes"), "{}", error);
+}
+
+#[test]
+fn test_strict_parsing_rejects_trailing_bytes_before_appended_acks() {
+    let mut packet = Packet::new_complete_ping_check(CompletePingCheck { ping_id: 7 });
+    packet.header.appended_acks = true;
+    packet.header.ack_list = Some(vec![1]);
+    let mut bytes = packet.to_bytes();
+    // garbage between the body and the acks, which are the last five bytes
+    let acks = bytes.split_off(bytes.len() - 5);
+    bytes.push(0xaa);
+    bytes.extend_from_slice(&acks);
+
+    assert!(Packet::from_bytes(&bytes).is_ok());
+    assert!(Packet::from_bytes_with_options(&bytes, ParseOptions::strict()).is_err());
+}
+
+#[test]
+fn test_strict_parsing_uses_what_the_parser_read() {
+    let packet = Packet::new_chat_from_viewer(ChatFromViewer {
+        agent_id: Uuid::nil(),
+        session_id: Uuid::nil(),
+        message: "hi".to_string(),
+        message_type: ClientChatType::Normal,
+        channel: 0,
+    });
+    let mut bytes = packet.header.to_bytes();
+    bytes.extend_from_slice(&[0; 32]);
+    // without its null terminator the message is a byte shorter than it would be written out,
+    // which hides the trailing byte from anything that measures the body by writing it again
+    bytes.extend_from_slice(&[2, 0, b'h', b'i']);
+    bytes.push(ClientChatType::Normal.to_bytes());
+    bytes.extend_from_slice(&0i32.to_le_bytes());
+    bytes.push(0xaa);
+
+    let error = Packet::from_bytes_with_options(&bytes, ParseOptions::strict()).unwrap_err();
+    assert!(error.to_string().contains("1 trailing bytes"), "{}", error);
+}
+
+#[test]
+fn test_default_options_are_lenient() {
+    assert!(!ParseOptions::default().strict);
+}
@@ -0,0 +1,28 @@
+use std::io;
+
+use metaverse_messages::{
+    chat_from_simulator::ChatFromSimulator,
+    circuit_code::CircuitCodeData,
+    complete_agent_movement::CompleteAgentMovementData,
+    complete_ping_check::CompletePingCheck,
+    packet::{Packet, PacketData},
+    start_ping_check::StartPingCheck,
+};
+
+fn assert_eof<T: std::fmt::Debug>(result: io::Result<T>) {
+    match result {
+        Err(e) => assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof),
+        Ok(data) => panic!("truncated data parsed as {:?}", data),
+    }
+}
+
+#[test]
+fn test_truncated_packets_return_errors() {
+    assert_eof(Packet::from_bytes(&[]));
+    assert_eof(Packet::from_bytes(&[0x40, 0, 0]));
+    assert_eof(ChatFromSimulator::from_bytes(b"name\0short"));
+    assert_eof(CircuitCodeData::from_bytes(&[1, 2, 3, 4, 5]));
+    assert_eof(CompleteAgentMovementData::from_bytes(&[0; 20]));
+    assert_eof(StartPingCheck::from_bytes(&[1, 2]));
+    assert_eof(CompletePingCheck::from_bytes(&[]));
+}
@@ -0,0 +1,40 @@
+use metaverse_messages::ui_events::UiEventTypes;
+
+#[test]
+fn test_every_tag_maps_back_to_its_event() {
+    let events: Vec<UiEventTypes> = (0..=u16::MAX).filter_map(UiEventTypes::from_tag).collect();
+    assert!(events.len() > 20);
+    for event in events {
+        assert_eq!(UiEventTypes::from_tag(event.tag()), Some(event));
+    }
+}
+
+#[test]
+fn test_tags_are_stable() {
+    // the UI may be built from a different version of the crate than the mailbox, so these must
+    // never change
+    assert_eq!(UiEventTypes::LoginResponseEvent.tag(), 0);
+    assert_eq!(UiEventTypes::Error.tag(), 2);
+    assert_eq!(UiEventTypes::ChatFromSimulatorEvent.tag(), 3);
+    assert_eq!(UiEventTypes::DisableSimulatorEvent.tag(), 5);
+    assert_eq!(UiEventTypes::TextureEvent.tag(), 12);
+    assert_eq!(UiEventTypes::None.tag(), 21);
+    assert_eq!(UiEventTypes::StateChangeEvent.tag(), 26);
+}
+
+#[test]
+fn test_events_are_encoded_as_their_tag() {
+    let event = UiEventTypes::ChatFromSimulatorEvent;
+    assert_eq!(bincode::serialize(&event).unwrap(), vec![3, 0]);
+    assert_eq!(serde_json::to_string(&event).unwrap(), "3");
+    assert_eq!(
+        bincode::deserialize::<UiEventTypes>(&[3, 0]).unwrap(),
+        event
+    );
+}
+
+#[test]
+fn test_unknown_tag_is_an_error() {
+    assert!(UiEventTypes::from_tag(u16::MAX).is_none());
+    assert!(bincode::deserialize::<UiEventTypes>(&u16::MAX.to_le_bytes()).is_err());
+}
@@ -0,0 +1,125 @@
+use glam::Vec3;
+use metaverse_messages::{
+    chat_from_simulator::{Audible, ChatFromSimulator, ChatType, SourceType},
+    packet::PacketData,
+    region_handshake::RegionHandshake,
+    utils::variable::{
+        read_string, read_string_1, read_string_2, read_uuid, read_variable_1, read_variable_2,
+        write_string_1, write_string_2, write_variable_1, write_variable_2,
+    },
+};
+use std::io::{Cursor, ErrorKind};
+use uuid::Uuid;
+
+#[test]
+fn test_size_prefixes_count_the_null_terminator() {
+    let mut bytes = Vec::new();
+    write_string_1(&mut bytes, "Lbsa");
+    write_string_2(&mut bytes, "Plaza");
+    assert_eq!(bytes, b"\x05Lbsa\0\x06\0Plaza\0");
+
+    let mut cursor = Cursor::new(bytes.as_slice());
+    assert_eq!(read_string_1(&mut cursor).unwrap(), "Lbsa");
+    assert_eq!(read_string_2(&mut cursor).unwrap(), "Plaza");
+    assert_eq!(cursor.position() as usize, bytes.len());
+}
+
+#[test]
+fn test_variable_fields_round_trip() {
+    let mut bytes = Vec::new();
+    write_variable_1(&mut bytes, &[1, 2, 3]);
+    write_variable_2(&mut bytes, &[]);
+    write_variable_2(&mut bytes, &[0; 300]);
+    assert_eq!(&bytes[..6], [3, 1, 2, 3, 0, 0]);
+
+    let mut cursor = Cursor::new(bytes.as_slice());
+    assert_eq!(read_variable_1(&mut cursor).unwrap(), [1, 2, 3]);
+    assert!(read_variable_2(&mut cursor).unwrap().is_empty());
+    assert_eq!(read_variable_2(&mut cursor).unwrap(), [0; 300]);
+}
+
+#[test]
+fn test_strings_without_a_terminator_are_read_whole() {
+    let bytes = b"\x04Lbsa\x00";
+    let mut cursor = Cursor::new(&bytes[..]);
+    assert_eq!(read_string_1(&mut cursor).unwrap(), "Lbsa");
+    // an empty field has no terminator to strip
+    assert_eq!(read_string_1(&mut cursor).unwrap(), "");
+}
+
+#[test]
+fn test_malformed_fields_are_errors() {
+    let mut cursor = Cursor::new(&b"\x09Lbsa\0"[..]);
+    let error = read_string_1(&mut cursor).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+
+    let mut cursor = Cursor::new(&[0x05][..]);
+    let error = read_variable_2(&mut cursor).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+
+    let mut cursor = Cursor::new(&b"\x03\xff\xfe\0"[..]);
+    let error = read_string_1(&mut cursor).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_long_fields_are_cut_to_fit_the_prefix() {
+    let mut bytes = Vec::new();
+    write_string_1(&mut bytes, &"a".repeat(300));
+    assert_eq!(bytes[0], 255);
+    assert_eq!(bytes.len(), 256);
+    assert_eq!(bytes.last(), Some(&0));
+
+    // a three byte character that would straddle the limit is left out whole
+    let mut bytes = Vec::new();
+    write_string_1(&mut bytes, &format!("{}€", "a".repeat(253)));
+    let string = read_string_1(&mut Cursor::new(bytes.as_slice())).unwrap();
+    assert_eq!(string, "a".repeat(253));
+
+    let mut bytes = Vec::new();
+    write_variable_1(&mut bytes, &[7; 256]);
+    assert_eq!(bytes.len(), 256);
+}
+
+#[test]
+fn test_chat_longer_than_the_prefix_does_not_overflow() {
+    let chat = ChatFromSimulator {
+        from_name: "a".repeat(255),
+        source_id: Uuid::from_u128(1),
+        owner_id: Uuid::from_u128(2),
+        source_type: SourceType::Agent,
+        chat_type: ChatType::Normal,
+        audible: Audible::Fully,
+        position: Vec3::ZERO,
+        message: "b".repeat(u16::MAX as usize),
+    };
+    let parsed = ChatFromSimulator::from_bytes(&chat.to_bytes()).unwrap();
+    assert_eq!(parsed.from_name.len(), 254);
+    assert_eq!(parsed.message.len(), u16::MAX as usize - 1);
+}
+
+#[test]
+fn test_region_handshake_sim_name_has_no_terminator() {
+    let mut bytes = vec![0, 0, 0, 0, 0, 13];
+    write_string_1(&mut bytes, "Lbsa Plaza");
+    bytes.extend_from_slice(&[0u8; 137]);
+    let handshake = RegionHandshake::from_bytes(&bytes).unwrap();
+    assert_eq!(handshake.region_info.sim_name, "Lbsa Plaza");
+}
+
+#[test]
+fn test_uuids_and_known_length_strings_are_read_in_place() {
+    let id = Uuid::new_v4();
+    let mut bytes = id.as_bytes().to_vec();
+    bytes.extend_from_slice(b"Plaza");
+    let mut cursor = Cursor::new(&bytes[..]);
+    assert_eq!(read_uuid(&mut cursor).unwrap(), id);
+    assert_eq!(read_string(&mut cursor, 5).unwrap(), "Plaza");
+
+    // a length past the end of the packet is an error, not an allocation
+    let mut cursor = Cursor::new(&bytes[..]);
+    let error = read_string(&mut cursor, usize::MAX).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    let mut cursor = Cursor::new(&bytes[..15]);
+    assert!(read_uuid(&mut cursor).is_err());
+}
@@ -0,0 +1,116 @@
+use glam::DVec3;
+use metaverse_messages::{
+    packet::{Packet, PacketData},
+    packet_types::PacketType,
+    ui_events::UiEventTypes,
+    viewer_effect::{Effect, EffectData, EffectType, LookAtType, PointAtType, ViewerEffect},
+};
+use uuid::{uuid, Uuid};
+
+const AGENT_ID: Uuid = uuid!("320dff8a-7a59-4720-a0f7-5a8df3698d9a");
+const TARGET_ID: Uuid = uuid!("224ecaea-372d-4d31-8b64-4805966418e5");
+
+fn viewer_effect() -> ViewerEffect {
+    ViewerEffect {
+        agent_id: AGENT_ID,
+        session_id: uuid!("89556747-24cb-43ed-920b-47caed15465f"),
+        effects: vec![
+            Effect::look_at(
+                Uuid::from_u128(1),
+                AGENT_ID,
+                TARGET_ID,
+                DVec3::new(256128.0, 256000.5, 22.25),
+                LookAtType::Select,
+            ),
+            Effect::point_at(
+                Uuid::from_u128(2),
+                AGENT_ID,
+                Uuid::nil(),
+                DVec3::new(256130.0, 256001.0, 21.0),
+                PointAtType::Grab,
+            ),
+            Effect {
+                id: Uuid::from_u128(3),
+                agent_id: AGENT_ID,
+                effect_type: EffectType::Edit,
+                duration: 0.5,
+                color: [255, 0, 0, 255],
+                data: EffectData::Beam {
+                    source_id: AGENT_ID,
+                    target_id: TARGET_ID,
+                    position: DVec3::new(1.0, 2.0, 3.0),
+                },
+            },
+            Effect {
+                id: Uuid::from_u128(4),
+                agent_id: AGENT_ID,
+                effect_type: EffectType::Unknown(99),
+                duration: 2.0,
+                color: [0, 0, 0, 0],
+                data: EffectData::Other(vec![1, 2, 3]),
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_viewer_effect_tofrom_bytes() {
+    let packet = Packet::new_viewer_effect(viewer_effect());
+    match Packet::from_bytes(&packet.to_bytes()) {
+        Ok(Packet {
+            body: PacketType::ViewerEffect(data),
+            ..
+        }) => {
+            assert_eq!(data.to_bytes(), viewer_effect().to_bytes());
+            assert_eq!(data.effects.len(), 4);
+            match &data.effects[0].data {
+                EffectData::LookAt {
+                    source_id,
+                    target_id,
+                    position,
+                    look_at_type,
+                } => {
+                    assert_eq!(*source_id, AGENT_ID);
+                    assert_eq!(*target_id, TARGET_ID);
+                    assert_eq!(*position, DVec3::new(256128.0, 256000.5, 22.25));
+                    assert_eq!(*look_at_type, LookAtType::Select);
+                }
+                other => panic!("expected a look at, got {:?}", other),
+            }
+            assert!(matches!(
+                data.effects[1].data,
+                EffectData::PointAt {
+                    point_at_type: PointAtType::Grab,
+                    ..
+                }
+            ));
+            assert!(matches!(data.effects[2].data, EffectData::Beam { .. }));
+            assert_eq!(data.effects[3].effect_type, EffectType::Unknown(99));
+        }
+        Ok(packet) => panic!("wrong packet type: {:?}", packet),
+        Err(e) => panic!("Error creating packet: {}", e),
+    }
+}
+
+#[test]
+fn test_look_at_type_data_is_57_bytes() {
+    // the viewer expects the source, the target, three f64s and the look at type
+    let bytes = ViewerEffect {
+        effects: vec![viewer_effect().effects.remove(0)],
+        ..viewer_effect()
+    }
+    .to_bytes();
+    // agent, session, count, then the effect up to the size of the type data
+    assert_eq!(bytes[32 + 1 + 16 + 16 + 1 + 4 + 4], 57);
+}
+
+#[test]
+fn test_viewer_effect_is_sent_to_the_ui() {
+    let packet = Packet::new_viewer_effect(viewer_effect());
+    let event = packet.body.ui_event();
+    assert!(matches!(event, UiEventTypes::ViewerEffectEvent));
+    match event.packet_type_from_bytes(&packet.body.to_bytes()) {
+        Some(PacketType::ViewerEffect(data)) => assert_eq!(data.effects.len(), 4),
+        other => panic!("wrong packet type: {:?}", other),
+    }
+}
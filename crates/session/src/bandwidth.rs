@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+use tokio::time::{Duration, Instant};
+
+/// how far back the current rate is measured
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Counts the bytes written to the server, and optionally caps them with a token bucket.
+/// The bucket holds one second of budget, so short bursts up to the limit are sent at once.
+/// Reliable packets over the budget wait until there is room for them. Unreliable packets over
+/// the budget are dropped, because they would be out of date by the time they are sent.
+#[derive(Debug)]
+pub struct Bandwidth {
+    max_bytes_per_second: Option<u32>,
+    /// how many bytes can be sent right now. Negative when reliable packets are waiting.
+    tokens: f64,
+    last_refill: Instant,
+    /// when each packet in the rate window was sent, and its size
+    recent: VecDeque<(Instant, usize)>,
+    bytes_sent: u64,
+    packets_sent: u64,
+    packets_dropped: u64,
+}
+
+/// how much has been written to the server
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BandwidthStats {
+    /// the bytes written in the last second
+    pub bytes_per_second: u64,
+    /// the limit, if there is one
+    pub max_bytes_per_second: Option<u32>,
+    /// every byte written since the mailbox started
+    pub bytes_sent: u64,
+    /// every packet written since the mailbox started
+    pub packets_sent: u64,
+    /// unreliable packets that were dropped for going over the limit
+    pub packets_dropped: u64,
+}
+
+impl Bandwidth {
+    /// count the bytes without limiting them
+    pub fn new() -> Self {
+        Bandwidth {
+            max_bytes_per_second: None,
+            tokens: 0.0,
+            last_refill: Instant::now(),
+            recent: VecDeque::new(),
+            bytes_sent: 0,
+            packets_sent: 0,
+            packets_dropped: 0,
+        }
+    }
+
+    /// count and limit the bytes
+    pub fn with_limit(max_bytes_per_second: u32) -> Self {
+        let mut bandwidth = Bandwidth::new();
+        bandwidth.set_limit(Some(max_bytes_per_second));
+        bandwidth
+    }
+
+    /// change the most bytes per second written to the server. None doesn't limit anything.
+    /// The bucket starts full, so the next second's budget can be sent at once.
+    pub fn set_limit(&mut self, max_bytes_per_second: Option<u32>) {
+        self.max_bytes_per_second = max_bytes_per_second;
+        self.tokens = max_bytes_per_second.unwrap_or(0) as f64;
+        self.last_refill = Instant::now();
+    }
+
+    /// Take the budget for a packet that is about to be sent.
+    /// Returns how long to wait before sending it, or None if it has to be dropped.
+    pub fn reserve(&mut self, size: usize, reliable: bool) -> Option<Duration> {
+        let now = Instant::now();
+        let mut delay = Duration::ZERO;
+        if let Some(limit) = self.max_bytes_per_second {
+            let limit = limit.max(1) as f64;
+            self.refill(limit);
+
+            let size = size as f64;
+            if self.tokens < size {
+                if !reliable {
+                    self.packets_dropped += 1;
+                    return None;
+                }
+                delay = Duration::from_secs_f64((size - self.tokens) / limit);
+            }
+            self.tokens -= size;
+        }
+
+        self.bytes_sent += size as u64;
+        self.packets_sent += 1;
+        self.recent.push_back((now + delay, size));
+        Some(delay)
+    }
+
+    /// How long until there is budget for a packet, without taking it.
+    /// A packet larger than the whole budget only waits for a full bucket.
+    pub fn wait_time(&mut self, size: usize) -> Duration {
+        let Some(limit) = self.max_bytes_per_second else {
+            return Duration::ZERO;
+        };
+        let limit = limit.max(1) as f64;
+        self.refill(limit);
+        let needed = (size as f64).min(limit);
+        if self.tokens >= needed {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((needed - self.tokens) / limit)
+        }
+    }
+
+    /// add the budget for the time since the last refill
+    fn refill(&mut self, limit: f64) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit).min(limit);
+        self.last_refill = now;
+    }
+
+    /// the usage so far
+    pub fn stats(&mut self) -> BandwidthStats {
+        let now = Instant::now();
+        while self
+            .recent
+            .front()
+            .is_some_and(|(sent, _)| now.saturating_duration_since(*sent) > RATE_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        BandwidthStats {
+            bytes_per_second: self
+                .recent
+                .iter()
+                .filter(|(sent, _)| *sent <= now)
+                .map(|(_, size)| *size as u64)
+                .sum(),
+            max_bytes_per_second: self.max_bytes_per_second,
+            bytes_sent: self.bytes_sent,
+            packets_sent: self.packets_sent,
+            packets_dropped: self.packets_dropped,
+        }
+    }
+}
+
+impl Default for Bandwidth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
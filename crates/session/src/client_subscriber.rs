@@ -1,17 +1,11 @@
 use crossbeam_channel::Sender;
 use metaverse_messages::packet_types::PacketType;
-use std::collections::HashMap;
 use std::net::UdpSocket;
 
 use log::{info, warn};
 
-use crate::mailbox::UiMessage;
-
-/// This stores the packet and the chunks for deserialization
-pub struct PacketStore {
-    /// the chunks that belong to that packet
-    chunks: HashMap<u16, Vec<u8>>,
-}
+use crate::mailbox::{UiMessage, MAX_UI_MESSAGE_SIZE};
+use crate::ui_stream::UiMessageAssembler;
 
 /// This is for your client to listen on the data coming out of the server.
 /// import this and use directly, or modify to suit your own needs.
@@ -61,51 +55,29 @@ pub struct PacketStore {
 ///```
 pub async fn listen_for_server_events(server_to_ui_socket: String, sender: Sender<PacketType>) {
     let socket = UdpSocket::bind(server_to_ui_socket).expect("Failed to bind UDP socket");
-    let mut message_store: HashMap<u16, PacketStore> = HashMap::new();
+    let mut assembler = UiMessageAssembler::new();
 
     info!("UI listening for server events on UDP: {:?}", socket);
+    // large enough for any chunk size the mailbox can be configured to send
+    let mut buf = vec![0u8; MAX_UI_MESSAGE_SIZE];
     loop {
-        let mut buf = [0u8; 1500];
         match socket.recv_from(&mut buf) {
             Ok((n, _)) => {
-                if let Some(received_chunk) = UiMessage::from_bytes(&buf[..n]) {
-                    let packet_store =
-                        message_store
-                            .entry(received_chunk.packet_number)
-                            .or_insert(PacketStore {
-                                chunks: HashMap::new(),
-                            });
-
-                    packet_store
-                        .chunks
-                        .insert(received_chunk.sequence_number, received_chunk.message);
-
-                    // Check if we have all chunks
-                    if packet_store.chunks.len() == received_chunk.total_packet_number as usize {
-                        let mut full_message = Vec::new();
-                        for i in 0..received_chunk.total_packet_number {
-                            if let Some(chunk) = packet_store.chunks.remove(&i) {
-                                full_message.extend_from_slice(&chunk);
-                            } else {
-                                warn!("Missing chunk {} for message reconstruction", i);
-                                return;
-                            }
-                        }
-                        // get the packet type and send that to the sender
-                        if let Some(packet) = received_chunk
-                            .message_type
-                            .packet_type_from_bytes(&full_message)
-                        {
-                            if let Err(e) = sender.send(packet) {
-                                warn!("Failed to send packet to UI: {:?}", e)
-                            };
-                        } else {
-                            warn!("Client failed to send packet to UI")
-                        };
-                    }
+                let Some(received_chunk) = UiMessage::from_bytes(&buf[..n]) else {
+                    warn!("UI failed to deserialize the packet chunk from server");
+                    continue;
+                };
+                let Some((message_type, full_message)) = assembler.push(received_chunk) else {
+                    continue;
+                };
+                // get the packet type and send that to the sender
+                if let Some(packet) = message_type.packet_type_from_bytes(&full_message) {
+                    if let Err(e) = sender.send(packet) {
+                        warn!("Failed to send packet to UI: {:?}", e)
+                    };
                 } else {
-                    warn!("UI failed to deserialize the packet chunk from server")
-                }
+                    warn!("Client failed to send packet to UI")
+                };
             }
             Err(e) => {
                 warn!("UI Failed to read buffer {}", e)
@@ -0,0 +1,97 @@
+use futures::future::BoxFuture;
+use std::fmt::Debug;
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+use tokio::time::{Duration, Instant};
+
+/// The time the mailbox measures ping latency and ack timeouts with.
+/// This is the system clock when running, but can be replaced with a MockClock to test
+/// time-dependent behavior without real sleeps.
+pub trait Clock: Debug + Send + Sync + 'static {
+    /// the current time
+    fn now(&self) -> Instant;
+    /// wait until the duration has passed
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// The real time, from tokio.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A Clock for tests, that only moves when it is advanced.
+/// Sleeps wake up once the clock is advanced past their deadline.
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<Instant>,
+    /// the sleeps that haven't woken up yet, and when they wake up
+    sleepers: Mutex<Vec<(Instant, oneshot::Sender<()>)>>,
+}
+
+impl MockClock {
+    /// create a clock that starts at the current time
+    pub fn new() -> Self {
+        MockClock {
+            now: Mutex::new(Instant::now()),
+            sleepers: Mutex::new(Vec::new()),
+        }
+    }
+    /// move the clock forward, and wake up every sleep whose deadline has passed
+    pub fn advance(&self, duration: Duration) {
+        let now = {
+            let mut now = self.now.lock().unwrap();
+            *now += duration;
+            *now
+        };
+        let mut sleepers = self.sleepers.lock().unwrap();
+        let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut *sleepers)
+            .into_iter()
+            .partition(|(deadline, _)| *deadline <= now);
+        *sleepers = waiting;
+        for (_, sender) in ready {
+            let _ = sender.send(());
+        }
+    }
+    /// the number of sleeps that are still waiting
+    pub fn sleepers(&self) -> usize {
+        let mut sleepers = self.sleepers.lock().unwrap();
+        // sleeps that were dropped before they woke up don't count
+        sleepers.retain(|(_, sender)| !sender.is_closed());
+        sleepers.len()
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let (sender, receiver) = oneshot::channel();
+        if duration.is_zero() {
+            let _ = sender.send(());
+        } else {
+            let deadline = self.now() + duration;
+            self.sleepers.lock().unwrap().push((deadline, sender));
+        }
+        Box::pin(async move {
+            // a clock that is dropped never wakes its sleeps up
+            if receiver.await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        })
+    }
+}
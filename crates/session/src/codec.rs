@@ -0,0 +1,61 @@
+use std::fmt::Debug;
+use std::io;
+
+/// A transform applied to every datagram at the boundary between the mailbox and the transport.
+/// Some private grids obfuscate or encrypt the UDP stream, and a codec lets the mailbox speak to
+/// them without changing how packets are built. The default is the IdentityCodec, which sends
+/// packets unchanged.
+pub trait PacketCodec: Debug + Send + Sync + 'static {
+    /// transform an encoded packet before it is written to the transport
+    fn encode(&self, data: Vec<u8>) -> Vec<u8>;
+    /// undo the transform on a datagram read from the transport, before it is parsed.
+    /// Datagrams that fail to decode are dropped.
+    fn decode(&self, data: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+/// A codec that leaves packets unchanged, for grids that speak plain LLUDP.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IdentityCodec;
+
+impl PacketCodec for IdentityCodec {
+    fn encode(&self, data: Vec<u8>) -> Vec<u8> {
+        data
+    }
+    fn decode(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(data.to_vec())
+    }
+}
+
+/// A codec that XORs every datagram with a repeating shared secret.
+/// This is obfuscation, not encryption.
+#[derive(Debug, Clone)]
+pub struct XorCodec {
+    key: Vec<u8>,
+}
+
+impl XorCodec {
+    /// create a codec with the shared secret. An empty key leaves packets unchanged.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        XorCodec { key: key.into() }
+    }
+    fn apply(&self, data: &mut [u8]) {
+        if self.key.is_empty() {
+            return;
+        }
+        for (byte, key) in data.iter_mut().zip(self.key.iter().cycle()) {
+            *byte ^= key;
+        }
+    }
+}
+
+impl PacketCodec for XorCodec {
+    fn encode(&self, mut data: Vec<u8>) -> Vec<u8> {
+        self.apply(&mut data);
+        data
+    }
+    fn decode(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut data = data.to_vec();
+        self.apply(&mut data);
+        Ok(data)
+    }
+}
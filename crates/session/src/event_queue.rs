@@ -0,0 +1,55 @@
+use actix::Addr;
+use log::{info, warn};
+use metaverse_messages::capabilities::event_queue::{event_queue_get, EventQueuePoll};
+use metaverse_messages::packet::PacketData;
+use metaverse_messages::ui_events::UiEventTypes;
+use tokio::time::{sleep, Duration};
+
+use crate::mailbox::{Mailbox, UiMessage};
+
+/// how long to wait before polling again after a failed poll
+const EVENT_QUEUE_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// the longest the retry delay backs off to
+const EVENT_QUEUE_MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Long-polls the EventQueueGet capability, and sends every event to the UI as an
+/// EventQueueEvent.
+/// Some events, like TeleportFinish and group chat, are only sent over this queue and never over
+/// UDP.
+/// cap_url is the EventQueueGet URL from the seed capability. This runs until the simulator
+/// closes the queue, which happens when the agent leaves the region or logs out.
+pub async fn event_queue_loop(cap_url: String, mailbox_addr: Addr<Mailbox>) {
+    let mut ack = None;
+    let mut retry_delay = EVENT_QUEUE_RETRY_DELAY;
+    loop {
+        match event_queue_get(&cap_url, ack).await {
+            Ok(EventQueuePoll::Events { id, events }) => {
+                ack = Some(id);
+                retry_delay = EVENT_QUEUE_RETRY_DELAY;
+                for event in events {
+                    if let Err(e) = mailbox_addr
+                        .send(UiMessage::new(
+                            UiEventTypes::EventQueueEvent,
+                            event.to_bytes(),
+                        ))
+                        .await
+                    {
+                        warn!("failed to send {} to ui: {:?}", event.message, e)
+                    };
+                }
+            }
+            Ok(EventQueuePoll::Timeout) => {
+                retry_delay = EVENT_QUEUE_RETRY_DELAY;
+            }
+            Ok(EventQueuePoll::Closed) => {
+                info!("event queue {} closed", cap_url);
+                break;
+            }
+            Err(e) => {
+                warn!("{}, retrying in {:?}", e, retry_delay);
+                sleep(retry_delay).await;
+                retry_delay = (retry_delay * 2).min(EVENT_QUEUE_MAX_RETRY_DELAY);
+            }
+        }
+    }
+}
@@ -0,0 +1,375 @@
+use actix::Addr;
+use glam::{Quat, Vec3};
+use metaverse_messages::agent_update::{AgentUpdate, ControlFlags, Flags, State};
+use metaverse_messages::chat_from_viewer::{ChatFromViewer, ClientChatType, PUBLIC_CHANNEL};
+use metaverse_messages::errors::{MailboxError, SessionError, TimeoutError};
+use metaverse_messages::improved_instant_message::TeleportLure;
+use metaverse_messages::logout_request::LogoutRequest;
+use metaverse_messages::money_balance_request::MoneyBalanceRequest;
+use metaverse_messages::object_add::{ObjectAdd, PCode};
+use metaverse_messages::object_delete::ObjectDelete;
+use metaverse_messages::object_deselect::ObjectDeselect;
+use metaverse_messages::object_select::ObjectSelect;
+use metaverse_messages::packet::Packet;
+use metaverse_messages::request_region_info::RequestRegionInfo;
+use metaverse_messages::script_dialog::ScriptDialog;
+use metaverse_messages::script_question::{ScriptPermissions, ScriptQuestion};
+use std::sync::Arc;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+use crate::clock::Clock;
+use crate::mailbox::{
+    Animate, GetClock, GetPosition, Mailbox, Pause, Ready, Resume, SendPacket, SetWindowSize,
+};
+
+/// how far the agent can see, in meters
+const DEFAULT_DRAW_DISTANCE: f32 = 64.0;
+/// how close move_to gets to its target, in meters. The position only comes from
+/// CoarseLocationUpdates, which are accurate to a meter and arrive about once a second, so a
+/// walking agent can't reliably stop much closer than this.
+pub const DEFAULT_ARRIVAL_DISTANCE: f32 = 2.0;
+/// how long move_to walks before giving up
+pub const DEFAULT_MOVE_TIMEOUT: Duration = Duration::from_secs(60);
+/// how often move_to sends an AgentUpdate while walking, like a viewer holding down a key
+const MOVE_STEP_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A typed API for sending packets to the server, for scripts and bots that don't want to build
+/// packets or talk to the mailbox actor themselves.
+/// Methods that send reliable packets resolve once the server has acked them, or fail with an
+/// AckError if it never does.
+///```no_run
+/// use metaverse_session::handle::SessionHandle;
+/// # async fn run(handle: SessionHandle) -> Result<(), metaverse_messages::errors::SessionError> {
+/// handle.chat("hello").await?;
+/// handle.logout().await?;
+/// # Ok(())
+/// # }
+///```
+#[derive(Debug, Clone)]
+pub struct SessionHandle {
+    mailbox: Addr<Mailbox>,
+    agent_id: Uuid,
+    session_id: Uuid,
+}
+
+impl SessionHandle {
+    /// wrap a mailbox that has been given a Session for this agent
+    pub fn new(mailbox: Addr<Mailbox>, agent_id: Uuid, session_id: Uuid) -> Self {
+        SessionHandle {
+            mailbox,
+            agent_id,
+            session_id,
+        }
+    }
+
+    /// whether the session is usable. Packets sent before this are held or rejected.
+    pub async fn is_ready(&self) -> bool {
+        self.mailbox.send(Ready).await.unwrap_or(false)
+    }
+
+    /// send any packet, and wait for the server to ack it if it is reliable
+    pub async fn send(&self, packet: Packet) -> Result<(), SessionError> {
+        self.mailbox
+            .send(SendPacket(packet))
+            .await
+            .map_err(|e| SessionError::Mailbox(MailboxError::new(format!("{}", e))))?
+    }
+
+    /// say something in local chat
+    pub async fn chat(&self, message: impl Into<String>) -> Result<(), SessionError> {
+        self.chat_on(message, ClientChatType::Normal, PUBLIC_CHANNEL)
+            .await
+    }
+
+    /// send chat with a specific chat type, like a whisper or a shout, on a specific channel
+    pub async fn chat_on(
+        &self,
+        message: impl Into<String>,
+        message_type: ClientChatType,
+        channel: i32,
+    ) -> Result<(), SessionError> {
+        self.send(Packet::new_chat_from_viewer(ChatFromViewer {
+            agent_id: self.agent_id,
+            session_id: self.session_id,
+            message: message.into(),
+            message_type,
+            channel,
+        }))
+        .await
+    }
+
+    /// move the agent with the given controls, facing along the x axis.
+    /// AgentUpdate is unreliable, so this resolves as soon as the packet is sent.
+    pub async fn move_agent(&self, control_flags: ControlFlags) -> Result<(), SessionError> {
+        self.move_facing(control_flags, Vec3::X).await
+    }
+
+    /// the agent's position in the current region, once the server has sent it
+    pub async fn position(&self) -> Result<Option<Vec3>, SessionError> {
+        self.mailbox
+            .send(GetPosition)
+            .await
+            .map_err(|e| SessionError::Mailbox(MailboxError::new(format!("{}", e))))
+    }
+
+    /// the clock the mailbox keeps time with
+    async fn clock(&self) -> Result<Arc<dyn Clock>, SessionError> {
+        self.mailbox
+            .send(GetClock)
+            .await
+            .map_err(|e| SessionError::Mailbox(MailboxError::new(format!("{}", e))))
+    }
+
+    /// walk the agent in a straight line to a position in the current region, and stop once it
+    /// is within DEFAULT_ARRIVAL_DISTANCE of it. There is no pathfinding, so obstacles aren't
+    /// walked around.
+    pub async fn move_to(&self, target: Vec3) -> Result<(), SessionError> {
+        self.move_to_within(target, DEFAULT_ARRIVAL_DISTANCE, DEFAULT_MOVE_TIMEOUT)
+            .await
+    }
+
+    /// walk the agent in a straight line until it is within arrival_distance of the target.
+    /// Only the horizontal distance counts, because walking can't change the agent's height.
+    /// Fails with a TimeoutError if the agent hasn't arrived after the timeout, on the mailbox's
+    /// clock.
+    pub async fn move_to_within(
+        &self,
+        target: Vec3,
+        arrival_distance: f32,
+        timeout: Duration,
+    ) -> Result<(), SessionError> {
+        let clock = self.clock().await?;
+        let deadline = clock.now() + timeout;
+        let mut facing = Vec3::X;
+        loop {
+            let position = self.position().await?;
+            if let Some(position) = position {
+                let offset = (target - position).with_z(0.0);
+                if offset.length() <= arrival_distance {
+                    return self.move_facing(ControlFlags::from_bytes(0), facing).await;
+                }
+                facing = offset.normalize();
+            }
+            if clock.now() >= deadline {
+                self.move_facing(ControlFlags::from_bytes(0), facing)
+                    .await?;
+                return Err(SessionError::Timeout(TimeoutError::new(format!(
+                    "the agent did not reach {} within {:?}",
+                    target, timeout
+                ))));
+            }
+            // the position isn't known until the agent has arrived in the region, and there's
+            // no way to tell which way to walk before it is
+            if position.is_some() {
+                let walk = ControlFlags {
+                    at_pos: true,
+                    ..ControlFlags::from_bytes(0)
+                };
+                self.move_facing(walk, facing).await?;
+            }
+            clock.sleep(MOVE_STEP_INTERVAL).await;
+        }
+    }
+
+    /// send an AgentUpdate with the agent's body and camera turned to face a horizontal direction
+    async fn move_facing(
+        &self,
+        control_flags: ControlFlags,
+        facing: Vec3,
+    ) -> Result<(), SessionError> {
+        let rotation = Quat::from_rotation_z(facing.y.atan2(facing.x));
+        self.send(Packet::new_agent_update(AgentUpdate {
+            agent_id: self.agent_id,
+            session_id: self.session_id,
+            body_rotation: rotation,
+            head_rotation: Quat::IDENTITY,
+            state: State {
+                typing: false,
+                editing: false,
+            },
+            camera_center: Vec3::ZERO,
+            camera_at_axis: rotation * Vec3::X,
+            camera_left_axis: rotation * Vec3::Y,
+            camera_up_axis: Vec3::Z,
+            far: DEFAULT_DRAW_DISTANCE,
+            control_flags,
+            flags: Flags {
+                none: false,
+                hide_title: false,
+            },
+        }))
+        .await
+    }
+
+    /// select objects by their local IDs, to interact with them.
+    /// The server sends their ObjectProperties to the UI.
+    pub async fn select_objects(&self, local_ids: Vec<u32>) -> Result<(), SessionError> {
+        self.send(Packet::new_object_select(ObjectSelect {
+            agent_id: self.agent_id,
+            session_id: self.session_id,
+            local_ids,
+        }))
+        .await
+    }
+
+    /// deselect objects that were selected with select_objects
+    pub async fn deselect_objects(&self, local_ids: Vec<u32>) -> Result<(), SessionError> {
+        self.send(Packet::new_object_deselect(ObjectDeselect {
+            agent_id: self.agent_id,
+            session_id: self.session_id,
+            local_ids,
+        }))
+        .await
+    }
+
+    /// ask the server for the agent's currency balance.
+    /// The reply is sent to the UI as a BalanceUpdateEvent.
+    pub async fn request_balance(&self) -> Result<(), SessionError> {
+        self.send(Packet::new_money_balance_request(MoneyBalanceRequest {
+            agent_id: self.agent_id,
+            session_id: self.session_id,
+            transaction_id: Uuid::new_v4(),
+        }))
+        .await
+    }
+
+    /// ask the server for the settings of the current region.
+    /// The reply is sent to the UI as a RegionInfoEvent.
+    pub async fn request_region_info(&self) -> Result<(), SessionError> {
+        self.send(Packet::new_request_region_info(RequestRegionInfo {
+            agent_id: self.agent_id,
+            session_id: self.session_id,
+        }))
+        .await
+    }
+
+    /// rez a new object with the default box shape at a position in the region
+    pub async fn rez_object(
+        &self,
+        pcode: PCode,
+        position: Vec3,
+        scale: Vec3,
+        rotation: Quat,
+    ) -> Result<(), SessionError> {
+        self.send(Packet::new_object_add(ObjectAdd::new(
+            self.agent_id,
+            self.session_id,
+            pcode,
+            position,
+            scale,
+            rotation,
+        )))
+        .await
+    }
+
+    /// delete objects by their local IDs. They are moved to the owner's trash.
+    pub async fn delete_objects(&self, local_ids: Vec<u32>) -> Result<(), SessionError> {
+        self.send(Packet::new_object_delete(ObjectDelete {
+            agent_id: self.agent_id,
+            session_id: self.session_id,
+            force: false,
+            local_ids,
+        }))
+        .await
+    }
+
+    /// start and stop animations on the agent's avatar, given as (animation UUID, start) pairs
+    pub async fn animate(&self, animations: Vec<(Uuid, bool)>) -> Result<(), SessionError> {
+        self.mailbox
+            .send(Animate(animations))
+            .await
+            .map_err(|e| SessionError::Mailbox(MailboxError::new(format!("{}", e))))?
+    }
+
+    /// start playing an animation on the agent's avatar, like a wave or a dance
+    pub async fn start_animation(&self, anim_id: Uuid) -> Result<(), SessionError> {
+        self.animate(vec![(anim_id, true)]).await
+    }
+
+    /// stop playing an animation on the agent's avatar
+    pub async fn stop_animation(&self, anim_id: Uuid) -> Result<(), SessionError> {
+        self.animate(vec![(anim_id, false)]).await
+    }
+
+    /// pick a button in a dialog from a ScriptDialogEvent, or send text to a text box.
+    /// The script hears the label on the dialog's channel.
+    pub async fn reply_to_dialog(
+        &self,
+        dialog: &ScriptDialog,
+        button_label: impl Into<String>,
+    ) -> Result<(), SessionError> {
+        self.send(Packet::new_script_dialog_reply(dialog.reply(
+            self.agent_id,
+            self.session_id,
+            button_label,
+        )))
+        .await
+    }
+
+    /// grant a script some of the permissions it asked for in a ScriptQuestionEvent.
+    /// Granting ScriptPermissions::none() declines the request.
+    pub async fn answer_script_question(
+        &self,
+        question: &ScriptQuestion,
+        granted: ScriptPermissions,
+    ) -> Result<(), SessionError> {
+        self.send(Packet::new_script_answer_yes(question.answer(
+            self.agent_id,
+            self.session_id,
+            granted,
+        )))
+        .await
+    }
+
+    /// accept a teleport another agent offered, from an instant message whose kind is
+    /// TeleportLure. The teleport itself finishes with a TeleportFinish on the event queue.
+    pub async fn accept_teleport_lure(&self, lure: &TeleportLure) -> Result<(), SessionError> {
+        self.send(Packet::new_teleport_lure_request(
+            lure.accept(self.agent_id, self.session_id),
+        ))
+        .await
+    }
+
+    /// tell the agent who offered a teleport that it was declined
+    pub async fn decline_teleport_lure(&self, lure: &TeleportLure) -> Result<(), SessionError> {
+        self.send(Packet::new_improved_instant_message(
+            lure.decline(self.agent_id, self.session_id),
+        ))
+        .await
+    }
+
+    /// ask the server to stop sending object and texture updates, while the agent is idle.
+    /// With suspend_pings, the session also stops pinging the server until it is resumed.
+    pub async fn pause(&self, suspend_pings: bool) -> Result<(), SessionError> {
+        self.mailbox
+            .send(Pause { suspend_pings })
+            .await
+            .map_err(|e| SessionError::Mailbox(MailboxError::new(format!("{}", e))))?
+    }
+
+    /// ask the server to send updates again after a pause
+    pub async fn resume(&self) -> Result<(), SessionError> {
+        self.mailbox
+            .send(Resume)
+            .await
+            .map_err(|e| SessionError::Mailbox(MailboxError::new(format!("{}", e))))?
+    }
+
+    /// tell the server the size of the client's window, on login and whenever it is resized
+    pub async fn set_window_size(&self, height: u16, width: u16) -> Result<(), SessionError> {
+        self.mailbox
+            .send(SetWindowSize { height, width })
+            .await
+            .map_err(|e| SessionError::Mailbox(MailboxError::new(format!("{}", e))))?
+    }
+
+    /// ask the server to log the agent out
+    pub async fn logout(&self) -> Result<(), SessionError> {
+        self.send(Packet::new_logout_request(LogoutRequest {
+            agent_id: self.agent_id,
+            session_id: self.session_id,
+        }))
+        .await
+    }
+}
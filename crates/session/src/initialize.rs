@@ -1,21 +1,49 @@
-use actix::Actor;
-use actix_rt::time;
+use actix::{Actor, Addr};
+use log::warn;
 use metaverse_messages::errors::{MailboxError, SessionError};
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::sync::Mutex;
-use std::time::Duration;
-use tokio::sync::Notify;
 use tokio::task::JoinHandle;
 
-use crate::mailbox::Mailbox;
-use crate::mailbox::{PingInfo, ServerState};
+use crate::mailbox::{Logout, Mailbox, Shutdown, DEFAULT_STARTUP_TIMEOUT};
 use crate::server_subscriber::listen_for_ui_messages;
 use portpicker::pick_unused_port;
 
-/// This starts the mailbox, and blocks forever.
+/// The mailbox and the UI listener started by initialize.
+/// Dropping this leaves both running. Use shutdown to stop them.
+#[derive(Debug)]
+pub struct ClientHandle {
+    /// the mailbox, for sending it packets and messages directly
+    pub mailbox: Addr<Mailbox>,
+    /// the task listening for messages from the UI. It runs until it is aborted.
+    pub listener: JoinHandle<()>,
+}
+
+impl ClientHandle {
+    /// log out of the session if there is one, then stop the UI listener and the mailbox.
+    /// Everything is stopped even if the logout fails, and the logout's error is returned.
+    pub async fn shutdown(self) -> Result<(), SessionError> {
+        let logout = match self.mailbox.send(Logout).await {
+            Ok(result) => result,
+            Err(e) => Err(SessionError::Mailbox(MailboxError::new(format!("{}", e)))),
+        };
+        if let Err(e) = &logout {
+            warn!("Failed to log out before shutting down: {:?}", e);
+        }
+        self.listener.abort();
+        let _ = self.listener.await;
+        // the mailbox may already have stopped
+        let _ = self.mailbox.send(Shutdown).await;
+        logout
+    }
+}
+
+/// This starts the mailbox and the UI listener.
+/// Returns a TimeoutError if the mailbox doesn't start running within DEFAULT_STARTUP_TIMEOUT.
+/// Await the returned listener to block forever, or call shutdown to stop them.
 /// This should be run in its own thread, so as not to block anything else.
 /// Also be sure that this is running within an actix system, or else it will fail silently.
+/// The mailbox started here never resumes a circuit whose pings go unanswered. To resume, start a
+/// mailbox with Mailbox::new and set its resume_after_missed_pongs, like to
+/// DEFAULT_MISSED_PONGS_BEFORE_RESUME.
 ///```
 /// use metaverse_session::initialize::initialize;
 /// use log::{info, error};
@@ -34,7 +62,7 @@ use portpicker::pick_unused_port;
 ///    System::new().block_on(async {
 ///        match initialize(ui_to_server_socket, server_to_ui_socket).await {
 ///            Ok(handle) => {
-///                match handle.await {
+///                match handle.listener.await {
 ///                    Ok(()) => info!("Listener exited successfully!"),
 ///                    Err(e) => error!("Listener exited with error {:?}", e),
 ///                };
@@ -49,39 +77,21 @@ use portpicker::pick_unused_port;
 pub async fn initialize(
     ui_to_server_socket: u16,
     server_to_ui_socket: u16,
-) -> Result<JoinHandle<()>, SessionError> {
-    let notify = Arc::new(Notify::new());
-    let state = Arc::new(Mutex::new(ServerState::Starting));
-
-    let mailbox = Mailbox {
-        client_socket: pick_unused_port().unwrap(),
-        server_to_ui_socket: format!("127.0.0.1:{}", server_to_ui_socket),
-        packet_sequence_number: Arc::new(Mutex::new(0u32)),
-
-        ack_queue: Arc::new(Mutex::new(HashMap::new())),
-
-        state: state.clone(),
-        notify: notify.clone(),
-        session: None,
-        sent_packet_count: 0,
-        ping_info: PingInfo {
-            ping_number: 0,
-            ping_latency: Duration::new(0, 0),
-            last_ping: time::Instant::now(),
-        },
-    }
-    .start();
-    // wait until the mailbox starts
-    notify.notified().await;
-    if *state.lock().unwrap() != ServerState::Running {
-        return Err(SessionError::Mailbox(MailboxError {
-            message: ("Mailbox failed to enter state Running.".to_string()),
-        }));
-    };
+) -> Result<ClientHandle, SessionError> {
+    let mailbox = Mailbox::new(
+        pick_unused_port().unwrap(),
+        format!("127.0.0.1:{}", server_to_ui_socket),
+    );
+    let running = mailbox.wait_for_running(DEFAULT_STARTUP_TIMEOUT);
+    let mailbox = mailbox.start();
+    running.await?;
 
-    let handle = actix::spawn(async move {
-        listen_for_ui_messages(format!("127.0.0.1:{}", ui_to_server_socket), mailbox).await;
+    let listener = actix::spawn({
+        let mailbox = mailbox.clone();
+        async move {
+            listen_for_ui_messages(format!("127.0.0.1:{}", ui_to_server_socket), mailbox).await;
+        }
     });
 
-    Ok(handle)
+    Ok(ClientHandle { mailbox, listener })
 }
@@ -9,11 +9,32 @@
 //! This isn't ready for any kind of serious use yet! Check back later for updates!
 
 #![warn(missing_docs)]
+/// This module counts and limits the bandwidth used by the packets sent to the server
+pub mod bandwidth;
 /// This module is for running in your client to subscribe to the server events
 pub mod client_subscriber;
+/// This module abstracts the time, for testing timeouts and latency
+pub mod clock;
+/// This module transforms the datagrams sent to and received from the server, for grids that
+/// obfuscate the UDP stream
+pub mod codec;
+/// This module long-polls the event queue capability for events that aren't sent over UDP
+pub mod event_queue;
+/// This module wraps the mailbox in a typed API for sending packets
+pub mod handle;
 /// This module initializes the mailbox
 pub mod initialize;
 /// This module handles packet IO and logic
 pub mod mailbox;
+/// This module puts reliable packets from the server back in order
+pub mod reorder;
+/// This module records the datagrams from the server, and replays them into the mailbox
+pub mod replay;
 /// This module is to allowe the server to receive messages from the UI.
 pub mod server_subscriber;
+/// This module assembles textures sent over UDP
+pub mod texture;
+/// This module abstracts the connection to the external server, for testing
+pub mod transport;
+/// This module receives the events the mailbox sends to the UI as a stream
+pub mod ui_stream;
@@ -1,41 +1,176 @@
 use actix::prelude::*;
 use actix_rt::time;
 use bincode;
+use glam::Vec3;
 use log::{error, info, warn};
+use metaverse_messages::agent_animation::AgentAnimation;
+use metaverse_messages::agent_height_width::AgentHeightWidth;
+use metaverse_messages::agent_pause::AgentPause;
+use metaverse_messages::agent_resume::AgentResume;
+use metaverse_messages::agent_set_appearance::AgentSetAppearance;
+use metaverse_messages::agent_throttle::{AgentThrottle, Throttles};
+use metaverse_messages::avatar_appearance::AvatarAppearance;
+use metaverse_messages::chat_from_simulator::{ChatFromSimulator, SourceType};
+use metaverse_messages::chat_from_viewer::ClientChatType;
+use metaverse_messages::circuit_code::CircuitCodeData;
+use metaverse_messages::complete_agent_movement::CompleteAgentMovementData;
 use metaverse_messages::complete_ping_check::CompletePingCheck;
-use metaverse_messages::packet::MessageType;
+use metaverse_messages::friend_presence::FriendPresence;
+use metaverse_messages::image_data::ImageData;
+use metaverse_messages::image_packet::ImagePacket;
+use metaverse_messages::login_system::login_response::{Buddy, HomeValues};
+use metaverse_messages::login_system::login_summary::LoginSummary;
+use metaverse_messages::logout_request::LogoutRequest;
+use metaverse_messages::object_update_cached::CachedObject;
 use metaverse_messages::packet::Packet;
-use metaverse_messages::packet_ack::PacketAck;
+use metaverse_messages::packet::PacketCategory;
+use metaverse_messages::packet::PacketData;
+use metaverse_messages::packet_ack::MAX_PACKET_ACKS;
 use metaverse_messages::packet_types::PacketType;
 use metaverse_messages::region_handshake_reply::AgentData;
 use metaverse_messages::region_handshake_reply::RegionHandshakeReply;
 use metaverse_messages::region_handshake_reply::ReplyRegionInfo;
+use metaverse_messages::request_image::{ImageRequest, RequestImage};
+use metaverse_messages::request_multiple_objects::{
+    CacheMissType, ObjectRequest, RequestMultipleObjects,
+};
+use metaverse_messages::start_ping_check::StartPingCheck;
 use metaverse_messages::ui_events::UiEventTypes;
+use metaverse_messages::utils::agent_access::AgentAccess;
+use metaverse_messages::utils::region_handle::RegionHandle;
+use metaverse_messages::uuid_name_reply::UUIDNameBlock;
+use metaverse_messages::uuid_name_request::UUIDNameRequest;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::net::UdpSocket as SyncUdpSocket;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket as SyncUdpSocket};
 use std::sync::Arc;
 use std::sync::Mutex;
 use tokio::net::UdpSocket;
-use tokio::sync::{oneshot, Notify};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::{oneshot, watch, Notify};
+use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use uuid::Uuid;
 
-use metaverse_messages::errors::{AckError, SessionError};
+use crate::bandwidth::{Bandwidth, BandwidthStats};
+use crate::clock::{Clock, SystemClock};
+use crate::codec::{IdentityCodec, PacketCodec};
+use crate::reorder::ReorderBuffer;
+use crate::replay::{RecordedDatagram, Recorder, RecordingTransport, ReplayTransport};
+use crate::texture::TextureAssembler;
+use crate::transport::Transport;
 
-const ACK_ATTEMPTS: i8 = 3;
+use metaverse_messages::errors::{AckError, MailboxError, ResumeError, SessionError, TimeoutError};
+
+const ACK_ATTEMPTS: u8 = 3;
 const ACK_TIMEOUT: Duration = Duration::from_secs(1);
+/// how long a packet that was given up on stays in the ack queue, so an ack that arrives late can
+/// be told apart from an ack for a packet that was never sent
+const LATE_ACK_WINDOW: Duration = Duration::from_secs(10);
+/// how long inbound acks wait for an outgoing packet to ride on before being sent on their own
+const ACK_FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+/// how many outgoing packets can wait for the socket before unreliable packets are dropped
+const OUTBOUND_QUEUE_SIZE: usize = 1024;
+/// how often the mailbox sends a StartPingCheck to the server by default
+pub const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(5);
+/// how long initialize waits for the mailbox to start running
+pub const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// a good number of pings in a row to let go unanswered before resuming the circuit
+pub const DEFAULT_MISSED_PONGS_BEFORE_RESUME: u32 = 3;
+/// how many latency samples are kept for the min and max latency
+const PING_WINDOW_SIZE: usize = 20;
+/// how much weight a new latency sample has in the smoothed average
+const PING_SMOOTHING_FACTOR: f64 = 0.125;
+/// the default size of the datagrams sent to the UI, including the chunk header
+pub const DEFAULT_UI_MESSAGE_SIZE: usize = 1024;
+/// the largest payload a UDP datagram can carry. The UI listener reads datagrams of this size, so
+/// any max_ui_message_size up to this can be used.
+pub const MAX_UI_MESSAGE_SIZE: usize = 65507;
+/// the encoded size of a UiMessage chunk with no content: the event's u16 tag, the sequence
+/// number, the chunk count, the message id and the length of the content
+pub const UI_MESSAGE_HEADER_SIZE: usize = 2 + 2 + 2 + 4 + 8;
+/// the smallest datagrams the mailbox falls back to when the OS rejects larger ones
+const MIN_UI_DATAGRAM_SIZE: usize = 1024;
+/// how many messages are held for the UI while it is not listening
+const UI_BACKLOG_SIZE: usize = 64;
+/// how long to wait before retrying the UI after it was not listening
+const UI_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// the longest the retry delay backs off to
+const UI_MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// the most packets held while waiting for the UDP socket. The oldest is dropped when it is full.
+const PENDING_PACKET_LIMIT: usize = 64;
+
+/// the reliable packets that are waiting for an ack, by sequence number
+pub type AckQueue = Arc<Mutex<HashMap<u32, PendingAck>>>;
+
+/// a reliable packet that is waiting for an ack
+#[derive(Debug)]
+pub struct PendingAck {
+    /// resolved when the ack arrives
+    pub sender: oneshot::Sender<()>,
+    /// when the packet was first sent
+    pub first_sent: time::Instant,
+    /// how many times the packet has been sent, including this one
+    pub attempts: u8,
+    /// when the last attempt timed out without an ack. The packet is no longer waiting, and is
+    /// removed once LATE_ACK_WINDOW has passed.
+    pub given_up: Option<time::Instant>,
+}
+
+/// resolves the packet that is waiting for an ack
+fn receive_ack(queue: &mut HashMap<u32, PendingAck>, packet_id: u32) {
+    match queue.remove(&packet_id) {
+        Some(PendingAck {
+            given_up: Some(given_up),
+            attempts,
+            ..
+        }) => {
+            info!(
+                "ack for packet {} arrived {:?} after it was given up on after {} attempts",
+                packet_id,
+                given_up.elapsed(),
+                attempts
+            );
+        }
+        Some(pending) => {
+            let _ = pending.sender.send(());
+        }
+        // the server acks each copy of a resent packet, so duplicate acks are expected
+        None => {}
+    }
+}
 
 /// This is the mailbox for handling packets and sessions in the client
 #[derive(Debug)]
 pub struct Mailbox {
     /// the client socket for UDP connections
     pub client_socket: u16,
+    /// the address the client socket is bound to. Defaults to every interface, so the server can
+    /// be on another machine. An unspecified address is bound in the family of the simulator's
+    /// address, so IPv6 simulators are reached over an IPv6 socket.
+    pub bind_address: IpAddr,
+    /// the address the session's UDP socket is bound to, once it is bound
+    pub local_address: Option<SocketAddr>,
     /// UDP socket for connecting mailbox to the UI
     pub server_to_ui_socket: String,
+    /// the socket used to send messages to the UI. It is bound on the first message and reused
+    /// for every chunk after that.
+    pub ui_socket: Option<SyncUdpSocket>,
+    /// messages waiting for the UI to start listening, oldest first
+    pub ui_backlog: VecDeque<UiMessage>,
+    /// the last message sent to the UI. The kernel only reports that nothing is listening on the
+    /// send after the one that was lost, so it is kept until then to be sent again.
+    pub ui_unconfirmed: Option<UiMessage>,
+    /// how long until the backlog is retried. None when the UI is listening.
+    pub ui_retry_delay: Option<Duration>,
 
     /// queue of ack packets to handle
-    pub ack_queue: Arc<Mutex<HashMap<u32, oneshot::Sender<()>>>>,
+    pub ack_queue: AckQueue,
+    /// sequence numbers of received reliable packets that have not been acked yet.
+    /// These are appended to the next outgoing packet, or flushed as a PacketAck.
+    pub pending_acks: Arc<Mutex<Vec<u32>>>,
 
     /// global number of received packets
     pub packet_sequence_number: Arc<Mutex<u32>>,
@@ -43,21 +178,130 @@ pub struct Mailbox {
     pub state: Arc<Mutex<ServerState>>,
     /// notify for etablishing when it begins running
     pub notify: Arc<Notify>,
+    /// publishes every state change, for consumers that need to react to all transitions
+    pub state_sender: watch::Sender<ServerState>,
     /// Session information for after login
     pub session: Option<Session>,
+    /// packets sent before the mailbox was running or the UDP socket was bound, like the
+    /// CircuitCode sent right after login. They are sent once the socket is ready.
+    pub pending_packets: VecDeque<Packet>,
+    /// whether the region handshake with the current simulator is complete. AgentUpdates are
+    /// dropped until it is, because some servers disconnect agents that move before it.
+    pub handshake_complete: bool,
+    /// the appearance of the agent. It is sent again every time the agent arrives in a region,
+    /// so other viewers don't draw it as a cloud.
+    pub appearance: Option<AgentSetAppearance>,
+    /// the name of the current region, from its RegionHandshake
+    pub region_name: Option<String>,
+    /// the agent's position in the current region, from the last AgentMovementComplete or
+    /// CoarseLocationUpdate. CoarseLocationUpdates are only accurate to a meter.
+    pub position: Option<Vec3>,
+    /// the agent's currency balance, from the last MoneyBalanceReply
+    pub balance: Option<i32>,
+    /// whether the UI has been sent the LoginCompleteEvent for the current session
+    pub login_complete: bool,
 
-    /// the global number of packets that have been sent to the UI
-    pub sent_packet_count: u16,
+    /// the global number of messages that have been sent to the UI.
+    /// This is the message_id of the next UiMessage. A message sent again takes a new one.
+    pub sent_packet_count: u32,
+    /// the largest datagram sent to the UI. Messages larger than this are split into chunks.
+    /// Raise this to send large payloads in fewer chunks. It is capped at MAX_UI_MESSAGE_SIZE.
+    pub max_ui_message_size: usize,
+    /// the largest datagram the OS lets the UI socket send, if it is smaller than
+    /// MAX_UI_MESSAGE_SIZE. This is found the first time a chunk is rejected as too large, and
+    /// the chunks are clamped to it from then on.
+    pub ui_max_datagram_size: Option<usize>,
 
     /// the global ping information
     pub ping_info: PingInfo,
+    /// the time ping latency and ack timeouts are measured with.
+    /// Set this to a MockClock before starting the mailbox to test them without real sleeps.
+    pub clock: Arc<dyn Clock>,
+    /// the transform applied to every datagram sent to and received from the server, for grids
+    /// that obfuscate the UDP stream. Set this before starting the mailbox.
+    pub codec: Arc<dyn PacketCodec>,
+    /// records every datagram received from the server, to replay it later with
+    /// replay_from_file. Set this before the session's socket is started.
+    pub recorder: Option<Arc<Recorder>>,
+    /// how often to send a StartPingCheck to the server. None disables pinging.
+    pub ping_interval: Option<Duration>,
+    /// whether pings are held back while the agent is paused
+    pub pings_suspended: bool,
+    /// the serial number of the last AgentPause or AgentResume
+    pub pause_serial_num: u32,
+    /// how many pings in a row can go unanswered before the circuit is resumed on a new socket,
+    /// like after the client's network changed. Resuming is opt-in: None, the default, never
+    /// resumes. Clients that move between networks, like on a phone, should set this to
+    /// Some(DEFAULT_MISSED_PONGS_BEFORE_RESUME) before starting the mailbox.
+    pub resume_after_missed_pongs: Option<u32>,
+    /// whether a ResumeCircuit is waiting for the server to accept the resumed circuit
+    pub resuming: bool,
+    /// the tasks reading from and writing to the session's socket. These are stopped before the
+    /// socket is replaced.
+    pub transport_tasks: Vec<JoinHandle<()>>,
+    /// the bytes written to the server. Call set_limit on it to cap the outgoing bandwidth.
+    pub bandwidth: Arc<Mutex<Bandwidth>>,
+
+    /// bandwidth limits sent to the server in the AgentThrottle after the handshake
+    pub throttles: Throttles,
+    /// the height and width of the client's window in pixels, sent to the server in an
+    /// AgentHeightWidth after the handshake. Use SetWindowSize to change it once connected.
+    pub window_size: Option<(u16, u16)>,
+    /// the generation of the last AgentHeightWidth sent, so the server can ignore old ones
+    pub height_width_gen_counter: u32,
+
+    /// first and last names of agents, from UUIDNameReply packets
+    pub name_cache: HashMap<Uuid, (String, String)>,
+
+    /// the CRC of every object in the region the client has received, by local ID.
+    /// ObjectUpdateCached entries are checked against it to find the objects to request.
+    pub object_crcs: HashMap<u32, u32>,
+
+    /// agents and objects whose chat is not sent to the UI. Chat is dropped if either its source
+    /// or its owner is muted, so muting an agent also mutes their objects.
+    pub mute_list: HashSet<Uuid>,
+    /// which kinds of chat are sent to the UI
+    pub chat_filter: ChatFilter,
+
+    /// textures requested over UDP that are still being received
+    pub textures: TextureAssembler,
+
+    /// the appearances of the other avatars in the region, from AvatarAppearance packets, by
+    /// agent ID
+    pub avatar_appearances: HashMap<Uuid, AvatarAppearance>,
+    /// request the baked textures of other avatars over UDP when their appearance arrives, so
+    /// they are sent to the UI as TextureEvents
+    pub request_avatar_textures: bool,
+
+    /// hold reliable packets from the server until the packets before them arrive, so the UI
+    /// receives them in order. A missing packet is waited on for at most this long.
+    /// None handles packets in the order they arrive, which adds no latency.
+    pub in_order_delivery: Option<Duration>,
+
+    /// send outgoing chat straight back to the UI as a ChatFromSimulatorEvent, instead of waiting
+    /// for the server to echo it. Leave this off if the server echo is displayed.
+    pub local_chat_echo: bool,
+
+    /// the neighboring regions the current simulator has enabled, and the addresses of their
+    /// circuits
+    pub neighbors: HashMap<RegionHandle, SocketAddr>,
+    /// connect to every neighboring region as soon as it is enabled, so crossing into it doesn't
+    /// have to wait for the circuit handshake
+    pub auto_connect_neighbors: bool,
+    /// the circuits to neighboring regions. Each one is a mailbox of its own, with its own socket
+    /// and sequence numbers, sharing this mailbox's bandwidth limit.
+    pub neighbor_circuits: HashMap<RegionHandle, Addr<Mailbox>>,
+    /// whether this mailbox is the circuit to a neighboring region. Neighbor circuits don't send
+    /// events to the UI, and stop when their simulator disables them.
+    pub is_neighbor: bool,
 }
 
 /// Session of the user
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct Session {
-    /// url of the server where the UDP session is connected to
+    /// url of the server where the UDP session is connected to. This is usually an IPv4 or IPv6
+    /// address, like the sim_ip of the login response.
     pub url: String,
     /// socket of the server where the UDP session is connected to
     pub server_socket: u16,
@@ -65,8 +309,53 @@ pub struct Session {
     pub agent_id: Uuid,
     /// session ID of the user
     pub session_id: Uuid,
-    /// the running UDP socket attached to the session  
-    pub socket: Option<Arc<UdpSocket>>,
+    /// circuit code of the UDP session
+    pub circuit_code: u32,
+    /// full name of the user, used as the sender of locally echoed chat
+    pub agent_name: String,
+    /// the current maturity access level of the user
+    pub agent_access: Option<AgentAccess>,
+    /// the highest maturity rating of region the user is allowed to enter
+    pub agent_access_max: Option<AgentAccess>,
+    /// the agent's home, from the login response
+    pub home: Option<HomeValues>,
+    /// the user's friends and the rights they have given each other, from the login response
+    pub buddy_list: Vec<Buddy>,
+    /// the running UDP socket attached to the session.
+    /// Set this to a MockTransport before sending the session to test without a network.
+    pub socket: Option<Arc<dyn Transport>>,
+    /// queue of outgoing packets, written to the socket one at a time by start_udp_write
+    pub outbound: Option<mpsc::Sender<OutboundPacket>>,
+    /// the avatars in the current region, keyed by agent ID.
+    /// This is replaced on every CoarseLocationUpdate. Use GetAgents to take a snapshot.
+    pub agent_list: Arc<Mutex<HashMap<Uuid, Avatar>>>,
+}
+
+/// an avatar in the current region
+#[derive(Debug, Clone, PartialEq)]
+pub struct Avatar {
+    /// agent ID of the avatar
+    pub agent_id: Uuid,
+    /// position of the avatar in the region, in meters.
+    /// This comes from the CoarseLocationUpdate, so it is only accurate to a meter, and the height
+    /// to four meters.
+    pub position: Vec3,
+    /// what the avatar looks like, once its AvatarAppearance has arrived
+    pub appearance: Option<AvatarAppearance>,
+}
+
+/// an encoded packet waiting to be written to the UDP socket
+#[derive(Debug)]
+pub struct OutboundPacket {
+    /// the encoded packet
+    pub data: Vec<u8>,
+    /// the address of the server to send it to
+    pub addr: String,
+    /// reliable packets wait for bandwidth instead of being dropped
+    pub reliable: bool,
+    /// told when the packet has been written to the socket, so a reliable packet's ack timeout
+    /// doesn't start while it is still waiting for bandwidth
+    pub sent: Option<oneshot::Sender<()>>,
 }
 
 /// Format for sending a serialized message from the mailbox to the UI.
@@ -75,12 +364,13 @@ pub struct Session {
 pub struct UiMessage {
     /// Type of message, for decoding in the UI
     pub message_type: UiEventTypes,
-    /// Which number in a series of messages is it
+    /// the index of this chunk within the message, starting at 0
     pub sequence_number: u16,
-    /// how mant messages are there in total
+    /// how many chunks the message was split into
     pub total_packet_number: u16,
-    /// for serializing
-    pub packet_number: u16,
+    /// the id of the message this chunk belongs to. Every message sent by the mailbox gets a new
+    /// id, so the UI can tell the chunks of messages that are in flight at the same time apart.
+    pub message_id: u32,
     /// the encoded message to be decoded by the UI
     pub message: Vec<u8>,
 }
@@ -101,7 +391,7 @@ impl UiMessage {
             message,
             sequence_number: 0,
             total_packet_number: 0,
-            packet_number: 0,
+            message_id: 0,
         }
     }
 }
@@ -112,26 +402,391 @@ impl UiMessage {
 pub struct PingInfo {
     /// the number of the ping
     pub ping_number: u8,
-    /// how long the latency is. Currently not doing anything.
+    /// the most recent latency sample
     pub ping_latency: Duration,
+    /// exponentially weighted moving average of the latency
+    pub average_latency: Duration,
+    /// the most recent latency samples, used for the min and max
+    pub latency_samples: VecDeque<Duration>,
     /// time of last ping
     pub last_ping: time::Instant,
+    /// whether the last ping is still waiting for its pong
+    pub awaiting_pong: bool,
+    /// how many pings in a row went unanswered
+    pub missed_pongs: u32,
+}
+impl PingInfo {
+    /// create a new PingInfo with no latency samples
+    pub fn new() -> Self {
+        PingInfo {
+            ping_number: 0,
+            ping_latency: Duration::new(0, 0),
+            average_latency: Duration::new(0, 0),
+            latency_samples: VecDeque::with_capacity(PING_WINDOW_SIZE),
+            last_ping: time::Instant::now(),
+            awaiting_pong: false,
+            missed_pongs: 0,
+        }
+    }
+
+    /// add a new latency sample, updating the average and the sample window
+    pub fn record_latency(&mut self, latency: Duration) {
+        self.ping_latency = latency;
+        self.average_latency = if self.latency_samples.is_empty() {
+            latency
+        } else {
+            self.average_latency.mul_f64(1.0 - PING_SMOOTHING_FACTOR)
+                + latency.mul_f64(PING_SMOOTHING_FACTOR)
+        };
+        if self.latency_samples.len() == PING_WINDOW_SIZE {
+            self.latency_samples.pop_front();
+        }
+        self.latency_samples.push_back(latency);
+    }
+
+    /// the current latency statistics
+    pub fn stats(&self) -> PingStats {
+        PingStats {
+            latency: self.ping_latency,
+            average_latency: self.average_latency,
+            min_latency: self
+                .latency_samples
+                .iter()
+                .min()
+                .copied()
+                .unwrap_or_default(),
+            max_latency: self
+                .latency_samples
+                .iter()
+                .max()
+                .copied()
+                .unwrap_or_default(),
+            bandwidth: BandwidthStats::default(),
+        }
+    }
+}
+impl Default for PingInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// latency statistics of the pings sent to the server, and how much has been written to it
+#[derive(Debug, Clone, Default, MessageResponse)]
+pub struct PingStats {
+    /// the most recent latency sample
+    pub latency: Duration,
+    /// the smoothed latency. This is the stable number to display.
+    pub average_latency: Duration,
+    /// the lowest latency in the sample window
+    pub min_latency: Duration,
+    /// the highest latency in the sample window
+    pub max_latency: Duration,
+    /// the bytes and packets written to the server
+    pub bandwidth: BandwidthStats,
+}
+
+/// message to send to the mailbox to retrieve the current PingStats
+#[derive(Debug, Message)]
+#[rtype(result = "PingStats")]
+pub struct PingQuery;
+
+/// message to send to the mailbox to look up an agent's first and last name.
+/// If the name isn't cached yet, this returns None and sends a UUIDNameRequest to the server.
+/// The UI receives a UUIDNameReplyEvent when the name arrives.
+#[derive(Debug, Message)]
+#[rtype(result = "Option<(String, String)>")]
+pub struct ResolveName(pub Uuid);
+
+/// message that gets sent when receiving a UUIDNameReply, to add the names to the cache
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct CacheNames {
+    names: Vec<UUIDNameBlock>,
+}
+
+/// Chooses which kinds of chat the mailbox sends to the UI, by where the chat came from.
+/// Agent chat is always shown. Everything is shown by default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatFilter {
+    /// show chat from objects, like HUDs and vendors
+    pub show_object: bool,
+    /// show chat from the simulator itself
+    pub show_system: bool,
+}
+
+impl Default for ChatFilter {
+    fn default() -> Self {
+        ChatFilter {
+            show_object: true,
+            show_system: true,
+        }
+    }
+}
+
+impl ChatFilter {
+    /// returns true if the chat should be sent to the UI
+    pub fn allows(&self, chat: &ChatFromSimulator) -> bool {
+        match chat.source_type {
+            SourceType::Object => self.show_object,
+            SourceType::System => self.show_system,
+            SourceType::Agent | SourceType::Unknown => true,
+        }
+    }
+}
+
+/// ask the mailbox whether the session is usable. See Mailbox::is_ready.
+#[derive(Debug, Message)]
+#[rtype(result = "bool")]
+pub struct Ready;
+
+/// send a packet to the server, and wait until it has been acked.
+/// Unreliable packets resolve as soon as they have been queued.
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), SessionError>")]
+pub struct SendPacket(pub Packet);
+
+/// send a packet to the server like a Packet message, with its reliability overridden.
+/// This is for packets that are only sometimes reliable, like viewers sending the first
+/// AgentUpdate reliably and the rest unreliably.
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct SendPacketWithReliability {
+    /// the packet to send
+    pub packet: Packet,
+    /// whether the packet is sent reliably. None keeps the packet's own reliability.
+    pub reliable: Option<bool>,
+}
+
+/// ask the server to stop sending object and texture updates to the agent, and wait until the
+/// server has acked it. With suspend_pings, no pings are sent until the agent is resumed.
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), SessionError>")]
+pub struct Pause {
+    /// stop pinging the server until the agent is resumed
+    pub suspend_pings: bool,
+}
+
+/// start and stop animations on the agent's own avatar, given as (animation UUID, start) pairs,
+/// and wait until the server has acked it
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), SessionError>")]
+pub struct Animate(pub Vec<(Uuid, bool)>);
+
+/// ask the server to send updates to the agent again after a Pause, and resume pinging
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), SessionError>")]
+pub struct Resume;
+
+/// tell the server the client's window has been resized, in pixels. The size is kept and sent
+/// again after every handshake.
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), SessionError>")]
+pub struct SetWindowSize {
+    /// the window's height in pixels
+    pub height: u16,
+    /// the window's width in pixels
+    pub width: u16,
+}
+
+/// resume the circuit on a new socket, without logging in again. The session's circuit code,
+/// agent and session IDs are sent to the simulator again in a UseCircuitCode and a
+/// CompleteAgentMovement.
+/// Resolves when the server acks the UseCircuitCode, or fails with a ResumeError if it doesn't.
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), SessionError>")]
+pub struct ResumeCircuit;
+
+/// play back recorded datagrams as if they were arriving from the server, instead of reading
+/// from the session's socket. Packets sent while the recording plays are discarded.
+/// Use replay_from_file to replay a recording made by a Recorder.
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct Replay(pub Vec<RecordedDatagram>);
+
+/// log the session's agent out, and wait until the server has acked it.
+/// Resolves immediately if there is no session.
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), SessionError>")]
+pub struct Logout;
+
+/// stop the mailbox. Packets that haven't been sent yet are dropped.
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct Shutdown;
+
+/// query the mailbox for the reliable packets that are still waiting for an ack, for debugging
+/// stuck retransmissions
+#[derive(Debug, Message)]
+#[rtype(result = "Vec<AckQueueEntry>")]
+pub struct DumpAckQueue;
+
+/// a reliable packet that is waiting for an ack, as returned by DumpAckQueue
+#[derive(Debug, Clone, PartialEq)]
+pub struct AckQueueEntry {
+    /// the sequence number of the packet
+    pub packet_id: u32,
+    /// how long it has been since the packet was first sent
+    pub waiting: Duration,
+    /// how many times the packet has been sent
+    pub attempts: u8,
+}
+
+/// stop sending chat from an agent or object to the UI
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct Mute(pub Uuid);
+
+/// send chat from a muted agent or object to the UI again
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct Unmute(pub Uuid);
+
+/// message that gets sent when receiving the first packet of a texture, to assemble it before it
+/// is sent to the UI
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct ReceivedImageData {
+    /// the first packet of the texture
+    pub image: ImageData,
+}
+
+/// message that gets sent when receiving a piece of a texture
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct ReceivedImagePacket {
+    /// the piece of the texture
+    pub image: ImagePacket,
+}
+
+/// message that gets sent when receiving objects, to remember their CRCs
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct CacheObjects {
+    /// the local ID and CRC of each object
+    pub objects: Vec<(u32, u32)>,
+}
+
+/// message that gets sent when receiving an ObjectUpdateCached, to request the objects that
+/// aren't cached
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct ReceivedCachedObjects {
+    /// the objects the server expects the client to have
+    pub objects: Vec<CachedObject>,
+}
+
+/// message that gets sent when receiving a ChatFromSimulator, to filter it before it is sent to
+/// the UI
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct ReceivedChat {
+    chat: ChatFromSimulator,
+}
+
+/// message that gets sent when receiving a CoarseLocationUpdate, to replace the agent list
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct UpdateAgents {
+    agents: Vec<(Option<Uuid>, Vec3)>,
+    /// the agent's own position, if the update has it
+    you: Option<Vec3>,
+}
+
+/// message that gets sent when receiving an AvatarAppearance, to remember it and request the
+/// avatar's baked textures
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct ReceivedAppearance {
+    appearance: AvatarAppearance,
 }
 
-/// this is a simple message that gets sent when receiving the CompletePingcheck
+/// query the mailbox for a snapshot of the avatars in the current region
+#[derive(Debug, Message)]
+#[rtype(result = "Vec<Avatar>")]
+pub struct GetAgents;
+
+/// query the mailbox for the agent's position in the current region, if it is known
+#[derive(Debug, Message)]
+#[rtype(result = "Option<Vec3>")]
+pub struct GetPosition;
+
+/// get the clock the mailbox keeps time with, so waits outside of the mailbox keep the same time
+#[derive(Debug, Message)]
+#[rtype(result = "Arc<dyn Clock>")]
+pub struct GetClock;
+
+/// this is a simple message that gets sent when receiving the StartPingCheck
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct Ping {
     ping_id: u8,
 }
 
+/// this is a simple message that gets sent when receiving the CompletePingCheck
+/// in response to a StartPingCheck sent by the mailbox
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct Pong {
+    ping_id: u8,
+}
+
+/// set the appearance of the agent, and send it to the simulator if the agent is in a region
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct SetAppearance(pub AgentSetAppearance);
+
+/// message to send when receiving an AgentMovementComplete
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct MovementComplete {
+    /// the region the agent arrived in
+    pub region_handle: RegionHandle,
+    /// where the agent arrived, in region coordinates
+    pub position: Vec3,
+}
+
 /// message to send when receiving a RegionHandshake
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
-pub struct RegionHandshakeMessage;
+pub struct RegionHandshakeMessage {
+    /// the name of the region
+    pub region_name: String,
+}
+
+/// message to send when receiving a MoneyBalanceReply, to remember the balance for the
+/// LoginCompleteEvent
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct UpdateBalance(pub i32);
 
-/// The state of the Mailbox
-#[derive(Debug, Clone, PartialEq)]
+/// message that gets sent when receiving an EnableSimulator, to remember the neighboring region
+/// and connect to it if auto_connect_neighbors is set
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct EnableNeighbor {
+    /// the neighboring region
+    pub region_handle: RegionHandle,
+    /// the address of the neighbor's simulator
+    pub address: SocketAddr,
+}
+
+/// move the session to a new simulator, after a teleport or a region crossing.
+/// The agent and session IDs are kept, and the circuit handshake is sent to the new simulator.
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct ChangeSimulator {
+    /// url of the new simulator
+    pub url: String,
+    /// UDP port of the new simulator
+    pub server_socket: u16,
+    /// circuit code for the new simulator
+    pub circuit_code: u32,
+}
+
+/// The state of the Mailbox.
+/// It is sent to the UI in a StateChangeEvent, encoded with to_bytes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ServerState {
     /// The mailbox starts in the Starting state
     Starting,
@@ -142,87 +797,155 @@ pub enum ServerState {
     /// the mailbox is stopped
     Stopped,
 }
+impl ServerState {
+    /// encode the state for a StateChangeEvent
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Failed to serialize ServerState")
+    }
+
+    /// decode the state from a StateChangeEvent
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        bincode::deserialize(bytes).ok()
+    }
+}
 
 impl Mailbox {
+    /// create a mailbox with the default settings, that sends UI events to server_to_ui_socket
+    pub fn new(client_socket: u16, server_to_ui_socket: String) -> Self {
+        Mailbox {
+            client_socket,
+            bind_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            local_address: None,
+            server_to_ui_socket,
+            ui_socket: None,
+            ui_backlog: VecDeque::new(),
+            ui_unconfirmed: None,
+            ui_retry_delay: None,
+            packet_sequence_number: Arc::new(Mutex::new(0u32)),
+
+            ack_queue: Arc::new(Mutex::new(HashMap::new())),
+            pending_acks: Arc::new(Mutex::new(Vec::new())),
+
+            state: Arc::new(Mutex::new(ServerState::Starting)),
+            notify: Arc::new(Notify::new()),
+            state_sender: watch::channel(ServerState::Starting).0,
+            session: None,
+            pending_packets: VecDeque::new(),
+            handshake_complete: false,
+            appearance: None,
+            region_name: None,
+            position: None,
+            balance: None,
+            login_complete: false,
+            sent_packet_count: 0,
+            max_ui_message_size: DEFAULT_UI_MESSAGE_SIZE,
+            ui_max_datagram_size: None,
+            ping_info: PingInfo::new(),
+            clock: Arc::new(SystemClock),
+            codec: Arc::new(IdentityCodec),
+            recorder: None,
+            ping_interval: Some(DEFAULT_PING_INTERVAL),
+            pings_suspended: false,
+            pause_serial_num: 0,
+            resume_after_missed_pongs: None,
+            resuming: false,
+            transport_tasks: Vec::new(),
+            bandwidth: Arc::new(Mutex::new(Bandwidth::new())),
+            throttles: Throttles::default(),
+            window_size: None,
+            height_width_gen_counter: 0,
+            name_cache: HashMap::new(),
+            object_crcs: HashMap::new(),
+            mute_list: HashSet::new(),
+            chat_filter: ChatFilter::default(),
+            textures: TextureAssembler::new(),
+            avatar_appearances: HashMap::new(),
+            request_avatar_textures: true,
+            local_chat_echo: false,
+            in_order_delivery: None,
+            neighbors: HashMap::new(),
+            auto_connect_neighbors: false,
+            neighbor_circuits: HashMap::new(),
+            is_neighbor: false,
+        }
+    }
+
     /// Start_udp_read is for reading packets coming from the external server
     async fn start_udp_read(
-        ack_queue: Arc<Mutex<HashMap<u32, oneshot::Sender<()>>>>,
-        sock: Arc<UdpSocket>,
+        ack_queue: AckQueue,
+        pending_acks: Arc<Mutex<Vec<u32>>>,
+        sock: Arc<dyn Transport>,
+        codec: Arc<dyn PacketCodec>,
         mailbox_address: Addr<Mailbox>,
+        in_order_delivery: Option<Duration>,
     ) {
         let mut buf = [0; 1500];
+        let mut reorder = in_order_delivery.map(ReorderBuffer::new);
         loop {
-            match sock.recv_from(&mut buf).await {
-                Ok((size, _addr)) => {
+            let deadline = reorder.as_ref().and_then(|reorder| reorder.deadline());
+            let received = tokio::select! {
+                received = sock.recv_from(&mut buf) => received,
+                _ = sleep_until_deadline(deadline) => {
+                    // stop waiting for the missing packet, and release the ones held behind it
+                    let ready = reorder
+                        .as_mut()
+                        .map(|reorder| reorder.release_expired())
+                        .unwrap_or_default();
+                    for packet in ready {
+                        if !Mailbox::handle_inbound(packet, &ack_queue, &mailbox_address).await {
+                            return;
+                        }
+                    }
+                    continue;
+                }
+            };
+            // nothing is left to hand the packets to once the mailbox is gone
+            if !mailbox_address.connected() {
+                info!("mailbox has stopped, no longer reading from the transport");
+                return;
+            }
+            match received {
+                Ok((size, addr)) => {
                     //info!("Received {} bytes from {:?}", size, addr);
 
-                    let packet = match Packet::from_bytes(&buf[..size]) {
+                    let data = match codec.decode(&buf[..size]) {
+                        Ok(data) => data,
+                        Err(e) => {
+                            warn!("Failed to decode datagram: {}", e);
+                            continue;
+                        }
+                    };
+                    let packet = match Packet::from_bytes(&data) {
                         Ok(packet) => packet,
                         Err(_) => {
                             continue;
                         }
                     };
+                    // queue the ack to be appended to the next outgoing packet
                     if packet.header.reliable {
-                        if let Err(e) = mailbox_address
-                            .send(Packet::new_packet_ack(PacketAck {
-                                packet_ids: vec![packet.header.sequence_number],
-                            }))
-                            .await
-                        {
-                            warn!("Ack failed to send {:?}", e)
-                        };
+                        pending_acks
+                            .lock()
+                            .unwrap()
+                            .push(packet.header.sequence_number);
                     }
-
-                    match &packet.body {
-                        PacketType::PacketAck(data) => {
-                            let mut queue = ack_queue.lock().unwrap();
-                            for id in data.packet_ids.clone() {
-                                if let Some(sender) = queue.remove(&id) {
-                                    let _ = sender.send(());
-                                }
-                            }
-                        }
-                        PacketType::StartPingCheck(data) => {
-                            if let Err(e) = mailbox_address
-                                .send(Ping {
-                                    ping_id: data.ping_id,
-                                })
-                                .await
-                            {
-                                warn!("failed to handle pong {:?}", e)
-                            };
+                    // acks can be appended to any packet coming from the server
+                    if let Some(ack_list) = &packet.header.ack_list {
+                        let mut queue = ack_queue.lock().unwrap();
+                        for id in ack_list {
+                            receive_ack(&mut queue, *id);
                         }
-                        PacketType::RegionHandshake(_) => {
-                            match mailbox_address.send(RegionHandshakeMessage {}).await {
-                                Ok(_) => {}
-                                Err(e) => error!("error: {:?}", e),
-                            }
-                        }
-                        PacketType::DisableSimulator(_) => {
-                            warn!("Simulator shutting down...");
-                            if let Err(e) = mailbox_address
-                                .send(UiMessage::new(
-                                    UiEventTypes::DisableSimulatorEvent {},
-                                    vec![],
-                                ))
-                                .await
-                            {
-                                warn!("failed to send to ui: {:?}", e)
-                            }
-                            break;
-                        }
-                        _ => {}
                     }
-                    if let MessageType::Event = &packet.body.message_type() {
-                        if let Err(e) = mailbox_address
-                            .send(UiMessage::new(
-                                packet.body.ui_event(),
-                                packet.body.to_bytes(),
-                            ))
-                            .await
-                        {
-                            warn!("failed to send to ui: {:?}", e)
-                        };
+
+                    // acks are handled as soon as the packet arrives, but the body can be held
+                    // until the packets before it arrive
+                    let ready = match reorder.as_mut() {
+                        Some(reorder) => reorder.push(packet, addr),
+                        None => vec![packet],
+                    };
+                    for packet in ready {
+                        if !Mailbox::handle_inbound(packet, &ack_queue, &mailbox_address).await {
+                            return;
+                        }
                     }
                 }
                 Err(e) => {
@@ -233,75 +956,547 @@ impl Mailbox {
         }
     }
 
-    fn set_state(&mut self, new_state: ServerState, _ctx: &mut Context<Self>) {
-        let state_clone = Arc::clone(&self.state);
-        {
-            let mut state = state_clone.lock().unwrap();
-            *state = new_state.clone();
+    /// handle the body of a packet from the server, and forward it to the UI if it is an event.
+    /// Returns false when the simulator is shutting down and reading should stop.
+    async fn handle_inbound(
+        packet: Packet,
+        ack_queue: &AckQueue,
+        mailbox_address: &Addr<Mailbox>,
+    ) -> bool {
+        match &packet.body {
+            PacketType::PacketAck(data) => {
+                let mut queue = ack_queue.lock().unwrap();
+                for id in data.packet_ids.clone() {
+                    receive_ack(&mut queue, id);
+                }
+            }
+            PacketType::StartPingCheck(data) => {
+                if let Err(e) = mailbox_address
+                    .send(Ping {
+                        ping_id: data.ping_id,
+                    })
+                    .await
+                {
+                    warn!("failed to handle ping {:?}", e)
+                };
+            }
+            PacketType::CompletePingCheck(data) => {
+                if let Err(e) = mailbox_address
+                    .send(Pong {
+                        ping_id: data.ping_id,
+                    })
+                    .await
+                {
+                    warn!("failed to handle pong {:?}", e)
+                };
+            }
+            PacketType::UUIDNameReply(data) => {
+                if let Err(e) = mailbox_address
+                    .send(CacheNames {
+                        names: data.names.clone(),
+                    })
+                    .await
+                {
+                    warn!("failed to cache names {:?}", e)
+                };
+            }
+            PacketType::ChatFromSimulator(data) => {
+                // chat goes through the mailbox's filters instead of straight to the UI
+                if let Err(e) = mailbox_address
+                    .send(ReceivedChat {
+                        chat: *data.clone(),
+                    })
+                    .await
+                {
+                    warn!("failed to handle chat {:?}", e)
+                };
+                return true;
+            }
+            PacketType::ImageData(data) => {
+                if let Err(e) = mailbox_address
+                    .send(ReceivedImageData {
+                        image: *data.clone(),
+                    })
+                    .await
+                {
+                    warn!("failed to handle image data {:?}", e)
+                };
+            }
+            PacketType::ImagePacket(data) => {
+                if let Err(e) = mailbox_address
+                    .send(ReceivedImagePacket {
+                        image: *data.clone(),
+                    })
+                    .await
+                {
+                    warn!("failed to handle image packet {:?}", e)
+                };
+            }
+            PacketType::ObjectUpdateCompressed(data) => {
+                if let Err(e) = mailbox_address
+                    .send(CacheObjects {
+                        objects: data
+                            .objects
+                            .iter()
+                            .map(|object| (object.local_id, object.crc))
+                            .collect(),
+                    })
+                    .await
+                {
+                    warn!("failed to cache objects {:?}", e)
+                };
+            }
+            PacketType::ObjectUpdateCached(data) => {
+                if let Err(e) = mailbox_address
+                    .send(ReceivedCachedObjects {
+                        objects: data.objects.clone(),
+                    })
+                    .await
+                {
+                    warn!("failed to handle cached objects {:?}", e)
+                };
+            }
+            PacketType::AvatarAppearance(data) => {
+                if let Err(e) = mailbox_address
+                    .send(ReceivedAppearance {
+                        appearance: *data.clone(),
+                    })
+                    .await
+                {
+                    warn!("failed to handle avatar appearance {:?}", e)
+                };
+            }
+            PacketType::CoarseLocationUpdate(data) => {
+                if let Err(e) = mailbox_address
+                    .send(UpdateAgents {
+                        agents: data.positions(),
+                        you: data.your_position(),
+                    })
+                    .await
+                {
+                    warn!("failed to update agents {:?}", e)
+                };
+            }
+            PacketType::AgentMovementComplete(data) => {
+                if let Err(e) = mailbox_address
+                    .send(MovementComplete {
+                        region_handle: RegionHandle(data.region_handle),
+                        position: data.position,
+                    })
+                    .await
+                {
+                    warn!("failed to handle movement complete {:?}", e)
+                };
+            }
+            PacketType::OnlineNotification(data) => {
+                send_friend_presence(&data.agent_ids, true, mailbox_address).await;
+            }
+            PacketType::OfflineNotification(data) => {
+                send_friend_presence(&data.agent_ids, false, mailbox_address).await;
+            }
+            PacketType::MoneyBalanceReply(data) => {
+                if let Err(e) = mailbox_address
+                    .send(UpdateBalance(data.money_balance))
+                    .await
+                {
+                    warn!("failed to update balance {:?}", e)
+                };
+            }
+            PacketType::RegionHandshake(data) => {
+                match mailbox_address
+                    .send(RegionHandshakeMessage {
+                        region_name: data.region_info.sim_name.clone(),
+                    })
+                    .await
+                {
+                    Ok(_) => {}
+                    Err(e) => error!("error: {:?}", e),
+                }
+            }
+            PacketType::EnableSimulator(data) => {
+                if let Err(e) = mailbox_address
+                    .send(EnableNeighbor {
+                        region_handle: data.region_handle,
+                        address: data.address(),
+                    })
+                    .await
+                {
+                    warn!("failed to record neighbor: {:?}", e)
+                }
+            }
+            PacketType::DisableSimulator(_) => {
+                warn!("Simulator shutting down...");
+                if let Err(e) = mailbox_address
+                    .send(UiMessage::new(
+                        UiEventTypes::DisableSimulatorEvent {},
+                        vec![],
+                    ))
+                    .await
+                {
+                    warn!("failed to send to ui: {:?}", e)
+                }
+                return false;
+            }
+            _ => {}
         }
-        // notify on start and stop
-        if new_state == ServerState::Running || new_state == ServerState::Stopped {
-            self.notify.notify_one();
+        if let PacketCategory::UiEvent(event) = packet.classify() {
+            if let Err(e) = mailbox_address
+                .send(UiMessage::new(event, packet.body.to_bytes()))
+                .await
+            {
+                warn!("failed to send to ui: {:?}", e)
+            };
         }
+        true
     }
-}
-
-impl Actor for Mailbox {
-    type Context = Context<Self>;
 
-    fn started(&mut self, ctx: &mut Self::Context) {
-        info!("Actix Mailbox has started");
-        self.set_state(ServerState::Running, ctx);
+    /// spawn the tasks that read from and write to the transport, and return the queue for
+    /// outgoing packets
+    fn start_transport(
+        ack_queue: AckQueue,
+        pending_acks: Arc<Mutex<Vec<u32>>>,
+        sock: Arc<dyn Transport>,
+        codec: Arc<dyn PacketCodec>,
+        mailbox_address: Addr<Mailbox>,
+        in_order_delivery: Option<Duration>,
+        bandwidth: Arc<Mutex<Bandwidth>>,
+    ) -> (mpsc::Sender<OutboundPacket>, Vec<JoinHandle<()>>) {
+        // Spawn a new Tokio task for reading from the socket
+        let read = tokio::spawn(Mailbox::start_udp_read(
+            ack_queue,
+            pending_acks,
+            sock.clone(),
+            codec.clone(),
+            mailbox_address,
+            in_order_delivery,
+        ));
+        // and one for writing the outgoing packets to it
+        let (outbound, receiver) = mpsc::channel(OUTBOUND_QUEUE_SIZE);
+        let write = tokio::spawn(Mailbox::start_udp_write(receiver, sock, codec, bandwidth));
+        (outbound, vec![read, write])
     }
-}
 
-impl Handler<RegionHandshakeMessage> for Mailbox {
-    type Result = ();
-    fn handle(&mut self, _: RegionHandshakeMessage, ctx: &mut Self::Context) -> Self::Result {
-        ctx.address()
-            .do_send(Packet::new_region_handshake_reply(RegionHandshakeReply {
-                agent_data: AgentData {
-                    session_id: self.session.as_ref().unwrap().session_id,
-                    agent_id: self.session.as_ref().unwrap().agent_id,
-                },
-                region_info: ReplyRegionInfo { flags: 0 },
-            }));
+    /// wrap the transport in a RecordingTransport, if the mailbox is recording
+    fn record_transport(
+        sock: Arc<dyn Transport>,
+        recorder: Option<Arc<Recorder>>,
+    ) -> Arc<dyn Transport> {
+        match recorder {
+            Some(recorder) => Arc::new(RecordingTransport::new(sock, recorder)),
+            None => sock,
+        }
     }
-}
 
-impl Handler<Ping> for Mailbox {
-    type Result = ();
-    fn handle(&mut self, msg: Ping, ctx: &mut Self::Context) -> Self::Result {
-        ctx.address()
-            .do_send(Packet::new_complete_ping_check(CompletePingCheck {
-                ping_id: msg.ping_id,
-            }));
-        self.ping_info.ping_latency = time::Instant::now() - self.ping_info.last_ping;
-    }
-}
+    /// bind a new UDP socket for the session, after stopping the tasks of the previous one so its
+    /// port is free. The packets held while there was no socket are sent once it is bound.
+    fn bind_socket(&mut self, ctx: &mut Context<Self>) -> ResponseActFuture<Self, io::Result<()>> {
+        let addr = SocketAddr::new(self.local_bind_address(), self.client_socket);
+        let mailbox_addr = ctx.address();
+        let ack_queue = self.ack_queue.clone();
+        let pending_acks = self.pending_acks.clone();
+        let in_order_delivery = self.in_order_delivery;
+        let bandwidth = self.bandwidth.clone();
+        let codec = self.codec.clone();
+        let recorder = self.recorder.clone();
+        let previous_tasks = std::mem::take(&mut self.transport_tasks);
 
-impl Handler<UiMessage> for Mailbox {
-    type Result = ();
-    fn handle(&mut self, msg: UiMessage, _: &mut Self::Context) -> Self::Result {
-        let max_message_size = 1024;
-        // leave a little room at the end
-        let overhead = 2;
+        let fut = async move {
+            for task in previous_tasks {
+                task.abort();
+                let _ = task.await;
+            }
+            match UdpSocket::bind(&addr).await {
+                Ok(sock) => {
+                    info!("Successfully bound to {}", &addr);
+                    let local_address = sock.local_addr().unwrap_or(addr);
+                    let sock = Mailbox::record_transport(Arc::new(sock), recorder);
+                    let (outbound, tasks) = Mailbox::start_transport(
+                        ack_queue,
+                        pending_acks,
+                        sock.clone(),
+                        codec,
+                        mailbox_addr,
+                        in_order_delivery,
+                        bandwidth,
+                    );
+                    Ok((sock, outbound, tasks, local_address))
+                }
+                Err(e) => {
+                    error!("Failed to bind to {}: {}", &addr, e);
+                    Err(e)
+                }
+            }
+        };
+
+        // wait for the socket to be successfully bound and then assign it
+        Box::pin(fut.into_actor(self).map(|result, act, ctx| {
+            let (sock, outbound, tasks, local_address) = result?;
+            act.transport_tasks = tasks;
+            act.local_address = Some(local_address);
+            if let Some(session) = &mut act.session {
+                session.socket = Some(sock);
+                session.outbound = Some(outbound);
+            }
+            act.flush_pending_packets(ctx);
+            Ok(())
+        }))
+    }
+
+    /// the address to bind the session's socket to. A socket can only send to addresses of its
+    /// own family, so an unspecified bind address follows the family of the simulator.
+    fn local_bind_address(&self) -> IpAddr {
+        match self.session.as_ref() {
+            Some(session) => self.bind_address_for(&session.url),
+            None => self.bind_address,
+        }
+    }
+
+    /// the address to bind a socket for a simulator at the url to
+    fn bind_address_for(&self, url: &str) -> IpAddr {
+        let sim_ip = parse_ip(url);
+        match (self.bind_address, sim_ip) {
+            (bind_address, Some(IpAddr::V6(_))) if bind_address == Ipv4Addr::UNSPECIFIED => {
+                IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+            }
+            (bind_address, Some(IpAddr::V4(_))) if bind_address == Ipv6Addr::UNSPECIFIED => {
+                IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+            }
+            (bind_address, _) => bind_address,
+        }
+    }
+
+    /// forget the sequence numbers and acks of the current circuit, before a new one is started
+    fn reset_circuit(&mut self) {
+        // dropping the ack senders stops the retransmission of packets meant for the old circuit
+        self.ack_queue.lock().unwrap().clear();
+        self.pending_acks.lock().unwrap().clear();
+        *self.packet_sequence_number.lock().unwrap() = 0;
+        // the simulator sends its own RegionHandshake on the new circuit
+        self.handshake_complete = false;
+        // local IDs belong to the region, so the next one's objects have to be received again
+        self.object_crcs.clear();
+        self.avatar_appearances.clear();
+        // the agent's position is in the old region's coordinates
+        self.position = None;
+    }
+
+    /// start_udp_write writes the queued outgoing packets to the external server in order.
+    /// Packets over the bandwidth limit are delayed if they are reliable, and dropped if not.
+    async fn start_udp_write(
+        mut outbound: mpsc::Receiver<OutboundPacket>,
+        sock: Arc<dyn Transport>,
+        codec: Arc<dyn PacketCodec>,
+        bandwidth: Arc<Mutex<Bandwidth>>,
+    ) {
+        // reliable packets over the limit wait here for their turn, instead of holding up the
+        // queue. Acks and pings behind them are still sent, or dropped, right away.
+        let mut paced: VecDeque<OutboundPacket> = VecDeque::new();
+        loop {
+            let deadline = paced.front().map(|packet| {
+                std::time::Instant::now() + bandwidth.lock().unwrap().wait_time(packet.data.len())
+            });
+            tokio::select! {
+                received = outbound.recv() => {
+                    let Some(mut packet) = received else {
+                        break;
+                    };
+                    // the bandwidth is counted in the bytes that go on the wire
+                    packet.data = codec.encode(std::mem::take(&mut packet.data));
+                    // reliable packets keep their order, so they wait behind the ones waiting
+                    if packet.reliable
+                        && (!paced.is_empty()
+                            || !bandwidth
+                                .lock()
+                                .unwrap()
+                                .wait_time(packet.data.len())
+                                .is_zero())
+                    {
+                        paced.push_back(packet);
+                        continue;
+                    }
+                    let reserved = bandwidth
+                        .lock()
+                        .unwrap()
+                        .reserve(packet.data.len(), packet.reliable);
+                    match reserved {
+                        Some(_) => Mailbox::write_packet(sock.as_ref(), packet).await,
+                        None => warn!("Over the bandwidth limit, dropping an unreliable packet"),
+                    }
+                }
+                _ = sleep_until_deadline(deadline) => {
+                    // an unreliable packet may have taken the budget in the meantime
+                    let ready = paced.front().is_some_and(|packet| {
+                        bandwidth
+                            .lock()
+                            .unwrap()
+                            .wait_time(packet.data.len())
+                            .is_zero()
+                    });
+                    if ready {
+                        let packet = paced.pop_front().unwrap();
+                        bandwidth.lock().unwrap().reserve(packet.data.len(), true);
+                        Mailbox::write_packet(sock.as_ref(), packet).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// write an encoded packet to the transport, and tell whoever is waiting that it is sent
+    async fn write_packet(sock: &dyn Transport, packet: OutboundPacket) {
+        if let Err(e) = sock.send_to(&packet.data, &packet.addr).await {
+            error!("Failed to send data: {}", e);
+        }
+        if let Some(sent) = packet.sent {
+            let _ = sent.send(());
+        }
+    }
+
+    /// move to a new state, and tell the subscribers and the UI about it
+    fn set_state(&mut self, new_state: ServerState, ctx: &mut Context<Self>) {
+        let state_clone = Arc::clone(&self.state);
+        {
+            let mut state = state_clone.lock().unwrap();
+            *state = new_state.clone();
+        }
+        self.state_sender.send_replace(new_state.clone());
+        // notify on start and stop
+        if new_state == ServerState::Running || new_state == ServerState::Stopped {
+            self.notify.notify_one();
+        }
+        // sent right away, because a stopping mailbox doesn't handle its queued messages
+        self.handle(
+            UiMessage::new(UiEventTypes::StateChangeEvent, new_state.to_bytes()),
+            ctx,
+        );
+    }
 
-        let message_type_len = msg.message_type.to_string().len();
-        let sequence_number_len = std::mem::size_of::<u16>(); // 2 bytes for the sequence number
-        let total_packet_number_len = std::mem::size_of::<u16>();
-        let packet_number_len = std::mem::size_of::<u16>();
+    /// send the next ping once the interval has passed on the mailbox's clock
+    fn schedule_ping(&mut self, ping_interval: Duration, ctx: &mut Context<Self>) {
+        ctx.spawn(
+            self.clock
+                .sleep(ping_interval)
+                .into_actor(self)
+                .map(move |_, act, ctx| {
+                    act.send_ping(ctx);
+                    act.schedule_ping(ping_interval, ctx);
+                }),
+        );
+    }
+
+    /// send a StartPingCheck to the server, and remember when it was sent for the latency
+    fn send_ping(&mut self, ctx: &mut Context<Self>) {
+        // don't ping until the UDP session is up
+        if self
+            .session
+            .as_ref()
+            .is_none_or(|session| session.socket.is_none())
+        {
+            return;
+        }
+        if self.pings_suspended {
+            return;
+        }
+        if self.ping_info.awaiting_pong {
+            self.ping_info.missed_pongs += 1;
+        }
+        if self
+            .resume_after_missed_pongs
+            .is_some_and(|limit| self.ping_info.missed_pongs >= limit)
+            && !self.resuming
+        {
+            warn!(
+                "{} pings went unanswered, resuming the circuit",
+                self.ping_info.missed_pongs
+            );
+            let mailbox = ctx.address();
+            ctx.spawn(
+                async move {
+                    match mailbox.send(ResumeCircuit).await {
+                        Ok(Err(e)) => {
+                            error!("Failed to resume the circuit: {}", e);
+                            mailbox.do_send(UiMessage::new(UiEventTypes::Error, e.to_bytes()));
+                        }
+                        Ok(Ok(())) => info!("resumed the circuit"),
+                        Err(e) => warn!("failed to resume the circuit {:?}", e),
+                    }
+                }
+                .into_actor(self),
+            );
+            return;
+        }
+        self.ping_info.awaiting_pong = true;
+        self.ping_info.ping_number = self.ping_info.ping_number.wrapping_add(1);
+        self.ping_info.last_ping = self.clock.now();
+        // tell the server the oldest reliable packet we are still waiting on an ack for
+        let oldest_unacked = self
+            .ack_queue
+            .lock()
+            .unwrap()
+            .keys()
+            .min()
+            .copied()
+            .unwrap_or_else(|| *self.packet_sequence_number.lock().unwrap());
+        ctx.address()
+            .do_send(Packet::new_start_ping_check(StartPingCheck {
+                ping_id: self.ping_info.ping_number,
+                oldest_unacked,
+            }));
+    }
+
+    /// split a message into chunks that fit in max_ui_message_size, and send them to the UI.
+    /// If the OS rejects a chunk as too large, the datagram size is lowered and the message is
+    /// split again. Stops at the first chunk that fails to send for any other reason.
+    fn send_to_ui(&mut self, msg: &UiMessage) -> io::Result<()> {
+        // the socket is connected to the UI, so the kernel reports when nothing is listening
+        if self.ui_socket.is_none() {
+            let socket = SyncUdpSocket::bind("0.0.0.0:0")?;
+            socket.connect(&self.server_to_ui_socket)?;
+            self.ui_socket = Some(socket);
+        }
+        loop {
+            let max_message_size = self
+                .max_ui_message_size
+                .min(self.ui_max_datagram_size.unwrap_or(MAX_UI_MESSAGE_SIZE));
+            match self.send_chunks(msg, max_message_size) {
+                // every chunk but the last is full, so only the first one can be too large, and
+                // nothing has been sent yet
+                Err(e) if datagram_too_large(&e) && max_message_size > MIN_UI_DATAGRAM_SIZE => {
+                    let smaller = (max_message_size / 2).max(MIN_UI_DATAGRAM_SIZE);
+                    warn!(
+                        "UI datagrams of {} bytes are too large, sending {} bytes instead",
+                        max_message_size, smaller
+                    );
+                    self.ui_max_datagram_size = Some(smaller);
+                }
+                result => {
+                    if result.is_ok() {
+                        self.ui_unconfirmed = Some(msg.clone());
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// send a message to the UI in chunks of at most max_message_size bytes, chunk header
+    /// included
+    fn send_chunks(&mut self, msg: &UiMessage, max_message_size: usize) -> io::Result<()> {
+        let Some(client_socket) = &self.ui_socket else {
+            return Ok(());
+        };
 
-        // Calculate the maximum size available for the actual message content
         let available_size = max_message_size
-            - (message_type_len
-                + sequence_number_len
-                + total_packet_number_len
-                + packet_number_len
-                + overhead);
+            .saturating_sub(UI_MESSAGE_HEADER_SIZE)
+            .max(1);
+
+        // a message that is sent again gets a new id, so the UI doesn't mix its chunks with the
+        // ones that made it the first time
+        let message_id = self.sent_packet_count;
+        self.sent_packet_count = self.sent_packet_count.wrapping_add(1);
 
         // Split the message content if it's larger than the available size
-        let message = msg.message;
+        let message = &msg.message;
         let total_chunks = usize::max(1, message.len().div_ceil(available_size));
 
         // Loop through each chunk and send it
@@ -310,182 +1505,1360 @@ impl Handler<UiMessage> for Mailbox {
             let end = usize::min(start + available_size, message.len());
             let chunk = &message[start..end];
 
-            // Increment the sequence number for each chunk
-            let sequence_number = msg.sequence_number + chunk_index as u16;
-
             // Create a new message with the chunked data
             let chunked_message = UiMessage {
                 message_type: msg.message_type.clone(),
-                sequence_number,
+                sequence_number: chunk_index as u16,
                 total_packet_number: total_chunks as u16, // Add total number of chunks
                 message: chunk.to_vec(),
-                packet_number: self.sent_packet_count,
+                message_id,
             };
 
-            let client_socket = SyncUdpSocket::bind("0.0.0.0:0").unwrap();
-            if let Err(e) =
-                client_socket.send_to(&chunked_message.as_bytes(), &self.server_to_ui_socket)
-            {
-                info!("sending to: {}", self.server_to_ui_socket);
+            client_socket.send(&chunked_message.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// hold a message until the UI is listening. Only the most recent UI_BACKLOG_SIZE messages
+    /// are kept.
+    fn buffer_ui_message(&mut self, msg: UiMessage) {
+        if self.ui_backlog.len() == UI_BACKLOG_SIZE {
+            self.ui_backlog.pop_front();
+        }
+        self.ui_backlog.push_back(msg);
+    }
+
+    /// try to send the buffered messages to the UI. If it is still not listening, try again
+    /// later, backing off up to UI_MAX_RETRY_DELAY.
+    fn flush_ui_backlog(&mut self, ctx: &mut Context<Self>) {
+        while let Some(msg) = self.ui_backlog.pop_front() {
+            match self.send_to_ui(&msg) {
+                Ok(()) => {}
+                Err(e) if ui_unavailable(&e) => {
+                    self.ui_backlog.push_front(msg);
+                    // the message before it was most likely lost too
+                    if let Some(lost) = self.ui_unconfirmed.take() {
+                        self.ui_backlog.push_front(lost);
+                    }
+                    let delay = self
+                        .ui_retry_delay
+                        .map_or(UI_RETRY_DELAY, |delay| delay * 2)
+                        .min(UI_MAX_RETRY_DELAY);
+                    self.ui_retry_delay = Some(delay);
+                    ctx.run_later(delay, |act, ctx| act.flush_ui_backlog(ctx));
+                    return;
+                }
+                Err(e) => {
+                    error!(
+                        "Error sending {} to {}: {:?}",
+                        msg.message_type, self.server_to_ui_socket, e
+                    )
+                }
+            }
+        }
+        info!("UI is listening on {}", self.server_to_ui_socket);
+        self.ui_retry_delay = None;
+    }
+
+    /// subscribe to the state of the mailbox.
+    /// The receiver starts with the current state, and is updated on every transition.
+    pub fn subscribe_state(&self) -> watch::Receiver<ServerState> {
+        self.state_sender.subscribe()
+    }
+
+    /// wait for the mailbox to be Running.
+    /// Returns a TimeoutError if it isn't Running within the timeout, and a MailboxError if it
+    /// stopped instead. Call this before starting the mailbox, and await the future after.
+    ///```
+    /// use actix::Actor;
+    /// use metaverse_session::mailbox::Mailbox;
+    /// use std::time::Duration;
+    ///
+    /// # actix_rt::System::new().block_on(async {
+    /// let mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    /// let running = mailbox.wait_for_running(Duration::from_secs(1));
+    /// let mailbox = mailbox.start();
+    /// running.await.unwrap();
+    /// # });
+    ///```
+    pub fn wait_for_running(
+        &self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<(), SessionError>> {
+        let mut states = self.subscribe_state();
+        async move {
+            let started = states
+                .wait_for(|state| *state == ServerState::Running || *state == ServerState::Stopped);
+            match tokio::time::timeout(timeout, started).await {
+                Ok(Ok(state)) if *state == ServerState::Running => Ok(()),
+                Ok(_) => Err(SessionError::Mailbox(MailboxError::new(
+                    "Mailbox failed to enter state Running.",
+                ))),
+                Err(_) => Err(SessionError::Timeout(TimeoutError::new(format!(
+                    "Mailbox did not enter state Running within {:?}",
+                    timeout
+                )))),
+            }
+        }
+    }
+
+    /// give the packet the next sequence number, and queue it to be sent to the server.
+    /// Reliable packets return a future that resolves when the server acks them. Unreliable
+    /// packets are sent without waiting, and return None.
+    fn send_packet(
+        &mut self,
+        mut msg: Packet,
+        ctx: &mut Context<Self>,
+    ) -> Result<Option<impl Future<Output = Result<(), SessionError>>>, SessionError> {
+        let state = self.state.lock().unwrap().clone();
+        if state != ServerState::Running {
+            return Err(SessionError::Mailbox(MailboxError::new(format!(
+                "Mailbox is {:?}, dropping packet {:?}",
+                state, msg.body
+            ))));
+        }
+        if !self.handshake_complete && matches!(msg.body, PacketType::AgentUpdate(_)) {
+            return Err(SessionError::Mailbox(MailboxError::new(
+                "Region handshake is not complete, dropping AgentUpdate",
+            )));
+        }
+        let Some(ref session) = self.session else {
+            return Err(SessionError::Mailbox(MailboxError::new(format!(
+                "No session, dropping packet {:?}",
+                msg.body
+            ))));
+        };
+        let addr = session.server_address();
+        let Some(outbound) = session.outbound.clone() else {
+            return Err(SessionError::Mailbox(MailboxError::new(format!(
+                "UDP socket is not ready, dropping packet {:?}",
+                msg.body
+            ))));
+        };
+        {
+            let sequence_number = self.packet_sequence_number.lock().unwrap();
+            msg.header.sequence_number = *sequence_number;
+        }
+        if self.local_chat_echo {
+            if let PacketType::ChatFromViewer(chat) = &msg.body {
+                if !matches!(
+                    chat.message_type,
+                    ClientChatType::StartTyping | ClientChatType::StopTyping
+                ) {
+                    let echo = ChatFromSimulator::local_echo(session.agent_name.clone(), chat);
+                    ctx.address().do_send(UiMessage::new(
+                        UiEventTypes::ChatFromSimulatorEvent,
+                        echo.to_bytes(),
+                    ));
+                }
+            }
+        }
+        // piggyback any pending acks onto the outgoing packet
+        if !matches!(msg.body, PacketType::PacketAck(_)) {
+            let acks = self.take_pending_acks();
+            if !acks.is_empty() {
+                msg.header.appended_acks = true;
+                msg.header.ack_list = Some(acks);
+            }
+        }
+        {
+            let mut sequence_number = self.packet_sequence_number.lock().unwrap();
+            *sequence_number += 1;
+        }
+
+        if msg.header.reliable {
+            return Ok(Some(send_ack(
+                msg,
+                addr,
+                self.ack_queue.clone(),
+                outbound,
+                self.clock.clone(),
+            )));
+        }
+        // unreliable packets are dropped instead of waiting for room in the queue
+        match outbound.try_send(OutboundPacket {
+            data: msg.to_bytes(),
+            addr,
+            reliable: false,
+            sent: None,
+        }) {
+            Ok(()) => Ok(None),
+            Err(TrySendError::Full(_)) => {
+                // the acks it carried go out with the next packet instead
+                let acks = match &mut msg.body {
+                    PacketType::PacketAck(ack) => std::mem::take(&mut ack.packet_ids),
+                    _ => msg.header.ack_list.take().unwrap_or_default(),
+                };
+                self.pending_acks.lock().unwrap().splice(0..0, acks);
+                Err(SessionError::Mailbox(MailboxError::new(format!(
+                    "Outbound queue is full, dropping {:?}",
+                    msg.body
+                ))))
+            }
+            Err(TrySendError::Closed(_)) => Err(SessionError::Mailbox(MailboxError::new(
+                "Outbound queue is closed",
+            ))),
+        }
+    }
+
+    /// whether the session is usable: the mailbox is running, and it has a session whose UDP
+    /// socket is bound
+    pub fn is_ready(&self) -> bool {
+        *self.state.lock().unwrap() == ServerState::Running
+            && self
+                .session
+                .as_ref()
+                .is_some_and(|session| session.socket.is_some())
+    }
+
+    /// whether packets have to wait for the mailbox to start or for the UDP socket to be bound
+    fn awaiting_transport(&self) -> bool {
+        *self.state.lock().unwrap() == ServerState::Starting
+            || self
+                .session
+                .as_ref()
+                .is_some_and(|session| session.outbound.is_none())
+    }
+
+    /// hold a packet until the UDP socket is ready
+    fn hold_packet(&mut self, msg: Packet) {
+        if self.pending_packets.len() == PENDING_PACKET_LIMIT {
+            if let Some(dropped) = self.pending_packets.pop_front() {
+                warn!(
+                    "Too many packets waiting to be sent, dropping {:?}",
+                    dropped.body
+                );
+            }
+        }
+        self.pending_packets.push_back(msg);
+    }
+
+    /// send the packets that were held while the UDP socket was not ready
+    fn flush_pending_packets(&mut self, ctx: &mut Context<Self>) {
+        if self.awaiting_transport() {
+            return;
+        }
+        for packet in self.pending_packets.drain(..) {
+            ctx.address().do_send(packet);
+        }
+    }
+
+    /// take up to MAX_PACKET_ACKS of the pending acks out of the queue
+    fn take_pending_acks(&self) -> Vec<u32> {
+        let mut pending_acks = self.pending_acks.lock().unwrap();
+        let count = usize::min(pending_acks.len(), MAX_PACKET_ACKS);
+        pending_acks.drain(..count).collect()
+    }
+}
+
+impl Actor for Mailbox {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        info!("Actix Mailbox has started");
+        self.set_state(ServerState::Running, ctx);
+        self.flush_pending_packets(ctx);
+
+        // send any acks that didn't get appended to an outgoing packet in time, batched into as
+        // few PacketAcks as they fit in
+        ctx.run_interval(ACK_FLUSH_INTERVAL, |act, ctx| {
+            // without a circuit there is nowhere to send them, so they wait for one
+            if !act.is_ready() {
+                return;
+            }
+            let acks: Vec<u32> = act.pending_acks.lock().unwrap().drain(..).collect();
+            for packet in Packet::new_packet_acks(&acks) {
+                ctx.address().do_send(packet);
+            }
+        });
+
+        // send exactly one ping to the server per interval
+        if let Some(ping_interval) = self.ping_interval {
+            self.schedule_ping(ping_interval, ctx);
+        }
+    }
+
+    fn stopping(&mut self, ctx: &mut Self::Context) -> Running {
+        info!("Actix Mailbox is stopping");
+        for (_, circuit) in self.neighbor_circuits.drain() {
+            circuit.do_send(Shutdown);
+        }
+        // the reader and writer hold on to the transport, so it is only released once they stop
+        for task in std::mem::take(&mut self.transport_tasks) {
+            task.abort();
+        }
+        self.set_state(ServerState::Stopping, ctx);
+        Running::Stop
+    }
+
+    fn stopped(&mut self, ctx: &mut Self::Context) {
+        info!("Actix Mailbox has stopped");
+        self.set_state(ServerState::Stopped, ctx);
+    }
+}
+
+impl Handler<RegionHandshakeMessage> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: RegionHandshakeMessage, ctx: &mut Self::Context) -> Self::Result {
+        self.region_name = Some(msg.region_name);
+        // a handshake queued by the read task can arrive after Shutdown has cleared the session
+        let Some(session) = self.session.as_ref() else {
+            warn!("received a region handshake with no session to reply on");
+            return;
+        };
+        ctx.address()
+            .do_send(Packet::new_region_handshake_reply(RegionHandshakeReply {
+                agent_data: AgentData {
+                    session_id: session.session_id,
+                    agent_id: session.agent_id,
+                },
+                region_info: ReplyRegionInfo { flags: 0 },
+            }));
+        // tell the server how much bandwidth we can handle once the handshake is complete
+        ctx.address()
+            .do_send(Packet::new_agent_throttle(AgentThrottle {
+                agent_id: session.agent_id,
+                session_id: session.session_id,
+                circuit_code: session.circuit_code,
+                gen_counter: 0,
+                throttles: self.throttles.clone(),
+            }));
+        if let Some((height, width)) = self.window_size {
+            self.height_width_gen_counter = self.height_width_gen_counter.wrapping_add(1);
+            ctx.address()
+                .do_send(Packet::new_agent_height_width(AgentHeightWidth {
+                    agent_id: session.agent_id,
+                    session_id: session.session_id,
+                    circuit_code: session.circuit_code,
+                    gen_counter: self.height_width_gen_counter,
+                    height,
+                    width,
+                }));
+        }
+        self.handshake_complete = true;
+    }
+}
+
+impl Handler<Ping> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: Ping, ctx: &mut Self::Context) -> Self::Result {
+        ctx.address()
+            .do_send(Packet::new_complete_ping_check(CompletePingCheck {
+                ping_id: msg.ping_id,
+            }));
+    }
+}
+
+// the pong only records the latency. The next ping is sent on the ping interval, not in
+// response to the pong.
+impl Handler<Pong> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: Pong, _: &mut Self::Context) -> Self::Result {
+        if msg.ping_id != self.ping_info.ping_number {
+            warn!(
+                "received pong for ping {}, expected {}",
+                msg.ping_id, self.ping_info.ping_number
+            );
+            return;
+        }
+        self.ping_info.awaiting_pong = false;
+        self.ping_info.missed_pongs = 0;
+        self.ping_info
+            .record_latency(self.clock.now() - self.ping_info.last_ping);
+    }
+}
+
+impl Handler<PingQuery> for Mailbox {
+    type Result = PingStats;
+    fn handle(&mut self, _: PingQuery, _: &mut Self::Context) -> Self::Result {
+        let mut stats = self.ping_info.stats();
+        stats.bandwidth = self.bandwidth.lock().unwrap().stats();
+        stats
+    }
+}
+
+impl Handler<ResolveName> for Mailbox {
+    type Result = Option<(String, String)>;
+    fn handle(&mut self, msg: ResolveName, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(name) = self.name_cache.get(&msg.0) {
+            return Some(name.clone());
+        }
+        ctx.address()
+            .do_send(Packet::new_uuid_name_request(UUIDNameRequest {
+                ids: vec![msg.0],
+            }));
+        None
+    }
+}
+
+impl Handler<CacheNames> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: CacheNames, _: &mut Self::Context) -> Self::Result {
+        for name in msg.names {
+            self.name_cache
+                .insert(name.id, (name.first_name, name.last_name));
+        }
+    }
+}
+
+impl Handler<Ready> for Mailbox {
+    type Result = bool;
+    fn handle(&mut self, _: Ready, _: &mut Self::Context) -> Self::Result {
+        self.is_ready()
+    }
+}
+
+impl Handler<DumpAckQueue> for Mailbox {
+    type Result = Vec<AckQueueEntry>;
+    fn handle(&mut self, _: DumpAckQueue, _: &mut Self::Context) -> Self::Result {
+        let now = self.clock.now();
+        let mut entries: Vec<AckQueueEntry> = self
+            .ack_queue
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, pending)| pending.given_up.is_none())
+            .map(|(packet_id, pending)| AckQueueEntry {
+                packet_id: *packet_id,
+                waiting: now.saturating_duration_since(pending.first_sent),
+                attempts: pending.attempts,
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.packet_id);
+        entries
+    }
+}
+
+impl Handler<Mute> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: Mute, _: &mut Self::Context) -> Self::Result {
+        self.mute_list.insert(msg.0);
+    }
+}
+
+impl Handler<Unmute> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: Unmute, _: &mut Self::Context) -> Self::Result {
+        self.mute_list.remove(&msg.0);
+    }
+}
+
+impl Handler<ReceivedChat> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: ReceivedChat, ctx: &mut Self::Context) -> Self::Result {
+        let chat = msg.chat;
+        if self.mute_list.contains(&chat.source_id) || self.mute_list.contains(&chat.owner_id) {
+            return;
+        }
+        if !self.chat_filter.allows(&chat) {
+            return;
+        }
+        ctx.address().do_send(UiMessage::new(
+            UiEventTypes::ChatFromSimulatorEvent,
+            chat.to_bytes(),
+        ));
+    }
+}
+
+impl Handler<ReceivedImageData> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: ReceivedImageData, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(texture) = self.textures.image_data(msg.image) {
+            ctx.address().do_send(UiMessage::new(
+                UiEventTypes::TextureEvent,
+                texture.to_bytes(),
+            ));
+        }
+    }
+}
+
+impl Handler<ReceivedImagePacket> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: ReceivedImagePacket, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(texture) = self.textures.image_packet(msg.image) {
+            ctx.address().do_send(UiMessage::new(
+                UiEventTypes::TextureEvent,
+                texture.to_bytes(),
+            ));
+        }
+    }
+}
+
+impl Handler<CacheObjects> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: CacheObjects, _: &mut Self::Context) -> Self::Result {
+        self.object_crcs.extend(msg.objects);
+    }
+}
+
+impl Handler<ReceivedCachedObjects> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: ReceivedCachedObjects, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        let misses: Vec<ObjectRequest> = msg
+            .objects
+            .iter()
+            .filter_map(|object| {
+                let cache_miss_type = match self.object_crcs.get(&object.local_id) {
+                    None => CacheMissType::Full,
+                    Some(crc) if *crc != object.crc => CacheMissType::Crc,
+                    Some(_) => return None,
+                };
+                Some(ObjectRequest {
+                    cache_miss_type,
+                    local_id: object.local_id,
+                })
+            })
+            .collect();
+        // the object count is a single byte
+        for chunk in misses.chunks(u8::MAX as usize) {
+            ctx.address().do_send(Packet::new_request_multiple_objects(
+                RequestMultipleObjects {
+                    agent_id: session.agent_id,
+                    session_id: session.session_id,
+                    objects: chunk.to_vec(),
+                },
+            ));
+        }
+    }
+}
+
+impl Handler<ReceivedAppearance> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: ReceivedAppearance, ctx: &mut Self::Context) -> Self::Result {
+        let appearance = msg.appearance;
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        // only the bakes that changed since the avatar's last appearance have to be downloaded
+        let previous = self
+            .avatar_appearances
+            .get(&appearance.agent_id)
+            .map(|previous| previous.baked_textures())
+            .unwrap_or_default();
+        let requests: Vec<ImageRequest> = appearance
+            .baked_textures()
+            .into_iter()
+            .filter(|texture| !previous.contains(texture))
+            .map(|image_id| ImageRequest {
+                image_id,
+                discard_level: 0,
+                download_priority: 1.0,
+                packet: 0,
+                image_type: 1,
+            })
+            .collect();
+        // a neighbor circuit's textures would never reach the UI
+        if self.request_avatar_textures && !self.is_neighbor && !requests.is_empty() {
+            ctx.address()
+                .do_send(Packet::new_request_image(RequestImage {
+                    agent_id: session.agent_id,
+                    session_id: session.session_id,
+                    requests,
+                }));
+        }
+        self.avatar_appearances
+            .insert(appearance.agent_id, appearance);
+    }
+}
+
+impl Handler<SetAppearance> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: SetAppearance, ctx: &mut Self::Context) -> Self::Result {
+        if self.handshake_complete {
+            ctx.address()
+                .do_send(Packet::new_agent_set_appearance(msg.0.clone()));
+        }
+        self.appearance = Some(msg.0);
+    }
+}
+
+impl Handler<MovementComplete> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: MovementComplete, ctx: &mut Self::Context) -> Self::Result {
+        self.position = Some(msg.position);
+        if let Some(appearance) = self.appearance.as_mut() {
+            appearance.serial_num += 1;
+            ctx.address()
+                .do_send(Packet::new_agent_set_appearance(appearance.clone()));
+        }
+        // the first arrival after login completes it. Later ones are teleports and crossings.
+        if self.login_complete {
+            return;
+        }
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        let summary = LoginSummary {
+            agent_name: session.agent_name.clone(),
+            agent_id: session.agent_id,
+            session_id: session.session_id,
+            region_name: self.region_name.clone().unwrap_or_default(),
+            region_handle: msg.region_handle,
+            home: session.home.clone(),
+            buddy_list: session.buddy_list.clone(),
+            balance: self.balance,
+        };
+        match serde_json::to_vec(&summary) {
+            Ok(bytes) => {
+                ctx.address()
+                    .do_send(UiMessage::new(UiEventTypes::LoginCompleteEvent, bytes));
+                self.login_complete = true;
+            }
+            Err(e) => warn!("failed to serialize the login summary {:?}", e),
+        }
+    }
+}
+
+impl Handler<UpdateBalance> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: UpdateBalance, _: &mut Self::Context) -> Self::Result {
+        self.balance = Some(msg.0);
+    }
+}
+
+impl Handler<UpdateAgents> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: UpdateAgents, _: &mut Self::Context) -> Self::Result {
+        if let Some(position) = msg.you {
+            self.position = Some(position);
+        }
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        // the update lists every avatar in the region, so avatars missing from it have left
+        let mut agent_list = session.agent_list.lock().unwrap();
+        agent_list.clear();
+        for (agent_id, position) in msg.agents {
+            // avatars are keyed by ID, so ones the server didn't send an ID for can't be tracked
+            if let Some(agent_id) = agent_id {
+                agent_list.insert(
+                    agent_id,
+                    Avatar {
+                        agent_id,
+                        position,
+                        appearance: None,
+                    },
+                );
+            }
+        }
+    }
+}
+
+impl Handler<GetPosition> for Mailbox {
+    type Result = Option<Vec3>;
+    fn handle(&mut self, _: GetPosition, _: &mut Self::Context) -> Self::Result {
+        self.position
+    }
+}
+
+impl Handler<GetClock> for Mailbox {
+    type Result = MessageResult<GetClock>;
+    fn handle(&mut self, _: GetClock, _: &mut Self::Context) -> Self::Result {
+        MessageResult(self.clock.clone())
+    }
+}
+
+impl Handler<GetAgents> for Mailbox {
+    type Result = Vec<Avatar>;
+    fn handle(&mut self, _: GetAgents, _: &mut Self::Context) -> Self::Result {
+        self.session
+            .as_ref()
+            .map(|session| {
+                session
+                    .agent_list
+                    .lock()
+                    .unwrap()
+                    .values()
+                    .map(|avatar| Avatar {
+                        appearance: self.avatar_appearances.get(&avatar.agent_id).cloned(),
+                        ..avatar.clone()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Handler<UiMessage> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: UiMessage, ctx: &mut Self::Context) -> Self::Result {
+        // the UI only follows the agent's main circuit
+        if self.is_neighbor {
+            if let UiEventTypes::DisableSimulatorEvent = msg.message_type {
+                ctx.stop();
+            }
+            return;
+        }
+        // keep the order of messages while waiting for the UI to come up
+        if self.ui_retry_delay.is_some() {
+            self.buffer_ui_message(msg);
+            return;
+        }
+        match self.send_to_ui(&msg) {
+            Ok(()) => {}
+            Err(e) if ui_unavailable(&e) => {
+                warn!(
+                    "UI is not listening on {}, buffering messages until it is",
+                    self.server_to_ui_socket
+                );
+                // the message before it was most likely lost too
+                if let Some(lost) = self.ui_unconfirmed.take() {
+                    self.buffer_ui_message(lost);
+                }
+                self.buffer_ui_message(msg);
+                self.ui_retry_delay = Some(UI_RETRY_DELAY);
+                ctx.run_later(UI_RETRY_DELAY, |act, ctx| act.flush_ui_backlog(ctx));
+            }
+            Err(e) => {
                 error!(
-                    "Error sending chunk {} of {} from mailbox: {:?}",
-                    sequence_number, total_chunks, e
+                    "Error sending {} to {}: {:?}",
+                    msg.message_type, self.server_to_ui_socket, e
                 )
             }
         }
-        self.sent_packet_count += 1;
     }
 }
 
+impl Session {
+    /// the address of the simulator, to send packets to. IPv6 addresses are put in brackets, so
+    /// the port isn't mistaken for part of the address.
+    pub fn server_address(&self) -> String {
+        match parse_ip(&self.url) {
+            Some(ip) => SocketAddr::new(ip, self.server_socket).to_string(),
+            None => format!("{}:{}", self.url, self.server_socket),
+        }
+    }
+}
+
+/// parse an IPv4 or IPv6 address, with or without the brackets around an IPv6 address
+fn parse_ip(url: &str) -> Option<IpAddr> {
+    url.strip_prefix('[')
+        .and_then(|url| url.strip_suffix(']'))
+        .unwrap_or(url)
+        .parse()
+        .ok()
+}
+
+/// errors that mean a datagram was larger than the socket can send (EMSGSIZE)
+fn datagram_too_large(error: &io::Error) -> bool {
+    #[cfg(target_os = "linux")]
+    const EMSGSIZE: i32 = 90;
+    #[cfg(windows)]
+    const EMSGSIZE: i32 = 10040;
+    #[cfg(not(any(target_os = "linux", windows)))]
+    const EMSGSIZE: i32 = 40;
+    error.raw_os_error() == Some(EMSGSIZE)
+}
+
+/// errors that mean the UI has not bound its socket yet, so sending should be retried
+fn ui_unavailable(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ConnectionRefused | io::ErrorKind::NotFound
+    )
+}
+
 // set the session to initialized.
 impl Handler<Session> for Mailbox {
     type Result = ();
     fn handle(&mut self, mut msg: Session, ctx: &mut Self::Context) -> Self::Result {
+        // keep using the transport of the previous session, unless it is a socket of the other
+        // address family than the new simulator, which a new socket is bound for below
+        let same_family = self
+            .local_address
+            .is_none_or(|local| local.is_ipv6() == self.bind_address_for(&msg.url).is_ipv6());
         if let Some(session) = self.session.as_ref() {
-            msg.socket = session.socket.clone();
+            if session.socket.is_some() && same_family {
+                msg.socket = session.socket.clone();
+                msg.outbound = session.outbound.clone();
+            }
+            msg.agent_list = session.agent_list.clone();
+        }
+        // a transport passed in with the session, like a MockTransport, is used instead of a
+        // UDP socket
+        if let (Some(sock), None) = (&msg.socket, &msg.outbound) {
+            info!("session established, starting processing on the provided transport");
+            let (outbound, tasks) = Mailbox::start_transport(
+                self.ack_queue.clone(),
+                self.pending_acks.clone(),
+                Mailbox::record_transport(sock.clone(), self.recorder.clone()),
+                self.codec.clone(),
+                ctx.address(),
+                self.in_order_delivery,
+                self.bandwidth.clone(),
+            );
+            msg.outbound = Some(outbound);
+            self.transport_tasks = tasks;
+        }
+        // a new login gets its own LoginCompleteEvent
+        if self
+            .session
+            .as_ref()
+            .is_none_or(|session| session.session_id != msg.session_id)
+        {
+            self.login_complete = false;
+            self.balance = None;
+        }
+        // the acks still pending belong to the old circuit. Their sequence numbers mean nothing
+        // on the new one, so they are failed instead of being retransmitted over it.
+        if self.session.as_ref().is_some_and(|session| {
+            session.session_id != msg.session_id
+                || session.circuit_code != msg.circuit_code
+                || session.url != msg.url
+                || session.server_socket != msg.server_socket
+        }) {
+            self.reset_circuit();
         }
         self.session = Some(msg);
+        self.flush_pending_packets(ctx);
 
         // if the session doesn't already have a UDP socket to watch, create one
-        if let Some(session) = self.session.as_ref() {
-            if session.socket.is_none() {
-                let addr = format!("0.0.0.0:{}", self.client_socket);
-
-                let addr_clone = addr.clone();
-                let mailbox_addr = ctx.address();
-
-                info!("session established, starting UDP processing");
-                let ack_queue = self.ack_queue.clone();
-
-                let fut = async move {
-                    match UdpSocket::bind(&addr).await {
-                        Ok(sock) => {
-                            info!("Successfully bound to {}", &addr);
-                            let sock = Arc::new(sock);
-                            // Spawn a new Tokio task for reading from the socket
-                            tokio::spawn(Mailbox::start_udp_read(
-                                ack_queue,
-                                sock.clone(),
-                                mailbox_addr,
-                            ));
-                            Ok(sock) // Return the socket wrapped in Arc
-                        }
-                        Err(e) => {
-                            error!("Failed to bind to {}: {}", &addr_clone, e);
-                            Err(e)
-                        }
-                    }
-                };
+        if self
+            .session
+            .as_ref()
+            .is_some_and(|session| session.socket.is_none())
+        {
+            info!("session established, starting UDP processing");
+            let bind = self.bind_socket(ctx).map(|result, _, ctx| {
+                if let Err(e) = result {
+                    error!("Failed to bind the session's socket, stopping: {}", e);
+                    ctx.stop();
+                }
+            });
+            ctx.spawn(bind);
+        }
+    }
+}
 
-                // wait for the socket to be successfully bound and then assign it
-                ctx.spawn(fut.into_actor(self).map(|result, act, _| match result {
-                    Ok(sock) => {
-                        if let Some(session) = &mut act.session {
-                            session.socket = Some(sock);
-                        }
-                    }
-                    Err(_) => {
-                        panic!("Socket binding failed");
-                    }
-                }));
-            }
+impl Handler<Replay> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: Replay, ctx: &mut Self::Context) -> Self::Result {
+        info!("replaying {} recorded datagrams", msg.0.len());
+        for task in std::mem::take(&mut self.transport_tasks) {
+            task.abort();
+        }
+        // the recording starts a circuit of its own
+        self.reset_circuit();
+        let transport: Arc<dyn Transport> =
+            Arc::new(ReplayTransport::new(msg.0, self.clock.clone()));
+        let (outbound, tasks) = Mailbox::start_transport(
+            self.ack_queue.clone(),
+            self.pending_acks.clone(),
+            transport.clone(),
+            self.codec.clone(),
+            ctx.address(),
+            self.in_order_delivery,
+            self.bandwidth.clone(),
+        );
+        self.transport_tasks = tasks;
+        if let Some(session) = self.session.as_mut() {
+            session.socket = Some(transport);
+            session.outbound = Some(outbound);
         }
     }
 }
 
-impl Handler<Packet> for Mailbox {
+impl Handler<ChangeSimulator> for Mailbox {
     type Result = ();
-    fn handle(&mut self, mut msg: Packet, ctx: &mut Self::Context) -> Self::Result {
-        if let Some(ref session) = self.session {
-            let addr = format!("{}:{}", session.url, session.server_socket);
-            {
-                let sequence_number = self.packet_sequence_number.lock().unwrap();
-                msg.header.sequence_number = *sequence_number;
+    fn handle(&mut self, msg: ChangeSimulator, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            warn!("no session to move to {}:{}", msg.url, msg.server_socket);
+            return;
+        };
+        // the new simulator's circuit is this one from now on, so a neighbor circuit to it would
+        // be a second circuit for the same agent
+        let neighbor = self
+            .neighbors
+            .iter()
+            .find(|(_, address)| {
+                parse_ip(&msg.url) == Some(address.ip()) && address.port() == msg.server_socket
+            })
+            .map(|(region_handle, _)| *region_handle);
+        if let Some(region_handle) = neighbor {
+            self.neighbors.remove(&region_handle);
+            if let Some(circuit) = self.neighbor_circuits.remove(&region_handle) {
+                circuit.do_send(Shutdown);
             }
+        }
+        info!(
+            "moving session from {}:{} to {}:{}",
+            session.url, session.server_socket, msg.url, msg.server_socket
+        );
+        session.url = msg.url;
+        session.server_socket = msg.server_socket;
+        session.circuit_code = msg.circuit_code;
+        let (agent_id, session_id) = (session.agent_id, session.session_id);
 
-            if msg.header.reliable {
-                let ack_future = send_ack(
-                    msg,
-                    addr,
-                    self.ack_queue.clone(),
-                    session.socket.as_ref().unwrap().clone(),
-                );
+        // the new circuit has its own sequence numbers and acks
+        self.reset_circuit();
+
+        // a socket can't reach a simulator of the other address family, so a new one is bound.
+        // The packets below are held until it is ready.
+        let bind_address = self.local_bind_address();
+        if self
+            .local_address
+            .is_some_and(|local| local.is_ipv6() != bind_address.is_ipv6())
+        {
+            if let Some(session) = self.session.as_mut() {
+                session.socket = None;
+                session.outbound = None;
+            }
+            let bind = self.bind_socket(ctx).map(|result, _, _| {
+                if let Err(e) = result {
+                    error!("Failed to bind a socket for the new simulator: {}", e);
+                }
+            });
+            ctx.spawn(bind);
+        }
+
+        ctx.address()
+            .do_send(Packet::new_circuit_code(CircuitCodeData {
+                code: msg.circuit_code,
+                session_id,
+                id: agent_id,
+            }));
+        ctx.address().do_send(Packet::new_complete_agent_movement(
+            CompleteAgentMovementData {
+                agent_id,
+                session_id,
+                circuit_code: msg.circuit_code,
+            },
+        ));
+    }
+}
+
+impl Handler<EnableNeighbor> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: EnableNeighbor, _: &mut Self::Context) -> Self::Result {
+        info!(
+            "neighbor region {:?} enabled at {}",
+            msg.region_handle, msg.address
+        );
+        self.neighbors.insert(msg.region_handle, msg.address);
+        if !self.auto_connect_neighbors || self.is_neighbor {
+            return;
+        }
+        if self
+            .neighbor_circuits
+            .get(&msg.region_handle)
+            .is_some_and(|circuit| circuit.connected())
+        {
+            return;
+        }
+        let Some(session) = self.session.as_ref() else {
+            warn!("no session to connect to neighbor {}", msg.address);
+            return;
+        };
+
+        let mut neighbor = Mailbox::new(0, self.server_to_ui_socket.clone());
+        neighbor.bind_address = self.bind_address;
+        neighbor.is_neighbor = true;
+        neighbor.ping_interval = self.ping_interval;
+        neighbor.clock = self.clock.clone();
+        neighbor.codec = self.codec.clone();
+        neighbor.resume_after_missed_pongs = None;
+        neighbor.throttles = self.throttles.clone();
+        neighbor.window_size = self.window_size;
+        neighbor.bandwidth = self.bandwidth.clone();
+        let circuit = neighbor.start();
+
+        // the agent already has a child presence in the neighbor with the same circuit code, so
+        // the circuit only has to be opened. The agent doesn't move there until it crosses.
+        circuit.do_send(Session {
+            url: msg.address.ip().to_string(),
+            server_socket: msg.address.port(),
+            agent_id: session.agent_id,
+            session_id: session.session_id,
+            circuit_code: session.circuit_code,
+            agent_name: session.agent_name.clone(),
+            agent_access: session.agent_access.clone(),
+            agent_access_max: session.agent_access_max.clone(),
+            home: None,
+            buddy_list: Vec::new(),
+            socket: None,
+            outbound: None,
+            agent_list: Arc::new(Mutex::new(HashMap::new())),
+        });
+        circuit.do_send(Packet::new_circuit_code(CircuitCodeData {
+            code: session.circuit_code,
+            session_id: session.session_id,
+            id: session.agent_id,
+        }));
+        self.neighbor_circuits.insert(msg.region_handle, circuit);
+    }
+}
+
+impl Handler<Packet> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: Packet, ctx: &mut Self::Context) -> Self::Result {
+        if self.awaiting_transport() {
+            self.hold_packet(msg);
+            return;
+        }
+        match self.send_packet(msg, ctx) {
+            Ok(Some(ack_future)) => {
+                let mailbox = ctx.address();
                 ctx.spawn(
                     async move {
                         if let Err(e) = ack_future.await {
-                            error!("Error sending acknowledgment: {:?}", e);
+                            error!("Error sending acknowledgment: {}", e);
+                            // let the UI know which packet was lost, so it can retry
+                            mailbox.do_send(UiMessage::new(UiEventTypes::Error, e.to_bytes()));
                         }
                     }
                     .into_actor(self),
                 );
-            } else {
-                let data = msg.to_bytes().clone();
-                let socket_clone = session.socket.as_ref().unwrap().clone();
-                let fut = async move {
-                    if let Err(e) = socket_clone.send_to(&data, &addr).await {
-                        error!("Failed to send data: {}", e);
-                    }
-                };
-                ctx.spawn(fut.into_actor(self));
-            };
-            {
-                let mut sequence_number = self.packet_sequence_number.lock().unwrap();
-                *sequence_number += 1;
             }
+            Ok(None) => {}
+            Err(e) => warn!("{}", e),
+        }
+    }
+}
+
+impl Handler<SendPacketWithReliability> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: SendPacketWithReliability, ctx: &mut Self::Context) -> Self::Result {
+        let mut packet = msg.packet;
+        if let Some(reliable) = msg.reliable {
+            packet.header.reliable = reliable;
+        }
+        self.handle(packet, ctx)
+    }
+}
+
+impl Handler<SendPacket> for Mailbox {
+    type Result = ResponseFuture<Result<(), SessionError>>;
+    fn handle(&mut self, msg: SendPacket, ctx: &mut Self::Context) -> Self::Result {
+        match self.send_packet(msg.0, ctx) {
+            Ok(Some(ack_future)) => Box::pin(ack_future),
+            Ok(None) => Box::pin(async { Ok(()) }),
+            Err(e) => Box::pin(async { Err(e) }),
+        }
+    }
+}
+
+impl Handler<ResumeCircuit> for Mailbox {
+    type Result = ResponseActFuture<Self, Result<(), SessionError>>;
+    fn handle(&mut self, _: ResumeCircuit, ctx: &mut Self::Context) -> Self::Result {
+        if self.resuming {
+            return Box::pin(fut::ready(Err(SessionError::Resume(ResumeError::new(
+                "the circuit is already being resumed",
+            )))));
         }
+        let Some(session) = self.session.as_mut() else {
+            return Box::pin(fut::ready(Err(SessionError::Resume(ResumeError::new(
+                "no session to resume",
+            )))));
+        };
+        info!(
+            "resuming the circuit to {}:{} on a new socket",
+            session.url, session.server_socket
+        );
+        session.socket = None;
+        session.outbound = None;
+        let use_circuit_code = Packet::new_circuit_code(CircuitCodeData {
+            code: session.circuit_code,
+            session_id: session.session_id,
+            id: session.agent_id,
+        });
+        let complete_agent_movement =
+            Packet::new_complete_agent_movement(CompleteAgentMovementData {
+                agent_id: session.agent_id,
+                session_id: session.session_id,
+                circuit_code: session.circuit_code,
+            });
+        self.reset_circuit();
+        self.resuming = true;
+        self.ping_info.awaiting_pong = false;
+        self.ping_info.missed_pongs = 0;
+
+        Box::pin(
+            self.bind_socket(ctx)
+                .then(move |bound, act, ctx| {
+                    let sent = bound
+                        .map_err(|e| {
+                            SessionError::Resume(ResumeError::new(format!(
+                                "failed to bind a new socket: {}",
+                                e
+                            )))
+                        })
+                        .and_then(|()| act.send_packet(use_circuit_code, ctx));
+                    ctx.address().do_send(complete_agent_movement);
+                    async move {
+                        match sent? {
+                            Some(ack) => ack.await.map_err(|e| {
+                                SessionError::Resume(ResumeError::new(format!(
+                                    "the server rejected the resumed circuit: {}",
+                                    e
+                                )))
+                            }),
+                            None => Ok(()),
+                        }
+                    }
+                    .into_actor(act)
+                })
+                .map(|result, act, _| {
+                    act.resuming = false;
+                    result
+                }),
+        )
+    }
+}
+
+impl Handler<Logout> for Mailbox {
+    type Result = ResponseFuture<Result<(), SessionError>>;
+    fn handle(&mut self, _: Logout, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_ref() else {
+            return Box::pin(async { Ok(()) });
+        };
+        let packet = Packet::new_logout_request(LogoutRequest {
+            agent_id: session.agent_id,
+            session_id: session.session_id,
+        });
+        self.handle(SendPacket(packet), ctx)
+    }
+}
+
+impl Handler<Pause> for Mailbox {
+    type Result = ResponseActFuture<Self, Result<(), SessionError>>;
+    fn handle(&mut self, msg: Pause, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_ref() else {
+            return Box::pin(fut::ready(Err(SessionError::Mailbox(MailboxError::new(
+                "no session to pause",
+            )))));
+        };
+        self.pause_serial_num = self.pause_serial_num.wrapping_add(1);
+        let serial_num = self.pause_serial_num;
+        let suspend_pings = msg.suspend_pings;
+        let packet = Packet::new_agent_pause(AgentPause {
+            agent_id: session.agent_id,
+            session_id: session.session_id,
+            serial_num,
+        });
+        // the server only stops expecting pings once it has the pause, and a resume sent in the
+        // meantime wins
+        Box::pin(self.handle(SendPacket(packet), ctx).into_actor(self).map(
+            move |result, act, _| {
+                if result.is_ok() && act.pause_serial_num == serial_num {
+                    act.pings_suspended = suspend_pings;
+                }
+                result
+            },
+        ))
+    }
+}
+
+impl Handler<Resume> for Mailbox {
+    type Result = ResponseFuture<Result<(), SessionError>>;
+    fn handle(&mut self, _: Resume, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_ref() else {
+            return Box::pin(async {
+                Err(SessionError::Mailbox(MailboxError::new(
+                    "no session to resume",
+                )))
+            });
+        };
+        self.pause_serial_num = self.pause_serial_num.wrapping_add(1);
+        self.pings_suspended = false;
+        let packet = Packet::new_agent_resume(AgentResume {
+            agent_id: session.agent_id,
+            session_id: session.session_id,
+            serial_num: self.pause_serial_num,
+        });
+        self.handle(SendPacket(packet), ctx)
+    }
+}
+
+impl Handler<SetWindowSize> for Mailbox {
+    type Result = ResponseFuture<Result<(), SessionError>>;
+    fn handle(&mut self, msg: SetWindowSize, ctx: &mut Self::Context) -> Self::Result {
+        self.window_size = Some((msg.height, msg.width));
+        // before the handshake the size is only stored, and sent when the handshake completes
+        let Some(session) = self.session.as_ref().filter(|_| self.handshake_complete) else {
+            return Box::pin(async { Ok(()) });
+        };
+        self.height_width_gen_counter = self.height_width_gen_counter.wrapping_add(1);
+        let packet = Packet::new_agent_height_width(AgentHeightWidth {
+            agent_id: session.agent_id,
+            session_id: session.session_id,
+            circuit_code: session.circuit_code,
+            gen_counter: self.height_width_gen_counter,
+            height: msg.height,
+            width: msg.width,
+        });
+        self.handle(SendPacket(packet), ctx)
+    }
+}
+
+impl Handler<Animate> for Mailbox {
+    type Result = ResponseFuture<Result<(), SessionError>>;
+    fn handle(&mut self, msg: Animate, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_ref() else {
+            return Box::pin(async {
+                Err(SessionError::Mailbox(MailboxError::new(
+                    "no session to animate",
+                )))
+            });
+        };
+        let packet = Packet::new_agent_animation(AgentAnimation::new(
+            session.agent_id,
+            session.session_id,
+            msg.0,
+        ));
+        self.handle(SendPacket(packet), ctx)
+    }
+}
+
+impl Handler<Shutdown> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, _: Shutdown, ctx: &mut Self::Context) -> Self::Result {
+        ctx.stop();
+    }
+}
+
+/// tell the UI that friends came online or went offline, one FriendPresenceEvent per friend
+async fn send_friend_presence(agent_ids: &[Uuid], online: bool, mailbox_address: &Addr<Mailbox>) {
+    for agent_id in agent_ids {
+        let presence = FriendPresence {
+            agent_id: *agent_id,
+            online,
+        };
+        if let Err(e) = mailbox_address
+            .send(UiMessage::new(
+                UiEventTypes::FriendPresenceEvent,
+                presence.to_bytes(),
+            ))
+            .await
+        {
+            warn!("failed to send friend presence to the ui: {:?}", e)
+        };
+    }
+}
+
+/// wait until the deadline, or forever if there is none
+async fn sleep_until_deadline(deadline: Option<std::time::Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
     }
 }
 
 async fn send_ack(
     packet: Packet,
     addr: String,
-    ack_queue: Arc<Mutex<HashMap<u32, oneshot::Sender<()>>>>,
-    socket: Arc<UdpSocket>,
+    ack_queue: AckQueue,
+    outbound: mpsc::Sender<OutboundPacket>,
+    clock: Arc<dyn Clock>,
 ) -> Result<(), SessionError> {
     let mut attempts = 0;
-    let mut received_ack = false;
     let packet_id = packet.header.sequence_number;
-    while attempts < ACK_ATTEMPTS && !received_ack {
-        let (tx, rx) = oneshot::channel();
-        let mut packet_clone = packet.clone();
+    // one channel is used for every attempt, so an ack that arrives between a timeout and the
+    // next attempt is not lost
+    let (tx, mut rx) = oneshot::channel();
+    {
+        let mut queue = ack_queue.lock().unwrap();
+        let now = clock.now();
+        queue.retain(|_, pending| {
+            pending
+                .given_up
+                .is_none_or(|given_up| now.saturating_duration_since(given_up) < LATE_ACK_WINDOW)
+        });
+        queue.insert(
+            packet_id,
+            PendingAck {
+                sender: tx,
+                first_sent: now,
+                attempts: 0,
+                given_up: None,
+            },
+        );
+    }
+    let closed = |attempts, addr| {
+        Err(SessionError::AckError(AckError::new(
+            "circuit was closed before the ack arrived",
+            packet_id,
+            attempts,
+            addr,
+        )))
+    };
+    while attempts < ACK_ATTEMPTS {
+        match rx.try_recv() {
+            Ok(()) => return Ok(()),
+            Err(oneshot::error::TryRecvError::Closed) => return closed(attempts, addr),
+            Err(oneshot::error::TryRecvError::Empty) => {}
+        }
+        if let Some(pending) = ack_queue.lock().unwrap().get_mut(&packet_id) {
+            pending.attempts = attempts + 1;
+        }
 
+        let mut packet_clone = packet.clone();
         // if there have been more than 1 attempt, set the resent to true.
         if attempts > 0 {
             packet_clone.header.resent = true;
         }
 
+        // Queue the packet, waiting for room if the queue is full
+        let (sent_tx, sent_rx) = oneshot::channel();
+        if outbound
+            .send(OutboundPacket {
+                data: packet_clone.to_bytes(),
+                addr: addr.clone(),
+                reliable: true,
+                sent: Some(sent_tx),
+            })
+            .await
+            .is_err()
         {
-            let mut queue = ack_queue.lock().unwrap();
-            queue.insert(packet_id, tx);
+            ack_queue.lock().unwrap().remove(&packet_id);
+            return Err(SessionError::AckError(AckError::new(
+                "outbound queue is closed",
+                packet_id,
+                attempts,
+                addr,
+            )));
         }
-        // Send the packet
 
-        let data = packet_clone.to_bytes().clone();
-        let addr_clone = addr.clone();
-        let sock_clone = socket.clone();
-        if let Err(e) = sock_clone.send_to(&data, addr_clone).await {
-            error!("Failed to send Ack: {}", e);
+        // the ack timeout starts once the packet is written, not while it waits for bandwidth.
+        // An earlier attempt can still be acked in the meantime.
+        tokio::select! {
+            result = &mut rx => match result {
+                Ok(()) => return Ok(()),
+                Err(_) => return closed(attempts, addr),
+            },
+            _ = sent_rx => {}
         }
-
         tokio::select! {
-            _ = rx => {
-                received_ack = true;
+            result = &mut rx => match result {
+                Ok(()) => return Ok(()),
+                // the ack queue was cleared, because the circuit was closed
+                Err(_) => return closed(attempts, addr),
             },
-            _ = tokio::time::sleep(ACK_TIMEOUT) => {
+            _ = clock.sleep(ACK_TIMEOUT) => {
                 attempts += 1;
-                if !received_ack && attempts >= ACK_ATTEMPTS {
-                    // Remove from queue after final attempt
-                    let mut queue = ack_queue.lock().unwrap();
-                    queue.remove(&packet_id);
-                }
             }
         }
     }
-    if received_ack {
-        Ok(())
-    } else {
-        Err(SessionError::AckError(AckError::new(
-            "failed to retrieve ack ".to_string(),
-        )))
+
+    let mut queue = ack_queue.lock().unwrap();
+    // the sender lives in the queue, so while the channel is open the packet is still in it.
+    // Checking under the lock means an ack can't slip in between.
+    match rx.try_recv() {
+        Ok(()) => Ok(()),
+        Err(oneshot::error::TryRecvError::Closed) => closed(attempts, addr),
+        Err(oneshot::error::TryRecvError::Empty) => {
+            // keep the packet around for a while, in case its ack is only late
+            if let Some(pending) = queue.get_mut(&packet_id) {
+                pending.given_up = Some(clock.now());
+            }
+            Err(SessionError::AckError(AckError::new(
+                "failed to retrieve ack",
+                packet_id,
+                attempts,
+                addr,
+            )))
+        }
     }
 }
@@ -0,0 +1,137 @@
+use metaverse_messages::packet::Packet;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// Puts reliable packets from the server back into sequence number order.
+/// Retransmitted reliable packets can arrive after the packets that were sent after them. This
+/// holds a reliable packet until every packet before it has arrived, or until it has waited for
+/// the hold time, and then releases it.
+/// Unreliable packets are never held, because they may never arrive.
+#[derive(Debug)]
+pub struct ReorderBuffer {
+    /// how long a gap in the sequence numbers is waited on before it is skipped
+    hold: Duration,
+    /// the sequence number of the next packet to release
+    next: Option<u32>,
+    /// the simulator the sequence numbers belong to
+    source: Option<SocketAddr>,
+    /// packets that arrived after a gap. Unreliable packets have already been released, and are
+    /// kept as None to mark their sequence number as received.
+    pending: BTreeMap<u32, Option<Packet>>,
+    /// when the current gap started
+    gap_since: Option<Instant>,
+}
+
+impl ReorderBuffer {
+    /// create a buffer that waits at most hold for a missing packet
+    pub fn new(hold: Duration) -> Self {
+        ReorderBuffer {
+            hold,
+            next: None,
+            source: None,
+            pending: BTreeMap::new(),
+            gap_since: None,
+        }
+    }
+
+    /// add a packet received from source, and return the packets that are ready, in order
+    pub fn push(&mut self, packet: Packet, source: SocketAddr) -> Vec<Packet> {
+        let mut ready = Vec::new();
+        // a new simulator starts its own sequence numbers
+        if self.source != Some(source) {
+            ready.extend(self.release_all());
+            self.source = Some(source);
+            self.next = None;
+        }
+
+        let sequence_number = packet.header.sequence_number;
+        let next = *self.next.get_or_insert(sequence_number);
+        if distance(next, sequence_number) < 0 {
+            // late, or a duplicate of a packet that was already released
+            ready.push(packet);
+        } else if sequence_number == next {
+            ready.push(packet);
+            self.next = Some(next.wrapping_add(1));
+            self.release_in_order(&mut ready);
+        } else {
+            if packet.header.reliable {
+                self.pending.insert(sequence_number, Some(packet));
+            } else {
+                self.pending.insert(sequence_number, None);
+                ready.push(packet);
+            }
+            self.gap_since.get_or_insert_with(Instant::now);
+        }
+        ready
+    }
+
+    /// when the current gap will be skipped, if there is one
+    pub fn deadline(&self) -> Option<Instant> {
+        self.gap_since.map(|since| since + self.hold)
+    }
+
+    /// skip the current gap if it has been waited on for the hold time, and return the packets
+    /// after it that are ready
+    pub fn release_expired(&mut self) -> Vec<Packet> {
+        let mut ready = Vec::new();
+        if self
+            .deadline()
+            .is_some_and(|deadline| deadline <= Instant::now())
+        {
+            self.next = self.first_pending();
+            self.release_in_order(&mut ready);
+        }
+        ready
+    }
+
+    /// release every held packet, skipping all gaps
+    fn release_all(&mut self) -> Vec<Packet> {
+        self.gap_since = None;
+        let mut pending: Vec<_> = std::mem::take(&mut self.pending).into_iter().collect();
+        // the sequence numbers may have wrapped around since next
+        if let Some(next) = self.next {
+            pending.sort_by_key(|(sequence_number, _)| distance(next, *sequence_number));
+        }
+        pending
+            .into_iter()
+            .filter_map(|(_, packet)| packet)
+            .collect()
+    }
+
+    /// the held sequence number closest after next, which is not always the lowest once the
+    /// sequence numbers wrap around
+    fn first_pending(&self) -> Option<u32> {
+        let next = self.next?;
+        self.pending
+            .keys()
+            .copied()
+            .min_by_key(|sequence_number| distance(next, *sequence_number))
+    }
+
+    /// release the held packets that follow next without a gap
+    fn release_in_order(&mut self, ready: &mut Vec<Packet>) {
+        let Some(mut next) = self.next else {
+            return;
+        };
+        let mut released = false;
+        while let Some(entry) = self.pending.remove(&next) {
+            ready.extend(entry);
+            next = next.wrapping_add(1);
+            released = true;
+        }
+        self.next = Some(next);
+        if self.pending.is_empty() {
+            self.gap_since = None;
+        } else if released {
+            // a new gap
+            self.gap_since = Some(Instant::now());
+        }
+    }
+}
+
+/// how far ahead of from a sequence number is, negative if it is behind. The sequence numbers
+/// wrap around at u32::MAX.
+fn distance(from: u32, to: u32) -> i32 {
+    to.wrapping_sub(from) as i32
+}
@@ -0,0 +1,250 @@
+use actix::Addr;
+use futures::future::BoxFuture;
+use log::warn;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Instant;
+use tokio::time::Duration;
+
+use crate::clock::Clock;
+use crate::mailbox::{Mailbox, Replay};
+use crate::transport::Transport;
+
+/// A datagram received from the server, and when it arrived
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedDatagram {
+    /// the time since the recording started
+    pub at: Duration,
+    /// the datagram as it came off the socket, before the codec decoded it
+    pub data: Vec<u8>,
+}
+
+/// Records every datagram the mailbox receives from the server to a file, to replay it later
+/// with replay_from_file. Each datagram is written as the microseconds since the recording
+/// started (u64), its length (u16) and its bytes, all little endian.
+/// The file is written on its own thread, so a slow disk doesn't hold up receiving. Whatever has
+/// been recorded is flushed whenever the thread catches up, so a recording survives the client
+/// crashing.
+#[derive(Debug)]
+pub struct Recorder {
+    writer: mpsc::Sender<RecorderCommand>,
+    start: Instant,
+}
+
+#[derive(Debug)]
+enum RecorderCommand {
+    /// append a record to the file
+    Record(Vec<u8>),
+    /// flush the file, and report back once it is done
+    Flush(mpsc::Sender<io::Result<()>>),
+}
+
+impl Recorder {
+    /// start a new recording, replacing the file if it exists
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        let (writer, commands) = mpsc::channel();
+        thread::Builder::new()
+            .name("recorder".to_string())
+            .spawn(move || Recorder::write_records(file, commands))?;
+        Ok(Recorder {
+            writer,
+            start: Instant::now(),
+        })
+    }
+
+    /// append a datagram to the recording. It is written to the file in the background.
+    pub fn record(&self, data: &[u8]) -> io::Result<()> {
+        let at = self.start.elapsed().as_micros() as u64;
+        let mut record = Vec::with_capacity(10 + data.len());
+        record.extend_from_slice(&at.to_le_bytes());
+        record.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        record.extend_from_slice(data);
+        self.send(RecorderCommand::Record(record))
+    }
+
+    /// wait until every datagram recorded so far is in the file
+    pub fn flush(&self) -> io::Result<()> {
+        let (done, result) = mpsc::channel();
+        self.send(RecorderCommand::Flush(done))?;
+        result.recv().map_err(|_| stopped())?
+    }
+
+    fn send(&self, command: RecorderCommand) -> io::Result<()> {
+        self.writer.send(command).map_err(|_| stopped())
+    }
+
+    /// write the records as they come, flushing whenever there are none waiting. Stops at the
+    /// first error, or once the Recorder is dropped.
+    fn write_records(mut file: BufWriter<File>, commands: mpsc::Receiver<RecorderCommand>) {
+        let mut waiting = commands.recv().ok();
+        while let Some(command) = waiting {
+            let written = match command {
+                RecorderCommand::Record(record) => file.write_all(&record),
+                RecorderCommand::Flush(done) => {
+                    let _ = done.send(file.flush());
+                    Ok(())
+                }
+            };
+            if let Err(e) = written {
+                warn!("Failed to record datagram, stopping the recording: {}", e);
+                return;
+            }
+            waiting = match commands.try_recv() {
+                Ok(command) => Some(command),
+                Err(_) => {
+                    if let Err(e) = file.flush() {
+                        warn!("Failed to record datagram, stopping the recording: {}", e);
+                        return;
+                    }
+                    commands.recv().ok()
+                }
+            };
+        }
+    }
+}
+
+fn stopped() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "the recording has stopped")
+}
+
+/// read every datagram of a recording made by a Recorder.
+/// Returns an error if the file is cut off in the middle of a datagram.
+pub fn read_recording(path: impl AsRef<Path>) -> io::Result<Vec<RecordedDatagram>> {
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+
+    let mut datagrams = Vec::new();
+    let mut rest = bytes.as_slice();
+    while !rest.is_empty() {
+        let (header, body) = rest
+            .split_at_checked(10)
+            .ok_or_else(|| truncated("datagram header"))?;
+        let at = u64::from_le_bytes(header[..8].try_into().unwrap());
+        let length = u16::from_le_bytes(header[8..].try_into().unwrap()) as usize;
+        let (data, next) = body
+            .split_at_checked(length)
+            .ok_or_else(|| truncated("datagram"))?;
+        datagrams.push(RecordedDatagram {
+            at: Duration::from_micros(at),
+            data: data.to_vec(),
+        });
+        rest = next;
+    }
+    Ok(datagrams)
+}
+
+fn truncated(what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("recording ends in the middle of a {}", what),
+    )
+}
+
+/// replay a recording into the mailbox, as if its datagrams were arriving from the server with
+/// their original timing. The mailbox stops reading from its socket while the recording plays,
+/// and the packets it sends are discarded.
+///```no_run
+/// use actix::Actor;
+/// use metaverse_session::{mailbox::Mailbox, replay::replay_from_file};
+/// # async fn run() -> std::io::Result<()> {
+/// let mailbox = Mailbox::new(0, "127.0.0.1:8081".to_string()).start();
+/// replay_from_file("bug_report.rec", mailbox).await?;
+/// # Ok(())
+/// # }
+///```
+pub async fn replay_from_file(path: impl AsRef<Path>, mailbox: Addr<Mailbox>) -> io::Result<()> {
+    let datagrams = read_recording(path)?;
+    mailbox
+        .send(Replay(datagrams))
+        .await
+        .map_err(|e| io::Error::other(format!("{}", e)))
+}
+
+/// A Transport that records every datagram it receives
+#[derive(Debug)]
+pub struct RecordingTransport {
+    inner: Arc<dyn Transport>,
+    recorder: Arc<Recorder>,
+}
+
+impl RecordingTransport {
+    /// record the datagrams received by the transport
+    pub fn new(inner: Arc<dyn Transport>, recorder: Arc<Recorder>) -> Self {
+        RecordingTransport { inner, recorder }
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn send_to<'a>(&'a self, data: &'a [u8], addr: &'a str) -> BoxFuture<'a, io::Result<usize>> {
+        self.inner.send_to(data, addr)
+    }
+    fn recv_from<'a>(
+        &'a self,
+        buf: &'a mut [u8],
+    ) -> BoxFuture<'a, io::Result<(usize, SocketAddr)>> {
+        Box::pin(async move {
+            let (size, addr) = self.inner.recv_from(buf).await?;
+            // losing the recording shouldn't lose the packet
+            if let Err(e) = self.recorder.record(&buf[..size]) {
+                warn!("Failed to record datagram: {}", e);
+            }
+            Ok((size, addr))
+        })
+    }
+}
+
+/// A Transport that plays back a recording. Each datagram is received once its time has passed
+/// on the clock, counted from when the transport was created. Sent datagrams are discarded, and
+/// receiving fails once the recording is over.
+#[derive(Debug)]
+pub struct ReplayTransport {
+    datagrams: tokio::sync::Mutex<std::vec::IntoIter<RecordedDatagram>>,
+    clock: Arc<dyn Clock>,
+    start: tokio::time::Instant,
+}
+
+impl ReplayTransport {
+    /// play back the datagrams on the clock
+    pub fn new(datagrams: Vec<RecordedDatagram>, clock: Arc<dyn Clock>) -> Self {
+        ReplayTransport {
+            datagrams: tokio::sync::Mutex::new(datagrams.into_iter()),
+            start: clock.now(),
+            clock,
+        }
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send_to<'a>(&'a self, data: &'a [u8], _: &'a str) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(async move { Ok(data.len()) })
+    }
+    fn recv_from<'a>(
+        &'a self,
+        buf: &'a mut [u8],
+    ) -> BoxFuture<'a, io::Result<(usize, SocketAddr)>> {
+        Box::pin(async move {
+            let mut datagrams = self.datagrams.lock().await;
+            // the datagram is only taken once it is received, so a receive that is cancelled
+            // while it waits doesn't lose it
+            let Some(datagram) = datagrams.as_slice().first().cloned() else {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "the recording is over",
+                ));
+            };
+            let elapsed = self.clock.now().saturating_duration_since(self.start);
+            if datagram.at > elapsed {
+                self.clock.sleep(datagram.at - elapsed).await;
+            }
+            datagrams.next();
+            let size = datagram.data.len().min(buf.len());
+            buf[..size].copy_from_slice(&datagram.data[..size]);
+            Ok((size, SocketAddr::from(([127, 0, 0, 1], 0))))
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use crate::mailbox::{Mailbox, Session, UiMessage};
+use crate::mailbox::{Mailbox, Ready, SendPacket, Session, UiMessage};
 use log::{info, warn};
 use metaverse_messages::circuit_code::CircuitCodeData;
 use metaverse_messages::complete_agent_movement::CompleteAgentMovementData;
@@ -11,7 +11,15 @@ use metaverse_messages::login_system::simulator_login_protocol::SimulatorLoginPr
 use metaverse_messages::packet::Packet;
 use metaverse_messages::packet_types::PacketType;
 use metaverse_messages::ui_events::UiEventTypes;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use tokio::net::UdpSocket;
+use tokio::time::{sleep, timeout, Duration};
+
+/// how long to wait for the simulator to accept the UseCircuitCode, resends included
+pub const USE_CIRCUIT_CODE_TIMEOUT: Duration = Duration::from_secs(5);
+/// how often to check whether the mailbox's socket is bound, before sending the UseCircuitCode
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 /// This is used for the server to listen to messages coming in from the UI.
 /// Messages from the UI are sent in bytes as packets, and deserialized in the same way that they
@@ -123,26 +131,31 @@ async fn handle_login(
             url: login_response.sim_ip.unwrap(),
             agent_id: login_response.agent_id.unwrap(),
             session_id: login_response.session_id.unwrap(),
+            circuit_code: login_response.circuit_code,
+            agent_name: format!("{} {}", login_response.first_name, login_response.last_name),
+            agent_access: login_response.agent_access,
+            agent_access_max: login_response.agent_access_max,
+            home: login_response.home.clone(),
+            buddy_list: login_response.buddy_list.clone().unwrap_or_default(),
             socket: None,
+            outbound: None,
+            agent_list: Arc::new(Mutex::new(HashMap::new())),
         })
         .await
     {
         return Err(SessionError::Mailbox(MailboxError::new(format!("{}", e))));
     };
 
-    if let Err(e) = mailbox_addr
-        .send(Packet::new_circuit_code(CircuitCodeData {
+    use_circuit_code(
+        mailbox_addr,
+        CircuitCodeData {
             code: login_response.circuit_code,
             session_id: login_response.session_id.unwrap(),
             id: login_response.agent_id.unwrap(),
-        }))
-        .await
-    {
-        return Err(SessionError::CircuitCode(CircuitCodeError::new(format!(
-            "{}",
-            e
-        ))));
-    };
+        },
+        USE_CIRCUIT_CODE_TIMEOUT,
+    )
+    .await?;
 
     if let Err(e) = mailbox_addr
         .send(Packet::new_complete_agent_movement(
@@ -161,3 +174,33 @@ async fn handle_login(
 
     Ok(())
 }
+
+/// Open the circuit to the simulator with a UseCircuitCode, and wait for the simulator to ack it.
+/// The packet is sent once the mailbox's socket is bound. Like any reliable packet, the mailbox
+/// sends it again until it is acked, and gives up after its own attempts. timeout bounds the
+/// whole wait.
+pub async fn use_circuit_code(
+    mailbox_addr: &actix::Addr<Mailbox>,
+    circuit_code: CircuitCodeData,
+    timeout_after: Duration,
+) -> Result<(), SessionError> {
+    let sent = timeout(timeout_after, async {
+        while !mailbox_addr.send(Ready).await? {
+            sleep(READY_POLL_INTERVAL).await;
+        }
+        mailbox_addr
+            .send(SendPacket(Packet::new_circuit_code(circuit_code)))
+            .await
+    })
+    .await;
+    let error = match sent {
+        Ok(Ok(Ok(()))) => return Ok(()),
+        Ok(Ok(Err(e))) => e.to_string(),
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => format!("the simulator did not ack within {:?}", timeout_after),
+    };
+    Err(SessionError::CircuitCode(CircuitCodeError::new(format!(
+        "the simulator did not accept the circuit code: {}",
+        error
+    ))))
+}
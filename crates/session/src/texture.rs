@@ -0,0 +1,119 @@
+use log::warn;
+use metaverse_messages::image_data::ImageData;
+use metaverse_messages::image_packet::ImagePacket;
+use metaverse_messages::texture::Texture;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Puts textures sent over UDP back together.
+/// A texture requested with RequestImage arrives as an ImageData, which is packet 0 and says how
+/// many packets there are, followed by ImagePackets. The packets can arrive in any order, so
+/// ImagePackets that arrive before their ImageData are held until it does.
+/// The number of packets depends on the discard level of the request, so a texture requested at
+/// a lower level of detail is complete with fewer packets.
+/// Textures whose packets stop arriving are never completed, so only a limited number are kept,
+/// and the one that has waited longest for a packet is dropped to make room.
+#[derive(Debug)]
+pub struct TextureAssembler {
+    partial: HashMap<Uuid, PartialTexture>,
+    /// how many incomplete textures are kept
+    max_pending: usize,
+    /// counts the packets added, to tell which texture was updated least recently
+    updates: u64,
+}
+
+/// how many incomplete textures are kept by default
+pub const MAX_PENDING_TEXTURES: usize = 256;
+
+impl Default for TextureAssembler {
+    fn default() -> Self {
+        Self::with_limit(MAX_PENDING_TEXTURES)
+    }
+}
+
+#[derive(Debug, Default)]
+struct PartialTexture {
+    /// the codec and packet count, once the ImageData has arrived
+    header: Option<(u8, u16)>,
+    /// the data of each packet that has arrived, by packet number
+    pieces: BTreeMap<u16, Vec<u8>>,
+    /// when the last packet arrived, in the assembler's updates
+    last_update: u64,
+}
+
+impl TextureAssembler {
+    /// create an empty assembler
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// create an empty assembler that keeps at most max_pending incomplete textures
+    pub fn with_limit(max_pending: usize) -> Self {
+        TextureAssembler {
+            partial: HashMap::new(),
+            max_pending: max_pending.max(1),
+            updates: 0,
+        }
+    }
+
+    /// add the first packet of a texture, and return the texture if it is complete
+    pub fn image_data(&mut self, image: ImageData) -> Option<Texture> {
+        let partial = self.entry(image.id);
+        // a texture that is requested again starts over
+        if partial.header.is_some() {
+            partial.pieces.clear();
+        }
+        partial.header = Some((image.codec, image.packets));
+        partial.pieces.insert(0, image.data);
+        self.complete(image.id)
+    }
+
+    /// add a piece of a texture, and return the texture if it is complete
+    pub fn image_packet(&mut self, image: ImagePacket) -> Option<Texture> {
+        self.entry(image.id).pieces.insert(image.packet, image.data);
+        self.complete(image.id)
+    }
+
+    /// the number of textures that are still missing packets
+    pub fn pending(&self) -> usize {
+        self.partial.len()
+    }
+
+    /// the partial texture for id, making room for it if it is new
+    fn entry(&mut self, id: Uuid) -> &mut PartialTexture {
+        if !self.partial.contains_key(&id) && self.partial.len() >= self.max_pending {
+            let stalest = self
+                .partial
+                .iter()
+                .min_by_key(|(_, partial)| partial.last_update)
+                .map(|(id, _)| *id);
+            if let Some(stalest) = stalest {
+                warn!("Too many incomplete textures, dropping {}", stalest);
+                self.partial.remove(&stalest);
+            }
+        }
+        self.updates += 1;
+        let partial = self.partial.entry(id).or_default();
+        partial.last_update = self.updates;
+        partial
+    }
+
+    fn complete(&mut self, id: Uuid) -> Option<Texture> {
+        let partial = self.partial.get(&id)?;
+        let (codec, packets) = partial.header?;
+        if (0..packets).any(|packet| !partial.pieces.contains_key(&packet)) {
+            return None;
+        }
+        let partial = self.partial.remove(&id)?;
+        Some(Texture {
+            id,
+            codec,
+            data: partial
+                .pieces
+                .into_iter()
+                .filter(|(packet, _)| *packet < packets)
+                .flat_map(|(_, data)| data)
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,120 @@
+use futures::future::BoxFuture;
+use std::fmt::Debug;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use tokio::net::UdpSocket;
+use tokio::sync::{mpsc, watch, Notify};
+
+/// The connection between the mailbox and the external server.
+/// This is a UDP socket when running, but can be replaced with a MockTransport for testing the
+/// mailbox without a network.
+pub trait Transport: Debug + Send + Sync + 'static {
+    /// send a datagram to the address
+    fn send_to<'a>(&'a self, data: &'a [u8], addr: &'a str) -> BoxFuture<'a, io::Result<usize>>;
+    /// wait for the next datagram, and return its size and where it came from
+    fn recv_from<'a>(&'a self, buf: &'a mut [u8])
+        -> BoxFuture<'a, io::Result<(usize, SocketAddr)>>;
+}
+
+impl Transport for UdpSocket {
+    fn send_to<'a>(&'a self, data: &'a [u8], addr: &'a str) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(UdpSocket::send_to(self, data, addr))
+    }
+    fn recv_from<'a>(
+        &'a self,
+        buf: &'a mut [u8],
+    ) -> BoxFuture<'a, io::Result<(usize, SocketAddr)>> {
+        Box::pin(UdpSocket::recv_from(self, buf))
+    }
+}
+
+/// An in-memory Transport for tests.
+/// Every datagram the mailbox sends is recorded, and datagrams can be injected as if they came
+/// from the server.
+#[derive(Debug)]
+pub struct MockTransport {
+    /// the datagrams sent by the mailbox, and the address they were sent to
+    pub sent: Mutex<Vec<(Vec<u8>, String)>>,
+    sent_notify: Notify,
+    /// whether sends are held until the transport is unstalled
+    stalled: watch::Sender<bool>,
+    inbound_sender: mpsc::UnboundedSender<Vec<u8>>,
+    inbound: tokio::sync::Mutex<mpsc::UnboundedReceiver<Vec<u8>>>,
+}
+
+impl MockTransport {
+    /// create a transport with nothing sent or received
+    pub fn new() -> Self {
+        let (inbound_sender, inbound) = mpsc::unbounded_channel();
+        MockTransport {
+            sent: Mutex::new(Vec::new()),
+            sent_notify: Notify::new(),
+            stalled: watch::Sender::new(false),
+            inbound_sender,
+            inbound: tokio::sync::Mutex::new(inbound),
+        }
+    }
+    /// deliver a datagram to the mailbox, as if it was sent by the server
+    pub fn inject(&self, data: Vec<u8>) {
+        let _ = self.inbound_sender.send(data);
+    }
+    /// hold the mailbox's sends until the transport is unstalled, like a socket whose send buffer
+    /// is full
+    pub fn stall(&self, stalled: bool) {
+        self.stalled.send_replace(stalled);
+    }
+    /// take all of the datagrams sent so far
+    pub fn take_sent(&self) -> Vec<(Vec<u8>, String)> {
+        std::mem::take(&mut *self.sent.lock().unwrap())
+    }
+    /// wait until at least count datagrams have been sent since they were last taken
+    pub async fn wait_for_sent(&self, count: usize) {
+        loop {
+            // registered before checking, so a send in between isn't missed
+            let notified = self.sent_notify.notified();
+            if self.sent.lock().unwrap().len() >= count {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+impl Default for MockTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for MockTransport {
+    fn send_to<'a>(&'a self, data: &'a [u8], addr: &'a str) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(async move {
+            let _ = self.stalled.subscribe().wait_for(|stalled| !stalled).await;
+            self.sent
+                .lock()
+                .unwrap()
+                .push((data.to_vec(), addr.to_string()));
+            self.sent_notify.notify_waiters();
+            Ok(data.len())
+        })
+    }
+    fn recv_from<'a>(
+        &'a self,
+        buf: &'a mut [u8],
+    ) -> BoxFuture<'a, io::Result<(usize, SocketAddr)>> {
+        Box::pin(async move {
+            match self.inbound.lock().await.recv().await {
+                Some(data) => {
+                    let size = data.len().min(buf.len());
+                    buf[..size].copy_from_slice(&data[..size]);
+                    Ok((size, SocketAddr::from(([127, 0, 0, 1], 0))))
+                }
+                None => Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "mock transport closed",
+                )),
+            }
+        })
+    }
+}
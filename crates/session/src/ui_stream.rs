@@ -0,0 +1,159 @@
+use futures::Stream;
+use log::warn;
+use metaverse_messages::ui_events::UiEventTypes;
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::ReadBuf;
+use tokio::net::{ToSocketAddrs, UdpSocket};
+
+use crate::mailbox::{UiMessage, MAX_UI_MESSAGE_SIZE};
+
+/// Puts the chunks of the messages the mailbox sends to the UI back together.
+/// Chunks of different messages can be interleaved, and are told apart by their message id.
+/// A message missing a chunk is never completed, so only a limited number are kept, and the one
+/// that has waited longest for a chunk is dropped to make room.
+#[derive(Debug)]
+pub struct UiMessageAssembler {
+    /// the chunks that have arrived for each message, by sequence number, and when the last one
+    /// arrived, in the assembler's updates
+    messages: HashMap<u32, (HashMap<u16, Vec<u8>>, u64)>,
+    /// how many incomplete messages are kept
+    max_pending: usize,
+    /// counts the chunks added, to tell which message was updated least recently
+    updates: u64,
+}
+
+/// how many incomplete messages are kept by default
+pub const MAX_PENDING_UI_MESSAGES: usize = 64;
+
+impl Default for UiMessageAssembler {
+    fn default() -> Self {
+        Self::with_limit(MAX_PENDING_UI_MESSAGES)
+    }
+}
+
+impl UiMessageAssembler {
+    /// create an empty assembler
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// create an empty assembler that keeps at most max_pending incomplete messages
+    pub fn with_limit(max_pending: usize) -> Self {
+        UiMessageAssembler {
+            messages: HashMap::new(),
+            max_pending: max_pending.max(1),
+            updates: 0,
+        }
+    }
+
+    /// add a chunk, and return the event type and the full message if it is complete
+    pub fn push(&mut self, chunk: UiMessage) -> Option<(UiEventTypes, Vec<u8>)> {
+        if !self.messages.contains_key(&chunk.message_id) && self.messages.len() >= self.max_pending
+        {
+            let stalest = self
+                .messages
+                .iter()
+                .min_by_key(|(_, (_, last_update))| *last_update)
+                .map(|(message_id, _)| *message_id);
+            if let Some(stalest) = stalest {
+                warn!(
+                    "Too many incomplete UI messages, dropping message {}",
+                    stalest
+                );
+                self.messages.remove(&stalest);
+            }
+        }
+        self.updates += 1;
+        let (chunks, last_update) = self.messages.entry(chunk.message_id).or_default();
+        *last_update = self.updates;
+        chunks.insert(chunk.sequence_number, chunk.message);
+        if chunks.len() < chunk.total_packet_number as usize {
+            return None;
+        }
+        // the message is complete, so its id is free for reuse once the counter wraps
+        let (mut chunks, _) = self.messages.remove(&chunk.message_id)?;
+        match (0..chunk.total_packet_number)
+            .map(|i| chunks.remove(&i))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(chunks) => Some((chunk.message_type, chunks.concat())),
+            None => {
+                warn!(
+                    "Missing chunk for message {} reconstruction",
+                    chunk.message_id
+                );
+                None
+            }
+        }
+    }
+
+    /// the number of messages that are still missing chunks
+    pub fn pending(&self) -> usize {
+        self.messages.len()
+    }
+}
+
+/// A stream of the events the mailbox sends to the UI.
+/// It owns the socket the UI listens on, and yields each message once all of its chunks have
+/// arrived. The message can be decoded with UiEventTypes::packet_type_from_bytes.
+///```no_run
+/// use futures::StreamExt;
+/// use metaverse_session::ui_stream::UiEventStream;
+/// # async fn run() -> std::io::Result<()> {
+/// let mut events = UiEventStream::bind("127.0.0.1:8081").await?;
+/// while let Some((event_type, data)) = events.next().await {
+///     let packet = event_type.packet_type_from_bytes(&data);
+/// }
+/// # Ok(())
+/// # }
+///```
+#[derive(Debug)]
+pub struct UiEventStream {
+    socket: UdpSocket,
+    assembler: UiMessageAssembler,
+    buf: Vec<u8>,
+}
+
+impl UiEventStream {
+    /// bind the socket the mailbox sends UI events to
+    pub async fn bind(server_to_ui_socket: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(UiEventStream {
+            socket: UdpSocket::bind(server_to_ui_socket).await?,
+            assembler: UiMessageAssembler::new(),
+            // large enough for any chunk size the mailbox can be configured to send
+            buf: vec![0u8; MAX_UI_MESSAGE_SIZE],
+        })
+    }
+
+    /// the address the stream is listening on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl Stream for UiEventStream {
+    type Item = (UiEventTypes, Vec<u8>);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let mut buf = ReadBuf::new(&mut this.buf);
+            match this.socket.poll_recv_from(cx, &mut buf) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(e)) => warn!("UI Failed to read buffer {}", e),
+                Poll::Ready(Ok(_)) => match UiMessage::from_bytes(buf.filled()) {
+                    Some(chunk) => {
+                        if let Some(event) = this.assembler.push(chunk) {
+                            return Poll::Ready(Some(event));
+                        }
+                    }
+                    None => warn!("UI failed to deserialize the packet chunk from server"),
+                },
+            }
+        }
+    }
+}
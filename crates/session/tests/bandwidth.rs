@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use metaverse_session::bandwidth::Bandwidth;
+
+#[test]
+fn test_unlimited_bandwidth_is_only_counted() {
+    let mut bandwidth = Bandwidth::new();
+    for _ in 0..10 {
+        assert_eq!(bandwidth.reserve(1000, false), Some(Duration::ZERO));
+    }
+    let stats = bandwidth.stats();
+    assert_eq!(stats.bytes_sent, 10_000);
+    assert_eq!(stats.packets_sent, 10);
+    assert_eq!(stats.bytes_per_second, 10_000);
+    assert_eq!(stats.packets_dropped, 0);
+    assert_eq!(stats.max_bytes_per_second, None);
+}
+
+#[test]
+fn test_unreliable_packets_over_the_limit_are_dropped() {
+    let mut bandwidth = Bandwidth::with_limit(1000);
+    assert_eq!(bandwidth.reserve(600, false), Some(Duration::ZERO));
+    assert_eq!(bandwidth.reserve(600, false), None);
+
+    let stats = bandwidth.stats();
+    assert_eq!(stats.bytes_sent, 600);
+    assert_eq!(stats.packets_dropped, 1);
+}
+
+#[test]
+fn test_reliable_packets_over_the_limit_wait() {
+    let mut bandwidth = Bandwidth::with_limit(1000);
+    assert_eq!(bandwidth.reserve(600, true), Some(Duration::ZERO));
+    // 200 bytes short, at 1000 bytes per second
+    let delay = bandwidth.reserve(600, true).unwrap();
+    assert!(delay > Duration::from_millis(190) && delay <= Duration::from_millis(200));
+    // the next packet waits behind it
+    let delay = bandwidth.reserve(100, true).unwrap();
+    assert!(delay > Duration::from_millis(290) && delay <= Duration::from_millis(300));
+
+    let stats = bandwidth.stats();
+    assert_eq!(stats.packets_sent, 3);
+    assert_eq!(stats.packets_dropped, 0);
+    // the delayed packets haven't been sent yet
+    assert_eq!(stats.bytes_per_second, 600);
+}
+
+#[test]
+fn test_the_budget_refills() {
+    let mut bandwidth = Bandwidth::with_limit(1000);
+    assert_eq!(bandwidth.reserve(1000, false), Some(Duration::ZERO));
+    assert_eq!(bandwidth.reserve(100, false), None);
+    std::thread::sleep(Duration::from_millis(150));
+    assert_eq!(bandwidth.reserve(100, false), Some(Duration::ZERO));
+}
+
+#[test]
+fn test_wait_time_does_not_take_the_budget() {
+    let mut bandwidth = Bandwidth::with_limit(1000);
+    assert_eq!(bandwidth.wait_time(600), Duration::ZERO);
+    assert_eq!(bandwidth.reserve(600, true), Some(Duration::ZERO));
+    // 200 bytes short, at 1000 bytes per second
+    let wait = bandwidth.wait_time(600);
+    assert!(wait > Duration::from_millis(190) && wait <= Duration::from_millis(200));
+    // asking again doesn't add to it
+    assert!(bandwidth.wait_time(600) <= wait);
+    // a packet larger than the whole budget waits for a full bucket
+    let wait = bandwidth.wait_time(5000);
+    assert!(wait > Duration::from_millis(590) && wait <= Duration::from_millis(600));
+    assert_eq!(bandwidth.stats().packets_sent, 1);
+}
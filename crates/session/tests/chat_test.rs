@@ -102,7 +102,7 @@ fn init_tests(incoming_socket_path: PathBuf, sender: Sender<PacketType>) {
         System::new().block_on(async {
             match initialize(incoming_socket_path_clone, outgoing_socket_path_clone2).await {
                 Ok(handle) => {
-                    match handle.await {
+                    match handle.listener.await {
                         Ok(()) => info!("Listener exited successfully!"),
                         Err(e) => error!("Listener exited with error {:?}", e),
                     };
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::FutureExt;
+use metaverse_session::clock::{Clock, MockClock};
+
+#[test]
+fn test_mock_clock_only_moves_when_advanced() {
+    let clock = MockClock::new();
+    let start = clock.now();
+    assert_eq!(clock.now(), start);
+    clock.advance(Duration::from_secs(3));
+    assert_eq!(clock.now() - start, Duration::from_secs(3));
+}
+
+#[tokio::test]
+async fn test_sleeps_wake_up_at_their_deadline() {
+    let clock = Arc::new(MockClock::new());
+    let mut short = clock.sleep(Duration::from_millis(100));
+    let mut long = clock.sleep(Duration::from_secs(1));
+    assert_eq!(clock.sleepers(), 2);
+
+    clock.advance(Duration::from_millis(99));
+    assert!((&mut short).now_or_never().is_none());
+    clock.advance(Duration::from_millis(1));
+    assert!((&mut short).now_or_never().is_some());
+    assert!((&mut long).now_or_never().is_none());
+    assert_eq!(clock.sleepers(), 1);
+
+    clock.advance(Duration::from_secs(5));
+    assert!(long.now_or_never().is_some());
+    assert_eq!(clock.sleepers(), 0);
+}
+
+#[tokio::test]
+async fn test_zero_sleep_is_ready_at_once() {
+    let clock = MockClock::new();
+    assert!(clock.sleep(Duration::ZERO).now_or_never().is_some());
+}
+
+#[tokio::test]
+async fn test_dropped_sleeps_are_not_counted() {
+    let clock = MockClock::new();
+    let sleep = clock.sleep(Duration::from_secs(1));
+    assert_eq!(clock.sleepers(), 1);
+    drop(sleep);
+    assert_eq!(clock.sleepers(), 0);
+}
@@ -0,0 +1,33 @@
+use metaverse_session::codec::{IdentityCodec, PacketCodec, XorCodec};
+
+#[test]
+fn test_identity_codec_leaves_packets_unchanged() {
+    let data = vec![0x40, 0, 0, 0, 1, 0, 0xff, 0xff, 0, 1];
+    assert_eq!(IdentityCodec.encode(data.clone()), data);
+    assert_eq!(IdentityCodec.decode(&data).unwrap(), data);
+}
+
+#[test]
+fn test_xor_codec_round_trip() {
+    let codec = XorCodec::new(b"secret".to_vec());
+    let data: Vec<u8> = (0..=255).collect();
+    let encoded = codec.encode(data.clone());
+    assert_ne!(encoded, data);
+    assert_eq!(encoded.len(), data.len());
+    assert_eq!(codec.decode(&encoded).unwrap(), data);
+}
+
+#[test]
+fn test_xor_codec_repeats_the_key() {
+    let codec = XorCodec::new(vec![0xff, 0x0f]);
+    assert_eq!(
+        codec.encode(vec![0, 0, 0, 0, 0]),
+        [0xff, 0x0f, 0xff, 0x0f, 0xff]
+    );
+}
+
+#[test]
+fn test_empty_xor_key_leaves_packets_unchanged() {
+    let codec = XorCodec::new(Vec::new());
+    assert_eq!(codec.encode(vec![1, 2, 3]), [1, 2, 3]);
+}
@@ -0,0 +1,44 @@
+use std::net::UdpSocket;
+use std::time::Duration;
+
+use actix::Actor;
+use metaverse_messages::errors::SessionError;
+use metaverse_session::initialize::initialize;
+use metaverse_session::mailbox::Mailbox;
+use portpicker::pick_unused_port;
+use tokio::time::sleep;
+
+#[actix_rt::test]
+async fn test_shutdown_stops_the_mailbox_and_the_listener() {
+    let ui_to_server_socket = pick_unused_port().unwrap();
+    let server_to_ui_socket = pick_unused_port().unwrap();
+    let handle = initialize(ui_to_server_socket, server_to_ui_socket)
+        .await
+        .unwrap();
+    let mailbox = handle.mailbox.clone();
+    sleep(Duration::from_millis(50)).await;
+    assert!(mailbox.connected());
+
+    // there is no session to log out of
+    handle.shutdown().await.unwrap();
+    sleep(Duration::from_millis(50)).await;
+    assert!(!mailbox.connected());
+    // the listener's socket has been released
+    assert!(UdpSocket::bind(format!("127.0.0.1:{}", ui_to_server_socket)).is_ok());
+}
+
+#[actix_rt::test]
+async fn test_wait_for_running_resolves_once_the_mailbox_starts() {
+    let mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    let running = mailbox.wait_for_running(Duration::from_secs(1));
+    let _mailbox = mailbox.start();
+    running.await.unwrap();
+}
+
+#[actix_rt::test]
+async fn test_wait_for_running_times_out() {
+    // the mailbox is never started
+    let mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    let result = mailbox.wait_for_running(Duration::from_millis(50)).await;
+    assert!(matches!(result, Err(SessionError::Timeout(_))));
+}
@@ -0,0 +1,2193 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use actix::{Actor, Addr};
+use glam::Vec3;
+use metaverse_messages::utils::texture_entry::TextureEntry;
+use metaverse_messages::{
+    agent_movement_complete::AgentMovementComplete,
+    agent_set_appearance::AgentSetAppearance,
+    agent_update::ControlFlags,
+    avatar_appearance::{AvatarAppearance, DEFAULT_AVATAR_TEXTURE},
+    chat_from_simulator::{Audible, ChatFromSimulator, ChatType, SourceType},
+    chat_from_viewer::{ChatFromViewer, ClientChatType},
+    circuit_code::CircuitCodeData,
+    coarse_location_update::{CoarseLocationUpdate, MinimapEntities},
+    complete_ping_check::CompletePingCheck,
+    enable_simulator::EnableSimulator,
+    errors::{SessionError, TimeoutError},
+    improved_instant_message::{InstantMessageDialog, TeleportLure},
+    object_update_cached::{CachedObject, ObjectUpdateCached},
+    offline_notification::OfflineNotification,
+    online_notification::OnlineNotification,
+    packet::{Packet, PacketData},
+    packet_ack::PacketAck,
+    packet_types::PacketType,
+    request_multiple_objects::CacheMissType,
+    script_dialog::ScriptDialog,
+    script_question::{ScriptPermissions, ScriptQuestion},
+    start_ping_check::StartPingCheck,
+    ui_events::UiEventTypes,
+    utils::region_handle::RegionHandle,
+};
+use metaverse_session::{
+    clock::MockClock,
+    codec::{PacketCodec, XorCodec},
+    handle::SessionHandle,
+    mailbox::{
+        CacheObjects, ChangeSimulator, ChatFilter, DumpAckQueue, GetAgents, GetPosition, Logout,
+        Mailbox, MovementComplete, Mute, Pause, PingQuery, Ready, RegionHandshakeMessage, Resume,
+        ResumeCircuit, SendPacket, SendPacketWithReliability, Session, SetAppearance,
+        SetWindowSize, Shutdown, UiMessage, Unmute, UpdateBalance,
+    },
+    server_subscriber::use_circuit_code,
+    transport::MockTransport,
+};
+use tokio::time::{sleep, timeout};
+use uuid::Uuid;
+
+/// how long to wait for the mailbox before failing a test
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// how often to check on things that can't be awaited, like a real socket
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+/// the ID of the pings settle sends
+const SETTLE_PING: u8 = 0xee;
+/// how long the mailbox waits for an ack before sending a packet again
+const ACK_TIMEOUT: Duration = Duration::from_secs(1);
+/// how often move_to sends an AgentUpdate while walking
+const MOVE_STEP_INTERVAL: Duration = Duration::from_millis(100);
+
+fn session(transport: Arc<MockTransport>) -> Session {
+    Session {
+        url: "127.0.0.1".to_string(),
+        server_socket: 13000,
+        agent_id: Uuid::new_v4(),
+        session_id: Uuid::new_v4(),
+        circuit_code: 1234,
+        agent_name: "Test User".to_string(),
+        agent_access: None,
+        agent_access_max: None,
+        home: None,
+        buddy_list: Vec::new(),
+        socket: Some(transport),
+        outbound: None,
+        agent_list: Arc::new(Mutex::new(HashMap::new())),
+    }
+}
+
+async fn start_mailbox(
+    ping_interval: Option<Duration>,
+    transport: Arc<MockTransport>,
+) -> Addr<Mailbox> {
+    start_mailbox_with_ui(ping_interval, transport, "127.0.0.1:9".to_string()).await
+}
+
+async fn start_mailbox_with_ui(
+    ping_interval: Option<Duration>,
+    transport: Arc<MockTransport>,
+    server_to_ui_socket: String,
+) -> Addr<Mailbox> {
+    let mut mailbox = Mailbox::new(0, server_to_ui_socket);
+    mailbox.ping_interval = ping_interval;
+    let mailbox = mailbox.start();
+    mailbox.send(session(transport)).await.unwrap();
+    mailbox
+}
+
+/// start a mailbox whose pings and ack timeouts follow the clock
+async fn start_mailbox_with_clock(
+    ping_interval: Option<Duration>,
+    transport: Arc<MockTransport>,
+    clock: Arc<MockClock>,
+) -> Addr<Mailbox> {
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    mailbox.ping_interval = ping_interval;
+    mailbox.clock = clock;
+    let mailbox = mailbox.start();
+    mailbox.send(session(transport)).await.unwrap();
+    mailbox
+}
+
+fn sent_packets(transport: &MockTransport) -> Vec<Packet> {
+    transport
+        .take_sent()
+        .iter()
+        .map(|(data, _)| Packet::from_bytes(data).unwrap())
+        .collect()
+}
+
+/// wait until the mailbox has sent at least count datagrams
+async fn wait_for_sent(transport: &MockTransport, count: usize) {
+    timeout(TIMEOUT, transport.wait_for_sent(count))
+        .await
+        .unwrap_or_else(|_| panic!("mailbox did not send {} datagrams", count));
+}
+
+/// take the packets the mailbox sends until the condition holds for them
+async fn sent_until(
+    transport: &MockTransport,
+    condition: impl Fn(&[Packet]) -> bool,
+) -> Vec<Packet> {
+    let mut packets = Vec::new();
+    let waiting = async {
+        while !condition(&packets) {
+            transport.wait_for_sent(1).await;
+            packets.extend(sent_packets(transport));
+        }
+    };
+    if timeout(TIMEOUT, waiting).await.is_err() {
+        panic!(
+            "mailbox did not send the expected packets, only {:?}",
+            packets
+        );
+    }
+    packets
+}
+
+/// wait for the mailbox to send count packets, and take them
+async fn wait_for_packets(transport: &MockTransport, count: usize) -> Vec<Packet> {
+    sent_until(transport, |packets| packets.len() >= count).await
+}
+
+/// wait for the mailbox to send a packet that matches, and return what matched
+async fn wait_for_packet<T>(
+    transport: &MockTransport,
+    matches: impl Fn(&Packet) -> Option<T>,
+) -> T {
+    sent_until(transport, |packets| {
+        packets.iter().any(|packet| matches(packet).is_some())
+    })
+    .await
+    .iter()
+    .find_map(matches)
+    .unwrap()
+}
+
+fn is_settle_pong(packet: &Packet) -> bool {
+    matches!(&packet.body, PacketType::CompletePingCheck(pong) if pong.ping_id == SETTLE_PING)
+}
+
+/// ping the mailbox from the server, and wait for the answer. The ping goes through the same
+/// queues as everything injected and sent before it, so once it is answered the mailbox has
+/// handled all of that. Returns the packets sent before the answer.
+async fn settle(transport: &MockTransport) -> Vec<Packet> {
+    transport.inject(
+        Packet::new_start_ping_check(StartPingCheck {
+            ping_id: SETTLE_PING,
+            oldest_unacked: 0,
+        })
+        .to_bytes(),
+    );
+    let mut packets = sent_until(transport, |packets| packets.iter().any(is_settle_pong)).await;
+    packets.retain(|packet| !is_settle_pong(packet));
+    packets
+}
+
+/// wait until the mailbox is waiting on the clock, so advancing it wakes something up
+async fn wait_for_sleepers(clock: &MockClock, count: usize) {
+    let waiting = async {
+        while clock.sleepers() < count {
+            sleep(POLL_INTERVAL).await;
+        }
+    };
+    timeout(TIMEOUT, waiting)
+        .await
+        .unwrap_or_else(|_| panic!("mailbox is not waiting on the clock"));
+}
+
+fn chat() -> Packet {
+    Packet::new_chat_from_viewer(ChatFromViewer {
+        agent_id: Uuid::new_v4(),
+        session_id: Uuid::new_v4(),
+        message: "hello".to_string(),
+        message_type: ClientChatType::Normal,
+        channel: 0,
+    })
+}
+
+fn ack(packet: &Packet) -> Vec<u8> {
+    Packet::new_packet_ack(PacketAck {
+        packet_ids: vec![packet.header.sequence_number],
+    })
+    .to_bytes()
+}
+
+/// a socket for the mailbox to send UI messages to
+fn ui_socket() -> UdpSocket {
+    let ui_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    ui_socket.set_nonblocking(true).unwrap();
+    ui_socket
+}
+
+/// the UI messages that have arrived so far
+fn ui_messages(ui_socket: &UdpSocket) -> Vec<UiMessage> {
+    let mut buf = [0u8; 1500];
+    let mut messages = Vec::new();
+    while let Ok((size, _)) = ui_socket.recv_from(&mut buf) {
+        let message = UiMessage::from_bytes(&buf[..size]).unwrap();
+        // the mailbox's own state changes aren't what these tests are after
+        if !matches!(message.message_type, UiEventTypes::StateChangeEvent) {
+            messages.push(message);
+        }
+    }
+    messages
+}
+
+/// read the UI messages until the condition holds for them
+async fn ui_messages_until(
+    ui_socket: &UdpSocket,
+    condition: impl Fn(&[UiMessage]) -> bool,
+) -> Vec<UiMessage> {
+    let mut messages = Vec::new();
+    let waiting = async {
+        loop {
+            messages.extend(ui_messages(ui_socket));
+            if condition(&messages) {
+                break;
+            }
+            sleep(POLL_INTERVAL).await;
+        }
+    };
+    if timeout(TIMEOUT, waiting).await.is_err() {
+        panic!("the UI did not receive the expected messages");
+    }
+    messages
+}
+
+#[actix_rt::test]
+async fn test_ping_latency_over_mock_transport() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(Some(Duration::from_millis(200)), transport.clone()).await;
+
+    let ping_id = wait_for_packet(&transport, |packet| match &packet.body {
+        PacketType::StartPingCheck(ping) => Some(ping.ping_id),
+        _ => None,
+    })
+    .await;
+
+    // the server takes a while to answer
+    sleep(Duration::from_millis(20)).await;
+    transport.inject(Packet::new_complete_ping_check(CompletePingCheck { ping_id }).to_bytes());
+    settle(&transport).await;
+
+    let stats = mailbox.send(PingQuery).await.unwrap();
+    assert!(stats.latency >= Duration::from_millis(20));
+}
+
+#[actix_rt::test]
+async fn test_inbound_acks_are_batched() {
+    let transport = Arc::new(MockTransport::new());
+    let _mailbox = start_mailbox(None, transport.clone()).await;
+
+    for sequence_number in 1..=300 {
+        let mut packet =
+            Packet::new_complete_ping_check(CompletePingCheck { ping_id: 0 }).reliable(true);
+        packet.header.sequence_number = sequence_number;
+        transport.inject(packet.to_bytes());
+    }
+
+    let acks = |packets: &[Packet]| -> Vec<Vec<u32>> {
+        packets
+            .iter()
+            .filter_map(|packet| match &packet.body {
+                PacketType::PacketAck(ack) => Some(ack.packet_ids.clone()),
+                _ => None,
+            })
+            .collect()
+    };
+    let sent = sent_until(&transport, |packets| acks(packets).concat().len() >= 300).await;
+    let acks = acks(&sent);
+    assert_eq!(acks.len(), 2);
+    assert_eq!(acks.concat(), (1..=300).collect::<Vec<u32>>());
+}
+
+#[actix_rt::test]
+async fn test_acks_wait_for_a_session() {
+    let transport = Arc::new(MockTransport::new());
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    mailbox.ping_interval = None;
+    mailbox.pending_acks.lock().unwrap().extend([7, 8]);
+    let pending_acks = mailbox.pending_acks.clone();
+    let mailbox = mailbox.start();
+
+    // a few flushes go by with nowhere to send the acks
+    sleep(Duration::from_millis(300)).await;
+    assert_eq!(*pending_acks.lock().unwrap(), vec![7, 8]);
+
+    mailbox.send(session(transport.clone())).await.unwrap();
+    let sent = wait_for_packet(&transport, |packet| match &packet.body {
+        PacketType::PacketAck(ack) => Some(ack.packet_ids.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(sent, vec![7, 8]);
+}
+
+#[actix_rt::test]
+async fn test_acks_on_a_dropped_packet_are_sent_later() {
+    let transport = Arc::new(MockTransport::new());
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    mailbox.ping_interval = None;
+    let pending_acks = mailbox.pending_acks.clone();
+    let mailbox = mailbox.start();
+    mailbox.send(session(transport.clone())).await.unwrap();
+
+    // the writer holds the first chat, and the rest fill the queue
+    transport.stall(true);
+    for _ in 0..1030 {
+        mailbox.send(chat().reliable(false)).await.unwrap();
+    }
+    let mut packet =
+        Packet::new_complete_ping_check(CompletePingCheck { ping_id: 0 }).reliable(true);
+    packet.header.sequence_number = 42;
+    transport.inject(packet.to_bytes());
+    let waiting = async {
+        while !pending_acks.lock().unwrap().contains(&42) {
+            sleep(POLL_INTERVAL).await;
+        }
+    };
+    timeout(TIMEOUT, waiting).await.unwrap();
+    // this one takes the ack with it, and is dropped
+    mailbox.send(chat().reliable(false)).await.unwrap();
+
+    transport.stall(false);
+    sent_until(&transport, |packets| {
+        packets.iter().any(|packet| {
+            let appended = packet.header.ack_list.as_deref().unwrap_or_default();
+            let acked: &[u32] = match &packet.body {
+                PacketType::PacketAck(ack) => &ack.packet_ids[..],
+                _ => &[],
+            };
+            appended.contains(&42) || acked.contains(&42)
+        })
+    })
+    .await;
+}
+
+#[actix_rt::test]
+async fn test_unacked_reliable_packet_is_resent() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+
+    mailbox.send(chat()).await.unwrap();
+    let first = wait_for_packets(&transport, 1).await;
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(ACK_TIMEOUT);
+    let second = wait_for_packets(&transport, 1).await;
+
+    assert_eq!((first.len(), second.len()), (1, 1));
+    assert!(!first[0].header.resent);
+    assert!(second[0].header.resent);
+    assert_eq!(
+        first[0].header.sequence_number,
+        second[0].header.sequence_number
+    );
+}
+
+#[actix_rt::test]
+async fn test_acked_reliable_packet_is_not_resent() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+
+    mailbox.send(chat()).await.unwrap();
+    let sent = wait_for_packets(&transport, 1).await;
+    assert_eq!(sent.len(), 1);
+
+    transport.inject(ack(&sent[0]));
+    assert!(settle(&transport).await.is_empty());
+    assert!(mailbox.send(DumpAckQueue).await.unwrap().is_empty());
+    clock.advance(ACK_TIMEOUT);
+    assert!(settle(&transport).await.is_empty());
+}
+
+#[actix_rt::test]
+async fn test_reliability_can_be_overridden_per_packet() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+
+    // chat is reliable, so this is sent without waiting for an ack
+    mailbox
+        .send(SendPacketWithReliability {
+            packet: chat(),
+            reliable: Some(false),
+        })
+        .await
+        .unwrap();
+    let sent = wait_for_packets(&transport, 1).await;
+    assert!(!sent[0].header.reliable);
+    assert!(mailbox.send(DumpAckQueue).await.unwrap().is_empty());
+
+    mailbox
+        .send(SendPacketWithReliability {
+            packet: chat(),
+            reliable: None,
+        })
+        .await
+        .unwrap();
+    let sent = wait_for_packets(&transport, 1).await;
+    assert!(sent[0].header.reliable);
+    assert_eq!(mailbox.send(DumpAckQueue).await.unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn test_unreliable_packets_over_the_bandwidth_limit_are_dropped() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    let size = chat().to_bytes().len();
+    // room for two chats
+    mailbox
+        .bandwidth
+        .lock()
+        .unwrap()
+        .set_limit(Some(size as u32 * 5 / 2));
+    let mailbox = mailbox.start();
+    mailbox.send(session(transport.clone())).await.unwrap();
+
+    for _ in 0..5 {
+        mailbox.send(chat().reliable(false)).await.unwrap();
+    }
+    // every chat is either sent or dropped by the writer
+    let waiting = async {
+        loop {
+            let stats = mailbox.send(PingQuery).await.unwrap().bandwidth;
+            if stats.packets_sent + stats.packets_dropped >= 5 {
+                break stats;
+            }
+            sleep(POLL_INTERVAL).await;
+        }
+    };
+    let stats = timeout(TIMEOUT, waiting).await.unwrap();
+    assert_eq!(sent_packets(&transport).len(), 2);
+    assert_eq!(stats.packets_sent, 2);
+    assert_eq!(stats.packets_dropped, 3);
+    assert_eq!(stats.bytes_per_second, size as u64 * 2);
+}
+
+#[actix_rt::test]
+async fn test_reliable_packets_over_the_bandwidth_limit_do_not_hold_up_the_rest() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    mailbox.ping_interval = None;
+    mailbox.clock = clock.clone();
+    let size = chat().to_bytes().len();
+    // room for two chats, and the third a fifth of a second later
+    mailbox
+        .bandwidth
+        .lock()
+        .unwrap()
+        .set_limit(Some(size as u32 * 5 / 2));
+    let mailbox = mailbox.start();
+    mailbox.send(session(transport.clone())).await.unwrap();
+
+    for _ in 0..3 {
+        mailbox.send(chat()).await.unwrap();
+    }
+    mailbox
+        .send(Packet::new_complete_ping_check(CompletePingCheck {
+            ping_id: 1,
+        }))
+        .await
+        .unwrap();
+
+    // the pong fits in what is left, so it goes out while the third chat waits
+    let sent = wait_for_packets(&transport, 3).await;
+    assert!(is_chat(&sent[0].body) && is_chat(&sent[1].body));
+    assert!(matches!(sent[2].body, PacketType::CompletePingCheck(_)));
+    // and the third chat's ack timeout doesn't start until it is sent
+    wait_for_sleepers(&clock, 2).await;
+    assert_eq!(clock.sleepers(), 2);
+
+    let sent = wait_for_packets(&transport, 1).await;
+    assert!(is_chat(&sent[0].body));
+    wait_for_sleepers(&clock, 3).await;
+}
+
+#[actix_rt::test]
+async fn test_dump_ack_queue_lists_unacked_packets() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+
+    mailbox.send(chat()).await.unwrap();
+    let sent = wait_for_packets(&transport, 1).await;
+    clock.advance(Duration::from_millis(100));
+
+    let queue = mailbox.send(DumpAckQueue).await.unwrap();
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].packet_id, sent[0].header.sequence_number);
+    assert_eq!(queue[0].attempts, 1);
+    assert_eq!(queue[0].waiting, Duration::from_millis(100));
+
+    transport.inject(ack(&sent[0]));
+    settle(&transport).await;
+    assert!(mailbox.send(DumpAckQueue).await.unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_ack_for_a_resent_packet_resolves_it() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+    let handle = SessionHandle::new(mailbox.clone(), Uuid::new_v4(), Uuid::new_v4());
+
+    let chat = actix_rt::spawn({
+        let handle = handle.clone();
+        async move { handle.chat("hello").await }
+    });
+    wait_for_packets(&transport, 1).await;
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(ACK_TIMEOUT);
+    let resent = wait_for_packets(&transport, 1).await;
+    assert!(resent[0].header.resent);
+
+    transport.inject(ack(&resent[0]));
+    assert!(chat.await.unwrap().is_ok());
+    assert!(mailbox.send(DumpAckQueue).await.unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_late_ack_after_giving_up() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+    let handle = SessionHandle::new(mailbox.clone(), Uuid::new_v4(), Uuid::new_v4());
+
+    let chat = actix_rt::spawn({
+        let handle = handle.clone();
+        async move { handle.chat("hello").await }
+    });
+    let mut sent = Vec::new();
+    for _ in 0..3 {
+        sent.extend(wait_for_packets(&transport, 1).await);
+        wait_for_sleepers(&clock, 1).await;
+        clock.advance(ACK_TIMEOUT);
+    }
+    assert!(matches!(
+        chat.await.unwrap(),
+        Err(SessionError::AckError(_))
+    ));
+    assert_eq!(sent.len(), 3);
+    // the packet is no longer waiting for an ack
+    assert!(mailbox.send(DumpAckQueue).await.unwrap().is_empty());
+
+    // the ack arrives anyway, and is ignored
+    transport.inject(ack(&sent[0]));
+    assert!(settle(&transport).await.is_empty());
+    assert!(mailbox.send(DumpAckQueue).await.unwrap().is_empty());
+}
+
+/// send a message that resolves once its packet is acked, and ack the packet it sent
+async fn send_and_ack<M>(mailbox: &Addr<Mailbox>, transport: &MockTransport, message: M) -> Packet
+where
+    M: actix::Message<Result = Result<(), SessionError>> + Send + 'static,
+    Mailbox: actix::Handler<M>,
+{
+    let response = mailbox.send(message);
+    let pending = actix_rt::spawn(response);
+    let sent = wait_for_packet(transport, |packet| {
+        packet.header.reliable.then(|| packet.clone())
+    })
+    .await;
+    transport.inject(ack(&sent));
+    pending.await.unwrap().unwrap().unwrap();
+    sent
+}
+
+#[actix_rt::test]
+async fn test_pause_suspends_pings_until_resume() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let ping_interval = Duration::from_secs(5);
+    let mailbox =
+        start_mailbox_with_clock(Some(ping_interval), transport.clone(), clock.clone()).await;
+    let is_ping = |packet: &Packet| matches!(packet.body, PacketType::StartPingCheck(_));
+
+    let pause = send_and_ack(
+        &mailbox,
+        &transport,
+        Pause {
+            suspend_pings: true,
+        },
+    )
+    .await;
+    match pause.body {
+        PacketType::AgentPause(pause) => assert_eq!(pause.serial_num, 1),
+        other => panic!("expected AgentPause, got {:?}", other),
+    }
+    settle(&transport).await;
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(ping_interval);
+    assert!(!settle(&transport).await.iter().any(is_ping));
+
+    let resume = send_and_ack(&mailbox, &transport, Resume).await;
+    match resume.body {
+        PacketType::AgentResume(resume) => assert_eq!(resume.serial_num, 2),
+        other => panic!("expected AgentResume, got {:?}", other),
+    }
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(ping_interval);
+    wait_for_packet(&transport, |packet| is_ping(packet).then_some(())).await;
+}
+
+#[actix_rt::test]
+async fn test_pings_are_not_suspended_until_the_pause_is_acked() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let ping_interval = Duration::from_secs(5);
+    let mailbox =
+        start_mailbox_with_clock(Some(ping_interval), transport.clone(), clock.clone()).await;
+    let is_ping = |packet: &Packet| matches!(packet.body, PacketType::StartPingCheck(_));
+
+    let pause = actix_rt::spawn({
+        let mailbox = mailbox.clone();
+        async move {
+            mailbox
+                .send(Pause {
+                    suspend_pings: true,
+                })
+                .await
+                .unwrap()
+        }
+    });
+    let sent = wait_for_packets(&transport, 1).await;
+    assert!(matches!(sent[0].body, PacketType::AgentPause(_)));
+    // the ping and the pause's ack timeout
+    wait_for_sleepers(&clock, 2).await;
+    clock.advance(ping_interval);
+    wait_for_packet(&transport, |packet| is_ping(packet).then_some(())).await;
+
+    transport.inject(ack(&sent[0]));
+    pause.await.unwrap().unwrap();
+    settle(&transport).await;
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(ping_interval);
+    assert!(!settle(&transport).await.iter().any(is_ping));
+}
+
+#[actix_rt::test]
+async fn test_logout_message_logs_the_session_out() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+
+    let logout = actix_rt::spawn({
+        let mailbox = mailbox.clone();
+        async move { mailbox.send(Logout).await.unwrap() }
+    });
+    let sent = wait_for_packets(&transport, 1).await;
+    assert!(matches!(sent[0].body, PacketType::LogoutRequest(_)));
+
+    transport.inject(ack(&sent[0]));
+    assert!(logout.await.unwrap().is_ok());
+}
+
+#[actix_rt::test]
+async fn test_session_handle_waits_for_the_ack() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    let handle = SessionHandle::new(mailbox.clone(), Uuid::new_v4(), Uuid::new_v4());
+
+    let chat = actix_rt::spawn({
+        let handle = handle.clone();
+        async move { handle.chat("hello").await }
+    });
+    let sent = wait_for_packets(&transport, 1).await;
+    assert_eq!(sent.len(), 1);
+    assert!(!chat.is_finished());
+
+    transport.inject(ack(&sent[0]));
+    assert!(chat.await.unwrap().is_ok());
+
+    // unreliable packets don't wait for an ack
+    mailbox
+        .send(RegionHandshakeMessage {
+            region_name: "Da Boom".to_string(),
+        })
+        .await
+        .unwrap();
+    settle(&transport).await;
+    handle
+        .move_agent(ControlFlags::from_bytes(0))
+        .await
+        .unwrap();
+    assert!(matches!(
+        wait_for_packets(&transport, 1).await[0].body,
+        PacketType::AgentUpdate(_)
+    ));
+}
+
+#[actix_rt::test]
+async fn test_agent_update_is_dropped_before_the_handshake() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    let handle = SessionHandle::new(mailbox.clone(), Uuid::new_v4(), Uuid::new_v4());
+
+    assert!(handle
+        .move_agent(ControlFlags::from_bytes(0))
+        .await
+        .is_err());
+    assert!(settle(&transport).await.is_empty());
+
+    mailbox
+        .send(RegionHandshakeMessage {
+            region_name: "Da Boom".to_string(),
+        })
+        .await
+        .unwrap();
+    settle(&transport).await;
+    handle
+        .move_agent(ControlFlags::from_bytes(0))
+        .await
+        .unwrap();
+    assert_eq!(settle(&transport).await.len(), 1);
+}
+
+#[actix_rt::test]
+async fn test_region_handshake_without_a_session_is_ignored() {
+    // like a handshake the read task queued before Shutdown cleared the session
+    let mailbox = Mailbox::new(0, "127.0.0.1:9".to_string()).start();
+    mailbox
+        .send(RegionHandshakeMessage {
+            region_name: "Da Boom".to_string(),
+        })
+        .await
+        .unwrap();
+    // the mailbox is still running
+    assert!(!mailbox.send(Ready).await.unwrap());
+}
+
+#[actix_rt::test]
+async fn test_appearance_is_sent_after_movement_complete() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    let appearance = AgentSetAppearance::new(
+        Uuid::new_v4(),
+        Uuid::new_v4(),
+        vec![127; 218],
+        TextureEntry::new(Uuid::new_v4(), Default::default()),
+    );
+    mailbox.send(SetAppearance(appearance)).await.unwrap();
+    assert!(settle(&transport).await.is_empty());
+
+    let movement_complete = AgentMovementComplete {
+        agent_id: Uuid::new_v4(),
+        session_id: Uuid::new_v4(),
+        position: Vec3::ZERO,
+        look_at: Vec3::X,
+        region_handle: 0,
+        timestamp: 0,
+        channel_version: "test".to_string(),
+    };
+    transport.inject(Packet::new_agent_movement_complete(movement_complete.clone()).to_bytes());
+    transport.inject(Packet::new_agent_movement_complete(movement_complete).to_bytes());
+
+    let serials: Vec<u32> = settle(&transport)
+        .await
+        .into_iter()
+        .filter_map(|packet| match packet.body {
+            PacketType::AgentSetAppearance(appearance) => Some(appearance.serial_num),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(serials, vec![2, 3]);
+}
+
+#[actix_rt::test]
+async fn test_login_complete_is_sent_on_the_first_arrival() {
+    let ui_socket = ui_socket();
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox_with_ui(
+        None,
+        transport.clone(),
+        ui_socket.local_addr().unwrap().to_string(),
+    )
+    .await;
+    mailbox
+        .send(RegionHandshakeMessage {
+            region_name: "Da Boom".to_string(),
+        })
+        .await
+        .unwrap();
+    mailbox.send(UpdateBalance(1250)).await.unwrap();
+
+    let movement_complete = Packet::new_agent_movement_complete(AgentMovementComplete {
+        agent_id: Uuid::new_v4(),
+        session_id: Uuid::new_v4(),
+        position: Vec3::ZERO,
+        look_at: Vec3::X,
+        region_handle: RegionHandle::from_grid(1000, 1000).0,
+        timestamp: 0,
+        channel_version: "test".to_string(),
+    });
+    transport.inject(movement_complete.to_bytes());
+    // a teleport doesn't complete the login again
+    transport.inject(movement_complete.to_bytes());
+    settle(&transport).await;
+
+    let summaries: Vec<_> = ui_messages(&ui_socket)
+        .into_iter()
+        .filter_map(|message| {
+            match message
+                .message_type
+                .packet_type_from_bytes(&message.message)
+            {
+                Some(PacketType::LoginComplete(summary)) => Some(summary),
+                _ => None,
+            }
+        })
+        .collect();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].agent_name, "Test User");
+    assert_eq!(summaries[0].region_name, "Da Boom");
+    assert_eq!(
+        summaries[0].region_handle,
+        RegionHandle::from_grid(1000, 1000)
+    );
+    assert_eq!(summaries[0].balance, Some(1250));
+}
+
+/// a simulator on a real UDP socket, for testing the sockets the mailbox binds
+fn simulator() -> UdpSocket {
+    simulator_on("127.0.0.1:0")
+}
+
+fn simulator_on(addr: &str) -> UdpSocket {
+    let simulator = UdpSocket::bind(addr).unwrap();
+    simulator.set_nonblocking(true).unwrap();
+    simulator
+}
+
+/// the packets the simulator has received so far, and where they came from
+fn received(simulator: &UdpSocket) -> Vec<(Packet, SocketAddr)> {
+    let mut buf = [0u8; 1500];
+    let mut packets = Vec::new();
+    while let Ok((size, addr)) = simulator.recv_from(&mut buf) {
+        packets.push((Packet::from_bytes(&buf[..size]).unwrap(), addr));
+    }
+    packets
+}
+
+/// read the packets the simulator receives until the condition holds for them
+async fn received_until(
+    simulator: &UdpSocket,
+    condition: impl Fn(&[(Packet, SocketAddr)]) -> bool,
+) -> Vec<(Packet, SocketAddr)> {
+    let mut packets = Vec::new();
+    let waiting = async {
+        loop {
+            packets.extend(received(simulator));
+            if condition(&packets) {
+                break;
+            }
+            sleep(POLL_INTERVAL).await;
+        }
+    };
+    if timeout(TIMEOUT, waiting).await.is_err() {
+        panic!(
+            "simulator did not receive the expected packets, only {:?}",
+            packets
+        );
+    }
+    packets
+}
+
+/// the simulator has received a packet of this kind
+fn has(packets: &[(Packet, SocketAddr)], kind: fn(&PacketType) -> bool) -> bool {
+    packets.iter().any(|(packet, _)| kind(&packet.body))
+}
+
+fn is_chat(body: &PacketType) -> bool {
+    matches!(body, PacketType::ChatFromViewer(_))
+}
+
+fn is_circuit_code(body: &PacketType) -> bool {
+    matches!(body, PacketType::CircuitCode(_))
+}
+
+fn is_ping(body: &PacketType) -> bool {
+    matches!(body, PacketType::StartPingCheck(_))
+}
+
+#[actix_rt::test]
+async fn test_packets_wait_for_the_udp_socket() {
+    let simulator = simulator();
+    let mut session = session(Arc::new(MockTransport::new()));
+    session.server_socket = simulator.local_addr().unwrap().port();
+    session.socket = None;
+
+    let mailbox = Mailbox::new(0, "127.0.0.1:9".to_string()).start();
+    mailbox.send(session).await.unwrap();
+    // sent before the socket has been bound
+    mailbox.send(chat()).await.unwrap();
+
+    received_until(&simulator, |packets| has(packets, is_chat)).await;
+}
+
+#[actix_rt::test]
+async fn test_client_socket_binds_to_the_bind_address() {
+    let simulator = simulator();
+    let mut session = session(Arc::new(MockTransport::new()));
+    session.server_socket = simulator.local_addr().unwrap().port();
+    session.socket = None;
+
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    // every address in 127.0.0.0/8 is loopback, so this is a second interface to bind
+    mailbox.bind_address = "127.0.0.2".parse().unwrap();
+    let mailbox = mailbox.start();
+    mailbox.send(session).await.unwrap();
+    mailbox.send(chat()).await.unwrap();
+
+    let packets = received_until(&simulator, |packets| has(packets, is_chat)).await;
+    assert_eq!(packets[0].1.ip().to_string(), "127.0.0.2");
+}
+
+#[test]
+fn test_server_address_supports_both_families() {
+    let mut session = session(Arc::new(MockTransport::new()));
+    session.server_socket = 13000;
+    for (url, address) in [
+        ("127.0.0.1", "127.0.0.1:13000"),
+        ("::1", "[::1]:13000"),
+        ("[::1]", "[::1]:13000"),
+        ("2001:db8::7", "[2001:db8::7]:13000"),
+        ("sim.example.com", "sim.example.com:13000"),
+    ] {
+        session.url = url.to_string();
+        assert_eq!(session.server_address(), address);
+    }
+}
+
+#[actix_rt::test]
+async fn test_ipv6_simulator_gets_an_ipv6_socket() {
+    let simulator = simulator_on("[::1]:0");
+    let mut session = session(Arc::new(MockTransport::new()));
+    session.url = "::1".to_string();
+    session.server_socket = simulator.local_addr().unwrap().port();
+    session.socket = None;
+
+    let mailbox = Mailbox::new(0, "127.0.0.1:9".to_string()).start();
+    mailbox.send(session).await.unwrap();
+    mailbox.send(chat()).await.unwrap();
+
+    let packets = received_until(&simulator, |packets| has(packets, is_chat)).await;
+    assert!(packets.iter().all(|(_, addr)| addr.is_ipv6()));
+}
+
+#[actix_rt::test]
+async fn test_changing_to_an_ipv6_simulator_rebinds_the_socket() {
+    let v4_simulator = simulator();
+    let v6_simulator = simulator_on("[::1]:0");
+    let mut session = session(Arc::new(MockTransport::new()));
+    session.server_socket = v4_simulator.local_addr().unwrap().port();
+    session.socket = None;
+
+    let mailbox = Mailbox::new(0, "127.0.0.1:9".to_string()).start();
+    mailbox.send(session).await.unwrap();
+    mailbox.send(chat()).await.unwrap();
+    received_until(&v4_simulator, |packets| has(packets, is_chat)).await;
+
+    mailbox
+        .send(ChangeSimulator {
+            url: "::1".to_string(),
+            server_socket: v6_simulator.local_addr().unwrap().port(),
+            circuit_code: 5678,
+        })
+        .await
+        .unwrap();
+    let packets = received_until(&v6_simulator, |packets| has(packets, is_circuit_code)).await;
+    assert!(packets.iter().all(|(_, addr)| addr.is_ipv6()));
+}
+
+#[actix_rt::test]
+async fn test_new_session_on_an_ipv6_simulator_rebinds_the_socket() {
+    let v4_simulator = simulator();
+    let v6_simulator = simulator_on("[::1]:0");
+    let mut first = session(Arc::new(MockTransport::new()));
+    first.server_socket = v4_simulator.local_addr().unwrap().port();
+    first.socket = None;
+
+    let mailbox = Mailbox::new(0, "127.0.0.1:9".to_string()).start();
+    mailbox.send(first).await.unwrap();
+    mailbox.send(chat()).await.unwrap();
+    received_until(&v4_simulator, |packets| has(packets, is_chat)).await;
+
+    // logging in again, to a simulator the IPv4 socket can't reach
+    let mut second = session(Arc::new(MockTransport::new()));
+    second.url = "::1".to_string();
+    second.server_socket = v6_simulator.local_addr().unwrap().port();
+    second.socket = None;
+    mailbox.send(second).await.unwrap();
+    mailbox.send(chat()).await.unwrap();
+    let packets = received_until(&v6_simulator, |packets| has(packets, is_chat)).await;
+    assert!(packets.iter().all(|(_, addr)| addr.is_ipv6()));
+}
+
+#[actix_rt::test]
+async fn test_failing_to_bind_stops_the_mailbox() {
+    let mut session = session(Arc::new(MockTransport::new()));
+    session.socket = None;
+
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    // a documentation address isn't on any interface, so it can't be bound
+    mailbox.bind_address = "192.0.2.1".parse().unwrap();
+    let mailbox = mailbox.start();
+    mailbox.send(session).await.unwrap();
+
+    let waiting = async {
+        while mailbox.connected() {
+            sleep(POLL_INTERVAL).await;
+        }
+    };
+    timeout(TIMEOUT, waiting)
+        .await
+        .unwrap_or_else(|_| panic!("mailbox did not stop"));
+}
+
+/// start a mailbox that pings the simulator every 100ms, and resumes after two missed pongs
+async fn start_mailbox_on_udp(simulator: &UdpSocket, session: Session) -> Addr<Mailbox> {
+    let mut session = session;
+    session.server_socket = simulator.local_addr().unwrap().port();
+    session.socket = None;
+
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    mailbox.bind_address = "127.0.0.1".parse().unwrap();
+    mailbox.ping_interval = Some(Duration::from_millis(100));
+    mailbox.resume_after_missed_pongs = Some(2);
+    let mailbox = mailbox.start();
+    mailbox.send(session).await.unwrap();
+    mailbox
+}
+
+/// wait until the mailbox has bound its UDP socket
+async fn wait_until_ready(mailbox: &Addr<Mailbox>) {
+    let waiting = async {
+        while !mailbox.send(Ready).await.unwrap() {
+            sleep(POLL_INTERVAL).await;
+        }
+    };
+    timeout(TIMEOUT, waiting)
+        .await
+        .unwrap_or_else(|_| panic!("mailbox did not bind its socket"));
+}
+
+#[actix_rt::test]
+async fn test_silent_circuit_is_resumed_on_a_new_socket() {
+    let simulator = simulator();
+    let session = session(Arc::new(MockTransport::new()));
+    let (agent_id, session_id, circuit_code) =
+        (session.agent_id, session.session_id, session.circuit_code);
+    let _mailbox = start_mailbox_on_udp(&simulator, session).await;
+
+    let (_, first_addr) = received_until(&simulator, |packets| has(packets, is_ping))
+        .await
+        .into_iter()
+        .find(|(packet, _)| is_ping(&packet.body))
+        .unwrap();
+
+    // nothing answers the pings, so the circuit is resumed after two of them are missed
+    let resumed_on = |packets: &[(Packet, SocketAddr)]| {
+        packets
+            .iter()
+            .find_map(|(packet, addr)| match &packet.body {
+                PacketType::CircuitCode(data) => Some((data.clone(), *addr)),
+                _ => None,
+            })
+    };
+    let packets = received_until(&simulator, |packets| {
+        resumed_on(packets).is_some_and(|(_, resumed_addr)| {
+            packets.iter().any(|(packet, addr)| {
+                *addr == resumed_addr
+                    && matches!(packet.body, PacketType::CompleteAgentMovementData(_))
+            })
+        })
+    })
+    .await;
+    let (use_circuit_code, resumed_addr) = resumed_on(&packets).unwrap();
+    assert_ne!(resumed_addr, first_addr);
+    assert_eq!(use_circuit_code.code, circuit_code);
+    assert_eq!(use_circuit_code.session_id, session_id);
+    assert_eq!(use_circuit_code.id, agent_id);
+}
+
+#[actix_rt::test]
+async fn test_rejected_resume_is_a_resume_error() {
+    let simulator = simulator();
+    let mailbox = start_mailbox_on_udp(&simulator, session(Arc::new(MockTransport::new()))).await;
+    wait_until_ready(&mailbox).await;
+
+    // the simulator never acks the UseCircuitCode
+    match mailbox.send(ResumeCircuit).await.unwrap() {
+        Err(SessionError::Resume(_)) => {}
+        other => panic!("expected a ResumeError, got {:?}", other),
+    }
+}
+
+#[actix_rt::test]
+async fn test_mailbox_is_ready_once_the_session_has_a_socket() {
+    let mailbox = Mailbox::new(0, "127.0.0.1:9".to_string()).start();
+    assert!(!mailbox.send(Ready).await.unwrap());
+
+    mailbox
+        .send(session(Arc::new(MockTransport::new())))
+        .await
+        .unwrap();
+    assert!(mailbox.send(Ready).await.unwrap());
+}
+
+#[actix_rt::test]
+async fn test_shutdown_releases_the_transport() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    assert!(Arc::strong_count(&transport) > 1);
+
+    mailbox.send(Shutdown).await.unwrap();
+    // the reader and writer are stopped, and the mailbox dropped its session
+    let waiting = async {
+        while Arc::strong_count(&transport) > 1 {
+            sleep(POLL_INTERVAL).await;
+        }
+    };
+    timeout(TIMEOUT, waiting)
+        .await
+        .unwrap_or_else(|_| panic!("the transport was not released"));
+}
+
+#[actix_rt::test]
+async fn test_change_simulator_handshakes_with_the_new_simulator() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+
+    mailbox
+        .send(ChangeSimulator {
+            url: "127.0.0.2".to_string(),
+            server_socket: 13001,
+            circuit_code: 5678,
+        })
+        .await
+        .unwrap();
+    wait_for_sent(&transport, 2).await;
+
+    let sent: Vec<(Packet, String)> = transport
+        .take_sent()
+        .iter()
+        .map(|(data, addr)| (Packet::from_bytes(data).unwrap(), addr.clone()))
+        .collect();
+    assert_eq!(sent.len(), 2);
+    assert!(sent.iter().all(|(_, addr)| addr == "127.0.0.2:13001"));
+    assert!(sent.iter().any(|(packet, _)| matches!(
+        &packet.body,
+        PacketType::CircuitCode(data) if data.code == 5678
+    )));
+    assert!(sent.iter().any(|(packet, _)| matches!(
+        &packet.body,
+        PacketType::CompleteAgentMovementData(data) if data.circuit_code == 5678
+    )));
+    // the new circuit starts its own sequence numbers
+    let mut sequence_numbers: Vec<u32> = sent
+        .iter()
+        .map(|(packet, _)| packet.header.sequence_number)
+        .collect();
+    sequence_numbers.sort();
+    assert_eq!(sequence_numbers, vec![0, 1]);
+}
+
+#[actix_rt::test]
+async fn test_lost_reliable_packet_is_reported_to_the_ui() {
+    let ui_socket = ui_socket();
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mut mailbox = Mailbox::new(0, ui_socket.local_addr().unwrap().to_string());
+    mailbox.ping_interval = None;
+    mailbox.clock = clock.clone();
+    let mailbox = mailbox.start();
+    mailbox.send(session(transport.clone())).await.unwrap();
+
+    mailbox.send(chat()).await.unwrap();
+    // three attempts, one ack timeout apart
+    for _ in 0..3 {
+        wait_for_packets(&transport, 1).await;
+        wait_for_sleepers(&clock, 1).await;
+        clock.advance(ACK_TIMEOUT);
+    }
+
+    let messages = ui_messages_until(&ui_socket, |messages| !messages.is_empty()).await;
+    match SessionError::from_bytes(&messages[0].message) {
+        Some(SessionError::AckError(e)) => {
+            assert_eq!(e.packet_id, 0);
+            assert_eq!(e.attempts, 3);
+            assert_eq!(e.address, "127.0.0.1:13000");
+        }
+        other => panic!("expected AckError, got {:?}", other),
+    }
+}
+
+#[actix_rt::test]
+async fn test_agent_list_follows_coarse_location_updates() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+    let update = |agent_ids: Vec<Uuid>| {
+        Packet::new_coarse_location_update(CoarseLocationUpdate {
+            locations: agent_ids
+                .iter()
+                .map(|_| MinimapEntities { x: 10, y: 20, z: 5 })
+                .collect(),
+            you: -1,
+            prey: -1,
+            agent_ids,
+        })
+        .to_bytes()
+    };
+
+    transport.inject(update(vec![first, second]));
+    settle(&transport).await;
+    let agents = mailbox.send(GetAgents).await.unwrap();
+    assert_eq!(agents.len(), 2);
+    let avatar = agents
+        .iter()
+        .find(|avatar| avatar.agent_id == first)
+        .unwrap();
+    assert_eq!(avatar.position.z, 20.0);
+
+    // the second avatar left the region
+    transport.inject(update(vec![first]));
+    settle(&transport).await;
+    let agents = mailbox.send(GetAgents).await.unwrap();
+    assert_eq!(agents.len(), 1);
+    assert_eq!(agents[0].agent_id, first);
+}
+
+fn chat_from(source_id: Uuid, owner_id: Uuid, source_type: SourceType) -> Vec<u8> {
+    Packet::new_chat_from_simulator(ChatFromSimulator {
+        from_name: "Someone".to_string(),
+        source_id,
+        owner_id,
+        source_type,
+        chat_type: ChatType::Normal,
+        audible: Audible::Fully,
+        position: Vec3::ZERO,
+        message: "hello".to_string(),
+    })
+    .to_bytes()
+}
+
+/// the chat the UI received, by source ID
+fn received_chat(ui_socket: &UdpSocket) -> Vec<ChatFromSimulator> {
+    ui_messages(ui_socket)
+        .iter()
+        .map(|message| ChatFromSimulator::from_bytes(&message.message).unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn test_chat_from_muted_agents_is_not_sent_to_the_ui() {
+    let ui_socket = ui_socket();
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox_with_ui(
+        None,
+        transport.clone(),
+        ui_socket.local_addr().unwrap().to_string(),
+    )
+    .await;
+    let (muted, other, object) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+    mailbox.send(Mute(muted)).await.unwrap();
+    transport.inject(chat_from(muted, muted, SourceType::Agent));
+    // objects owned by a muted agent are muted too
+    transport.inject(chat_from(object, muted, SourceType::Object));
+    transport.inject(chat_from(other, other, SourceType::Agent));
+    settle(&transport).await;
+    let chat = received_chat(&ui_socket);
+    assert_eq!(chat.len(), 1);
+    assert_eq!(chat[0].source_id, other);
+
+    mailbox.send(Unmute(muted)).await.unwrap();
+    transport.inject(chat_from(muted, muted, SourceType::Agent));
+    settle(&transport).await;
+    let chat = received_chat(&ui_socket);
+    assert_eq!(chat.len(), 1);
+    assert_eq!(chat[0].source_id, muted);
+}
+
+#[actix_rt::test]
+async fn test_chat_filter_hides_object_chat() {
+    let ui_socket = ui_socket();
+    let transport = Arc::new(MockTransport::new());
+    let mut mailbox = Mailbox::new(0, ui_socket.local_addr().unwrap().to_string());
+    mailbox.ping_interval = None;
+    mailbox.chat_filter = ChatFilter {
+        show_object: false,
+        show_system: true,
+    };
+    let mailbox = mailbox.start();
+    mailbox.send(session(transport.clone())).await.unwrap();
+    let (agent, object) = (Uuid::new_v4(), Uuid::new_v4());
+
+    transport.inject(chat_from(object, agent, SourceType::Object));
+    transport.inject(chat_from(agent, agent, SourceType::Agent));
+    transport.inject(chat_from(Uuid::nil(), Uuid::nil(), SourceType::System));
+    settle(&transport).await;
+
+    let chat = received_chat(&ui_socket);
+    assert_eq!(chat.len(), 2);
+    assert!(chat
+        .iter()
+        .all(|chat| !matches!(chat.source_type, SourceType::Object)));
+}
+
+#[actix_rt::test]
+async fn test_uncached_objects_are_requested() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    mailbox
+        .send(CacheObjects {
+            objects: vec![(1, 100), (2, 200)],
+        })
+        .await
+        .unwrap();
+
+    let cached = |local_id, crc| CachedObject {
+        local_id,
+        crc,
+        update_flags: 0,
+    };
+    transport.inject(
+        Packet::new_object_update_cached(ObjectUpdateCached {
+            region_handle: RegionHandle::from_grid(1000, 1000),
+            time_dilation: 65535,
+            // the first object is up to date, the second has changed and the third is new
+            objects: vec![cached(1, 100), cached(2, 201), cached(3, 300)],
+        })
+        .to_bytes(),
+    );
+
+    let requests: Vec<_> = settle(&transport)
+        .await
+        .into_iter()
+        .filter_map(|packet| match packet.body {
+            PacketType::RequestMultipleObjects(request) => Some(*request),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(requests.len(), 1);
+    let requested: Vec<_> = requests[0]
+        .objects
+        .iter()
+        .map(|object| (object.local_id, object.cache_miss_type))
+        .collect();
+    assert_eq!(
+        requested,
+        vec![(2, CacheMissType::Crc), (3, CacheMissType::Full)]
+    );
+}
+
+#[actix_rt::test]
+async fn test_neighbors_are_connected_ahead_of_time() {
+    let neighbor = simulator();
+    let neighbor_addr = neighbor.local_addr().unwrap();
+    let transport = Arc::new(MockTransport::new());
+    let session = session(transport.clone());
+    let (agent_id, session_id, circuit_code) =
+        (session.agent_id, session.session_id, session.circuit_code);
+
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    mailbox.ping_interval = None;
+    mailbox.auto_connect_neighbors = true;
+    let mailbox = mailbox.start();
+    mailbox.send(session).await.unwrap();
+
+    transport.inject(
+        Packet::new_enable_simulator(EnableSimulator {
+            region_handle: RegionHandle::from_grid(1000, 1001),
+            ip: Ipv4Addr::LOCALHOST,
+            port: neighbor_addr.port(),
+        })
+        .to_bytes(),
+    );
+
+    // the neighbor gets the UseCircuitCode on a socket of its own
+    let use_circuit_code = received_until(&neighbor, |packets| has(packets, is_circuit_code))
+        .await
+        .into_iter()
+        .find_map(|(packet, _)| match packet.body {
+            PacketType::CircuitCode(data) => Some(data),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(use_circuit_code.code, circuit_code);
+    assert_eq!(use_circuit_code.session_id, session_id);
+    assert_eq!(use_circuit_code.id, agent_id);
+    assert!(!settle(&transport)
+        .await
+        .iter()
+        .any(|packet| is_circuit_code(&packet.body)));
+}
+
+#[actix_rt::test]
+async fn test_animations_are_started_and_stopped() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    let handle = SessionHandle::new(mailbox.clone(), Uuid::new_v4(), Uuid::new_v4());
+    let wave = Uuid::new_v4();
+    let dance = Uuid::new_v4();
+
+    let animate = actix_rt::spawn({
+        let handle = handle.clone();
+        async move { handle.animate(vec![(wave, true), (dance, false)]).await }
+    });
+    let sent = wait_for_packets(&transport, 1).await;
+    assert_eq!(sent.len(), 1);
+    match &sent[0].body {
+        PacketType::AgentAnimation(animation) => {
+            let changes: Vec<(Uuid, bool)> = animation
+                .animations
+                .iter()
+                .map(|change| (change.anim_id, change.start))
+                .collect();
+            assert_eq!(changes, vec![(wave, true), (dance, false)]);
+        }
+        other => panic!("expected AgentAnimation, got {:?}", other),
+    }
+
+    transport.inject(ack(&sent[0]));
+    assert!(animate.await.unwrap().is_ok());
+}
+
+#[actix_rt::test]
+async fn test_baked_textures_of_other_avatars_are_requested() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    let agent_id = Uuid::new_v4();
+    let (head, upper, new_upper) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+    let appearance = |faces: Vec<(u8, Uuid)>| AvatarAppearance {
+        agent_id,
+        is_trial: false,
+        texture_entry: TextureEntry::new(DEFAULT_AVATAR_TEXTURE, faces.into_iter().collect()),
+        visual_params: vec![127; 218],
+        appearance_data: Vec::new(),
+        hover_height: Vec::new(),
+        attachments: Vec::new(),
+    };
+    let requested = |packets: Vec<Packet>| -> Vec<Vec<(Uuid, u8)>> {
+        packets
+            .into_iter()
+            .filter_map(|packet| match packet.body {
+                PacketType::RequestImage(request) => Some(
+                    request
+                        .requests
+                        .iter()
+                        .map(|image| (image.image_id, image.image_type))
+                        .collect(),
+                ),
+                _ => None,
+            })
+            .collect()
+    };
+
+    transport
+        .inject(Packet::new_avatar_appearance(appearance(vec![(8, head), (9, upper)])).to_bytes());
+    // both are baked textures
+    assert_eq!(
+        requested(settle(&transport).await),
+        vec![vec![(head, 1), (upper, 1)]]
+    );
+
+    // only the bake that changed is downloaded again
+    transport.inject(
+        Packet::new_avatar_appearance(appearance(vec![(8, head), (9, new_upper)])).to_bytes(),
+    );
+    assert_eq!(
+        requested(settle(&transport).await),
+        vec![vec![(new_upper, 1)]]
+    );
+
+    transport.inject(
+        Packet::new_coarse_location_update(CoarseLocationUpdate {
+            locations: vec![MinimapEntities { x: 10, y: 20, z: 5 }],
+            you: -1,
+            prey: -1,
+            agent_ids: vec![agent_id],
+        })
+        .to_bytes(),
+    );
+    settle(&transport).await;
+    let agents = mailbox.send(GetAgents).await.unwrap();
+    let appearance = agents[0].appearance.as_ref().unwrap();
+    assert_eq!(appearance.baked_textures(), vec![head, new_upper]);
+}
+
+#[actix_rt::test]
+async fn test_retransmission_follows_the_clock() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+
+    let sending = actix_rt::spawn(mailbox.send(SendPacket(chat())));
+    assert_eq!(wait_for_packets(&transport, 1).await.len(), 1);
+
+    // the ack timeout is a second
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(Duration::from_millis(999));
+    assert!(settle(&transport).await.is_empty());
+    let queue = mailbox.send(DumpAckQueue).await.unwrap();
+    assert_eq!(queue[0].waiting, Duration::from_millis(999));
+    clock.advance(Duration::from_millis(1));
+    let sent = wait_for_packets(&transport, 1).await;
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].header.resent);
+
+    // the third attempt is the last
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(ACK_TIMEOUT);
+    assert_eq!(wait_for_packets(&transport, 1).await.len(), 1);
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(ACK_TIMEOUT);
+    assert!(matches!(
+        sending.await.unwrap().unwrap(),
+        Err(SessionError::AckError(_))
+    ));
+    assert!(settle(&transport).await.is_empty());
+}
+
+#[actix_rt::test]
+async fn test_ping_latency_follows_the_clock() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(
+        Some(Duration::from_secs(5)),
+        transport.clone(),
+        clock.clone(),
+    )
+    .await;
+    assert!(settle(&transport).await.is_empty());
+
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(Duration::from_secs(5));
+    let ping_id = wait_for_packet(&transport, |packet| match &packet.body {
+        PacketType::StartPingCheck(ping) => Some(ping.ping_id),
+        _ => None,
+    })
+    .await;
+
+    clock.advance(Duration::from_millis(120));
+    transport.inject(Packet::new_complete_ping_check(CompletePingCheck { ping_id }).to_bytes());
+    settle(&transport).await;
+    let stats = mailbox.send(PingQuery).await.unwrap();
+    assert_eq!(stats.latency, Duration::from_millis(120));
+}
+
+#[actix_rt::test]
+async fn test_missed_pongs_follow_the_clock() {
+    let simulator = simulator();
+    let clock = Arc::new(MockClock::new());
+    let mut session = session(Arc::new(MockTransport::new()));
+    session.server_socket = simulator.local_addr().unwrap().port();
+    session.socket = None;
+
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    mailbox.bind_address = "127.0.0.1".parse().unwrap();
+    mailbox.ping_interval = Some(Duration::from_secs(5));
+    mailbox.resume_after_missed_pongs = Some(2);
+    mailbox.clock = clock.clone();
+    let mailbox = mailbox.start();
+    mailbox.send(session).await.unwrap();
+
+    // the first ping, and the one after it that notices the first was missed
+    for _ in 0..2 {
+        wait_for_sleepers(&clock, 1).await;
+        clock.advance(Duration::from_secs(5));
+        let packets = received_until(&simulator, |packets| has(packets, is_ping)).await;
+        assert!(!has(&packets, is_circuit_code));
+    }
+    // the second missed pong resumes the circuit
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(Duration::from_secs(5));
+    received_until(&simulator, |packets| has(packets, is_circuit_code)).await;
+}
+
+#[actix_rt::test]
+async fn test_pong_records_the_latency_and_clears_missed_pongs() {
+    let simulator = simulator();
+    let clock = Arc::new(MockClock::new());
+    let mut session = session(Arc::new(MockTransport::new()));
+    session.server_socket = simulator.local_addr().unwrap().port();
+    session.socket = None;
+
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    mailbox.bind_address = "127.0.0.1".parse().unwrap();
+    mailbox.ping_interval = Some(Duration::from_secs(5));
+    mailbox.resume_after_missed_pongs = Some(2);
+    mailbox.clock = clock.clone();
+    let mailbox = mailbox.start();
+    mailbox.send(session).await.unwrap();
+
+    // the first ping goes unanswered, the second is answered late
+    let mut last_ping = None;
+    for _ in 0..2 {
+        wait_for_sleepers(&clock, 1).await;
+        clock.advance(Duration::from_secs(5));
+        let packets = received_until(&simulator, |packets| has(packets, is_ping)).await;
+        last_ping = packets
+            .into_iter()
+            .find_map(|(packet, addr)| match packet.body {
+                PacketType::StartPingCheck(ping) => Some((ping.ping_id, addr)),
+                _ => None,
+            });
+    }
+    let (ping_id, addr) = last_ping.unwrap();
+    clock.advance(Duration::from_millis(120));
+    simulator
+        .send_to(
+            &Packet::new_complete_ping_check(CompletePingCheck { ping_id }).to_bytes(),
+            addr,
+        )
+        .unwrap();
+    let recorded = async {
+        while mailbox.send(PingQuery).await.unwrap().latency != Duration::from_millis(120) {
+            sleep(POLL_INTERVAL).await;
+        }
+    };
+    timeout(TIMEOUT, recorded).await.unwrap();
+
+    // the missed pong was forgotten, so two more pings only miss one
+    for _ in 0..2 {
+        wait_for_sleepers(&clock, 1).await;
+        clock.advance(Duration::from_secs(5));
+        let packets = received_until(&simulator, |packets| has(packets, is_ping)).await;
+        assert!(!has(&packets, is_circuit_code));
+    }
+}
+
+#[actix_rt::test]
+async fn test_pong_for_another_ping_is_ignored() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(
+        Some(Duration::from_secs(5)),
+        transport.clone(),
+        clock.clone(),
+    )
+    .await;
+    assert!(settle(&transport).await.is_empty());
+
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(Duration::from_secs(5));
+    let ping_id = wait_for_packet(&transport, |packet| match &packet.body {
+        PacketType::StartPingCheck(ping) => Some(ping.ping_id),
+        _ => None,
+    })
+    .await;
+
+    clock.advance(Duration::from_millis(120));
+    transport.inject(
+        Packet::new_complete_ping_check(CompletePingCheck {
+            ping_id: ping_id.wrapping_add(1),
+        })
+        .to_bytes(),
+    );
+    settle(&transport).await;
+    let stats = mailbox.send(PingQuery).await.unwrap();
+    assert_eq!(stats.latency, Duration::ZERO);
+    assert_eq!(stats.max_latency, Duration::ZERO);
+}
+
+/// the control flags and facing of the AgentUpdates in the packets
+fn agent_updates(packets: &[Packet]) -> Vec<(ControlFlags, Vec3)> {
+    packets
+        .iter()
+        .filter_map(|packet| match &packet.body {
+            PacketType::AgentUpdate(update) => Some((update.control_flags, update.camera_at_axis)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// ack the reliable packets, so the only sleep left on the clock is the next step of a walk
+async fn ack_reliable(transport: &MockTransport, packets: &[Packet]) {
+    for packet in packets.iter().filter(|packet| packet.header.reliable) {
+        transport.inject(ack(packet));
+    }
+    settle(transport).await;
+}
+
+#[actix_rt::test]
+async fn test_move_to_walks_until_the_agent_arrives() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+    let handle = SessionHandle::new(mailbox.clone(), Uuid::new_v4(), Uuid::new_v4());
+    mailbox
+        .send(RegionHandshakeMessage {
+            region_name: "Da Boom".to_string(),
+        })
+        .await
+        .unwrap();
+    mailbox
+        .send(MovementComplete {
+            region_handle: RegionHandle::from_grid(1000, 1000),
+            position: Vec3::new(10.0, 10.0, 20.0),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        mailbox.send(GetPosition).await.unwrap(),
+        Some(Vec3::new(10.0, 10.0, 20.0))
+    );
+    let setup = settle(&transport).await;
+    ack_reliable(&transport, &setup).await;
+
+    let walk = actix_rt::spawn({
+        let handle = handle.clone();
+        async move { handle.move_to(Vec3::new(10.0, 30.0, 20.0)).await }
+    });
+    // a step every MOVE_STEP_INTERVAL on the mailbox's clock
+    let mut sent = sent_until(&transport, |packets| agent_updates(packets).len() >= 1).await;
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(MOVE_STEP_INTERVAL);
+    sent.extend(sent_until(&transport, |packets| agent_updates(packets).len() >= 1).await);
+    for (control_flags, facing) in agent_updates(&sent) {
+        assert!(control_flags.at_pos);
+        // the target is north, along the y axis
+        assert!(facing.abs_diff_eq(Vec3::Y, 1e-6));
+    }
+    assert!(!walk.is_finished());
+
+    // the agent's own entry in the update is within reach of the target
+    transport.inject(
+        Packet::new_coarse_location_update(CoarseLocationUpdate {
+            locations: vec![MinimapEntities { x: 10, y: 29, z: 5 }],
+            you: 0,
+            prey: -1,
+            agent_ids: Vec::new(),
+        })
+        .to_bytes(),
+    );
+    settle(&transport).await;
+    wait_for_sleepers(&clock, 1).await;
+    clock.advance(MOVE_STEP_INTERVAL);
+    walk.await.unwrap().unwrap();
+    let (control_flags, _) = agent_updates(&settle(&transport).await).pop().unwrap();
+    assert!(!control_flags.at_pos);
+}
+
+#[actix_rt::test]
+async fn test_move_to_gives_up_after_the_timeout() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+    let handle = SessionHandle::new(mailbox.clone(), Uuid::new_v4(), Uuid::new_v4());
+    mailbox
+        .send(RegionHandshakeMessage {
+            region_name: "Da Boom".to_string(),
+        })
+        .await
+        .unwrap();
+    let setup = settle(&transport).await;
+    ack_reliable(&transport, &setup).await;
+
+    // the server never says where the agent is
+    let walk = actix_rt::spawn({
+        let handle = handle.clone();
+        async move {
+            handle
+                .move_to_within(Vec3::new(10.0, 30.0, 20.0), 1.0, MOVE_STEP_INTERVAL * 2)
+                .await
+        }
+    });
+    for _ in 0..2 {
+        wait_for_sleepers(&clock, 1).await;
+        clock.advance(MOVE_STEP_INTERVAL);
+    }
+    let result = walk.await.unwrap();
+    assert!(matches!(
+        result,
+        Err(SessionError::Timeout(TimeoutError { .. }))
+    ));
+    // it doesn't know which way to walk, so it only stops
+    let updates = agent_updates(&settle(&transport).await);
+    assert_eq!(updates.len(), 1);
+    assert!(!updates[0].0.at_pos);
+}
+
+#[actix_rt::test]
+async fn test_codec_wraps_every_datagram() {
+    let transport = Arc::new(MockTransport::new());
+    let codec = XorCodec::new(b"shared secret".to_vec());
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    mailbox.ping_interval = None;
+    mailbox.codec = Arc::new(codec.clone());
+    let mailbox = mailbox.start();
+    mailbox.send(session(transport.clone())).await.unwrap();
+
+    // outgoing packets are encoded on the wire
+    let packet = chat();
+    mailbox.do_send(SendPacketWithReliability {
+        packet: packet.clone(),
+        reliable: Some(false),
+    });
+    wait_for_sent(&transport, 1).await;
+    let sent = transport.take_sent();
+    assert_eq!(sent.len(), 1);
+    assert_ne!(sent[0].0, packet.to_bytes());
+    let decoded = Packet::from_bytes(&codec.decode(&sent[0].0).unwrap()).unwrap();
+    assert_eq!(decoded.body.to_bytes(), packet.body.to_bytes());
+
+    // incoming datagrams are decoded before they are parsed, so their acks are sent
+    let mut ping = Packet::new_complete_ping_check(CompletePingCheck { ping_id: 0 }).reliable(true);
+    ping.header.sequence_number = 7;
+    transport.inject(codec.encode(ping.to_bytes()));
+    // a datagram the server sent without the codec doesn't parse, and isn't acked
+    let mut plain = ping.clone();
+    plain.header.sequence_number = 8;
+    transport.inject(plain.to_bytes());
+    // settle has to go through the codec too
+    transport.inject(
+        codec.encode(
+            Packet::new_start_ping_check(StartPingCheck {
+                ping_id: SETTLE_PING,
+                oldest_unacked: 0,
+            })
+            .to_bytes(),
+        ),
+    );
+    let mut sent = Vec::new();
+    let waiting = async {
+        while !sent.iter().any(is_settle_pong) {
+            transport.wait_for_sent(1).await;
+            sent.extend(
+                transport
+                    .take_sent()
+                    .iter()
+                    .map(|(data, _)| Packet::from_bytes(&codec.decode(data).unwrap()).unwrap()),
+            );
+        }
+    };
+    timeout(TIMEOUT, waiting).await.unwrap();
+    let acks: Vec<u32> = sent
+        .into_iter()
+        .flat_map(|packet| match packet.body {
+            PacketType::PacketAck(ack) => ack.packet_ids,
+            _ => packet.header.ack_list.unwrap_or_default(),
+        })
+        .collect();
+    assert_eq!(acks, vec![7]);
+}
+
+#[actix_rt::test]
+async fn test_session_handle_answers_scripts() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    let agent_id = Uuid::new_v4();
+    let handle = SessionHandle::new(mailbox.clone(), agent_id, Uuid::new_v4());
+    let object_id = Uuid::new_v4();
+
+    let dialog = ScriptDialog {
+        object_id,
+        first_name: String::new(),
+        last_name: String::new(),
+        object_name: "Teleporter".to_string(),
+        message: "Where to?".to_string(),
+        chat_channel: -4242,
+        image_id: Uuid::nil(),
+        buttons: vec!["Sky".to_string(), "Ground".to_string()],
+        owner_ids: Vec::new(),
+    };
+    actix_rt::spawn({
+        let handle = handle.clone();
+        async move { handle.reply_to_dialog(&dialog, "Sky").await }
+    });
+    let question = ScriptQuestion {
+        task_id: object_id,
+        item_id: Uuid::new_v4(),
+        object_name: "Teleporter".to_string(),
+        object_owner: String::new(),
+        questions: ScriptPermissions {
+            teleport: true,
+            ..Default::default()
+        },
+    };
+    actix_rt::spawn({
+        let handle = handle.clone();
+        async move {
+            handle
+                .answer_script_question(&question, ScriptPermissions::all())
+                .await
+        }
+    });
+
+    let sent = wait_for_packets(&transport, 2).await;
+    assert_eq!(sent.len(), 2);
+    for packet in sent {
+        assert!(packet.header.reliable);
+        match packet.body {
+            PacketType::ScriptDialogReply(reply) => {
+                assert_eq!(reply.agent_id, agent_id);
+                assert_eq!(reply.chat_channel, -4242);
+                assert_eq!(reply.button_label, "Sky");
+            }
+            PacketType::ScriptAnswerYes(answer) => {
+                assert_eq!(answer.task_id, object_id);
+                assert!(answer.questions.teleport);
+                assert!(!answer.questions.debit);
+            }
+            other => panic!("unexpected packet {:?}", other),
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn test_session_handle_answers_teleport_lures() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    let agent_id = Uuid::new_v4();
+    let handle = SessionHandle::new(mailbox.clone(), agent_id, Uuid::new_v4());
+    let lure = TeleportLure {
+        from_agent_id: Uuid::new_v4(),
+        from_agent_name: "Friend Resident".to_string(),
+        message: "Join me".to_string(),
+        lure_id: Uuid::new_v4(),
+        godlike: false,
+        destination: None,
+    };
+
+    actix_rt::spawn({
+        let (handle, lure) = (handle.clone(), lure.clone());
+        async move { handle.accept_teleport_lure(&lure).await }
+    });
+    actix_rt::spawn({
+        let (handle, lure) = (handle.clone(), lure.clone());
+        async move { handle.decline_teleport_lure(&lure).await }
+    });
+
+    let sent = wait_for_packets(&transport, 2).await;
+    assert_eq!(sent.len(), 2);
+    for packet in sent {
+        assert!(packet.header.reliable);
+        match packet.body {
+            PacketType::TeleportLureRequest(request) => {
+                assert_eq!(request.agent_id, agent_id);
+                assert_eq!(request.lure_id, lure.lure_id);
+            }
+            PacketType::ImprovedInstantMessage(decline) => {
+                assert_eq!(decline.to_agent_id, lure.from_agent_id);
+                assert_eq!(decline.dialog, InstantMessageDialog::DenyTeleport);
+                assert_eq!(decline.id, lure.lure_id);
+            }
+            other => panic!("unexpected packet {:?}", other),
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn test_window_size_is_sent_after_the_handshake_and_on_resize() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    let handle = SessionHandle::new(mailbox.clone(), Uuid::new_v4(), Uuid::new_v4());
+
+    // before the handshake the size is only kept
+    handle.set_window_size(768, 1024).await.unwrap();
+    assert!(settle(&transport).await.is_empty());
+
+    mailbox
+        .send(RegionHandshakeMessage {
+            region_name: "Da Boom".to_string(),
+        })
+        .await
+        .unwrap();
+    let sizes: Vec<_> = settle(&transport)
+        .await
+        .into_iter()
+        .filter_map(|packet| match packet.body {
+            PacketType::AgentHeightWidth(size) => Some(*size),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(sizes.len(), 1);
+    assert_eq!((sizes[0].height, sizes[0].width), (768, 1024));
+    assert_eq!(sizes[0].circuit_code, 1234);
+
+    actix_rt::spawn(mailbox.send(SetWindowSize {
+        height: 1080,
+        width: 1920,
+    }));
+    let sent = wait_for_packets(&transport, 1).await;
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].header.reliable);
+    match &sent[0].body {
+        PacketType::AgentHeightWidth(size) => {
+            assert_eq!((size.height, size.width), (1080, 1920));
+            assert!(size.gen_counter > sizes[0].gen_counter);
+        }
+        other => panic!("unexpected packet {:?}", other),
+    }
+}
+
+#[actix_rt::test]
+async fn test_use_circuit_code_waits_for_the_ack() {
+    let transport = Arc::new(MockTransport::new());
+    let mailbox = start_mailbox(None, transport.clone()).await;
+    let circuit_code = CircuitCodeData {
+        code: 1234,
+        session_id: Uuid::new_v4(),
+        id: Uuid::new_v4(),
+    };
+
+    let opening = actix_rt::spawn({
+        let (mailbox, circuit_code) = (mailbox.clone(), circuit_code.clone());
+        async move { use_circuit_code(&mailbox, circuit_code, TIMEOUT).await }
+    });
+    let sent = wait_for_packets(&transport, 1).await;
+    assert_eq!(sent.len(), 1);
+    assert!(matches!(sent[0].body, PacketType::CircuitCode(_)));
+    transport.inject(ack(&sent[0]));
+    assert!(opening.await.unwrap().is_ok());
+}
+
+#[actix_rt::test]
+async fn test_use_circuit_code_gives_up_with_the_mailbox() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+    let circuit_code = CircuitCodeData {
+        code: 1234,
+        session_id: Uuid::new_v4(),
+        id: Uuid::new_v4(),
+    };
+
+    let opening = actix_rt::spawn({
+        let mailbox = mailbox.clone();
+        async move { use_circuit_code(&mailbox, circuit_code, TIMEOUT).await }
+    });
+    // the mailbox sends the same packet again each time the ack doesn't come
+    let mut sent = Vec::new();
+    for _ in 0..3 {
+        sent.extend(wait_for_packets(&transport, 1).await);
+        wait_for_sleepers(&clock, 1).await;
+        clock.advance(ACK_TIMEOUT);
+    }
+    let result = opening.await.unwrap();
+    assert!(matches!(result, Err(SessionError::CircuitCode(_))));
+    assert!(sent
+        .iter()
+        .all(|packet| matches!(packet.body, PacketType::CircuitCode(_))));
+    assert!(sent
+        .iter()
+        .all(|packet| packet.header.sequence_number == sent[0].header.sequence_number));
+    assert!(transport.take_sent().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_use_circuit_code_times_out() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock).await;
+    let circuit_code = CircuitCodeData {
+        code: 1234,
+        session_id: Uuid::new_v4(),
+        id: Uuid::new_v4(),
+    };
+
+    // the mailbox's clock never moves, so only the timeout can end the wait
+    let result = use_circuit_code(&mailbox, circuit_code, Duration::from_millis(100)).await;
+    assert!(matches!(result, Err(SessionError::CircuitCode(_))));
+    let sent = sent_packets(&transport);
+    assert_eq!(sent.len(), 1);
+    assert!(matches!(sent[0].body, PacketType::CircuitCode(_)));
+}
+
+#[actix_rt::test]
+async fn test_friend_notifications_reach_the_ui_as_presence() {
+    let ui_socket = ui_socket();
+    let transport = Arc::new(MockTransport::new());
+    let _mailbox = start_mailbox_with_ui(
+        None,
+        transport.clone(),
+        ui_socket.local_addr().unwrap().to_string(),
+    )
+    .await;
+    let (first, second) = (Uuid::from_u128(1), Uuid::from_u128(2));
+    transport.inject(
+        Packet::new_online_notification(OnlineNotification {
+            agent_ids: vec![first, second],
+        })
+        .to_bytes(),
+    );
+    transport.inject(
+        Packet::new_offline_notification(OfflineNotification {
+            agent_ids: vec![first],
+        })
+        .to_bytes(),
+    );
+    settle(&transport).await;
+
+    let presence: Vec<_> = ui_messages(&ui_socket)
+        .into_iter()
+        .filter_map(|message| {
+            match message
+                .message_type
+                .packet_type_from_bytes(&message.message)
+            {
+                Some(PacketType::FriendPresence(friend)) => Some((friend.agent_id, friend.online)),
+                _ => None,
+            }
+        })
+        .collect();
+    assert_eq!(
+        presence,
+        vec![(first, true), (second, true), (first, false)]
+    );
+}
+
+#[actix_rt::test]
+async fn test_new_session_fails_the_old_circuits_acks() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+
+    let sending = actix_rt::spawn(mailbox.send(SendPacket(chat())));
+    let first = wait_for_packets(&transport, 1).await;
+    assert_eq!(first.len(), 1);
+
+    // logging in again replaces the circuit
+    mailbox.send(session(transport.clone())).await.unwrap();
+    assert!(matches!(
+        sending.await.unwrap().unwrap(),
+        Err(SessionError::AckError(_))
+    ));
+    assert!(mailbox.send(DumpAckQueue).await.unwrap().is_empty());
+
+    // the old packet is not retransmitted, and the new circuit starts its own numbering
+    clock.advance(ACK_TIMEOUT);
+    assert!(settle(&transport).await.is_empty());
+    actix_rt::spawn(mailbox.send(SendPacket(chat())));
+    let second = wait_for_packets(&transport, 1).await;
+    assert_eq!(second.len(), 1);
+    assert_eq!(
+        second[0].header.sequence_number,
+        first[0].header.sequence_number
+    );
+}
+
+#[actix_rt::test]
+async fn test_same_session_keeps_its_acks() {
+    let transport = Arc::new(MockTransport::new());
+    let clock = Arc::new(MockClock::new());
+    let mailbox = start_mailbox_with_clock(None, transport.clone(), clock.clone()).await;
+    let first = session(transport.clone());
+    let mut again = session(transport.clone());
+    again.agent_id = first.agent_id;
+    again.session_id = first.session_id;
+    mailbox.send(first).await.unwrap();
+
+    actix_rt::spawn(mailbox.send(SendPacket(chat())));
+    wait_for_packets(&transport, 1).await;
+    // sending the same session again, like after the socket was rebound, keeps the circuit
+    mailbox.send(again).await.unwrap();
+    assert_eq!(mailbox.send(DumpAckQueue).await.unwrap().len(), 1);
+}
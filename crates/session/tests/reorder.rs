@@ -0,0 +1,128 @@
+use std::net::SocketAddr;
+use std::thread::sleep;
+use std::time::Duration;
+
+use metaverse_messages::{complete_ping_check::CompletePingCheck, packet::Packet};
+use metaverse_session::reorder::ReorderBuffer;
+
+const SIM: ([u8; 4], u16) = ([127, 0, 0, 1], 13000);
+
+fn packet(sequence_number: u32, reliable: bool) -> Packet {
+    let mut packet = Packet::new_complete_ping_check(CompletePingCheck {
+        ping_id: sequence_number as u8,
+    })
+    .reliable(reliable);
+    packet.header.sequence_number = sequence_number;
+    packet
+}
+
+fn sequence_numbers(packets: Vec<Packet>) -> Vec<u32> {
+    packets
+        .iter()
+        .map(|packet| packet.header.sequence_number)
+        .collect()
+}
+
+#[test]
+fn test_reliable_packets_are_released_in_order() {
+    let mut buffer = ReorderBuffer::new(Duration::from_secs(1));
+    let sim = SocketAddr::from(SIM);
+
+    assert_eq!(sequence_numbers(buffer.push(packet(1, true), sim)), vec![1]);
+    // 2 was lost, and will be resent
+    assert!(buffer.push(packet(3, true), sim).is_empty());
+    assert!(buffer.push(packet(4, true), sim).is_empty());
+    assert!(buffer.deadline().is_some());
+    assert_eq!(
+        sequence_numbers(buffer.push(packet(2, true), sim)),
+        vec![2, 3, 4]
+    );
+    assert!(buffer.deadline().is_none());
+}
+
+#[test]
+fn test_unreliable_packets_are_not_held() {
+    let mut buffer = ReorderBuffer::new(Duration::from_secs(1));
+    let sim = SocketAddr::from(SIM);
+
+    buffer.push(packet(1, true), sim);
+    assert!(buffer.push(packet(3, true), sim).is_empty());
+    assert_eq!(
+        sequence_numbers(buffer.push(packet(4, false), sim)),
+        vec![4]
+    );
+    // the unreliable packet fills its place in the sequence
+    assert_eq!(
+        sequence_numbers(buffer.push(packet(2, true), sim)),
+        vec![2, 3]
+    );
+    assert_eq!(sequence_numbers(buffer.push(packet(5, true), sim)), vec![5]);
+}
+
+#[test]
+fn test_gap_is_skipped_after_the_hold_time() {
+    let mut buffer = ReorderBuffer::new(Duration::from_millis(50));
+    let sim = SocketAddr::from(SIM);
+
+    buffer.push(packet(1, true), sim);
+    assert!(buffer.push(packet(3, true), sim).is_empty());
+    assert!(buffer.release_expired().is_empty());
+
+    sleep(Duration::from_millis(60));
+    assert_eq!(sequence_numbers(buffer.release_expired()), vec![3]);
+    assert!(buffer.deadline().is_none());
+    // the missing packet is still handled if it shows up late
+    assert_eq!(sequence_numbers(buffer.push(packet(2, true), sim)), vec![2]);
+}
+
+#[test]
+fn test_new_simulator_restarts_the_sequence() {
+    let mut buffer = ReorderBuffer::new(Duration::from_secs(1));
+    let sim = SocketAddr::from(SIM);
+    let new_sim = SocketAddr::from(([127, 0, 0, 2], 13001));
+
+    buffer.push(packet(10, true), sim);
+    assert!(buffer.push(packet(12, true), sim).is_empty());
+    // the packet held for the old simulator is released, and the new one starts over
+    assert_eq!(
+        sequence_numbers(buffer.push(packet(0, true), new_sim)),
+        vec![12, 0]
+    );
+    assert_eq!(
+        sequence_numbers(buffer.push(packet(1, true), new_sim)),
+        vec![1]
+    );
+}
+
+#[test]
+fn test_sequence_numbers_wrap_around() {
+    let mut buffer = ReorderBuffer::new(Duration::from_millis(50));
+    let sim = SocketAddr::from(SIM);
+
+    assert_eq!(
+        sequence_numbers(buffer.push(packet(u32::MAX - 1, true), sim)),
+        vec![u32::MAX - 1]
+    );
+    // u32::MAX was lost, and the packets after it wrapped around to 0
+    assert!(buffer.push(packet(0, true), sim).is_empty());
+    assert!(buffer.push(packet(1, true), sim).is_empty());
+    assert_eq!(
+        sequence_numbers(buffer.push(packet(u32::MAX, true), sim)),
+        vec![u32::MAX, 0, 1]
+    );
+    // a late packet from before the wrap is still released right away
+    assert_eq!(
+        sequence_numbers(buffer.push(packet(u32::MAX - 1, true), sim)),
+        vec![u32::MAX - 1]
+    );
+
+    // the gap before the wrap is skipped first
+    let mut buffer = ReorderBuffer::new(Duration::from_millis(50));
+    buffer.push(packet(u32::MAX - 2, true), sim);
+    assert!(buffer.push(packet(1, true), sim).is_empty());
+    assert!(buffer.push(packet(u32::MAX, true), sim).is_empty());
+    sleep(Duration::from_millis(60));
+    assert_eq!(sequence_numbers(buffer.release_expired()), vec![u32::MAX]);
+    sleep(Duration::from_millis(60));
+    assert_eq!(sequence_numbers(buffer.release_expired()), vec![1]);
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use actix::Actor;
+use futures::{FutureExt, StreamExt};
+use glam::Vec3;
+use metaverse_messages::{
+    chat_from_simulator::{Audible, ChatFromSimulator, ChatType, SourceType},
+    packet::{Packet, PacketData},
+    ui_events::UiEventTypes,
+};
+use metaverse_session::{
+    clock::MockClock,
+    mailbox::{Mailbox, Session},
+    replay::{read_recording, replay_from_file, RecordedDatagram, Recorder, ReplayTransport},
+    transport::{MockTransport, Transport},
+    ui_stream::UiEventStream,
+};
+use tokio::time::{sleep, timeout};
+use uuid::Uuid;
+
+fn chat(message: &str) -> Vec<u8> {
+    Packet::new_chat_from_simulator(ChatFromSimulator {
+        from_name: "Someone".to_string(),
+        source_id: Uuid::new_v4(),
+        owner_id: Uuid::new_v4(),
+        source_type: SourceType::Agent,
+        chat_type: ChatType::Normal,
+        audible: Audible::Fully,
+        position: Vec3::ZERO,
+        message: message.to_string(),
+    })
+    .to_bytes()
+}
+
+/// write a recording by hand, in the format the Recorder uses
+fn write_recording(path: &std::path::Path, datagrams: &[RecordedDatagram]) {
+    let mut bytes = Vec::new();
+    for datagram in datagrams {
+        bytes.extend_from_slice(&(datagram.at.as_micros() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(datagram.data.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&datagram.data);
+    }
+    fs::write(path, bytes).unwrap();
+}
+
+#[actix_rt::test]
+async fn test_mailbox_records_inbound_datagrams() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.rec");
+    let transport = Arc::new(MockTransport::new());
+    let mut mailbox = Mailbox::new(0, "127.0.0.1:9".to_string());
+    mailbox.ping_interval = None;
+    let recorder = Arc::new(Recorder::create(&path).unwrap());
+    mailbox.recorder = Some(recorder.clone());
+    let mailbox = mailbox.start();
+    mailbox
+        .send(Session {
+            url: "127.0.0.1".to_string(),
+            server_socket: 13000,
+            agent_id: Uuid::new_v4(),
+            session_id: Uuid::new_v4(),
+            circuit_code: 1234,
+            agent_name: "Test User".to_string(),
+            agent_access: None,
+            agent_access_max: None,
+            home: None,
+            buddy_list: Vec::new(),
+            socket: Some(transport.clone()),
+            outbound: None,
+            agent_list: Arc::new(Mutex::new(HashMap::new())),
+        })
+        .await
+        .unwrap();
+
+    let (first, second) = (chat("first"), chat("second"));
+    transport.inject(first.clone());
+    sleep(Duration::from_millis(50)).await;
+    transport.inject(second.clone());
+    let waiting = async {
+        loop {
+            recorder.flush().unwrap();
+            let recording = read_recording(&path).unwrap();
+            if recording.len() >= 2 {
+                break recording;
+            }
+            sleep(Duration::from_millis(5)).await;
+        }
+    };
+    let recording = timeout(Duration::from_secs(5), waiting).await.unwrap();
+    assert_eq!(recording.len(), 2);
+    assert_eq!(recording[0].data, first);
+    assert_eq!(recording[1].data, second);
+    assert!(recording[1].at >= recording[0].at + Duration::from_millis(50));
+}
+
+#[test]
+fn test_truncated_recording_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("truncated.rec");
+    let recorder = Recorder::create(&path).unwrap();
+    recorder.record(&chat("hello")).unwrap();
+    recorder.record(&chat("cut off")).unwrap();
+    recorder.flush().unwrap();
+    assert_eq!(read_recording(&path).unwrap().len(), 2);
+
+    let bytes = fs::read(&path).unwrap();
+    for end in [bytes.len() - 1, bytes.len() - chat("cut off").len() - 4] {
+        fs::write(&path, &bytes[..end]).unwrap();
+        assert!(read_recording(&path).is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_replay_transport_keeps_the_original_timing() {
+    let clock = Arc::new(MockClock::new());
+    let transport = ReplayTransport::new(
+        vec![
+            RecordedDatagram {
+                at: Duration::ZERO,
+                data: vec![1],
+            },
+            RecordedDatagram {
+                at: Duration::from_secs(2),
+                data: vec![2, 2],
+            },
+        ],
+        clock.clone(),
+    );
+    let mut buf = [0u8; 16];
+    assert_eq!(transport.recv_from(&mut buf).await.unwrap().0, 1);
+
+    {
+        let mut receiving = transport.recv_from(&mut buf);
+        assert!((&mut receiving).now_or_never().is_none());
+        clock.advance(Duration::from_secs(1));
+        assert!((&mut receiving).now_or_never().is_none());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(receiving.now_or_never().unwrap().unwrap().0, 2);
+    }
+    assert_eq!(&buf[..2], [2, 2]);
+    assert!(transport.recv_from(&mut buf).await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_replay_from_file_sends_the_events_to_the_ui() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bug_report.rec");
+    write_recording(
+        &path,
+        &[
+            RecordedDatagram {
+                at: Duration::ZERO,
+                data: chat("first"),
+            },
+            RecordedDatagram {
+                at: Duration::from_secs(5),
+                data: chat("second"),
+            },
+        ],
+    );
+
+    let mut events = UiEventStream::bind("127.0.0.1:0").await.unwrap();
+    let clock = Arc::new(MockClock::new());
+    let mut mailbox = Mailbox::new(0, events.local_addr().unwrap().to_string());
+    mailbox.ping_interval = None;
+    mailbox.clock = clock.clone();
+    let mailbox = mailbox.start();
+    replay_from_file(&path, mailbox).await.unwrap();
+
+    let mut next_chat = async || loop {
+        let (event, data) = timeout(Duration::from_millis(200), events.next())
+            .await
+            .ok()??;
+        if event == UiEventTypes::StateChangeEvent {
+            continue;
+        }
+        assert_eq!(event, UiEventTypes::ChatFromSimulatorEvent);
+        break Some(ChatFromSimulator::from_bytes(&data).unwrap().message);
+    };
+    assert_eq!(next_chat().await.as_deref(), Some("first"));
+    // the second datagram arrived five seconds into the recording
+    assert_eq!(next_chat().await, None);
+    clock.advance(Duration::from_secs(5));
+    assert_eq!(next_chat().await.as_deref(), Some("second"));
+}
+
+#[test]
+fn test_recording_is_written_in_the_background() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("background.rec");
+    let recorder = Recorder::create(&path).unwrap();
+    for i in 0..100u8 {
+        recorder.record(&[i; 100]).unwrap();
+    }
+    recorder.flush().unwrap();
+    let recording = read_recording(&path).unwrap();
+    assert_eq!(recording.len(), 100);
+    assert!(recording
+        .iter()
+        .enumerate()
+        .all(|(i, datagram)| datagram.data == [i as u8; 100]));
+
+    // dropping the recorder writes whatever is left
+    recorder.record(&[1, 2, 3]).unwrap();
+    drop(recorder);
+    // the file can be read while the thread is still writing it
+    let written = || read_recording(&path).is_ok_and(|recording| recording.len() == 101);
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while !written() {
+        assert!(std::time::Instant::now() < deadline);
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
@@ -0,0 +1,90 @@
+use metaverse_messages::{image_data::ImageData, image_packet::ImagePacket};
+use metaverse_session::texture::TextureAssembler;
+use uuid::{uuid, Uuid};
+
+const TEXTURE: Uuid = uuid!("5748decc-f629-461c-9a36-a35a221fe21f");
+
+fn image_data(packets: u16, data: &[u8]) -> ImageData {
+    ImageData {
+        id: TEXTURE,
+        codec: 2,
+        size: 6,
+        packets,
+        data: data.to_vec(),
+    }
+}
+
+fn image_packet(packet: u16, data: &[u8]) -> ImagePacket {
+    ImagePacket {
+        id: TEXTURE,
+        packet,
+        data: data.to_vec(),
+    }
+}
+
+#[test]
+fn test_texture_is_assembled_in_packet_order() {
+    let mut textures = TextureAssembler::new();
+
+    assert!(textures.image_data(image_data(3, &[1, 2])).is_none());
+    assert!(textures.image_packet(image_packet(2, &[5, 6])).is_none());
+    let texture = textures.image_packet(image_packet(1, &[3, 4])).unwrap();
+    assert_eq!(texture.id, TEXTURE);
+    assert_eq!(texture.codec, 2);
+    assert_eq!(texture.data, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(textures.pending(), 0);
+}
+
+#[test]
+fn test_packets_before_the_image_data_are_held() {
+    let mut textures = TextureAssembler::new();
+
+    assert!(textures.image_packet(image_packet(1, &[3, 4])).is_none());
+    assert_eq!(textures.pending(), 1);
+    let texture = textures.image_data(image_data(2, &[1, 2])).unwrap();
+    assert_eq!(texture.data, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn test_single_packet_texture() {
+    let mut textures = TextureAssembler::new();
+    let texture = textures.image_data(image_data(1, &[1, 2, 3])).unwrap();
+    assert_eq!(texture.data, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_the_stalest_incomplete_texture_is_dropped() {
+    let mut textures = TextureAssembler::with_limit(2);
+    let other = Uuid::new_v4();
+    let another = Uuid::new_v4();
+
+    assert!(textures.image_data(image_data(2, &[1, 2])).is_none());
+    assert!(textures
+        .image_packet(ImagePacket {
+            id: other,
+            packet: 1,
+            data: vec![3, 4],
+        })
+        .is_none());
+    // TEXTURE is still getting packets, so other is the one that has waited longest
+    assert!(textures.image_packet(image_packet(3, &[7, 8])).is_none());
+    assert!(textures
+        .image_packet(ImagePacket {
+            id: another,
+            packet: 1,
+            data: vec![5, 6],
+        })
+        .is_none());
+    assert_eq!(textures.pending(), 2);
+
+    // other starts over without the packet that was dropped
+    assert!(textures
+        .image_data(ImageData {
+            id: other,
+            ..image_data(2, &[1, 2])
+        })
+        .is_none());
+    // which makes room by dropping TEXTURE, so its last packet no longer completes it
+    assert_eq!(textures.pending(), 2);
+    assert!(textures.image_packet(image_packet(1, &[3, 4])).is_none());
+}
@@ -0,0 +1,185 @@
+use std::net::{SocketAddr, UdpSocket};
+use std::time::Duration;
+
+use actix::Actor;
+use metaverse_messages::ui_events::UiEventTypes;
+use metaverse_session::mailbox::{Mailbox, UiMessage, MAX_UI_MESSAGE_SIZE, UI_MESSAGE_HEADER_SIZE};
+
+/// the chunks sent to the UI, leaving out the state changes the mailbox sends on its own
+fn receive_chunks(socket: &UdpSocket) -> Vec<(UiMessage, SocketAddr)> {
+    let mut buf = vec![0u8; 65507];
+    let mut chunks = Vec::new();
+    while let Ok((size, addr)) = socket.recv_from(&mut buf) {
+        let chunk = UiMessage::from_bytes(&buf[..size]).unwrap();
+        if !is_state_change(&chunk) {
+            chunks.push((chunk, addr));
+        }
+    }
+    chunks
+}
+
+fn is_state_change(chunk: &UiMessage) -> bool {
+    matches!(chunk.message_type, UiEventTypes::StateChangeEvent)
+}
+
+fn ui_socket() -> UdpSocket {
+    let ui_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    ui_socket
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+    ui_socket
+}
+
+async fn send_to_ui(max_ui_message_size: usize, message: Vec<u8>) -> Vec<UiMessage> {
+    let ui_socket = ui_socket();
+    let mut mailbox = Mailbox::new(0, ui_socket.local_addr().unwrap().to_string());
+    mailbox.max_ui_message_size = max_ui_message_size;
+    let mailbox = mailbox.start();
+    mailbox
+        .send(UiMessage::new(
+            UiEventTypes::ChatFromSimulatorEvent,
+            message,
+        ))
+        .await
+        .unwrap();
+
+    receive_chunks(&ui_socket)
+        .into_iter()
+        .map(|(chunk, _)| chunk)
+        .collect()
+}
+
+#[actix_rt::test]
+async fn test_default_ui_message_size_chunks_large_messages() {
+    let message: Vec<u8> = (0..1500).map(|i| i as u8).collect();
+    let chunks = send_to_ui(1024, message.clone()).await;
+
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks.iter().all(|chunk| chunk.total_packet_number == 2));
+    let reassembled: Vec<u8> = chunks.into_iter().flat_map(|chunk| chunk.message).collect();
+    assert_eq!(reassembled, message);
+}
+
+#[actix_rt::test]
+async fn test_larger_ui_message_size_sends_fewer_chunks() {
+    let message: Vec<u8> = (0..1500).map(|i| i as u8).collect();
+    let chunks = send_to_ui(8192, message.clone()).await;
+
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].message, message);
+}
+
+#[actix_rt::test]
+async fn test_ui_messages_are_sent_from_one_socket() {
+    let ui_socket = ui_socket();
+    let mailbox = Mailbox::new(0, ui_socket.local_addr().unwrap().to_string()).start();
+    for _ in 0..2 {
+        mailbox
+            .send(UiMessage::new(
+                UiEventTypes::ChatFromSimulatorEvent,
+                vec![0u8; 3000],
+            ))
+            .await
+            .unwrap();
+    }
+
+    let chunks = receive_chunks(&ui_socket);
+    assert!(chunks.len() > 2);
+    assert!(chunks.iter().all(|(_, addr)| *addr == chunks[0].1));
+}
+
+#[actix_rt::test]
+async fn test_each_ui_message_gets_its_own_id() {
+    let ui_socket = ui_socket();
+    let mailbox = Mailbox::new(0, ui_socket.local_addr().unwrap().to_string()).start();
+    for _ in 0..2 {
+        mailbox
+            .send(UiMessage::new(
+                UiEventTypes::ChatFromSimulatorEvent,
+                vec![0u8; 1500],
+            ))
+            .await
+            .unwrap();
+    }
+
+    let chunks: Vec<(u32, u16)> = receive_chunks(&ui_socket)
+        .into_iter()
+        .map(|(chunk, _)| (chunk.message_id, chunk.sequence_number))
+        .collect();
+    // the Running state change took the first id
+    assert_eq!(chunks, vec![(1, 0), (1, 1), (2, 0), (2, 1)]);
+}
+
+#[actix_rt::test]
+async fn test_ui_messages_are_buffered_until_the_ui_listens() {
+    let ui_address = format!("127.0.0.1:{}", portpicker::pick_unused_port().unwrap());
+    let mailbox = Mailbox::new(0, ui_address.clone()).start();
+
+    // nothing is listening yet. The kernel only reports the refused connection on the send
+    // after the first, so the first is buffered again along with the rest.
+    for i in 1..=3u8 {
+        mailbox
+            .send(UiMessage::new(
+                UiEventTypes::ChatFromSimulatorEvent,
+                vec![i],
+            ))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let ui_socket = UdpSocket::bind(&ui_address).unwrap();
+    ui_socket
+        .set_read_timeout(Some(Duration::from_millis(500)))
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let chunks: Vec<UiMessage> = receive_chunks(&ui_socket)
+        .into_iter()
+        .map(|(chunk, _)| chunk)
+        .collect();
+    let messages: Vec<&[u8]> = chunks.iter().map(|chunk| &chunk.message[..]).collect();
+    assert_eq!(messages, vec![[1], [2], [3]]);
+    // messages sent again get new ids, so they aren't mixed with the chunks sent before
+    let ids: Vec<u32> = chunks.iter().map(|chunk| chunk.message_id).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(ids[0] > 1);
+}
+
+#[actix_rt::test]
+async fn test_chunks_of_the_largest_size_fit_in_a_datagram() {
+    let ui_socket = ui_socket();
+    let mut mailbox = Mailbox::new(0, ui_socket.local_addr().unwrap().to_string());
+    mailbox.max_ui_message_size = MAX_UI_MESSAGE_SIZE;
+    let mailbox = mailbox.start();
+    // a short event name leaves the least room for the chunk header
+    let message: Vec<u8> = (0..100_000).map(|i| i as u8).collect();
+    mailbox
+        .send(UiMessage::new(UiEventTypes::Error, message.clone()))
+        .await
+        .unwrap();
+
+    let mut buf = vec![0u8; MAX_UI_MESSAGE_SIZE + 1];
+    let mut chunks = Vec::new();
+    while let Ok(size) = ui_socket.recv(&mut buf) {
+        assert!(size <= MAX_UI_MESSAGE_SIZE);
+        let chunk = UiMessage::from_bytes(&buf[..size]).unwrap();
+        if !is_state_change(&chunk) {
+            chunks.push(chunk);
+        }
+    }
+    assert_eq!(chunks.len(), 2);
+    // the first chunk is filled up to the limit
+    assert_eq!(chunks[0].as_bytes().len(), MAX_UI_MESSAGE_SIZE);
+    let reassembled: Vec<u8> = chunks.into_iter().flat_map(|chunk| chunk.message).collect();
+    assert_eq!(reassembled, message);
+}
+
+#[test]
+fn test_header_size_is_the_same_for_every_event() {
+    for event in (0..=u16::MAX).filter_map(UiEventTypes::from_tag) {
+        let mut empty = UiMessage::new(event, Vec::new());
+        empty.message_id = u32::MAX;
+        assert_eq!(empty.as_bytes().len(), UI_MESSAGE_HEADER_SIZE);
+    }
+}
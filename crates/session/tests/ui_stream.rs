@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use actix::Actor;
+use futures::StreamExt;
+use metaverse_messages::ui_events::UiEventTypes;
+use metaverse_session::mailbox::{Mailbox, ServerState, Shutdown, UiMessage};
+use metaverse_session::ui_stream::{UiEventStream, UiMessageAssembler};
+use tokio::time::timeout;
+
+fn chunk(message_id: u32, sequence_number: u16, total: u16, message: &[u8]) -> UiMessage {
+    UiMessage {
+        message_type: UiEventTypes::ChatFromSimulatorEvent,
+        sequence_number,
+        total_packet_number: total,
+        message_id,
+        message: message.to_vec(),
+    }
+}
+
+async fn next_event(events: &mut UiEventStream) -> (UiEventTypes, Vec<u8>) {
+    timeout(Duration::from_secs(1), events.next())
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn test_assembler_puts_interleaved_chunks_back_together() {
+    let mut assembler = UiMessageAssembler::new();
+    assert!(assembler.push(chunk(1, 1, 2, b"world")).is_none());
+    assert!(assembler.push(chunk(2, 0, 2, b"good")).is_none());
+    assert_eq!(assembler.pending(), 2);
+
+    let (event, message) = assembler.push(chunk(1, 0, 2, b"hello ")).unwrap();
+    assert!(matches!(event, UiEventTypes::ChatFromSimulatorEvent));
+    assert_eq!(message, b"hello world");
+
+    let (_, message) = assembler.push(chunk(2, 1, 2, b"bye")).unwrap();
+    assert_eq!(message, b"goodbye");
+    assert_eq!(assembler.pending(), 0);
+}
+
+#[test]
+fn test_assembler_drops_the_stalest_incomplete_message() {
+    let mut assembler = UiMessageAssembler::with_limit(2);
+    assert!(assembler.push(chunk(1, 0, 2, b"hello ")).is_none());
+    assert!(assembler.push(chunk(2, 0, 2, b"good")).is_none());
+    // 1 gets another chunk, so 2 is the one that has waited longest
+    assert!(assembler.push(chunk(1, 2, 3, b"!")).is_none());
+    assert!(assembler.push(chunk(3, 0, 2, b"lost")).is_none());
+    assert_eq!(assembler.pending(), 2);
+
+    let (_, message) = assembler.push(chunk(1, 1, 3, b"world")).unwrap();
+    assert_eq!(message, b"hello world!");
+    // the rest of 2 starts it over, without its first chunk
+    assert!(assembler.push(chunk(2, 1, 2, b"bye")).is_none());
+}
+
+#[actix_rt::test]
+async fn test_stream_yields_messages_from_the_mailbox() {
+    let mut events = UiEventStream::bind("127.0.0.1:0").await.unwrap();
+    let mut mailbox = Mailbox::new(0, events.local_addr().unwrap().to_string());
+    mailbox.max_ui_message_size = 512;
+    let mailbox = mailbox.start();
+
+    let message: Vec<u8> = (0..1500).map(|i| i as u8).collect();
+    for data in [message.clone(), b"short".to_vec()] {
+        mailbox
+            .send(UiMessage::new(UiEventTypes::ChatFromSimulatorEvent, data))
+            .await
+            .unwrap();
+    }
+
+    let (event, data) = next_event(&mut events).await;
+    assert!(matches!(event, UiEventTypes::StateChangeEvent));
+    assert_eq!(ServerState::from_bytes(&data), Some(ServerState::Running));
+    let (event, data) = next_event(&mut events).await;
+    assert!(matches!(event, UiEventTypes::ChatFromSimulatorEvent));
+    assert_eq!(data, message);
+    let (_, data) = next_event(&mut events).await;
+    assert_eq!(data, b"short");
+}
+
+#[actix_rt::test]
+async fn test_state_changes_reach_the_ui() {
+    let mut events = UiEventStream::bind("127.0.0.1:0").await.unwrap();
+    let mailbox = Mailbox::new(0, events.local_addr().unwrap().to_string()).start();
+    mailbox.send(Shutdown).await.unwrap();
+
+    let mut states = Vec::new();
+    for _ in 0..3 {
+        let (event, data) = next_event(&mut events).await;
+        assert!(matches!(event, UiEventTypes::StateChangeEvent));
+        assert!(event.packet_type_from_bytes(&data).is_none());
+        states.push(ServerState::from_bytes(&data).unwrap());
+    }
+    assert_eq!(
+        states,
+        vec![
+            ServerState::Running,
+            ServerState::Stopping,
+            ServerState::Stopped
+        ]
+    );
+}
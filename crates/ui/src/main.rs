@@ -185,6 +185,15 @@ fn handle_queue(
                 SessionError::CompleteAgentMovement(e) => {
                     info!("CompleteAgentMovmentError {:?}", e)
                 }
+                SessionError::Capability(e) => {
+                    info!("CapabilityError {:?}", e)
+                }
+                SessionError::Resume(e) => {
+                    info!("ResumeError {:?}", e)
+                }
+                SessionError::Timeout(e) => {
+                    info!("TimeoutError {:?}", e)
+                }
             },
             PacketType::ChatFromSimulator(chat_from_simulator) => {
                 chat_messages.messages.push(ChatFromClientMessage {
@@ -222,7 +231,7 @@ fn start_client(sockets: Res<Sockets>) {
         System::new().block_on(async {
             match initialize(ui_to_server_socket, server_to_ui_socket).await {
                 Ok(handle) => {
-                    match handle.await {
+                    match handle.listener.await {
                         Ok(()) => info!("Listener exited successfully!"),
                         Err(e) => error!("Listener exited with error {:?}", e),
                     };